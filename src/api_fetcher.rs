@@ -0,0 +1,573 @@
+// This script downloads the roblox API dump, reporting how far along the download is
+
+use reqwest::header::{ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use reqwest::{Certificate, Client, Proxy, StatusCode};
+use serde::{Deserialize, Serialize};
+use std::{env, error::Error, fmt, fs, time::Duration};
+
+const CLIENT_VERSION_URL: &str =
+    "https://clientsettingscdn.roblox.com/v1/client-version/WindowsStudio64";
+// Serves versionQTStudio and <version>-API-Dump.json
+const SETUP_URL: &str = "https://setup.rbxcdn.com";
+// Community client tracker, Full-API-Dump.json(Same dump plus property defaults) and api-docs/en-us.json
+const TRACKER_URL: &str =
+    "https://raw.githubusercontent.com/MaximumADHD/Roblox-Client-Tracker/roblox";
+
+// Environment variables used when settings leave the matching field out
+const SETUP_URL_ENV: &str = "RBLX_REACT_LSP_SETUP_URL";
+const CLIENT_VERSION_URL_ENV: &str = "RBLX_REACT_LSP_CLIENT_VERSION_URL";
+const TRACKER_URL_ENV: &str = "RBLX_REACT_LSP_TRACKER_URL";
+const PROXY_ENV: &str = "RBLX_REACT_LSP_PROXY";
+const CA_CERTIFICATE_ENV: &str = "RBLX_REACT_LSP_CA_CERTIFICATE";
+
+// Where downloads go and how, None fields use the defaults
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FetcherConfig {
+    pub setup_url: Option<String>,
+    pub client_version_url: Option<String>,
+    pub tracker_url: Option<String>,
+    pub proxy: Option<String>, // http(s):// or socks5:// url, every request goes through it
+    pub ca_certificate: Option<String>, // PEM file path, trusted next to the built in roots
+}
+
+impl FetcherConfig {
+    pub fn from_env() -> Self {
+        let var = |name| env::var(name).ok().filter(|value| !value.trim().is_empty());
+        Self {
+            setup_url: var(SETUP_URL_ENV),
+            client_version_url: var(CLIENT_VERSION_URL_ENV),
+            tracker_url: var(TRACKER_URL_ENV),
+            proxy: var(PROXY_ENV),
+            ca_certificate: var(CA_CERTIFICATE_ENV),
+        }
+    }
+
+    // Fields of self, falling back to fallback's where self has none
+    pub fn or(self, fallback: Self) -> Self {
+        Self {
+            setup_url: self.setup_url.or(fallback.setup_url),
+            client_version_url: self.client_version_url.or(fallback.client_version_url),
+            tracker_url: self.tracker_url.or(fallback.tracker_url),
+            proxy: self.proxy.or(fallback.proxy),
+            ca_certificate: self.ca_certificate.or(fallback.ca_certificate),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DownloadProgress {
+    pub downloaded: u64,    // Bytes of API dump received so far
+    pub total: Option<u64>, // None if server didn't send a content length
+}
+
+impl DownloadProgress {
+    pub fn percentage(&self) -> Option<u32> {
+        let total = self.total.filter(|&total| total > 0)?;
+        Some((self.downloaded.min(total) * 100 / total) as u32)
+    }
+}
+
+// Headers a later request sends back to ask whether the dump changed since
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct ResponseValidators {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+}
+
+pub type FetchedDump = (String, String, ResponseValidators); // (Dump, version, validators)
+
+// Why a request failed, worded for the client log since that's where it ends up
+#[derive(Debug)]
+pub enum FetchError {
+    Dns(String),     // Host name didn't resolve(Offline, or a typo in an overridden url)
+    Connect(String), // Resolved but connecting failed(Refused, TLS, proxy)
+    Timeout(String), // Connecting or the whole request took too long
+    Client(StatusCode, String), // 4xx, asking again won't help
+    Server(StatusCode, String), // 5xx or 429, usually a CDN hiccup
+    Body(String),    // Connection dropped while reading the response
+    Other(String),
+}
+
+impl FetchError {
+    fn classify(e: reqwest::Error, url: &str) -> Self {
+        if let Some(status) = e.status() {
+            return Self::from_status(status, url);
+        }
+        // Source chain is the only place reqwest says it was DNS, its last link says what went wrong
+        let mut causes = Vec::new();
+        let mut source = e.source();
+        while let Some(cause) = source {
+            causes.push(cause.to_string());
+            source = cause.source();
+        }
+        let root_cause = causes.last().cloned().unwrap_or_else(|| e.to_string());
+        let host = reqwest::Url::parse(url)
+            .ok()
+            .and_then(|url| url.host_str().map(str::to_string))
+            .unwrap_or_else(|| url.to_string());
+        if e.is_timeout() {
+            Self::Timeout(host)
+        } else if e.is_connect() && causes.iter().any(|cause| cause.contains("dns")) {
+            Self::Dns(host)
+        } else if e.is_connect() {
+            Self::Connect(format!("{} ({})", host, root_cause))
+        } else if e.is_body() || e.is_decode() {
+            Self::Body(url.to_string())
+        } else {
+            Self::Other(e.to_string())
+        }
+    }
+
+    fn from_status(status: StatusCode, url: &str) -> Self {
+        if status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS {
+            Self::Server(status, url.to_string())
+        } else {
+            Self::Client(status, url.to_string())
+        }
+    }
+
+    // Worth trying again after a moment
+    pub fn is_transient(&self) -> bool {
+        !matches!(self, Self::Client(..) | Self::Other(_))
+    }
+}
+
+impl fmt::Display for FetchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Dns(host) => write!(f, "Couldn't resolve {}, check the network connection", host),
+            Self::Connect(host) => write!(f, "Couldn't connect to {}", host),
+            Self::Timeout(host) => write!(f, "Request to {} timed out", host),
+            Self::Client(status, url) => write!(f, "{} answered {}", url, status),
+            Self::Server(status, url) => {
+                write!(f, "{} answered {}, server is having trouble", url, status)
+            }
+            Self::Body(url) => write!(f, "Connection dropped while downloading {}", url),
+            Self::Other(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl Error for FetchError {}
+
+// Each request gets attempts tries, waiting base_delay and then twice as long after every transient failure
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryPolicy {
+    pub attempts: u32,
+    pub base_delay: Duration,
+    pub request_timeout: Duration, // Whole request including body, the dump is a few MB
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            attempts: 3,
+            base_delay: Duration::from_secs(1),
+            request_timeout: Duration::from_secs(60),
+        }
+    }
+}
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+// Cheap to clone, clones share one connection pool
+#[derive(Debug, Clone)]
+pub struct ApiFetcher {
+    client: Client,
+    config: FetcherConfig,
+    retry: RetryPolicy,
+}
+
+impl Default for ApiFetcher {
+    fn default() -> Self {
+        Self::with_config(FetcherConfig::default()).expect("Default fetcher config is valid")
+    }
+}
+
+impl ApiFetcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[cfg(test)]
+    pub fn with_retry(self, retry: RetryPolicy) -> Self {
+        Self { retry, ..self }
+    }
+
+    // Client gets built once here, so changing config means building a new fetcher
+    pub fn with_config(
+        config: FetcherConfig,
+    ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let mut builder = Client::builder().connect_timeout(CONNECT_TIMEOUT);
+        if let Some(proxy) = &config.proxy {
+            builder = builder.proxy(Proxy::all(proxy)?);
+        }
+        if let Some(path) = &config.ca_certificate {
+            let pem = fs::read(path)
+                .map_err(|e| format!("Failed to read CA certificate {}: {}", path, e))?;
+            builder = builder.add_root_certificate(Certificate::from_pem(&pem)?);
+        }
+        Ok(Self {
+            client: builder.build()?,
+            config,
+            retry: RetryPolicy::default(),
+        })
+    }
+
+    pub fn config(&self) -> &FetcherConfig {
+        &self.config
+    }
+
+    fn setup_url(&self, path: &str) -> String {
+        let base = self.config.setup_url.as_deref().unwrap_or(SETUP_URL);
+        format!("{}/{}", base.trim_end_matches('/'), path)
+    }
+
+    fn tracker_url(&self, path: &str) -> String {
+        let base = self.config.tracker_url.as_deref().unwrap_or(TRACKER_URL);
+        format!("{}/{}", base.trim_end_matches('/'), path)
+    }
+
+    fn api_dump_url(&self, version: &str) -> String {
+        self.setup_url(&format!("{}-API-Dump.json", version))
+    }
+
+    pub async fn live_version(&self) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        let url = self
+            .config
+            .client_version_url
+            .as_deref()
+            .unwrap_or(CLIENT_VERSION_URL);
+        let (text, _) = self.download_text(url, &mut |_| {}).await?;
+        let version_json: serde_json::Value = serde_json::from_str(&text)?;
+        Ok(version_json["clientVersionUpload"]
+            .as_str()
+            .ok_or("Failed to parse clientVersionUpload from response")?
+            .to_string())
+    }
+
+    // With validators the request is conditional, None means server said it's unchanged
+    // Transient failures get retried with backoff, progress starts over from 0 when they do
+    async fn download(
+        &self,
+        url: &str,
+        validators: Option<&ResponseValidators>,
+        on_progress: &mut (impl FnMut(DownloadProgress) + Send),
+    ) -> Result<Option<(String, ResponseValidators)>, FetchError> {
+        let mut delay = self.retry.base_delay;
+        let mut attempt = 1;
+        loop {
+            match self.download_once(url, validators, on_progress).await {
+                Err(e) if e.is_transient() && attempt < self.retry.attempts => {
+                    eprintln!(
+                        "{} (attempt {}/{}), retrying in {:?}",
+                        e, attempt, self.retry.attempts, delay
+                    );
+                    tokio::time::sleep(delay).await;
+                    delay *= 2;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    // Streams body chunk by chunk so on_progress gets called as bytes come in
+    async fn download_once(
+        &self,
+        url: &str,
+        validators: Option<&ResponseValidators>,
+        on_progress: &mut (impl FnMut(DownloadProgress) + Send),
+    ) -> Result<Option<(String, ResponseValidators)>, FetchError> {
+        let classify = |e| FetchError::classify(e, url);
+        let mut request = self.client.get(url).timeout(self.retry.request_timeout);
+        if let Some(validators) = validators {
+            if let Some(etag) = &validators.etag {
+                request = request.header(IF_NONE_MATCH, etag);
+            }
+            if let Some(last_modified) = &validators.last_modified {
+                request = request.header(IF_MODIFIED_SINCE, last_modified);
+            }
+        }
+        let mut response = request.send().await.map_err(classify)?;
+        if response.status() == StatusCode::NOT_MODIFIED {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            return Err(FetchError::from_status(response.status(), url));
+        }
+        let header = |name| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string)
+        };
+        let validators = ResponseValidators {
+            etag: header(ETAG),
+            last_modified: header(LAST_MODIFIED),
+        };
+        let total = response.content_length();
+        let mut body = Vec::with_capacity(total.unwrap_or(0) as usize);
+
+        on_progress(DownloadProgress {
+            downloaded: 0,
+            total,
+        });
+        while let Some(chunk) = response.chunk().await.map_err(classify)? {
+            body.extend_from_slice(&chunk);
+            on_progress(DownloadProgress {
+                downloaded: body.len() as u64,
+                total,
+            });
+        }
+
+        let text = String::from_utf8(body)
+            .map_err(|_| FetchError::Other(format!("{} isn't valid UTF-8", url)))?;
+        Ok(Some((text, validators)))
+    }
+
+    async fn download_text(
+        &self,
+        url: &str,
+        on_progress: &mut (impl FnMut(DownloadProgress) + Send),
+    ) -> Result<(String, ResponseValidators), Box<dyn std::error::Error + Send + Sync>> {
+        Ok(self
+            .download(url, None, on_progress)
+            .await?
+            .ok_or("Server answered an unconditional request with 304")?)
+    }
+
+    // Defaults aren't in the official dump, callers treat failing here as not having them
+    pub async fn fetch_full_dump(
+        &self,
+    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        let (full_dump, _) = self
+            .download_text(&self.tracker_url("Full-API-Dump.json"), &mut |_| {})
+            .await?;
+        Ok(full_dump)
+    }
+
+    // Optional like the full dump, completions just go without docs
+    pub async fn fetch_api_docs(&self) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        let (api_docs, _) = self
+            .download_text(&self.tracker_url("api-docs/en-us.json"), &mut |_| {})
+            .await?;
+        Ok(api_docs)
+    }
+
+    // Dump of one exact version hash(e.g. "version-0123456789abcdef"), for pinned setups
+    pub async fn fetch_version(
+        &self,
+        version: &str,
+        mut on_progress: impl FnMut(DownloadProgress) + Send,
+    ) -> Result<FetchedDump, Box<dyn std::error::Error + Send + Sync>> {
+        let (dump, validators) = self
+            .download_text(&self.api_dump_url(version), &mut on_progress)
+            .await?;
+        Ok((dump, version.to_string(), validators))
+    }
+
+    // Latest dump, or None when it's still the cached one(Same version and server says unchanged)
+    pub async fn fetch_if_modified(
+        &self,
+        cached_version: &str,
+        validators: &ResponseValidators,
+        mut on_progress: impl FnMut(DownloadProgress) + Send,
+    ) -> Result<Option<FetchedDump>, Box<dyn std::error::Error + Send + Sync>> {
+        let version = self.live_version().await?;
+        let validators = (version == cached_version).then_some(validators);
+        let fetched = self
+            .download(&self.api_dump_url(&version), validators, &mut on_progress)
+            .await?;
+        Ok(fetched.map(|(dump, validators)| (dump, version, validators)))
+    }
+
+    // Returns (dump, version), falling back to QTStudio version if client settings are down
+    pub async fn fetch(
+        &self,
+        mut on_progress: impl FnMut(DownloadProgress) + Send,
+    ) -> Result<FetchedDump, Box<dyn std::error::Error + Send + Sync>> {
+        let primary = match self.live_version().await {
+            Ok(version) => self
+                .download_text(&self.api_dump_url(&version), &mut on_progress)
+                .await
+                .map(|(dump, validators)| (dump, version, validators)),
+            Err(e) => Err(e),
+        };
+        match primary {
+            Ok(result) => return Ok(result),
+            Err(e) => eprintln!(
+                "Primary API source failed ({}), falling back to QTStudio...",
+                e
+            ),
+        }
+
+        // Fallback
+        let (version, _) = self
+            .download_text(&self.setup_url("versionQTStudio"), &mut |_| {})
+            .await?;
+        let version = version.trim().to_string();
+        let (dump, validators) = self
+            .download_text(&self.api_dump_url(&version), &mut on_progress)
+            .await?;
+        Ok((dump, version, validators))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::thread;
+    use std::time::Duration;
+
+    use crate::api_fetcher::{
+        ApiFetcher, DownloadProgress, FetchError, FetcherConfig, RetryPolicy,
+    };
+
+    // Answers one connection per response in order, returns its setup url
+    fn serve(responses: Vec<&'static str>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        thread::spawn(move || {
+            for response in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut request = [0; 4096];
+                let _ = stream.read(&mut request);
+                let _ = stream.write_all(response.as_bytes());
+            }
+        });
+        url
+    }
+
+    fn fetcher(setup_url: String) -> ApiFetcher {
+        let config = FetcherConfig {
+            setup_url: Some(setup_url),
+            ..Default::default()
+        };
+        ApiFetcher::with_config(config)
+            .unwrap()
+            .with_retry(RetryPolicy {
+                attempts: 3,
+                base_delay: Duration::from_millis(1),
+                request_timeout: Duration::from_secs(5),
+            })
+    }
+
+    const UNAVAILABLE: &str =
+        "HTTP/1.1 503 Service Unavailable\r\nContent-Length: 0\r\nConnection: close\r\n\r\n";
+    const NOT_FOUND: &str =
+        "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n";
+    const DUMP: &str =
+        "HTTP/1.1 200 OK\r\nContent-Length: 15\r\nConnection: close\r\n\r\n{\"Classes\": []}";
+
+    #[test]
+    fn test_download_percentage() {
+        let progress = |downloaded, total| DownloadProgress { downloaded, total };
+        assert_eq!(progress(0, Some(200)).percentage(), Some(0));
+        assert_eq!(progress(150, Some(200)).percentage(), Some(75));
+        assert_eq!(progress(300, Some(200)).percentage(), Some(100));
+        assert_eq!(progress(150, None).percentage(), None);
+        assert_eq!(progress(0, Some(0)).percentage(), None);
+    }
+
+    #[test]
+    fn test_fetcher_config() {
+        let settings = FetcherConfig {
+            setup_url: Some("https://mirror.example.com/setup/".to_string()),
+            ..Default::default()
+        };
+        let env = FetcherConfig {
+            setup_url: Some("https://env.example.com".to_string()),
+            tracker_url: Some("https://env.example.com/tracker".to_string()),
+            ..Default::default()
+        };
+        let config = settings.or(env);
+        assert_eq!(
+            config.setup_url.as_deref(),
+            Some("https://mirror.example.com/setup/")
+        );
+        assert_eq!(
+            config.tracker_url.as_deref(),
+            Some("https://env.example.com/tracker")
+        );
+
+        let fetcher = ApiFetcher::with_config(config).unwrap();
+        assert_eq!(
+            fetcher.api_dump_url("version-1"),
+            "https://mirror.example.com/setup/version-1-API-Dump.json"
+        );
+        assert_eq!(
+            fetcher.tracker_url("api-docs/en-us.json"),
+            "https://env.example.com/tracker/api-docs/en-us.json"
+        );
+        assert_eq!(
+            ApiFetcher::new().api_dump_url("version-1"),
+            "https://setup.rbxcdn.com/version-1-API-Dump.json"
+        );
+
+        // Broken network setup fails up front instead of on the first download
+        let missing_ca = FetcherConfig {
+            ca_certificate: Some("/nonexistent/ca.pem".to_string()),
+            ..Default::default()
+        };
+        assert!(ApiFetcher::with_config(missing_ca).is_err());
+        let bad_proxy = FetcherConfig {
+            proxy: Some("not a url".to_string()),
+            ..Default::default()
+        };
+        assert!(ApiFetcher::with_config(bad_proxy).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_download_retries() {
+        // CDN hiccups get retried until the dump comes through
+        let url = serve(vec![UNAVAILABLE, UNAVAILABLE, DUMP]);
+        let (dump, version, _) = fetcher(url)
+            .fetch_version("version-1", |_| {})
+            .await
+            .unwrap();
+        assert_eq!(dump, r#"{"Classes": []}"#);
+        assert_eq!(version, "version-1");
+
+        // Running out of attempts reports the last failure
+        let url = serve(vec![UNAVAILABLE, UNAVAILABLE, UNAVAILABLE]);
+        let error = fetcher(url.clone())
+            .fetch_version("version-1", |_| {})
+            .await
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "{}/version-1-API-Dump.json answered 503 Service Unavailable, server is having trouble",
+                url
+            )
+        );
+
+        // Missing files aren't asked for again, the next response would be the dump otherwise
+        let url = serve(vec![NOT_FOUND, DUMP]);
+        let error = fetcher(url)
+            .fetch_version("version-1", |_| {})
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<FetchError>(),
+            Some(FetchError::Client(status, _)) if status.as_u16() == 404
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_connect_error() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        drop(listener);
+        let error = fetcher(url)
+            .fetch_version("version-1", |_| {})
+            .await
+            .unwrap_err();
+        let error = error.downcast_ref::<FetchError>().unwrap();
+        assert!(matches!(error, FetchError::Connect(_)));
+        assert!(error.is_transient());
+    }
+}
@@ -1,51 +1,1012 @@
+use crate::api_fetcher::{ApiFetcher, DownloadProgress, FetchedDump};
 use crate::api_parser::{
-    cache_file, download_api_with_version, get_cache, parse_api_dump, ParsedInstance,
+    bundled_cache, cache_dir, cache_file, get_cache, intern_members, local_cache, merge_defaults,
+    parse_api_docs, parse_api_dump, parse_data_types, CachedApi, ParsedDataType,
+    ParsedDataTypeMember, ParsedDataTypes, ParsedDocs, ParsedEnumItem, ParsedEnums, ParsedInstance,
+    ParsedInstances, ParsedProperty, SharedProperty, CACHE_FORMAT,
 };
-use std::collections::HashMap;
+use crate::settings::{ApiSettings, CompletionSettings, CompletionSorting, MemberSettings};
+use serde_json::{json, Value};
+use std::{
+    collections::{HashMap, HashSet},
+    future::Future,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tokio::sync::RwLock;
+use tokio_util::sync::CancellationToken;
+
+// Runs operation until it finishes, gets cancelled or runs out of time(Whichever comes first)
+async fn run_bounded<T>(
+    operation: impl Future<Output = Result<T, Box<dyn std::error::Error + Send + Sync>>>,
+    cancel: &CancellationToken,
+    timeout: Duration,
+    what: &str,
+) -> Result<T, Box<dyn std::error::Error + Send + Sync>> {
+    tokio::select! {
+        result = operation => result,
+        _ = cancel.cancelled() => Err(format!("{} cancelled", what).into()),
+        _ = tokio::time::sleep(timeout) => {
+            Err(format!("{} timed out after {:?}", what, timeout).into())
+        }
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|since| since.as_secs())
+        .unwrap_or(0)
+}
+
+// Caches that don't know when they were fetched(Written by older formats) are always stale
+fn is_stale(fetched_at: u64, max_age: Option<Duration>, now: u64) -> bool {
+    max_age.is_some_and(|max_age| now.saturating_sub(fetched_at) > max_age.as_secs())
+}
+
+// Stage of an API download, callers forward these as work done progress
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ApiStage {
+    Downloading(DownloadProgress),
+    Parsing,
+    Caching,
+}
+
+impl ApiStage {
+    pub fn message(&self) -> &'static str {
+        match self {
+            Self::Downloading(_) => "Downloading API dump…",
+            Self::Parsing => "Parsing…",
+            Self::Caching => "Caching…",
+        }
+    }
+
+    // Dump download fills most of the bar, parsing and caching take the rest
+    pub fn percentage(&self) -> Option<u32> {
+        match self {
+            Self::Downloading(progress) => progress.percentage().map(|p| p * 8 / 10),
+            Self::Parsing => Some(80),
+            Self::Caching => Some(95),
+        }
+    }
+}
+
+type ParsedApi = (ParsedInstances, ParsedEnums, ParsedDocs, ParsedDataTypes);
+
+// Dump plus whatever of defaults and docs downloaded, the latter two are skipped when they fail
+fn parse_fetched(
+    dump: &str,
+    full_dump: Result<String, Box<dyn std::error::Error + Send + Sync>>,
+    docs: Result<String, Box<dyn std::error::Error + Send + Sync>>,
+) -> Result<ParsedApi, serde_json::Error> {
+    let (mut parsed_instances, parsed_enums) = parse_api_dump(dump)?;
+    match full_dump.map(|full_dump| merge_defaults(&mut parsed_instances, &full_dump)) {
+        Ok(Ok(())) => {}
+        Ok(Err(e)) => eprintln!("Failed to parse property defaults ({}), skipping them", e),
+        Err(e) => eprintln!(
+            "Failed to download property defaults ({}), skipping them",
+            e
+        ),
+    }
+    let parsed = docs.map(|docs| {
+        parse_api_docs(&docs)
+            .and_then(|parsed_docs| Ok((parsed_docs, parse_data_types(&docs, &parsed_instances)?)))
+    });
+    let (parsed_docs, parsed_data_types) = match parsed {
+        Ok(Ok(parsed)) => parsed,
+        Ok(Err(e)) => {
+            eprintln!("Failed to parse API docs ({}), skipping them", e);
+            Default::default()
+        }
+        Err(e) => {
+            eprintln!("Failed to download API docs ({}), skipping them", e);
+            Default::default()
+        }
+    };
+    Ok((
+        parsed_instances,
+        parsed_enums,
+        parsed_docs,
+        parsed_data_types,
+    ))
+}
+
+// Parses a downloaded dump into a cache(With defaults and docs when those downloads work out) and writes it
+async fn build_cache(
+    cache_dir: &Path,
+    fetcher: &ApiFetcher,
+    (dump, version, validators): FetchedDump,
+    cancel: &CancellationToken,
+    timeout: Duration,
+    on_stage: &mut (impl FnMut(ApiStage) + Send),
+) -> Result<CachedApi, Box<dyn std::error::Error + Send + Sync>> {
+    let full_dump = run_bounded(
+        fetcher.fetch_full_dump(),
+        cancel,
+        timeout,
+        "API defaults download",
+    )
+    .await;
+    let docs = run_bounded(
+        fetcher.fetch_api_docs(),
+        cancel,
+        timeout,
+        "API docs download",
+    )
+    .await;
+
+    on_stage(ApiStage::Parsing);
+    // Dumps are tens of megabytes of json, so parsing them stays off the runtime's threads
+    let (parsed_instances, parsed_enums, parsed_docs, parsed_data_types) =
+        tokio::task::spawn_blocking(move || parse_fetched(&dump, full_dump, docs)).await??;
+
+    // Parsing takes a moment too, don't overwrite cache if user gave up in the meantime
+    if cancel.is_cancelled() {
+        return Err("API download cancelled".into());
+    }
+
+    let cache = CachedApi {
+        version,
+        instances: parsed_instances,
+        enums: parsed_enums,
+        format: CACHE_FORMAT,
+        docs: parsed_docs,
+        data_types: parsed_data_types,
+        validators,
+        fetched_at: unix_now(),
+    };
+    on_stage(ApiStage::Caching);
+    cache_file(cache_dir, &cache)?;
+    Ok(cache)
+}
+
+// Asks whether the latest dump is still the cached one, returning the new cache when it isn't
+async fn revalidate_cache(
+    cache_dir: &Path,
+    mut cache: CachedApi,
+    fetcher: &ApiFetcher,
+    cancel: &CancellationToken,
+    timeout: Duration,
+    on_stage: &mut (impl FnMut(ApiStage) + Send),
+) -> Result<Option<CachedApi>, Box<dyn std::error::Error + Send + Sync>> {
+    let fetch = fetcher.fetch_if_modified(&cache.version, &cache.validators, |progress| {
+        on_stage(ApiStage::Downloading(progress))
+    });
+    let fetched = run_bounded(fetch, cancel, timeout, "API revalidation").await?;
+    match fetched {
+        Some(fetched) => Ok(Some(
+            build_cache(cache_dir, fetcher, fetched, cancel, timeout, on_stage).await?,
+        )),
+        None => {
+            cache.fetched_at = unix_now();
+            cache_file(cache_dir, &cache)?;
+            Ok(None)
+        }
+    }
+}
+
+// Scores for fuzzy_score, per matched char plus whichever of the rest apply to it
+const MATCH_SCORE: i32 = 16;
+const START_BONUS: i32 = 24; // First char of text
+const HUMP_BONUS: i32 = 12; // Start of a camelCase word, digit run or after a separator
+const CONSECUTIVE_BONUS: i32 = 10; // Right after the previous match
+const GAP_PENALTY: i32 = 3; // Skipping ahead from the previous match
+const PREFIX_BONUS: i32 = 24; // Whole pattern is where text starts
+const EXACT_BONUS: i32 = 40; // Pattern is all of text
+
+// Usage and class tier on top of match scores, so they decide between similar matches but can't
+// lift a hump match over an exact one
+const FREQ_SCORE: i32 = 6;
+const FREQ_SCORE_CAP: usize = 10;
+const TIER_PENALTY: i32 = 8;
+
+// Case insensitive fuzzy match of pattern against text, None unless its chars appear in text in order
+// (So "txl" matches TextLabel, with "Text" matching TextLabel's start scoring above SelectionText's hump)
+pub(crate) fn fuzzy_score(pattern: &str, text: &str) -> Option<i32> {
+    let pattern: Vec<char> = pattern.chars().map(|c| c.to_ascii_lowercase()).collect();
+    let Some((&first, rest)) = pattern.split_first() else {
+        return Some(0);
+    };
+    let chars: Vec<char> = text.chars().collect();
+    let lower: Vec<char> = chars.iter().map(char::to_ascii_lowercase).collect();
+    let bonus = |j: usize| {
+        let (c, prev) = (chars[j], j.checked_sub(1).map(|i| chars[i]));
+        let next_lower = chars.get(j + 1).is_some_and(|next| next.is_lowercase());
+        match prev {
+            None => START_BONUS,
+            Some(prev) if !prev.is_alphanumeric() => HUMP_BONUS,
+            Some(prev) if c.is_uppercase() && (prev.is_lowercase() || prev.is_ascii_digit()) => {
+                HUMP_BONUS
+            }
+            // Last capital of an acronym starting a word(The G of UIGridLayout)
+            Some(prev) if c.is_uppercase() && prev.is_uppercase() && next_lower => HUMP_BONUS,
+            Some(prev) if c.is_ascii_digit() && !prev.is_ascii_digit() => HUMP_BONUS,
+            _ => 0,
+        }
+    };
+
+    // Best score of the pattern so far with its last char matched at each index of text
+    let mut scores: Vec<Option<i32>> = (0..chars.len())
+        .map(|j| (lower[j] == first).then(|| MATCH_SCORE + bonus(j) - j.min(10) as i32))
+        .collect();
+    for &c in rest {
+        let mut best_before = None; // Best score ending at least two chars back
+        let mut next = vec![None; chars.len()];
+        for j in 0..chars.len() {
+            if j >= 2 {
+                best_before = best_before.max(scores[j - 2]);
+            }
+            if lower[j] == c {
+                let consecutive = j
+                    .checked_sub(1)
+                    .and_then(|i| scores[i])
+                    .map(|score| score + CONSECUTIVE_BONUS);
+                let gapped = best_before.map(|score| score - GAP_PENALTY);
+                next[j] = consecutive
+                    .max(gapped)
+                    .map(|score| score + MATCH_SCORE + bonus(j));
+            }
+        }
+        scores = next;
+    }
+
+    let mut score = scores.into_iter().flatten().max()?;
+    if lower.starts_with(&pattern) {
+        score += PREFIX_BONUS;
+        if lower.len() == pattern.len() {
+            score += EXACT_BONUS;
+        }
+    }
+    Some(score)
+}
+
+// Case insensitive check that pattern's chars appear in text in order(So "txl" matches TextLabel)
+pub(crate) fn is_subsequence(pattern: &str, text: &str) -> bool {
+    let pattern_lower = pattern.to_lowercase();
+    let mut pattern_chars = pattern_lower.chars();
+    let mut current_char = pattern_chars.next();
+
+    for c in text.to_lowercase().chars() {
+        if Some(c) == current_char {
+            current_char = pattern_chars.next();
+            if current_char.is_none() {
+                return true;
+            }
+        }
+    }
+    current_char.is_none()
+}
 
 #[derive(Debug)]
 pub struct ApiManager {
     instances: Option<HashMap<String, ParsedInstance>>,
     names: Option<Vec<String>>,
+    // Hierarchy precomputed on every (re)load, class -> itself and its superclasses / direct subclasses
+    chains: HashMap<String, Vec<String>>,
+    subclasses: HashMap<String, Vec<String>>,
+    enums: ParsedEnums,
+    docs: ParsedDocs, // "Class" / "Class.Member" -> official documentation
+    data_types: ParsedDataTypes,
+    version: Option<String>, // Studio version hash the loaded API was built from
+    pinned_version: Option<String>, // Downloads fetch this hash instead of the latest one
+    max_age: Option<Duration>, // Loading revalidates caches older than this, None never does
+    fetched_at: Option<u64>, // Unix time loaded cache was last checked against the server
+    dump_path: Option<PathBuf>, // Loading and downloading parse this instead when set
+    cache_dir: PathBuf,      // Where the cache and usage counts are read and written
     pub freq_lookup: HashMap<String, usize>,
+    freq_names: HashSet<String>, // Class and property names freq_lookup tracks, rebuilt with names
+    // Class name -> properties hidden from completions(Also applies to subclasses)
+    excluded_properties: HashMap<String, Vec<String>>,
+    member_settings: MemberSettings, // Which tagged members completions offer
+    class_bases: Vec<String>,        // Offered class names descend from one of these(Empty is any)
+    // Source file -> classes defined there(Own members only), merged on top of the API
+    custom_classes: HashMap<PathBuf, Vec<ParsedInstance>>,
+    custom_names: HashSet<String>, // Currently merged custom class names
+    // Ignores freq_lookup when ranking, so tests get the same order no matter what was typed before
+    deterministic_ranking: bool,
+    deterministic_forced: bool, // Stays on whatever settings say(Test harnesses)
+    sorting: CompletionSorting,
 }
 
+// Bumped on any breaking change to usage_report's shape
+pub const USAGE_REPORT_SCHEMA_VERSION: u32 = 1;
+
 impl ApiManager {
     pub fn new() -> Self {
         Self {
             instances: None,
             names: None,
+            chains: HashMap::new(),
+            subclasses: HashMap::new(),
+            enums: ParsedEnums::new(),
+            docs: ParsedDocs::new(),
+            data_types: ParsedDataTypes::new(),
+            version: None,
+            pinned_version: None,
+            max_age: ApiSettings::default().max_age(),
+            fetched_at: None,
+            dump_path: None,
+            cache_dir: cache_dir(None),
             freq_lookup: HashMap::new(),
+            freq_names: HashSet::new(),
+            excluded_properties: HashMap::new(),
+            member_settings: MemberSettings::default(),
+            class_bases: CompletionSettings::default().class_bases,
+            custom_classes: HashMap::new(),
+            custom_names: HashSet::new(),
+            deterministic_ranking: false,
+            deterministic_forced: false,
+            sorting: CompletionSorting::default(),
+        }
+    }
+
+    pub fn with_forced_deterministic_ranking(self, forced: bool) -> Self {
+        Self {
+            deterministic_forced: forced,
+            ..self
         }
     }
 
-    // This downloads and caches new api file, which then gets loaded
-    pub async fn download_api(&mut self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let (dump, version) = download_api_with_version().await?;
-        let parsed_instances = parse_api_dump(&dump)?;
+    pub fn set_deterministic_ranking(&mut self, enabled: bool) {
+        self.deterministic_ranking = enabled;
+    }
 
-        cache_file(&parsed_instances, &version)?;
-        self.names = Some(parsed_instances.keys().cloned().collect());
-        self.instances = Some(parsed_instances);
+    pub fn freq_of(&self, name: &str) -> usize {
+        if self.deterministic_ranking || self.deterministic_forced {
+            return 0;
+        }
+        self.freq_lookup.get(name).copied().unwrap_or(0)
+    }
+
+    fn rank(&self, a: &str, b: &str) -> std::cmp::Ordering {
+        self.freq_of(b)
+            .cmp(&self.freq_of(a)) // First by freq
+            .then_with(|| b.len().cmp(&a.len())) // Then by length(Longer text is annoying to type)
+            .then_with(|| a.cmp(b)) // Then by lex as tie breaker
+    }
+
+    pub fn set_excluded_properties(&mut self, excluded: HashMap<String, Vec<String>>) {
+        self.excluded_properties = excluded;
+    }
+
+    pub fn set_sorting(&mut self, sorting: CompletionSorting) {
+        self.sorting = sorting;
+    }
+
+    // Orders members the way completions offer them(API order is what instances already store)
+    fn sort_members(&self, members: &mut [(String, String)]) {
+        match self.sorting {
+            CompletionSorting::Frequency => members.sort_by(|a, b| self.rank(&a.0, &b.0)),
+            CompletionSorting::Alphabetical => members.sort_by(|a, b| a.0.cmp(&b.0)),
+            CompletionSorting::ApiOrder => {}
+        }
+    }
+
+    pub fn set_member_settings(&mut self, member_settings: MemberSettings) {
+        self.member_settings = member_settings;
+    }
+
+    pub fn set_class_bases(&mut self, class_bases: Vec<String>) {
+        self.class_bases = class_bases;
+    }
+
+    // Whether createElement can make the class, custom ones are always kept since they were declared on purpose
+    fn is_offered_class(&self, instances: &ParsedInstances, name: &str) -> bool {
+        if self.custom_names.contains(name) {
+            return true;
+        }
+        let Some(instance) = instances.get(name) else {
+            return false;
+        };
+        let is_creatable = instance.tags.iter().all(|tag| match tag.as_str() {
+            "Deprecated" => self.member_settings.include_deprecated,
+            _ => false, // NotCreatable, Service
+        });
+        is_creatable
+            && (self.class_bases.is_empty()
+                || self.class_bases.iter().any(|base| self.is_a(name, base)))
+    }
+
+    // Whether member's tags keep it out of completions under current settings
+    fn is_filtered_out(&self, member: &ParsedProperty, allow_read_only: bool) -> bool {
+        let settings = self.member_settings;
+        member.tags.iter().any(|tag| match tag.as_str() {
+            "Deprecated" => !settings.include_deprecated,
+            "ReadOnly" => !settings.include_read_only && !allow_read_only,
+            "Hidden" => !settings.include_hidden,
+            _ => false,
+        })
+    }
+
+    // Walks instance and its superclasses, checking if any of them hides the property
+    fn is_property_excluded(&self, inst_name: &str, prop_name: &str) -> bool {
+        if self.excluded_properties.is_empty() {
+            return false;
+        }
+        self.chains.get(inst_name).is_some_and(|chain| {
+            chain.iter().any(|name| {
+                self.excluded_properties
+                    .get(name)
+                    .is_some_and(|props| props.iter().any(|p| p == prop_name))
+            })
+        })
+    }
+
+    // Same as lookup_properties check but ignores exclusions, hidden properties are still valid ones
+    pub fn has_property(&self, inst_name: &str, prop_name: &str) -> Option<bool> {
+        let instance = self.instances.as_ref()?.get(inst_name)?;
+        Some(instance.properties.iter().any(|p| p.name == prop_name))
+    }
+
+    // Class itself followed by all of its superclasses
+    pub fn superclass_chain(&self, inst_name: &str) -> Vec<String> {
+        self.chains.get(inst_name).cloned().unwrap_or_default()
+    }
+
+    // Whether child is ancestor or inherits from it(False for unknown classes)
+    pub fn is_a(&self, child: &str, ancestor: &str) -> bool {
+        self.chains
+            .get(child)
+            .is_some_and(|chain| chain.iter().any(|class| class == ancestor))
+    }
+
+    // Every class inheriting from name, directly or not, sorted
+    pub fn subclasses_of(&self, name: &str) -> Vec<String> {
+        let mut found = Vec::new();
+        let mut pending = vec![name];
+        while let Some(current) = pending.pop() {
+            for child in self.subclasses.get(current).into_iter().flatten() {
+                // Custom classes can (wrongly) extend each other in a cycle
+                if !found.contains(child) {
+                    found.push(child.clone());
+                    pending.push(child);
+                }
+            }
+        }
+        found.sort();
+        found
+    }
+
+    fn rebuild_hierarchy(&mut self) {
+        self.chains.clear();
+        self.subclasses.clear();
+        let Some(instances) = self.instances.as_ref() else {
+            return;
+        };
+
+        for (name, inst) in instances {
+            let mut chain = Vec::new();
+            let mut current = Some(inst);
+            // Bounded by class count, so a (broken) cyclic custom class can't hang
+            while let Some(inst) = current.filter(|_| chain.len() <= instances.len()) {
+                chain.push(inst.instance.clone());
+                current = instances.get(&inst.superclass);
+            }
+            self.chains.insert(name.clone(), chain);
+            if instances.contains_key(&inst.superclass) {
+                self.subclasses
+                    .entry(inst.superclass.clone())
+                    .or_default()
+                    .push(name.clone());
+            }
+        }
+    }
+
+    // (Type, class that first declares it) of a property or event, walking up the superclass chain
+    pub fn member_info(
+        &self,
+        inst_name: &str,
+        member_name: &str,
+        is_event: bool,
+    ) -> Option<(String, String)> {
+        if is_event {
+            self.declared_member(inst_name, member_name, |inst| &inst.events)
+        } else {
+            self.declared_member(inst_name, member_name, |inst| &inst.properties)
+        }
+    }
+
+    // Why scripts can't set a property(NotScriptable, ReadOnly or the security level it needs), None if they can
+    pub fn property_restriction(&self, inst_name: &str, prop_name: &str) -> Option<String> {
+        let instance = self.instances.as_ref()?.get(inst_name)?;
+        if let Some(restricted) = instance.restricted.iter().find(|r| r.name == prop_name) {
+            return Some(restricted.reason.clone());
+        }
+        let property = instance.properties.iter().find(|p| p.name == prop_name)?;
+        property
+            .tags
+            .iter()
+            .any(|tag| tag == "ReadOnly")
+            .then(|| "ReadOnly".to_string())
+    }
+
+    // Inherited properties carry their default along, so the class itself is enough
+    pub fn property_default(&self, inst_name: &str, prop_name: &str) -> Option<String> {
+        let instance = self.instances.as_ref()?.get(inst_name)?;
+        let property = instance.properties.iter().find(|p| p.name == prop_name)?;
+        property.default.clone()
+    }
+
+    // Official docs of a class, or of one of its members(Looked up where the member is declared)
+    pub fn documentation(&self, inst_name: &str, member_name: Option<&str>) -> Option<&str> {
+        let key = match member_name {
+            Some(member_name) => format!("{}.{}", inst_name, member_name),
+            None => inst_name.to_string(),
+        };
+        self.docs.get(&key).map(String::as_str)
+    }
+
+    #[cfg(test)]
+    pub fn set_docs(&mut self, docs: ParsedDocs) {
+        self.docs = docs;
+    }
+
+    // Constructors, properties and methods of a datatype like UDim2
+    pub fn data_type(&self, type_name: &str) -> Option<&ParsedDataType> {
+        self.data_types.get(type_name)
+    }
+
+    // Member of a datatype by name, constructors first since those are what values get written with
+    pub fn data_type_member(
+        &self,
+        type_name: &str,
+        member_name: &str,
+    ) -> Option<&ParsedDataTypeMember> {
+        let data_type = self.data_type(type_name)?;
+        data_type
+            .constructors
+            .iter()
+            .chain(&data_type.properties)
+            .chain(&data_type.methods)
+            .find(|member| member.name == member_name)
+    }
+
+    #[cfg(test)]
+    pub fn set_data_types(&mut self, data_types: ParsedDataTypes) {
+        self.data_types = data_types;
+    }
+
+    // Same as member_info, for functions and callbacks(Type is their signature)
+    pub fn method_info(&self, inst_name: &str, method_name: &str) -> Option<(String, String)> {
+        self.declared_member(inst_name, method_name, |inst| &inst.methods)
+    }
+
+    fn declared_member(
+        &self,
+        inst_name: &str,
+        member_name: &str,
+        members: fn(&ParsedInstance) -> &[SharedProperty],
+    ) -> Option<(String, String)> {
+        let instances = self.instances.as_ref()?;
+        let find = |inst: &ParsedInstance| {
+            members(inst)
+                .iter()
+                .find(|m| m.name == member_name)
+                .cloned()
+        };
+
+        let mut declared_in = instances.get(inst_name)?;
+        let member = find(declared_in)?;
+        while let Some(parent) = instances.get(&declared_in.superclass) {
+            if find(parent).is_none() {
+                break;
+            }
+            declared_in = parent;
+        }
+        Some((member.data_type.clone(), declared_in.instance.clone()))
+    }
+
+    // Whether any API got loaded yet(Custom classes alone don't count)
+    pub fn is_loaded(&self) -> bool {
+        self.instances.is_some()
+    }
+
+    pub fn api_version(&self) -> Option<&str> {
+        self.version.as_deref()
+    }
+
+    pub fn pinned_version(&self) -> Option<&str> {
+        self.pinned_version.as_deref()
+    }
+
+    pub fn set_pinned_version(&mut self, version: Option<String>) {
+        self.pinned_version = version;
+    }
+
+    pub fn set_max_age(&mut self, max_age: Option<Duration>) {
+        self.max_age = max_age;
+    }
+
+    pub fn set_dump_path(&mut self, dump_path: Option<PathBuf>) {
+        self.dump_path = dump_path;
+    }
+
+    // None goes back to the platform cache directory
+    pub fn set_cache_dir(&mut self, dir: Option<PathBuf>) {
+        self.cache_dir = cache_dir(dir);
+    }
+
+    pub fn cache_dir(&self) -> &Path {
+        &self.cache_dir
+    }
+
+    // Loaded, pinned and latest version(None when it couldn't be checked), returned by the apiVersion command
+    pub fn version_info(&self, latest_version: Option<&str>) -> Value {
+        let target = self.pinned_version().or(latest_version);
+        json!({
+            "version": self.api_version(),
+            "pinnedVersion": self.pinned_version,
+            "latestVersion": latest_version,
+            "upToDate": target.map(|target| self.api_version() == Some(target)),
+        })
+    }
+
+    // Summary of what's loaded, returned by the apiInfo command
+    pub fn api_info(&self) -> Value {
+        json!({
+            "version": self.version,
+            "classCount": self.instances.as_ref().map(|i| i.len()).unwrap_or(0),
+            "excludedProperties": self.excluded_properties,
+        })
+    }
+
+    // Counts of what's loaded, the stats command adds file and document ones on top
+    pub fn stats(&self) -> Value {
+        let instances = self
+            .instances
+            .iter()
+            .flat_map(|instances| instances.values());
+        let (properties, events) = instances.clone().fold((0, 0), |(properties, events), i| {
+            (properties + i.properties.len(), events + i.events.len())
+        });
+        json!({
+            "version": self.version,
+            "classCount": instances.count(),
+            "propertyCount": properties,
+            "eventCount": events,
+            "enumCount": self.enums.len(),
+            "customClassCount": self.custom_names.len(),
+            "cacheAgeSeconds": self.fetched_at.map(|fetched_at| unix_now().saturating_sub(fetched_at)),
+            "frequencyTableSize": self.freq_lookup.len(),
+        })
+    }
+
+    // Usage counts completions rank by, most used first(See schemas/usage-report.v1.schema.json)
+    pub fn usage_report(&self) -> Value {
+        let mut usage: Vec<(&String, &usize)> = self
+            .freq_lookup
+            .iter()
+            .filter(|(_, &count)| count > 0)
+            .collect();
+        usage.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+        json!({
+            "schemaVersion": USAGE_REPORT_SCHEMA_VERSION,
+            "apiVersion": self.version,
+            "usage": usage
+                .into_iter()
+                .map(|(name, count)| json!({ "name": name, "count": count }))
+                .collect::<Vec<_>>(),
+        })
+    }
+
+    // Readable parsed data of one class plus its hierarchy, returned by readCache's single class mode
+    pub fn class_json(&self, inst_name: &str) -> Option<Value> {
+        let instance = self.instances.as_ref()?.get(inst_name)?;
+        let mut value = serde_json::to_value(instance).ok()?;
+        value["superclassChain"] = json!(self.superclass_chain(inst_name));
+        value["subclasses"] = json!(self.subclasses_of(inst_name));
+        Some(value)
+    }
+
+    // This downloads and caches new api file(Pinned version if set), which then gets swapped in
+    // Lock is only taken to read settings and to swap, so completions keep working meanwhile
+    pub async fn download_api(
+        api_manager: &RwLock<Self>,
+        fetcher: &ApiFetcher,
+        cancel: &CancellationToken,
+        timeout: Duration,
+        mut on_stage: impl FnMut(ApiStage) + Send,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let (pinned_version, dump_path, cache_dir) = {
+            let api_manager = api_manager.read().await;
+            (
+                api_manager.pinned_version.clone(),
+                api_manager.dump_path.clone(),
+                api_manager.cache_dir.clone(),
+            )
+        };
+        if let Some(path) = dump_path {
+            Self::load_dump_file(api_manager, path, cancel, timeout, on_stage).await?;
+            return Ok(());
+        }
+        let fetch = async {
+            let on_progress = |progress| on_stage(ApiStage::Downloading(progress));
+            match &pinned_version {
+                Some(version) => fetcher.fetch_version(version, on_progress).await,
+                None => fetcher.fetch(on_progress).await,
+            }
+        };
+        let fetched = run_bounded(fetch, cancel, timeout, "API download").await?;
+        let cache =
+            build_cache(&cache_dir, fetcher, fetched, cancel, timeout, &mut on_stage).await?;
+        api_manager.write().await.set_cache(cache);
 
         Ok(())
     }
 
     // This loads api from cached file, returns the cached version string so the
     // caller can compare it against the live version and prompt for updates if needed
-    pub async fn load_api(&mut self) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
-        let cache = get_cache()?.ok_or("Failed to load api from cache!")?;
+    // A cache older than max age gets revalidated(Serving completions meanwhile), kept if that fails
+    // Without a cache it downloads one, serving the bundled snapshot(Version BUNDLED_VERSION) in the
+    // meantime and keeping it if the download fails
+    // A set dump path skips all of that and just parses the file
+    pub async fn load_api(
+        api_manager: &RwLock<Self>,
+        fetcher: &ApiFetcher,
+        cancel: &CancellationToken,
+        timeout: Duration,
+        mut on_stage: impl FnMut(ApiStage) + Send,
+    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        let (pinned_version, max_age, dump_path, cache_dir) = {
+            let api_manager = api_manager.read().await;
+            (
+                api_manager.pinned_version.clone(),
+                api_manager.max_age,
+                api_manager.dump_path.clone(),
+                api_manager.cache_dir.clone(),
+            )
+        };
+        if let Some(path) = dump_path {
+            return Self::load_dump_file(api_manager, path, cancel, timeout, on_stage).await;
+        }
+        // Cache read is blocking file io, so move it off the runtime to keep it bounded
+        let cached = run_bounded(
+            async {
+                let dir = cache_dir.clone();
+                tokio::task::spawn_blocking(move || get_cache(&dir)).await?
+            },
+            cancel,
+            timeout,
+            "API cache load",
+        )
+        .await;
+        let cache = match cached {
+            Ok(Some(cache)) => cache,
+            Ok(None) => {
+                return Self::load_without_cache(api_manager, fetcher, cancel, timeout, on_stage)
+                    .await
+            }
+            Err(e) => {
+                eprintln!("Failed to load API cache ({})", e);
+                return Self::load_without_cache(api_manager, fetcher, cancel, timeout, on_stage)
+                    .await;
+            }
+        };
 
         let version = cache.version.clone();
-        self.instances = Some(cache.instances);
-        self.names = self
-            .instances
-            .as_ref()
-            .map(|map| map.keys().cloned().collect());
+        // Pinned versions never change, so there's nothing to revalidate
+        if !is_stale(cache.fetched_at, max_age, unix_now()) || pinned_version.is_some() {
+            api_manager.write().await.set_cache(cache);
+            return Ok(version);
+        }
+        api_manager.write().await.set_cache(cache.clone());
+        match revalidate_cache(&cache_dir, cache, fetcher, cancel, timeout, &mut on_stage).await {
+            Ok(Some(fresh)) => {
+                let version = fresh.version.clone();
+                api_manager.write().await.set_cache(fresh);
+                Ok(version)
+            }
+            Ok(None) => Ok(version),
+            Err(e) => {
+                eprintln!("Failed to revalidate API cache ({}), using cached copy", e);
+                Ok(version)
+            }
+        }
+    }
+
+    async fn load_without_cache(
+        api_manager: &RwLock<Self>,
+        fetcher: &ApiFetcher,
+        cancel: &CancellationToken,
+        timeout: Duration,
+        on_stage: impl FnMut(ApiStage) + Send,
+    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        // Not written to disk, so the next start tries downloading again
+        let bundled = tokio::task::spawn_blocking(bundled_cache).await??;
+        let bundled_version = bundled.version.clone();
+        api_manager.write().await.set_cache(bundled);
+        match Self::download_api(api_manager, fetcher, cancel, timeout, on_stage).await {
+            Ok(()) => Ok(api_manager.read().await.version.clone().unwrap_or_default()),
+            Err(e) => {
+                eprintln!("Failed to download API ({}), using bundled snapshot", e);
+                Ok(bundled_version)
+            }
+        }
+    }
 
+    // Parses a local API-Dump.json and swaps it in(Version LOCAL_VERSION_PREFIX + path), never cached
+    // since the file itself is what later loads read, so editing it and reloading picks up changes
+    pub async fn load_dump_file(
+        api_manager: &RwLock<Self>,
+        path: PathBuf,
+        cancel: &CancellationToken,
+        timeout: Duration,
+        mut on_stage: impl FnMut(ApiStage) + Send,
+    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        on_stage(ApiStage::Parsing);
+        let cache = run_bounded(
+            async { tokio::task::spawn_blocking(move || local_cache(&path)).await? },
+            cancel,
+            timeout,
+            "API dump load",
+        )
+        .await?;
+        let version = cache.version.clone();
+        api_manager.write().await.set_cache(cache);
         Ok(version)
     }
 
+    fn set_cache(&mut self, cache: CachedApi) {
+        self.set_instances(cache.instances);
+        self.enums = cache.enums;
+        self.docs = cache.docs;
+        self.data_types = cache.data_types;
+        self.version = Some(cache.version);
+        // Bundled and local dumps were never fetched
+        self.fetched_at = (cache.fetched_at > 0).then_some(cache.fetched_at);
+    }
+
+    fn set_instances(&mut self, mut instances: ParsedInstances) {
+        intern_members(instances.values_mut());
+        self.instances = Some(instances);
+        self.custom_names.clear();
+        self.merge_custom_classes();
+    }
+
+    // Replaces classes coming from source(Empty list removes them), returns names of every class that changed
+    pub fn set_custom_classes(
+        &mut self,
+        source: PathBuf,
+        classes: Vec<ParsedInstance>,
+    ) -> Vec<String> {
+        let mut affected: Vec<String> = self
+            .custom_classes
+            .remove(&source)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|class| class.instance)
+            .collect();
+        affected.extend(classes.iter().map(|class| class.instance.clone()));
+        if !classes.is_empty() {
+            self.custom_classes.insert(source, classes);
+        }
+
+        self.merge_custom_classes();
+        affected.sort();
+        affected.dedup();
+        affected
+    }
+
+    // Only custom classes get rebuilt, so this stays cheap compared to reloading whole API
+    fn merge_custom_classes(&mut self) {
+        let Some(instances) = self.instances.as_mut() else {
+            return;
+        };
+        for name in self.custom_names.drain() {
+            instances.remove(&name);
+        }
+
+        // Sorted by source so the same name defined twice always resolves the same way
+        let mut sources: Vec<&PathBuf> = self.custom_classes.keys().collect();
+        sources.sort();
+        let mut pending: Vec<&ParsedInstance> = sources
+            .into_iter()
+            .flat_map(|source| &self.custom_classes[source])
+            .filter(|class| !instances.contains_key(&class.instance)) // Never shadow real classes
+            .collect();
+
+        // Custom classes can extend each other, so keep going until superclasses are in place
+        while !pending.is_empty() {
+            let ready = pending
+                .iter()
+                .position(|class| {
+                    class.superclass.is_empty()
+                        || instances.contains_key(&class.superclass)
+                        || !pending
+                            .iter()
+                            .any(|other| other.instance == class.superclass)
+                })
+                .unwrap_or(0);
+            let class = pending.remove(ready);
+            if self.custom_names.contains(&class.instance) {
+                continue;
+            }
+
+            let mut merged = class.clone();
+            if let Some(parent) = instances.get(&class.superclass) {
+                for property in &parent.properties {
+                    if !merged.properties.iter().any(|p| p.name == property.name) {
+                        merged.properties.push(property.clone());
+                    }
+                }
+                for event in &parent.events {
+                    if !merged.events.iter().any(|e| e.name == event.name) {
+                        merged.events.push(event.clone());
+                    }
+                }
+                for method in &parent.methods {
+                    if !merged.methods.iter().any(|m| m.name == method.name) {
+                        merged.methods.push(method.clone());
+                    }
+                }
+                for restricted in &parent.restricted {
+                    if !merged.restricted.iter().any(|r| r.name == restricted.name) {
+                        merged.restricted.push(restricted.clone());
+                    }
+                }
+            }
+            self.custom_names.insert(class.instance.clone());
+            instances.insert(class.instance.clone(), merged);
+        }
+
+        self.freq_names = instances
+            .iter()
+            .flat_map(|(name, inst)| {
+                std::iter::once(name).chain(inst.properties.iter().map(|p| &p.name))
+            })
+            .cloned()
+            .collect();
+        self.rebuild_hierarchy();
+        self.names = Some(self.tree_order());
+    }
+
+    // Class names the way API dumps list them, each class followed by its subclasses
+    // (Siblings alphabetical, classes only reachable through a custom class cycle go last)
+    fn tree_order(&self) -> Vec<String> {
+        let Some(instances) = self.instances.as_ref() else {
+            return Vec::new();
+        };
+        let mut pending: Vec<&String> = instances
+            .iter()
+            .filter(|(_, inst)| !instances.contains_key(&inst.superclass))
+            .map(|(name, _)| name)
+            .collect();
+        pending.sort_by(|a, b| b.cmp(a));
+
+        let mut order = Vec::with_capacity(instances.len());
+        let mut visited = HashSet::new();
+        while let Some(name) = pending.pop() {
+            if !visited.insert(name) {
+                continue;
+            }
+            order.push(name.clone());
+            let mut children: Vec<&String> =
+                self.subclasses.get(name).into_iter().flatten().collect();
+            children.sort_by(|a, b| b.cmp(a));
+            pending.extend(children);
+        }
+        let mut rest: Vec<String> = instances
+            .keys()
+            .filter(|name| !visited.contains(name))
+            .cloned()
+            .collect();
+        rest.sort();
+        order.extend(rest);
+        order
+    }
+
+    // Loads the api_dump.json snapshot at the repo root, so tests don't need network access
+    #[cfg(test)]
+    pub fn from_local_dump() -> Self {
+        let dump = std::fs::read_to_string(concat!(env!("CARGO_MANIFEST_DIR"), "/api_dump.json"))
+            .expect("Failed to read api_dump.json!");
+        let (instances, enums) = parse_api_dump(&dump).expect("Failed to parse api_dump.json!");
+        let mut api_manager = Self::new();
+        api_manager.set_instances(instances);
+        api_manager.enums = enums;
+        api_manager
+    }
+
     fn build_word_freq(doc: &str) -> HashMap<String, usize> {
         let mut freq = HashMap::new();
         for word in doc.split(|c: char| !c.is_alphanumeric() && c != '_') {
@@ -56,52 +1017,87 @@ impl ApiManager {
         freq
     }
 
+    #[cfg(test)]
     pub fn update_freq(&mut self, doc: &str) {
-        let word_freq = Self::build_word_freq(doc);
-        let look_up = &mut self.freq_lookup;
-
-        if let Some(instance_list) = self.instances.as_ref() {
-            for (name, inst) in instance_list {
-                if let Some(&count) = word_freq.get(name) {
-                    if count > 0 {
-                        look_up.insert(name.clone(), count);
-                    }
-                }
+        let updates = self.freq_updates(doc);
+        self.apply_freq_updates(Vec::new(), updates);
+    }
 
-                for property in &inst.properties {
-                    let prop_name = &property.name;
-                    if let Some(&count) = word_freq.get(prop_name) {
-                        if count > 0 {
-                            look_up.insert(prop_name.clone(), count);
-                        }
-                    }
-                }
+    // Counts of API names in text, split out of applying them so the scan only needs a read guard
+    // (Walks the words of text, not the API, so small regions stay cheap whatever the dump size)
+    pub fn freq_updates(&self, text: &str) -> Vec<(String, usize)> {
+        Self::build_word_freq(text)
+            .into_iter()
+            .filter(|(word, _)| self.freq_names.contains(word))
+            .collect()
+    }
+
+    // Counts of text a scan re-read come off, so stored ones follow names getting removed too
+    // (A region's old and new counts, or nothing and the counts of a newly opened document)
+    pub fn apply_freq_updates(
+        &mut self,
+        removed: Vec<(String, usize)>,
+        added: Vec<(String, usize)>,
+    ) {
+        for (name, count) in removed {
+            if let Some(stored) = self.freq_lookup.get_mut(&name) {
+                *stored = stored.saturating_sub(count);
             }
         }
+        for (name, count) in added {
+            *self.freq_lookup.entry(name).or_insert(0) += count;
+        }
+    }
+
+    // Counts saved by an earlier session, ones counted since then win if they're higher
+    pub fn restore_freq(&mut self, counts: HashMap<String, usize>) {
+        for (name, count) in counts {
+            let stored = self.freq_lookup.entry(name).or_insert(0);
+            *stored = (*stored).max(count);
+        }
     }
 
     pub fn lookup_properties(&self, inst_name: &str) -> Option<Vec<(String, String)>> {
+        self.collect_properties(inst_name, false)
+    }
+
+    // Properties [React.Change.X] can watch, read-only ones change too(e.g. AbsoluteSize)
+    pub fn lookup_changeable_properties(&self, inst_name: &str) -> Option<Vec<(String, String)>> {
+        self.collect_properties(inst_name, true)
+    }
+
+    fn collect_properties(
+        &self,
+        inst_name: &str,
+        allow_read_only: bool,
+    ) -> Option<Vec<(String, String)>> {
         let instances = self.instances.as_ref()?;
         let instance = instances.get(inst_name)?;
 
         let mut props: Vec<(String, String)> = instance
             .properties
             .iter()
+            .filter(|p| {
+                !self.is_property_excluded(inst_name, &p.name)
+                    && !self.is_filtered_out(p, allow_read_only)
+            })
             .map(|p| (p.name.clone(), p.data_type.clone()))
             .collect();
 
-        props.sort_by(|a, b| {
-            let freq_a = self.freq_lookup.get(&a.0).copied().unwrap_or(0);
-            let freq_b = self.freq_lookup.get(&b.0).copied().unwrap_or(0);
-            freq_b
-                .cmp(&freq_a) // First by freq
-                .then_with(|| b.0.len().cmp(&a.0.len())) // Then by length(Longer text is annoying to type)
-                .then_with(|| a.0.cmp(&b.0)) // Then by lex as tie breaker
-        });
+        self.sort_members(&mut props);
 
         Some(props)
     }
 
+    // Items of an enum(E.g. "Font"), without the Enum. prefix properties carry in their type
+    pub fn lookup_enum(&self, enum_name: &str) -> Option<&[ParsedEnumItem]> {
+        self.enums.get(enum_name).map(Vec::as_slice)
+    }
+
+    pub fn enum_names(&self) -> impl Iterator<Item = &String> {
+        self.enums.keys()
+    }
+
     pub fn lookup_events(&self, inst_name: &str) -> Option<Vec<(String, String)>> {
         let instances = self.instances.as_ref()?;
         let instance = instances.get(inst_name)?;
@@ -109,55 +1105,491 @@ impl ApiManager {
         let mut props: Vec<(String, String)> = instance
             .events
             .iter()
+            .filter(|e| !self.is_filtered_out(e, false))
             .map(|p| (p.name.clone(), p.data_type.clone()))
             .collect();
 
-        props.sort_by(|a, b| {
-            let freq_a = self.freq_lookup.get(&a.0).copied().unwrap_or(0);
-            let freq_b = self.freq_lookup.get(&b.0).copied().unwrap_or(0);
-            freq_b
-                .cmp(&freq_a) // First by freq
-                .then_with(|| b.0.len().cmp(&a.0.len())) // Then by length(Longer text is annoying to type)
-                .then_with(|| a.0.cmp(&b.0)) // Then by lex as tie breaker
-        });
+        self.sort_members(&mut props);
 
         Some(props)
     }
 
     pub fn get_all_inst(&self, index: &str) -> Option<Vec<String>> {
+        let instances = self.instances.as_ref()?;
         self.names.as_ref().map(|names| {
-            let mut filtered: Vec<String> = names
+            let mut filtered: Vec<(String, i32)> = names
                 .iter()
-                .filter(|name| self.is_subsequence(index, name))
-                .cloned()
+                .filter(|name| self.is_offered_class(instances, name))
+                .filter_map(|name| Some((name.clone(), self.class_score(index, name)?)))
                 .collect();
 
-            filtered.sort_by(|a, b| {
-                let freq_a = self.freq_lookup.get(a).copied().unwrap_or(0);
-                let freq_b = self.freq_lookup.get(b).copied().unwrap_or(0);
-                freq_b
-                    .cmp(&freq_a) // First by freq
-                    .then_with(|| b.len().cmp(&a.len())) // Then by length(Longer text is annoying to type)
-                    .then_with(|| a.cmp(b)) // Then by lex as tie breaker
-            });
+            match self.sorting {
+                CompletionSorting::Frequency => {
+                    filtered.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| self.rank(&a.0, &b.0)))
+                }
+                CompletionSorting::Alphabetical => filtered.sort_by(|a, b| a.0.cmp(&b.0)),
+                CompletionSorting::ApiOrder => {} // Names already are
+            }
 
-            filtered
+            filtered.into_iter().map(|(name, _)| name).collect()
         })
     }
 
-    fn is_subsequence(&self, pattern: &str, text: &str) -> bool {
-        let pattern_lower = pattern.to_lowercase();
-        let mut pattern_chars = pattern_lower.chars();
-        let mut current_char = pattern_chars.next();
+    // How well name matches what was typed, raised by usage and lowered by its tier
+    // (Visible GUI before layer collectors/modifiers before the rest)
+    fn class_score(&self, index: &str, name: &str) -> Option<i32> {
+        let usage = self.freq_of(name).min(FREQ_SCORE_CAP) as i32 * FREQ_SCORE;
+        let tier = self.class_tier(name) as i32 * TIER_PENALTY;
+        Some(fuzzy_score(index, name)? + usage - tier)
+    }
 
-        for c in text.to_lowercase().chars() {
-            if Some(c) == current_char {
-                current_char = pattern_chars.next();
-                if current_char.is_none() {
-                    return true;
-                }
-            }
+    // Lower is more likely what an element wants(0 GuiObject, 1 other GuiBase2d/UIBase, 2 anything else)
+    fn class_tier(&self, name: &str) -> usize {
+        if self.is_a(name, "GuiObject") {
+            0
+        } else if self.is_a(name, "GuiBase2d") || self.is_a(name, "UIBase") {
+            1
+        } else {
+            2
         }
-        current_char.is_none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, path::PathBuf, time::Duration};
+    use tokio::sync::RwLock;
+    use tokio_util::sync::CancellationToken;
+
+    use crate::{
+        api_fetcher::{ApiFetcher, DownloadProgress},
+        api_manager::{
+            fuzzy_score, is_stale, run_bounded, ApiManager, ApiStage, USAGE_REPORT_SCHEMA_VERSION,
+        },
+        custom_classes::parse_type_stubs,
+        settings::{CompletionSorting, MemberSettings},
+    };
+
+    async fn never() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        std::future::pending().await
+    }
+
+    #[tokio::test]
+    async fn test_run_bounded_cancel() {
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+        let result = run_bounded(never(), &cancel, Duration::from_secs(60), "Download").await;
+        assert_eq!(result.unwrap_err().to_string(), "Download cancelled");
+    }
+
+    #[tokio::test]
+    async fn test_run_bounded_timeout() {
+        let cancel = CancellationToken::new();
+        let result = run_bounded(never(), &cancel, Duration::from_millis(10), "Download").await;
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Download timed out after 10ms"
+        );
+
+        let result = run_bounded(async { Ok(1) }, &cancel, Duration::from_secs(60), "Load").await;
+        assert_eq!(result.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_load_dump_file() {
+        let path = PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/api_dump.json"));
+        let mut api_manager = ApiManager::new();
+        api_manager.set_dump_path(Some(path.clone()));
+        let api_manager = RwLock::new(api_manager);
+        let fetcher = ApiFetcher::new();
+        let cancel = CancellationToken::new();
+        assert!(!api_manager.read().await.is_loaded());
+
+        // Neither the cache nor the network get touched
+        let mut stages = Vec::new();
+        let version = ApiManager::load_api(
+            &api_manager,
+            &fetcher,
+            &cancel,
+            Duration::from_secs(60),
+            |stage| stages.push(stage),
+        )
+        .await
+        .unwrap();
+        assert_eq!(version, format!("local:{}", path.display()));
+        assert_eq!(stages, [ApiStage::Parsing]);
+        assert!(api_manager.read().await.is_a("TextButton", "GuiObject"));
+        assert!(api_manager.read().await.is_loaded());
+
+        api_manager
+            .write()
+            .await
+            .set_dump_path(Some(PathBuf::from("/nonexistent/API-Dump.json")));
+        let result = ApiManager::download_api(
+            &api_manager,
+            &fetcher,
+            &cancel,
+            Duration::from_secs(60),
+            |_| {},
+        )
+        .await;
+        assert!(result.unwrap_err().to_string().contains("Failed to read"));
+        // Failed reload keeps what was loaded before
+        assert_eq!(
+            api_manager.read().await.api_version(),
+            Some(version.as_str())
+        );
+    }
+
+    #[test]
+    fn test_custom_classes_merge() {
+        let mut api_manager = ApiManager::from_local_dump();
+        let source = PathBuf::from("/project/types.d.luau");
+        let classes = parse_type_stubs(
+            "declare class FancyButton extends TextButton\n    Variant: string\nend\n\
+             declare class FancierButton extends FancyButton\n    Glow: number\nend\n\
+             declare class Frame\n    Broken: number\nend\n",
+        );
+
+        let affected = api_manager.set_custom_classes(source.clone(), classes);
+        assert_eq!(affected, vec!["FancierButton", "FancyButton", "Frame"]);
+        assert_eq!(
+            api_manager.has_property("FancierButton", "Variant"),
+            Some(true)
+        );
+        assert_eq!(
+            api_manager.has_property("FancierButton", "Text"),
+            Some(true)
+        );
+        // Real classes can't be replaced
+        assert_eq!(api_manager.has_property("Frame", "Broken"), Some(false));
+
+        let affected = api_manager.set_custom_classes(source, Vec::new());
+        assert_eq!(affected, vec!["FancierButton", "FancyButton", "Frame"]);
+        assert_eq!(api_manager.has_property("FancyButton", "Variant"), None);
+        assert_eq!(api_manager.has_property("Frame", "Size"), Some(true));
+    }
+
+    #[test]
+    fn test_freq_updates() {
+        let mut api_manager = ApiManager::from_local_dump();
+        // Words that aren't class or property names aren't tracked
+        let mut updates = api_manager.freq_updates("local Visible = Frame.Visible or Nonsense");
+        updates.sort();
+        assert_eq!(
+            updates,
+            [("Frame".to_string(), 1), ("Visible".to_string(), 2)]
+        );
+        api_manager.apply_freq_updates(Vec::new(), updates);
+        assert_eq!(api_manager.freq_lookup["Visible"], 2);
+
+        // Edited regions swap their old counts for new ones, so removing a name lowers it
+        let old = api_manager.freq_updates("local Visible = Frame.Visible");
+        let new = api_manager.freq_updates("local shown = Frame.Visible");
+        api_manager.apply_freq_updates(old, new);
+        assert_eq!(api_manager.freq_lookup["Visible"], 1);
+        assert_eq!(api_manager.freq_lookup["Frame"], 1);
+        let old = api_manager.freq_updates("local shown = Frame.Visible");
+        api_manager.apply_freq_updates(old, Vec::new());
+        assert_eq!(api_manager.freq_lookup["Visible"], 0);
+        api_manager.apply_freq_updates(vec![("Visible".to_string(), 2)], Vec::new());
+        assert_eq!(api_manager.freq_lookup["Visible"], 0);
+
+        // Restored counts don't lower what this session already counted
+        api_manager.update_freq("Frame");
+        let restored = HashMap::from([("Visible".to_string(), 4), ("Frame".to_string(), 0)]);
+        api_manager.restore_freq(restored);
+        assert_eq!(api_manager.freq_lookup["Visible"], 4);
+        assert_eq!(api_manager.freq_lookup["Frame"], 1);
+    }
+
+    #[test]
+    fn test_deterministic_ranking() {
+        let mut api_manager = ApiManager::from_local_dump();
+        api_manager.update_freq("Visible Visible Visible");
+        let first =
+            |api_manager: &ApiManager| api_manager.lookup_properties("Frame").unwrap()[0].0.clone();
+        assert_eq!(first(&api_manager), "Visible");
+
+        api_manager.set_deterministic_ranking(true);
+        let deterministic = first(&api_manager);
+        assert_ne!(deterministic, "Visible");
+        assert_eq!(
+            ApiManager::from_local_dump()
+                .lookup_properties("Frame")
+                .unwrap()[0]
+                .0,
+            deterministic
+        );
+
+        // Forced one ignores settings turning it off
+        let mut forced = api_manager.with_forced_deterministic_ranking(true);
+        forced.set_deterministic_ranking(false);
+        assert_eq!(first(&forced), deterministic);
+    }
+
+    #[test]
+    fn test_class_json() {
+        let api_manager = ApiManager::from_local_dump();
+        let label = api_manager.class_json("TextLabel").unwrap();
+        assert_eq!(label["instance"], "TextLabel");
+        assert_eq!(label["superclass"], "GuiLabel");
+        assert!(label["properties"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|p| p["name"] == "Text"));
+        assert!(api_manager.class_json("NotAClass").is_none());
+        let button = api_manager.class_json("GuiButton").unwrap();
+        assert_eq!(button["superclassChain"][1], "GuiObject");
+        assert!(button["subclasses"]
+            .as_array()
+            .unwrap()
+            .contains(&"TextButton".into()));
+    }
+
+    #[test]
+    fn test_stats() {
+        let empty = ApiManager::new().stats();
+        assert_eq!(empty["classCount"], 0);
+        assert_eq!(empty["cacheAgeSeconds"], serde_json::Value::Null);
+
+        let mut api_manager = ApiManager::from_local_dump();
+        api_manager.update_freq("Visible Size");
+        let stats = api_manager.stats();
+        assert!(stats["classCount"].as_u64().unwrap() > 100);
+        assert!(stats["propertyCount"].as_u64() > stats["classCount"].as_u64());
+        assert!(stats["eventCount"].as_u64().unwrap() > 0);
+        assert_eq!(stats["frequencyTableSize"], 2);
+    }
+
+    #[test]
+    fn test_api_stages() {
+        let downloading = |downloaded| {
+            ApiStage::Downloading(DownloadProgress {
+                downloaded,
+                total: Some(100),
+            })
+        };
+        assert_eq!(downloading(50).percentage(), Some(40));
+        assert_eq!(downloading(100).percentage(), Some(80));
+        assert_eq!(ApiStage::Parsing.percentage(), Some(80));
+        assert_eq!(ApiStage::Caching.percentage(), Some(95));
+        assert_eq!(ApiStage::Caching.message(), "Caching…");
+    }
+
+    #[test]
+    fn test_cache_staleness() {
+        let day = Duration::from_secs(24 * 60 * 60);
+        let now = 10 * day.as_secs();
+        assert!(!is_stale(now - 60, Some(day), now));
+        assert!(is_stale(now - 2 * day.as_secs(), Some(day), now));
+        assert!(is_stale(0, Some(day), now));
+        assert!(!is_stale(0, None, now));
+    }
+
+    #[test]
+    fn test_version_info() {
+        let mut api_manager = ApiManager::new();
+        assert_eq!(
+            api_manager.version_info(None)["upToDate"],
+            serde_json::Value::Null
+        );
+        api_manager.version = Some("version-a".to_string());
+        assert_eq!(api_manager.api_version(), Some("version-a"));
+        assert_eq!(
+            api_manager.version_info(Some("version-b"))["upToDate"],
+            false
+        );
+
+        // Pinned version wins over the latest one
+        api_manager.set_pinned_version(Some("version-a".to_string()));
+        let info = api_manager.version_info(Some("version-b"));
+        assert_eq!(info["pinnedVersion"], "version-a");
+        assert_eq!(info["latestVersion"], "version-b");
+        assert_eq!(info["upToDate"], true);
+    }
+
+    #[test]
+    fn test_class_hierarchy() {
+        let api_manager = ApiManager::from_local_dump();
+        assert_eq!(
+            api_manager.superclass_chain("TextButton")[..3],
+            ["TextButton", "GuiButton", "GuiObject"]
+        );
+        assert!(api_manager.is_a("TextButton", "GuiObject"));
+        assert!(api_manager.is_a("Frame", "Frame"));
+        assert!(!api_manager.is_a("Frame", "GuiButton"));
+        assert!(!api_manager.is_a("NotAClass", "Instance"));
+
+        let layouts = api_manager.subclasses_of("UILayout");
+        assert!(layouts.contains(&"UIGridStyleLayout".to_string()));
+        assert!(layouts.contains(&"UIListLayout".to_string())); // Through UIGridStyleLayout
+        assert!(!layouts.contains(&"UILayout".to_string()));
+        assert!(api_manager.subclasses_of("Frame").is_empty());
+    }
+
+    #[test]
+    fn test_usage_report() {
+        let schema: serde_json::Value =
+            serde_json::from_str(include_str!("../schemas/usage-report.v1.schema.json")).unwrap();
+        assert_eq!(
+            schema["properties"]["schemaVersion"]["const"],
+            USAGE_REPORT_SCHEMA_VERSION
+        );
+
+        let mut api_manager = ApiManager::from_local_dump();
+        api_manager.update_freq("Visible Visible Size");
+        let report = api_manager.usage_report();
+        for key in schema["required"].as_array().unwrap() {
+            assert!(report.get(key.as_str().unwrap()).is_some());
+        }
+        assert_eq!(report["usage"][0]["name"], "Visible");
+        assert_eq!(report["usage"][0]["count"].as_u64(), Some(2));
+    }
+
+    #[test]
+    fn test_creatable_classes() {
+        let mut api_manager = ApiManager::from_local_dump();
+        let offered = |api_manager: &ApiManager, name: &str| {
+            api_manager
+                .get_all_inst("")
+                .unwrap()
+                .iter()
+                .any(|class| class == name)
+        };
+        for class in ["Frame", "ScreenGui", "UICorner", "UIListLayout"] {
+            assert!(offered(&api_manager, class), "{}", class);
+        }
+        // Abstract classes, services and non GUI ones
+        for class in ["GuiObject", "GuiButton", "Workspace", "Part"] {
+            assert!(!offered(&api_manager, class), "{}", class);
+        }
+
+        api_manager.set_class_bases(Vec::new());
+        assert!(offered(&api_manager, "Part"));
+        assert!(!offered(&api_manager, "Workspace"));
+    }
+
+    #[test]
+    fn test_class_ranking() {
+        let mut api_manager = ApiManager::from_local_dump();
+        api_manager.set_deterministic_ranking(true);
+        api_manager.set_class_bases(Vec::new());
+
+        let classes = api_manager.get_all_inst("Frame").unwrap();
+        let position = |name: &str| classes.iter().position(|class| class == name).unwrap();
+        assert!(position("ScrollingFrame") < position("CFrameValue"));
+        assert!(position("Frame") < position("CFrameValue"));
+
+        // Texture is shorter, but isn't GUI
+        let classes = api_manager.get_all_inst("Text").unwrap();
+        assert_eq!(classes[..3], ["TextButton", "TextLabel", "TextBox"]);
+
+        // Usage still beats hierarchy, but not an exact match
+        api_manager.set_deterministic_ranking(false);
+        api_manager.update_freq("CFrameValue CFrameValue");
+        let classes = api_manager.get_all_inst("Frame").unwrap();
+        let position = |name: &str| classes.iter().position(|class| class == name).unwrap();
+        assert!(position("CFrameValue") < position("ScrollingFrame"));
+        assert_eq!(classes[0], "Frame");
+    }
+
+    #[test]
+    fn test_completion_sorting() {
+        let mut api_manager = ApiManager::from_local_dump();
+        api_manager.update_freq("ZIndex ZIndex Visible");
+        let names = |api_manager: &ApiManager| -> Vec<String> {
+            let props = api_manager.lookup_properties("TextLabel").unwrap();
+            props.into_iter().map(|(name, _)| name).collect()
+        };
+        assert_eq!(names(&api_manager)[..2], ["ZIndex", "Visible"]);
+
+        api_manager.set_sorting(CompletionSorting::Alphabetical);
+        let alphabetical = names(&api_manager);
+        assert!(alphabetical.windows(2).all(|pair| pair[0] < pair[1]));
+        let classes = api_manager.get_all_inst("Text").unwrap();
+        assert!(classes.windows(2).all(|pair| pair[0] < pair[1]));
+
+        // Own members first, classes right after their superclass
+        api_manager.set_sorting(CompletionSorting::ApiOrder);
+        let api_order = names(&api_manager);
+        assert_eq!(
+            api_manager
+                .member_info("TextLabel", &api_order[0], false)
+                .unwrap()
+                .1,
+            "TextLabel"
+        );
+        assert_eq!(
+            api_manager
+                .member_info("TextLabel", api_order.last().unwrap(), false)
+                .unwrap()
+                .1,
+            "Instance"
+        );
+        let classes = api_manager.get_all_inst("").unwrap();
+        let position = |name: &str| classes.iter().position(|class| class == name).unwrap();
+        assert!(position("Frame") < position("TextLabel"));
+        assert!(position("TextLabel") < position("BillboardGui"));
+        assert_eq!(api_manager.names.as_ref().unwrap()[0], "Instance");
+    }
+
+    #[test]
+    fn test_fuzzy_score() {
+        let score = |pattern: &str, text: &str| fuzzy_score(pattern, text).unwrap();
+        assert!(fuzzy_score("lx", "TextLabel").is_none());
+        assert_eq!(fuzzy_score("", "Frame"), Some(0));
+
+        // Exact beats prefix beats humps beats anywhere
+        assert!(score("frame", "Frame") > score("frame", "FrameWork"));
+        assert!(score("Text", "TextBox") > score("Text", "SelectionText"));
+        assert!(score("tl", "TextLabel") > score("tl", "Tool"));
+        assert!(score("grid", "UIGridLayout") > score("grid", "UIgridLayout"));
+        assert!(score("ib", "Image_b") > score("ib", "Imageb"));
+        // Consecutive chars beat the same ones spread out
+        assert!(score("fra", "Frame") > score("fra", "FoRAm"));
+    }
+
+    #[test]
+    fn test_member_settings() {
+        let mut api_manager = ApiManager::from_local_dump();
+        let offered = |api_manager: &ApiManager, name: &str| {
+            api_manager
+                .lookup_properties("Frame")
+                .unwrap()
+                .iter()
+                .chain(&api_manager.lookup_events("Frame").unwrap())
+                .any(|(member, _)| member == name)
+        };
+        let changeable = |api_manager: &ApiManager, name: &str| {
+            api_manager
+                .lookup_changeable_properties("Frame")
+                .unwrap()
+                .iter()
+                .any(|(member, _)| member == name)
+        };
+
+        // Defaults offer hidden members only
+        assert!(offered(&api_manager, "Transparency"));
+        assert!(!offered(&api_manager, "Draggable"));
+        assert!(!offered(&api_manager, "DragBegin"));
+        assert!(!offered(&api_manager, "AbsoluteSize"));
+        assert!(changeable(&api_manager, "AbsoluteSize"));
+        assert_eq!(
+            api_manager.property_restriction("Frame", "AbsoluteSize"),
+            Some("ReadOnly".to_string())
+        );
+        assert_eq!(api_manager.has_property("Frame", "Draggable"), Some(true));
+
+        api_manager.set_member_settings(MemberSettings {
+            include_deprecated: true,
+            include_read_only: true,
+            include_hidden: false,
+        });
+        assert!(!offered(&api_manager, "Transparency"));
+        assert!(offered(&api_manager, "Draggable"));
+        assert!(offered(&api_manager, "DragBegin"));
+        assert!(offered(&api_manager, "AbsoluteSize"));
     }
 }
@@ -1,18 +1,44 @@
 // This script handles scraping roblox API and generating look up table
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
 use std::fs::File;
 use std::io::Write;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::{env, fs};
 
-type ParsedInstances = HashMap<String, ParsedInstance>;
+use crate::api_fetcher::{ApiFetcher, ResponseValidators};
+
+pub type ParsedInstances = HashMap<String, ParsedInstance>;
+pub type ParsedEnums = HashMap<String, Vec<ParsedEnumItem>>; // Enum name -> items in value order
+pub type ParsedDocs = HashMap<String, String>; // "Class" or "Class.Member" -> markdown documentation
 
 #[derive(Deserialize, Debug)]
 pub struct ApiDump {
     #[serde(rename = "Classes")]
     pub classes: Vec<Instance>,
+    #[serde(default, rename = "Enums")]
+    pub enums: Vec<Enum>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Enum {
+    #[serde(default, rename = "Name")]
+    pub name: String, // Enum name (e.g., "Font")
+    #[serde(default, rename = "Items")]
+    pub items: Vec<EnumItem>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct EnumItem {
+    #[serde(default, rename = "Name")]
+    pub name: String, // Item name (e.g., "GothamBold")
+    #[serde(default, rename = "Tags")]
+    pub tags: Vec<String>,
+    #[serde(default, rename = "Value")]
+    pub value: i64,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -37,13 +63,28 @@ pub struct Member {
     pub member_type: String, // Member type (e.g., "Property")
     #[serde(default, rename = "Name")]
     pub name: String, // Member name (e.g., "Archivable")
+    #[serde(default, rename = "Parameters")]
+    pub parameters: Vec<Parameter>, // Arguments of events and functions
+    #[serde(default, rename = "Default")]
+    pub default: Option<Value>, // Only in full dumps(Like the client tracker's Full-API-Dump.json)
+    #[serde(default, rename = "ReturnType")]
+    pub return_type: ValueType, // What functions and callbacks return
+    #[serde(default, rename = "Security")]
+    pub security: MemberSecurity,
     #[serde(default, rename = "Tags")]
     pub tags: Vec<String>,
     #[serde(default, rename = "ValueType")]
     pub value_type: ValueType, // Value type (e.g., {"Category": "Primitive", "Name": "bool"})
 }
 
-/*
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Parameter {
+    #[serde(default, rename = "Name")]
+    pub name: String, // Parameter name (e.g., "input")
+    #[serde(default, rename = "Type")]
+    pub value_type: ValueType,
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct Security {
     #[serde(default, rename = "Read")]
@@ -52,6 +93,32 @@ pub struct Security {
     pub write: String, // Security level for write access
 }
 
+// Properties have separate read and write levels, other members a single one(e.g. "PluginSecurity")
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum MemberSecurity {
+    Access(Security),
+    Level(String),
+}
+
+impl Default for MemberSecurity {
+    fn default() -> Self {
+        Self::Level("None".to_string())
+    }
+}
+
+impl MemberSecurity {
+    // Level needed to set a property or use any other member
+    fn write(&self) -> &str {
+        match self {
+            Self::Access(security) => &security.write,
+            Self::Level(level) => level,
+        }
+    }
+}
+
+/*
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct Serialization {
     #[serde(default, rename = "CanLoad")]
@@ -80,85 +147,646 @@ impl Default for ValueType {
 pub struct ParsedInstance {
     pub instance: String,
     pub superclass: String,
-    pub properties: Vec<ParsedProperty>,
-    pub events: Vec<ParsedProperty>,
+    pub properties: Vec<SharedProperty>,
+    pub events: Vec<SharedProperty>,
+    pub methods: Vec<SharedProperty>, // Functions and callbacks, typed as their signature
+    pub restricted: Vec<RestrictedProperty>, // Properties scripts can't set, left out of properties
+    pub tags: Vec<String>, // Class tags completions filter by(NotCreatable, Service, Deprecated)
 }
 
+// Members are shared between every class declaring or inheriting the same one(Name, Parent and co. sit in ~700 classes)
+pub type SharedProperty = Arc<ParsedProperty>;
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RestrictedProperty {
+    pub name: String,
+    pub reason: String, // "NotScriptable" or the security level needed(e.g. "PluginSecurity")
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
 pub struct ParsedProperty {
     pub name: String,
     pub data_type: String,
+    pub default: Option<String>, // Only properties have one, and only when the dump says it
+    pub tags: Vec<String>,       // Ones settings can filter by(Deprecated, ReadOnly, Hidden)
+}
+
+// Points members equal to one already seen at that one, so each distinct member is kept in memory once
+pub fn intern_members<'a>(instances: impl IntoIterator<Item = &'a mut ParsedInstance>) {
+    let mut seen: HashSet<SharedProperty> = HashSet::new();
+    for inst in instances {
+        let members = inst
+            .properties
+            .iter_mut()
+            .chain(&mut inst.events)
+            .chain(&mut inst.methods);
+        for member in members {
+            match seen.get(member.as_ref()) {
+                Some(shared) => *member = shared.clone(),
+                None => {
+                    seen.insert(member.clone());
+                }
+            }
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ParsedEnumItem {
+    pub name: String,
+    pub value: i64,
+}
+
+const FILTER_TAGS: [&str; 3] = ["Deprecated", "ReadOnly", "Hidden"];
+const CLASS_FILTER_TAGS: [&str; 3] = ["NotCreatable", "Service", "Deprecated"];
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ParsedDataTypeMember {
+    pub name: String,
+    pub params: Option<Vec<String>>, // Names only(The docs carry no types), None when it isn't callable
+    pub documentation: String,
+}
+
+// Members of a datatype like UDim2, each list sorted by name
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ParsedDataType {
+    pub constructors: Vec<ParsedDataTypeMember>, // Reached through the type itself(UDim2.fromScale, Vector2.zero)
+    pub properties: Vec<ParsedDataTypeMember>,
+    pub methods: Vec<ParsedDataTypeMember>,
 }
 
+pub type ParsedDataTypes = HashMap<String, ParsedDataType>;
+
+// Bumped whenever what gets parsed out of the dump changes(2: event signatures, 3: methods, 4: defaults, 5: restrictions, 6: tags, 7: class tags, 8: docs, 9: datatypes, 10: validators, 11: member table)
+pub(crate) const CACHE_FORMAT: u32 = 11;
+
+// Cache files start with magic, CACHE_FORMAT they were written with and a checksum of the bincode after it
+// (Files without one are from before it existed, those are stale like any older format)
+const CACHE_MAGIC: &[u8; 8] = b"RRLSPAPI";
+const CACHE_HEADER_LEN: usize = CACHE_MAGIC.len() + 4 + 8;
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct CachedApi {
     pub version: String,
     pub instances: ParsedInstances,
+    pub enums: ParsedEnums,
+    pub format: u32,
+    pub docs: ParsedDocs, // Class and member documentation, may be empty
+    pub data_types: ParsedDataTypes,
+    // Where the dump came from and when, so load can revalidate it once it's old
+    pub validators: ResponseValidators,
+    pub fetched_at: u64, // Unix seconds, 0 when unknown
 }
 
-fn get_cache_file_path() -> PathBuf {
-    let exe_path = env::current_exe().expect("Failed to get current exe path!");
-    let exe_dir = exe_path.parent().expect("Failed to get exe dir!");
-    exe_dir.join("serialized_api.bin")
+// Cache layout since format 11, each distinct member is stored once and classes hold indices into that table
+// (Written from borrowed CachedApi parts, read back as InternedCache)
+#[derive(Serialize)]
+struct InternedCacheRef<'a> {
+    version: &'a str,
+    members: Vec<&'a ParsedProperty>,
+    instances: HashMap<&'a str, InternedInstance>,
+    enums: &'a ParsedEnums,
+    format: u32,
+    docs: &'a ParsedDocs,
+    data_types: &'a ParsedDataTypes,
+    validators: &'a ResponseValidators,
+    fetched_at: u64,
 }
 
-pub fn get_cache() -> Result<Option<CachedApi>, Box<dyn std::error::Error + Send + Sync>> {
-    let api_cache_path = get_cache_file_path();
-    if !api_cache_path.exists() {
-        return Ok(None);
-    }
+#[derive(Deserialize)]
+struct InternedCache {
+    version: String,
+    members: Vec<SharedProperty>,
+    instances: HashMap<String, InternedInstance>,
+    enums: ParsedEnums,
+    format: u32,
+    docs: ParsedDocs,
+    data_types: ParsedDataTypes,
+    validators: ResponseValidators,
+    fetched_at: u64,
+}
 
-    let bytes = fs::read(&api_cache_path)?;
+#[derive(Serialize, Deserialize)]
+struct InternedInstance {
+    instance: String,
+    superclass: String,
+    properties: Vec<u32>,
+    events: Vec<u32>,
+    methods: Vec<u32>,
+    restricted: Vec<RestrictedProperty>,
+    tags: Vec<String>,
+}
 
-    // Try new format
-    if let Ok(cache) = bincode::deserialize::<CachedApi>(&bytes) {
-        return Ok(Some(cache));
+impl<'a> InternedCacheRef<'a> {
+    fn new(cache: &'a CachedApi) -> Self {
+        let mut members: Vec<&ParsedProperty> = Vec::new();
+        let mut indices: HashMap<&ParsedProperty, u32> = HashMap::new();
+        let mut intern = |list: &'a [SharedProperty]| -> Vec<u32> {
+            list.iter()
+                .map(|member| {
+                    *indices.entry(member.as_ref()).or_insert_with(|| {
+                        members.push(member);
+                        members.len() as u32 - 1
+                    })
+                })
+                .collect()
+        };
+        let instances = cache
+            .instances
+            .iter()
+            .map(|(name, inst)| {
+                let interned = InternedInstance {
+                    instance: inst.instance.clone(),
+                    superclass: inst.superclass.clone(),
+                    properties: intern(&inst.properties),
+                    events: intern(&inst.events),
+                    methods: intern(&inst.methods),
+                    restricted: inst.restricted.clone(),
+                    tags: inst.tags.clone(),
+                };
+                (name.as_str(), interned)
+            })
+            .collect();
+        Self {
+            version: &cache.version,
+            members,
+            instances,
+            enums: &cache.enums,
+            format: cache.format,
+            docs: &cache.docs,
+            data_types: &cache.data_types,
+            validators: &cache.validators,
+            fetched_at: cache.fetched_at,
+        }
     }
+}
 
-    // Fall back to old format (raw ParsedInstances) — treat version as unknown
-    // so it will always prompt the user to update once, then save in new format
-    if let Ok(instances) = bincode::deserialize::<ParsedInstances>(&bytes) {
-        return Ok(Some(CachedApi {
-            version: "unknown".to_string(),
+impl InternedCache {
+    // None when an index points past the table
+    fn resolve(self) -> Option<CachedApi> {
+        let members = self.members;
+        let lookup = |indices: Vec<u32>| -> Option<Vec<SharedProperty>> {
+            indices
+                .into_iter()
+                .map(|index| members.get(index as usize).cloned())
+                .collect()
+        };
+        let instances = self
+            .instances
+            .into_iter()
+            .map(|(name, inst)| {
+                let resolved = ParsedInstance {
+                    instance: inst.instance,
+                    superclass: inst.superclass,
+                    properties: lookup(inst.properties)?,
+                    events: lookup(inst.events)?,
+                    methods: lookup(inst.methods)?,
+                    restricted: inst.restricted,
+                    tags: inst.tags,
+                };
+                Some((name, resolved))
+            })
+            .collect::<Option<ParsedInstances>>()?;
+        Some(CachedApi {
+            version: self.version,
             instances,
-        }));
+            enums: self.enums,
+            format: self.format,
+            docs: self.docs,
+            data_types: self.data_types,
+            validators: self.validators,
+            fetched_at: self.fetched_at,
+        })
+    }
+}
+
+// Snapshot shipped inside the binary, for first runs that can't download the API(Written by
+// test_generate_bundled_cache in the cache file's own format, so loading it skips parsing any JSON)
+const BUNDLED_CACHE: &[u8] = include_bytes!("../bundled/serialized_api.bin");
+pub const BUNDLED_VERSION: &str = "bundled";
+// Version of caches parsed from a user's dump file, followed by its path
+pub const LOCAL_VERSION_PREFIX: &str = "local:";
+
+pub fn bundled_cache() -> Result<CachedApi, Box<dyn std::error::Error + Send + Sync>> {
+    let cache = decode_cache(BUNDLED_CACHE).ok_or(
+        "Bundled API snapshot is outdated, regenerate it with test_generate_bundled_cache",
+    )?;
+    Ok(CachedApi {
+        version: BUNDLED_VERSION.to_string(),
+        ..cache
+    })
+}
+
+// Reads dump at path(Same shape as setup.rbxcdn.com's API-Dump.json), docs and defaults aren't in there
+pub fn local_cache(path: &Path) -> Result<CachedApi, Box<dyn std::error::Error + Send + Sync>> {
+    let dump = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read API dump {}: {}", path.display(), e))?;
+    let version = format!("{}{}", LOCAL_VERSION_PREFIX, path.display());
+    uncached(&dump, version)
+        .map_err(|e| format!("Failed to parse API dump {}: {}", path.display(), e).into())
+}
+
+// Cache built straight from a dump, these never get written to disk
+fn uncached(dump: &str, version: String) -> Result<CachedApi, serde_json::Error> {
+    let (instances, enums) = parse_api_dump(dump)?;
+    Ok(CachedApi {
+        version,
+        instances,
+        enums,
+        format: CACHE_FORMAT,
+        docs: ParsedDocs::new(),
+        data_types: ParsedDataTypes::new(),
+        validators: ResponseValidators::default(),
+        fetched_at: 0,
+    })
+}
+
+const CACHE_FILE_NAME: &str = "serialized_api.bin";
+const READABLE_FILE_NAME: &str = "readable_serialized_api.json"; // Written by readCache into a given dir
+const CACHE_DIR_NAME: &str = "rblx-react-lsp";
+const USAGE_DIR_NAME: &str = "usage"; // Per workspace usage counts, inside the cache directory
+
+// Per user cache directory(No dirs crate in the tree, so this resolves the same places it would)
+fn platform_cache_dir() -> Option<PathBuf> {
+    let var = |name| {
+        env::var_os(name)
+            .filter(|value| !value.is_empty())
+            .map(PathBuf::from)
+    };
+    let base = if cfg!(windows) {
+        var("LOCALAPPDATA")
+    } else if cfg!(target_os = "macos") {
+        var("HOME").map(|home| home.join("Library/Caches"))
+    } else {
+        var("XDG_CACHE_HOME").or_else(|| var("HOME").map(|home| home.join(".cache")))
+    };
+    base.map(|base| base.join(CACHE_DIR_NAME))
+}
+
+// Directory cache io goes to, override_dir(api.cacheDir) replaces the platform one when set
+pub fn cache_dir(override_dir: Option<PathBuf>) -> PathBuf {
+    override_dir
+        .or_else(platform_cache_dir)
+        .unwrap_or_else(|| env::temp_dir().join(CACHE_DIR_NAME))
+}
+
+fn get_cache_file_path(dir: &Path) -> PathBuf {
+    dir.join(CACHE_FILE_NAME)
+}
+
+// Where older versions kept the cache, read only on many systems once installed as an extension
+fn legacy_cache_file_path() -> Option<PathBuf> {
+    Some(env::current_exe().ok()?.parent()?.join(CACHE_FILE_NAME))
+}
+
+// Moves legacy cache to target unless target already has one, returns whether it did
+// Copied rather than renamed since the two can be on different drives, and the exe dir may not allow removal
+fn migrate_cache(legacy: &Path, target: &Path) -> std::io::Result<bool> {
+    if target.exists() || !legacy.is_file() {
+        return Ok(false);
+    }
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::copy(legacy, target)?;
+    fs::remove_file(legacy).ok();
+    Ok(true)
+}
+
+// FNV-1a, only has to catch truncated or scribbled over files
+fn checksum(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+fn encode_cache(cache: &CachedApi) -> Result<Vec<u8>, bincode::Error> {
+    let payload = bincode::serialize(&InternedCacheRef::new(cache))?;
+    let mut bytes = Vec::with_capacity(CACHE_HEADER_LEN + payload.len());
+    bytes.extend_from_slice(CACHE_MAGIC);
+    bytes.extend_from_slice(&CACHE_FORMAT.to_le_bytes());
+    bytes.extend_from_slice(&checksum(&payload).to_le_bytes());
+    bytes.extend_from_slice(&payload);
+    Ok(bytes)
+}
+
+// Bincode after a valid header, None when the checksum doesn't match or another format wrote it
+fn checked_payload(bytes: &[u8]) -> Option<&[u8]> {
+    if bytes.len() < CACHE_HEADER_LEN {
+        return None;
+    }
+    let (header, payload) = bytes.split_at(CACHE_HEADER_LEN);
+    let format = u32::from_le_bytes(header[8..12].try_into().ok()?);
+    let sum = u64::from_le_bytes(header[12..20].try_into().ok()?);
+    (format == CACHE_FORMAT && checksum(payload) == sum).then_some(payload)
+}
+
+// Reads a cache encode_cache wrote with this CACHE_FORMAT
+// None when it's corrupted, headerless or from another format, so callers download a fresh one instead
+fn decode_cache(bytes: &[u8]) -> Option<CachedApi> {
+    if !bytes.starts_with(CACHE_MAGIC) {
+        return None;
+    }
+    bincode::deserialize::<InternedCache>(checked_payload(bytes)?)
+        .ok()
+        .filter(|cache| cache.format == CACHE_FORMAT)
+        .and_then(InternedCache::resolve)
+}
+
+pub fn get_cache(
+    dir: &Path,
+) -> Result<Option<CachedApi>, Box<dyn std::error::Error + Send + Sync>> {
+    let api_cache_path = get_cache_file_path(dir);
+    // Runs on startup's load, so a cache from before the move gets picked up instead of downloaded again
+    if let Some(legacy) = legacy_cache_file_path() {
+        match migrate_cache(&legacy, &api_cache_path) {
+            Ok(true) => eprintln!(
+                "Moved API cache from {} to {}",
+                legacy.display(),
+                api_cache_path.display()
+            ),
+            Ok(false) => {}
+            Err(e) => eprintln!("Failed to move API cache from {} ({})", legacy.display(), e),
+        }
+    }
+    if !api_cache_path.exists() {
+        return Ok(None);
     }
 
-    // Probably missing
-    Ok(None)
+    let bytes = fs::read(&api_cache_path)?;
+    let cache = decode_cache(&bytes);
+    if cache.is_none() {
+        // Stale or unreadable, the fresh download gets written in its place
+        eprintln!(
+            "API cache at {} is corrupted or from another version, deleting it",
+            api_cache_path.display()
+        );
+        fs::remove_file(&api_cache_path).ok();
+    }
+    Ok(cache)
 }
 
 pub fn cache_file(
-    parsed_instances: &ParsedInstances,
-    version: &str,
+    dir: &Path,
+    cache: &CachedApi,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    let api_cache_path = get_cache_file_path();
-    let cache = CachedApi {
-        version: version.to_string(),
-        instances: parsed_instances.clone(),
-    };
-    let encoded = bincode::serialize(&cache)?;
-    let mut file = File::create(api_cache_path)?;
-    file.write_all(&encoded)?;
+    let api_cache_path = get_cache_file_path(dir);
+    if let Some(parent) = api_cache_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    // Written next to it and renamed over, so a crash mid write never leaves a half cache behind
+    let partial_path = api_cache_path.with_extension("bin.partial");
+    let mut file = File::create(&partial_path)?;
+    file.write_all(&encode_cache(cache)?)?;
+    file.sync_all()?;
+    fs::rename(partial_path, api_cache_path)?;
     Ok(())
 }
 
+pub fn cache_file_size(dir: &Path) -> Option<u64> {
+    fs::metadata(get_cache_file_path(dir))
+        .ok()
+        .map(|metadata| metadata.len())
+}
+
+// Deletes the cache(And its legacy/partial copies, plus the readable export in readable_dir), returns removed paths
+pub fn clear_cache_files(dir: &Path, readable_dir: Option<&Path>) -> std::io::Result<Vec<PathBuf>> {
+    let cache_path = get_cache_file_path(dir);
+    let mut paths = vec![cache_path.with_extension("bin.partial"), cache_path];
+    paths.extend(legacy_cache_file_path());
+    paths.extend(readable_dir.map(|dir| dir.join(READABLE_FILE_NAME)));
+
+    let mut removed = Vec::new();
+    for path in paths {
+        match fs::remove_file(&path) {
+            Ok(()) => removed.push(path),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(removed)
+}
+
+#[derive(Deserialize)]
+struct UsageCounts {
+    workspace: PathBuf, // Tells apart roots whose names hash the same
+    counts: HashMap<String, usize>,
+}
+
+// File named after a hash of workspace root, roots themselves can be too long for a file name
+fn usage_counts_path(dir: &Path, workspace: &Path) -> PathBuf {
+    let key = checksum(workspace.to_string_lossy().as_bytes());
+    dir.join(USAGE_DIR_NAME).join(format!("{:016x}.json", key))
+}
+
+fn read_usage_counts(path: &Path, workspace: &Path) -> Option<HashMap<String, usize>> {
+    let usage: UsageCounts = serde_json::from_slice(&fs::read(path).ok()?).ok()?;
+    (usage.workspace == workspace).then_some(usage.counts)
+}
+
+// Empty counts remove the file instead(What clearCache leaves behind)
+fn write_usage_counts(
+    path: &Path,
+    workspace: &Path,
+    counts: &HashMap<String, usize>,
+) -> std::io::Result<()> {
+    if counts.is_empty() {
+        return match fs::remove_file(path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        };
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let json = serde_json::json!({ "workspace": workspace, "counts": counts });
+    // Same partial write and rename as the cache, a crash mid save keeps the last counts
+    let partial_path = path.with_extension("json.partial");
+    fs::write(&partial_path, serde_json::to_vec(&json)?)?;
+    fs::rename(partial_path, path)
+}
+
+// Usage counts saved for workspace by an earlier session, None if there are none(Or they're unreadable)
+pub fn load_usage_counts(dir: &Path, workspace: &Path) -> Option<HashMap<String, usize>> {
+    read_usage_counts(&usage_counts_path(dir, workspace), workspace)
+}
+
+pub fn save_usage_counts(
+    dir: &Path,
+    workspace: &Path,
+    counts: &HashMap<String, usize>,
+) -> std::io::Result<()> {
+    write_usage_counts(&usage_counts_path(dir, workspace), workspace, counts)
+}
+
 pub async fn create_api_file_readable(
+    fetcher: &ApiFetcher,
     path: PathBuf,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    let file_path = path.join("readable_serialized_api.json");
+    let file_path = path.join(READABLE_FILE_NAME);
     let mut file = fs::File::create(file_path)?;
 
-    let (dump, _version) = download_api_with_version().await?;
-    let processed_result = parse_api_dump(&dump)?;
-
-    let json_string = serde_json::to_string_pretty(&processed_result)?;
+    let (dump, _version, _) = fetcher.fetch(|_| {}).await?;
+    let json_string = tokio::task::spawn_blocking(move || {
+        let (processed_result, _enums) = parse_api_dump(&dump)?;
+        serde_json::to_string_pretty(&processed_result)
+    })
+    .await??;
     file.write_all(json_string.as_bytes())?;
     file.flush()?;
 
     Ok(())
 }
 
+// Type of event's listener or a function, e.g. "(input: InputObject) -> ()"
+fn member_signature(member: &Member) -> String {
+    let params: Vec<String> = member
+        .parameters
+        .iter()
+        .map(|param| format!("{}: {}", param.name, param.value_type.name))
+        .collect();
+    // Events have no return type, functions returning nothing say "null"
+    let return_type = match member.return_type.name.as_str() {
+        "" | "null" => "()",
+        name => name,
+    };
+    format!("({}) -> {}", params.join(", "), return_type)
+}
+
+// Defaults come as strings mostly, anything else is shown as its json
+fn default_text(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+// Tags of a member that settings can filter by, the rest don't need to be cached
+fn filter_tags(member: &Member) -> Vec<String> {
+    member
+        .tags
+        .iter()
+        .filter(|tag| FILTER_TAGS.contains(&tag.as_str()))
+        .cloned()
+        .collect()
+}
+
+// Why scripts can't set a property, None when they can
+fn property_restriction(member: &Member) -> Option<String> {
+    if member.tags.iter().any(|tag| tag == "NotScriptable") {
+        return Some("NotScriptable".to_string());
+    }
+    match member.security.write() {
+        "" | "None" => None,
+        level => Some(level.to_string()),
+    }
+}
+
+fn is_method(member: &Member) -> bool {
+    member.member_type == "Function" || member.member_type == "Callback"
+}
+
+// Converts one class with its flattened members(Own ones first, then each superclass' in order)
+fn parse_instance(inst: &Instance, inst_members: &[&Member]) -> ParsedInstance {
+    let (methods, members): (Vec<&Member>, Vec<&Member>) =
+        inst_members.iter().copied().partition(|m| is_method(m));
+    let (props, events): (Vec<&Member>, Vec<&Member>) = members
+        .into_iter()
+        .partition(|m| m.member_type == "Property");
+    let restricted: Vec<RestrictedProperty> = props
+        .iter()
+        .filter_map(|member| {
+            Some(RestrictedProperty {
+                name: member.name.clone(),
+                reason: property_restriction(member)?,
+            })
+        })
+        .collect();
+
+    let properties: Vec<SharedProperty> = props
+        .into_iter()
+        .filter(|member| property_restriction(member).is_none())
+        .map(|member| {
+            Arc::new(ParsedProperty {
+                name: member.name.clone(),
+                // Enum typed ones get prefixed, Font alone is the FontFace datatype
+                data_type: if member.value_type.category == "Enum" {
+                    format!("Enum.{}", member.value_type.name)
+                } else {
+                    member.value_type.name.clone()
+                },
+                default: member.default.as_ref().map(default_text),
+                tags: filter_tags(member),
+            })
+        })
+        .collect();
+    let events: Vec<SharedProperty> = events
+        .into_iter()
+        .map(|member| {
+            Arc::new(ParsedProperty {
+                name: member.name.clone(),
+                data_type: member_signature(member),
+                default: None,
+                tags: filter_tags(member),
+            })
+        })
+        .collect();
+    let methods: Vec<SharedProperty> = methods
+        .into_iter()
+        .map(|member| {
+            Arc::new(ParsedProperty {
+                name: member.name.clone(),
+                data_type: member_signature(member),
+                default: None,
+                tags: filter_tags(member),
+            })
+        })
+        .collect();
+
+    ParsedInstance {
+        instance: inst.name.clone(),
+        superclass: inst.superclass.clone(),
+        properties,
+        events,
+        methods,
+        restricted,
+        tags: inst
+            .tags
+            .iter()
+            .filter(|tag| CLASS_FILTER_TAGS.contains(&tag.as_str()))
+            .cloned()
+            .collect(),
+    }
+}
+
+// Converts classes on every core, which is most of parsing once the json is read
+// (No rayon in the tree, scoped threads over even chunks do the same job here)
+fn parse_instances(classes: &[(&Instance, &[&Member])]) -> ParsedInstances {
+    let threads = std::thread::available_parallelism().map_or(1, |threads| threads.get());
+    let chunk_size = classes.len().div_ceil(threads).max(1);
+    std::thread::scope(|scope| {
+        let workers: Vec<_> = classes
+            .chunks(chunk_size)
+            .map(|chunk| {
+                scope.spawn(move || {
+                    chunk
+                        .iter()
+                        .map(|(inst, members)| (inst.name.clone(), parse_instance(inst, members)))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|worker| {
+                worker
+                    .join()
+                    .unwrap_or_else(|e| std::panic::resume_unwind(e))
+            })
+            .collect()
+    })
+}
+
 fn process_api_dump_json(api_dump_json: &ApiDump) -> ParsedInstances {
     let mut inst_cache = HashMap::new();
     let mut inst_look_up = HashMap::new();
@@ -167,7 +795,6 @@ fn process_api_dump_json(api_dump_json: &ApiDump) -> ParsedInstances {
         inst_look_up.insert(instance.name.as_str(), instance);
     }
 
-    let mut parsed_instances = HashMap::new();
     let mut parsing_stack = Vec::new();
 
     for (&name, &inst) in &inst_look_up {
@@ -196,117 +823,181 @@ fn process_api_dump_json(api_dump_json: &ApiDump) -> ParsedInstances {
                 .members
                 .iter()
                 .filter(|m| {
-                    (m.member_type == "Property" || m.member_type == "Event")
-                        && !m.tags.contains(&"Deprecated".to_string())
-                        && !m.tags.contains(&"ReadOnly".to_string())
+                    // Restricted properties are kept to report them, other restricted members just go
+                    // Deprecated, read-only and hidden ones are kept too, settings filter those by their tags
+                    let is_usable = m.member_type == "Property"
+                        || matches!(m.security.write(), "" | "None")
+                            && !m.tags.iter().any(|tag| tag == "NotScriptable");
+                    (m.member_type == "Property" || m.member_type == "Event" || is_method(m))
+                        && is_usable
                 })
                 .collect();
             if let Some(parent_inst) = inst_cache.get(top.superclass.as_str()) {
                 inst_members.extend(parent_inst);
             }
 
-            let (props, events): (Vec<&Member>, Vec<&Member>) = inst_members
-                .clone()
-                .into_iter()
-                .partition(|m| m.member_type == "Property");
+            inst_cache.insert(top.name.as_str(), inst_members);
+        }
+    }
 
-            let properties: Vec<ParsedProperty> = props
-                .into_iter()
-                .map(|member| ParsedProperty {
-                    name: member.name.clone(),
-                    data_type: member.value_type.name.clone(),
-                })
-                .collect();
-            let events: Vec<ParsedProperty> = events
-                .into_iter()
-                .map(|member| ParsedProperty {
-                    name: member.name.clone(),
-                    data_type: "Function".to_string(),
+    // Flattening above only moves references around, building the owned classes is the slow part
+    let classes: Vec<(&Instance, &[&Member])> = inst_cache
+        .iter()
+        .map(|(name, members)| (inst_look_up[name], members.as_slice()))
+        .collect();
+    parse_instances(&classes)
+}
+
+fn process_api_enums(api_dump_json: &ApiDump) -> ParsedEnums {
+    api_dump_json
+        .enums
+        .iter()
+        .map(|e| {
+            let mut items: Vec<ParsedEnumItem> = e
+                .items
+                .iter()
+                .filter(|item| !item.tags.contains(&"Deprecated".to_string()))
+                .map(|item| ParsedEnumItem {
+                    name: item.name.clone(),
+                    value: item.value,
                 })
                 .collect();
+            items.sort_by_key(|item| item.value);
+            (e.name.clone(), items)
+        })
+        .collect()
+}
 
-            inst_cache.insert(top.name.as_str(), inst_members); // You need to cache before parsing properties otherwise it will throw error as you are moving references
-            parsed_instances.insert(
-                top.name.clone(),
-                ParsedInstance {
-                    instance: top.name.clone(),
-                    superclass: top.superclass.clone(),
-                    properties,
-                    events,
-                },
-            );
+// Picks class and member docs out of the client tracker's api-docs/en-us.json
+// Keys look like "@roblox/globaltype/Frame" and "@roblox/globaltype/GuiObject.Visible"
+pub fn parse_api_docs(api_docs: &str) -> Result<ParsedDocs, serde_json::Error> {
+    let entries: HashMap<String, Value> = serde_json::from_str(api_docs)?;
+    Ok(entries
+        .into_iter()
+        .filter_map(|(key, entry)| {
+            let name = key.strip_prefix("@roblox/globaltype/")?;
+            let documentation = entry.get("documentation")?.as_str()?.trim();
+            (!documentation.is_empty()).then(|| (name.to_string(), documentation.to_string()))
+        })
+        .collect())
+}
+
+// Datatype members out of the same docs, "@roblox/global/UDim2.fromScale" is reached through the type
+// and "@roblox/globaltype/Color3.Lerp" through a value. Classes and libraries(math, task) are skipped
+pub fn parse_data_types(
+    api_docs: &str,
+    instances: &ParsedInstances,
+) -> Result<ParsedDataTypes, serde_json::Error> {
+    let entries: HashMap<String, Value> = serde_json::from_str(api_docs)?;
+    let mut data_types = ParsedDataTypes::new();
+    for (key, entry) in entries {
+        let (on_type, path) = match key.strip_prefix("@roblox/global/") {
+            Some(path) => (true, path),
+            None => match key.strip_prefix("@roblox/globaltype/") {
+                Some(path) => (false, path),
+                None => continue,
+            },
+        };
+        // Param and return docs live under their own "/param/0" keys
+        let Some((type_name, name)) = path.split_once('.').filter(|_| !path.contains('/')) else {
+            continue;
+        };
+        if !type_name.starts_with(|c: char| c.is_ascii_uppercase())
+            || instances.contains_key(type_name)
+            || name.contains('.')
+        {
+            continue;
+        }
+
+        let params = entry.get("params").and_then(Value::as_array).map(|params| {
+            params
+                .iter()
+                .filter_map(|param| param.get("name")?.as_str())
+                .filter(|name| *name != "self")
+                .map(str::to_string)
+                .collect::<Vec<String>>()
+        });
+        let data_type = data_types.entry(type_name.to_string()).or_default();
+        let members = match (on_type, &params) {
+            (true, _) => &mut data_type.constructors,
+            (false, Some(_)) => &mut data_type.methods,
+            (false, None) => &mut data_type.properties,
+        };
+        members.push(ParsedDataTypeMember {
+            name: name.to_string(),
+            params,
+            documentation: entry
+                .get("documentation")
+                .and_then(Value::as_str)
+                .unwrap_or("")
+                .trim()
+                .to_string(),
+        });
+    }
+    for data_type in data_types.values_mut() {
+        for members in [
+            &mut data_type.constructors,
+            &mut data_type.properties,
+            &mut data_type.methods,
+        ] {
+            members.sort_by(|a, b| a.name.cmp(&b.name));
         }
     }
+    Ok(data_types)
+}
 
-    parsed_instances
+// Fills in property defaults from a full dump, matching by class and property name
+pub fn merge_defaults(
+    instances: &mut ParsedInstances,
+    full_dump: &str,
+) -> Result<(), serde_json::Error> {
+    let (full_instances, _) = parse_api_dump(full_dump)?;
+    for (name, instance) in instances.iter_mut() {
+        let Some(full_instance) = full_instances.get(name) else {
+            continue;
+        };
+        for property in &mut instance.properties {
+            if property.default.is_some() {
+                continue;
+            }
+            let default = full_instance
+                .properties
+                .iter()
+                .find(|full| full.name == property.name)
+                .and_then(|full| full.default.clone());
+            if default.is_some() {
+                Arc::make_mut(property).default = default;
+            }
+        }
+    }
+    Ok(())
 }
 
-pub fn parse_api_dump(api_dump: &str) -> Result<ParsedInstances, serde_json::Error> {
+pub fn parse_api_dump(api_dump: &str) -> Result<(ParsedInstances, ParsedEnums), serde_json::Error> {
     let api_dump_json: ApiDump = serde_json::from_str(api_dump)?;
-    Ok(process_api_dump_json(&api_dump_json))
-}
-
-pub async fn get_live_version() -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
-    let version_url = "https://clientsettingscdn.roblox.com/v1/client-version/WindowsStudio64";
-    let version_json: serde_json::Value = reqwest::get(version_url).await?.json().await?;
-    Ok(version_json["clientVersionUpload"]
-        .as_str()
-        .ok_or("Failed to parse clientVersionUpload from response")?
-        .to_string())
-}
-
-pub async fn download_api_with_version(
-) -> Result<(String, String), Box<dyn std::error::Error + Send + Sync>> {
-    match download_api_from_clientsettings().await {
-        Ok(result) => return Ok(result),
-        Err(e) => eprintln!(
-            "Primary API source failed ({}), falling back to QTStudio...",
-            e
-        ),
-    }
-
-    // Fallback
-    let version = reqwest::get("https://setup.rbxcdn.com/versionQTStudio")
-        .await?
-        .text()
-        .await?;
-    let version = version.trim().to_string();
-    let dump = reqwest::get(format!(
-        "https://setup.rbxcdn.com/{}-API-Dump.json",
-        version
+    Ok((
+        process_api_dump_json(&api_dump_json),
+        process_api_enums(&api_dump_json),
     ))
-    .await?
-    .text()
-    .await?;
-    Ok((dump, version))
-}
-
-async fn download_api_from_clientsettings(
-) -> Result<(String, String), Box<dyn std::error::Error + Send + Sync>> {
-    let version_url = "https://clientsettingscdn.roblox.com/v1/client-version/WindowsStudio64";
-    let version_json: serde_json::Value = reqwest::get(version_url).await?.json().await?;
-
-    let version = version_json["clientVersionUpload"]
-        .as_str()
-        .ok_or("Failed to parse clientVersionUpload from response")?
-        .to_string();
-
-    let api_dump_url = format!("https://setup.rbxcdn.com/{}-API-Dump.json", version);
-    let dump = reqwest::get(&api_dump_url).await?.text().await?;
-    Ok((dump, version))
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::api_fetcher::ApiFetcher;
     use crate::api_parser::{
-        cache_file, download_api_with_version, get_live_version, parse_api_dump, CachedApi,
-        ParsedInstances,
+        bundled_cache, cache_dir, cache_file, cache_file_size, clear_cache_files, decode_cache,
+        encode_cache, get_cache_file_path, intern_members, local_cache, merge_defaults,
+        migrate_cache, parse_api_docs, parse_api_dump, parse_data_types, read_usage_counts,
+        usage_counts_path, write_usage_counts, CachedApi, ParsedEnumItem, ParsedInstances,
+        BUNDLED_VERSION, CACHE_FORMAT, LOCAL_VERSION_PREFIX,
     };
+    use std::collections::HashMap;
+    use std::sync::Arc;
     use std::{env, fs, path::Path};
 
     // Download without needing version
     pub async fn download_api() -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
-        let (dump, _) = download_api_with_version().await?;
+        let (dump, _, _) = ApiFetcher::new().fetch(|_| {}).await?;
         Ok(dump)
     }
 
@@ -316,6 +1007,357 @@ mod tests {
         dir
     }
 
+    #[test]
+    fn test_enum_cache() {
+        let dump = r#"{ "Classes": [], "Enums": [{ "Name": "SortOrder", "Items": [
+            { "Name": "Name", "Value": 1 },
+            { "Name": "LayoutOrder", "Value": 2 },
+            { "Name": "Custom", "Value": 0, "Tags": ["Deprecated"] }
+        ] }] }"#;
+        let (instances, enums) = parse_api_dump(dump).unwrap();
+        let expected = vec![
+            ParsedEnumItem {
+                name: "Name".to_string(),
+                value: 1,
+            },
+            ParsedEnumItem {
+                name: "LayoutOrder".to_string(),
+                value: 2,
+            },
+        ];
+        assert_eq!(enums["SortOrder"], expected);
+
+        let cache = CachedApi {
+            version: "version-1".to_string(),
+            instances: instances.clone(),
+            enums: enums.clone(),
+            format: CACHE_FORMAT,
+            docs: Default::default(),
+            data_types: Default::default(),
+            validators: Default::default(),
+            fetched_at: 0,
+        };
+        let decoded = decode_cache(&encode_cache(&cache).unwrap()).unwrap();
+        assert_eq!(decoded.version, "version-1");
+        assert_eq!(decoded.enums["SortOrder"], expected);
+
+        // Headerless caches are from before the header existed, so they're stale and get downloaded again
+        assert!(decode_cache(&bincode::serialize(&cache).unwrap()).is_none());
+        let unformatted =
+            bincode::serialize(&("version-1".to_string(), instances.clone(), enums)).unwrap();
+        assert!(decode_cache(&unformatted).is_none());
+        let legacy = bincode::serialize(&("version-1".to_string(), instances)).unwrap();
+        assert!(decode_cache(&legacy).is_none());
+    }
+
+    #[test]
+    fn test_property_defaults() {
+        let dump = r#"{ "Classes": [
+            { "Name": "GuiObject", "Superclass": "GuiBase2d", "Members": [
+                { "MemberType": "Property", "Name": "BorderSizePixel",
+                  "ValueType": { "Category": "Primitive", "Name": "int" } },
+                { "MemberType": "Property", "Name": "Visible",
+                  "ValueType": { "Category": "Primitive", "Name": "bool" } }
+            ] },
+            { "Name": "Frame", "Superclass": "GuiObject", "Members": [] }
+        ] }"#;
+        let full_dump = r#"{ "Classes": [
+            { "Name": "GuiObject", "Superclass": "GuiBase2d", "Members": [
+                { "MemberType": "Property", "Name": "BorderSizePixel", "Default": "1",
+                  "ValueType": { "Category": "Primitive", "Name": "int" } },
+                { "MemberType": "Property", "Name": "Visible", "Default": true,
+                  "ValueType": { "Category": "Primitive", "Name": "bool" } }
+            ] },
+            { "Name": "Frame", "Superclass": "GuiObject", "Members": [] }
+        ] }"#;
+        let (mut instances, _) = parse_api_dump(dump).unwrap();
+        assert!(instances["Frame"]
+            .properties
+            .iter()
+            .all(|p| p.default.is_none()));
+
+        merge_defaults(&mut instances, full_dump).unwrap();
+        let defaults: HashMap<&str, Option<&str>> = instances["Frame"]
+            .properties
+            .iter()
+            .map(|p| (p.name.as_str(), p.default.as_deref()))
+            .collect();
+        assert_eq!(defaults["BorderSizePixel"], Some("1"));
+        assert_eq!(defaults["Visible"], Some("true"));
+    }
+
+    #[test]
+    fn test_api_docs() {
+        let api_docs = r#"{
+            "@roblox/globaltype/Frame": { "documentation": "A plain rectangle.", "keys": {} },
+            "@roblox/globaltype/GuiObject.Visible": { "documentation": " Whether it renders. " },
+            "@roblox/globaltype/GuiObject.Size": { "documentation": "" },
+            "@roblox/enum/FillDirection": { "documentation": "Not a class" }
+        }"#;
+        let docs = parse_api_docs(api_docs).unwrap();
+        assert_eq!(docs.len(), 2);
+        assert_eq!(docs["Frame"], "A plain rectangle.");
+        assert_eq!(docs["GuiObject.Visible"], "Whether it renders.");
+    }
+
+    #[test]
+    fn test_bundled_cache() {
+        let cache = bundled_cache().unwrap();
+        assert_eq!(cache.version, BUNDLED_VERSION);
+        assert_eq!(cache.instances["TextButton"].superclass, "GuiButton");
+        assert!(!cache.enums.is_empty());
+
+        // Snapshot went through the same conversion, with inherited members after a class' own
+        let button = &cache.instances["TextButton"].properties;
+        let own = button.iter().position(|p| p.name == "Text").unwrap();
+        let inherited = button.iter().position(|p| p.name == "Visible").unwrap();
+        assert!(own < inherited);
+    }
+
+    #[test]
+    fn test_member_table() {
+        let dump =
+            fs::read_to_string(concat!(env!("CARGO_MANIFEST_DIR"), "/api_dump.json")).unwrap();
+        let (instances, enums) = parse_api_dump(&dump).unwrap();
+        let mut cache = CachedApi {
+            version: "version-1".to_string(),
+            instances,
+            enums,
+            format: CACHE_FORMAT,
+            docs: Default::default(),
+            data_types: Default::default(),
+            validators: Default::default(),
+            fetched_at: 0,
+        };
+        let shared = |instances: &ParsedInstances| {
+            let visible = |class: &str| {
+                instances[class]
+                    .properties
+                    .iter()
+                    .find(|p| p.name == "Visible")
+                    .unwrap()
+                    .clone()
+            };
+            Arc::ptr_eq(&visible("Frame"), &visible("TextLabel"))
+        };
+        assert!(!shared(&cache.instances));
+        intern_members(cache.instances.values_mut());
+        assert!(shared(&cache.instances));
+
+        // Inherited members are written once, and come back shared and unchanged
+        let encoded = encode_cache(&cache).unwrap();
+        assert!(encoded.len() * 2 < bincode::serialize(&cache).unwrap().len());
+        let decoded = decode_cache(&encoded).unwrap();
+        assert_eq!(decoded.version, "version-1");
+        assert!(shared(&decoded.instances));
+        let frame = &decoded.instances["Frame"];
+        assert_eq!(frame.properties, cache.instances["Frame"].properties);
+        assert_eq!(frame.methods, cache.instances["Frame"].methods);
+    }
+
+    #[test]
+    fn test_cache_corruption() {
+        let (instances, enums) = parse_api_dump(
+            r#"{ "Classes": [{ "Name": "Frame", "Superclass": "GuiObject", "Members": [] }] }"#,
+        )
+        .unwrap();
+        let cache = CachedApi {
+            version: "version-1".to_string(),
+            instances,
+            enums,
+            format: CACHE_FORMAT,
+            docs: Default::default(),
+            data_types: Default::default(),
+            validators: Default::default(),
+            fetched_at: 0,
+        };
+        let encoded = encode_cache(&cache).unwrap();
+        assert!(decode_cache(&encoded).is_some());
+
+        // Truncated writes and flipped bytes get rejected instead of decoding garbage
+        assert!(decode_cache(&encoded[..encoded.len() - 1]).is_none());
+        assert!(decode_cache(&encoded[..10]).is_none());
+        let mut flipped = encoded.clone();
+        *flipped.last_mut().unwrap() ^= 1;
+        assert!(decode_cache(&flipped).is_none());
+
+        // Written by a newer server, whose layout this one can't know
+        let mut newer = encoded;
+        newer[8..12].copy_from_slice(&(CACHE_FORMAT + 1).to_le_bytes());
+        assert!(decode_cache(&newer).is_none());
+    }
+
+    #[test]
+    fn test_usage_counts() {
+        let dir = env::temp_dir().join("rblx_react_lsp_tests/usage_counts");
+        let _ = fs::remove_dir_all(&dir);
+        let (game, other) = (Path::new("/projects/game"), Path::new("/projects/other"));
+        let path = usage_counts_path(&dir, game);
+        assert_ne!(path, usage_counts_path(&dir, other));
+        assert!(read_usage_counts(&path, game).is_none());
+
+        let counts = HashMap::from([("Visible".to_string(), 3), ("Frame".to_string(), 1)]);
+        write_usage_counts(&path, game, &counts).unwrap();
+        assert_eq!(read_usage_counts(&path, game), Some(counts));
+        // Another root that happened to land on the same file doesn't get them
+        assert!(read_usage_counts(&path, other).is_none());
+
+        // Cleared counts take the file with them
+        write_usage_counts(&path, game, &HashMap::new()).unwrap();
+        assert!(!path.exists());
+        write_usage_counts(&path, game, &HashMap::new()).unwrap();
+    }
+
+    #[test]
+    fn test_cache_location() {
+        let dir = env::temp_dir().join("rblx_react_lsp_tests/cache_location");
+        let _ = fs::remove_dir_all(&dir);
+        let cache = cache_dir(Some(dir.join("cache")));
+        assert_eq!(
+            get_cache_file_path(&cache),
+            dir.join("cache/serialized_api.bin")
+        );
+
+        // Clearing takes the readable export with it, missing files are skipped
+        fs::create_dir_all(dir.join("cache")).unwrap();
+        fs::write(get_cache_file_path(&cache), b"cache").unwrap();
+        fs::write(dir.join("readable_serialized_api.json"), b"{}").unwrap();
+        assert_eq!(cache_file_size(&cache), Some(5));
+        let removed = clear_cache_files(&cache, Some(&dir)).unwrap();
+        assert_eq!(
+            removed,
+            [
+                dir.join("cache/serialized_api.bin"),
+                dir.join("readable_serialized_api.json")
+            ]
+        );
+        assert_eq!(cache_file_size(&cache), None);
+        assert!(clear_cache_files(&cache, Some(&dir)).unwrap().is_empty());
+        assert!(
+            get_cache_file_path(&cache_dir(None)).ends_with("rblx-react-lsp/serialized_api.bin")
+        );
+
+        // Exe adjacent cache moves over once, an existing one is never replaced
+        let legacy = dir.join("exe/serialized_api.bin");
+        let target = dir.join("cache/serialized_api.bin");
+        fs::create_dir_all(legacy.parent().unwrap()).unwrap();
+        fs::write(&legacy, b"old").unwrap();
+        assert!(migrate_cache(&legacy, &target).unwrap());
+        assert_eq!(fs::read(&target).unwrap(), b"old");
+        assert!(!legacy.exists());
+
+        fs::write(&legacy, b"older").unwrap();
+        assert!(!migrate_cache(&legacy, &target).unwrap());
+        assert_eq!(fs::read(&target).unwrap(), b"old");
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_local_cache() {
+        let dir = env::temp_dir().join("rblx_react_lsp_tests/local_dump");
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("API-Dump.json");
+        fs::write(
+            &path,
+            r#"{ "Classes": [{ "Name": "BetaFrame", "Superclass": "GuiObject", "Members": [] }], "Enums": [] }"#,
+        )
+        .unwrap();
+
+        let cache = local_cache(&path).unwrap();
+        assert_eq!(
+            cache.version,
+            format!("{}{}", LOCAL_VERSION_PREFIX, path.display())
+        );
+        assert_eq!(cache.instances["BetaFrame"].superclass, "GuiObject");
+
+        let missing = local_cache(&dir.join("Missing.json")).unwrap_err();
+        assert!(missing.to_string().contains("Missing.json"));
+        fs::write(&path, "not json").unwrap();
+        assert!(local_cache(&path).is_err());
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_data_types() {
+        let api_docs = r#"{
+            "@roblox/global/UDim2.fromScale": { "documentation": "Scale only.",
+                "params": [{ "name": "xScale" }, { "name": "yScale" }] },
+            "@roblox/global/UDim2.fromScale/param/0": { "documentation": "X" },
+            "@roblox/global/Vector2.zero": { "documentation": "(0, 0)" },
+            "@roblox/globaltype/Vector2.Magnitude": { "documentation": "Length." },
+            "@roblox/globaltype/Color3.Lerp": { "documentation": "Blends.",
+                "params": [{ "name": "self" }, { "name": "color" }, { "name": "alpha" }] },
+            "@roblox/global/Instance.new": { "params": [{ "name": "className" }] },
+            "@roblox/global/math.abs": { "params": [{ "name": "x" }] }
+        }"#;
+        let (instances, _) = parse_api_dump(
+            r#"{ "Classes": [{ "Name": "Instance", "Superclass": "<<<ROOT>>>", "Members": [] }] }"#,
+        )
+        .unwrap();
+        let data_types = parse_data_types(api_docs, &instances).unwrap();
+        let mut names: Vec<&String> = data_types.keys().collect();
+        names.sort();
+        assert_eq!(names, ["Color3", "UDim2", "Vector2"]);
+
+        let from_scale = &data_types["UDim2"].constructors[0];
+        assert_eq!(from_scale.name, "fromScale");
+        assert_eq!(
+            from_scale.params,
+            Some(vec!["xScale".into(), "yScale".into()])
+        );
+        assert_eq!(from_scale.documentation, "Scale only.");
+        assert_eq!(data_types["Vector2"].constructors[0].params, None);
+        assert_eq!(data_types["Vector2"].properties[0].name, "Magnitude");
+        // Self isn't something callers pass
+        assert_eq!(
+            data_types["Color3"].methods[0].params,
+            Some(vec!["color".into(), "alpha".into()])
+        );
+    }
+
+    #[test]
+    fn test_member_inheritance() {
+        let dump = r#"{ "Classes": [
+            { "Name": "GuiObject", "Superclass": "GuiBase2d", "Members": [
+                { "MemberType": "Event", "Name": "InputBegan", "Parameters": [
+                    { "Name": "input", "Type": { "Category": "Class", "Name": "InputObject" } }
+                ] },
+                { "MemberType": "Function", "Name": "TweenPosition", "Parameters": [
+                    { "Name": "endPosition", "Type": { "Category": "DataType", "Name": "UDim2" } }
+                ], "ReturnType": { "Category": "Primitive", "Name": "bool" } }
+            ] },
+            { "Name": "TextButton", "Superclass": "GuiObject", "Members": [
+                { "MemberType": "Event", "Name": "Activated", "Parameters": [] }
+            ] }
+        ] }"#;
+        let (instances, _) = parse_api_dump(dump).unwrap();
+        let events: Vec<(&str, &str)> = instances["TextButton"]
+            .events
+            .iter()
+            .map(|event| (event.name.as_str(), event.data_type.as_str()))
+            .collect();
+        assert!(events.contains(&("Activated", "() -> ()")));
+        assert!(events.contains(&("InputBegan", "(input: InputObject) -> ()")));
+        assert_eq!(events.len(), 2);
+        let methods = &instances["TextButton"].methods;
+        assert_eq!(methods.len(), 1);
+        assert_eq!(methods[0].name, "TweenPosition");
+        assert_eq!(methods[0].data_type, "(endPosition: UDim2) -> bool");
+
+        // A cache written with an older format is stale even though it decodes
+        let cache = CachedApi {
+            version: "version-1".to_string(),
+            instances,
+            enums: Default::default(),
+            format: CACHE_FORMAT - 1,
+            docs: Default::default(),
+            data_types: Default::default(),
+            validators: Default::default(),
+            fetched_at: 0,
+        };
+        assert!(decode_cache(&encode_cache(&cache).unwrap()).is_none());
+    }
+
     #[tokio::test]
     async fn test_downloading_api() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         let download_result = download_api().await?;
@@ -325,12 +1367,18 @@ mod tests {
 
     #[tokio::test]
     async fn test_processing_with_cache() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let (dump, version) = download_api_with_version().await?;
-        let parsed_instances = parse_api_dump(&dump)?;
+        let (dump, version, _) = ApiFetcher::new().fetch(|_| {}).await?;
+        let (parsed_instances, parsed_enums) = parse_api_dump(&dump)?;
 
         let cache = CachedApi {
             version: version.clone(),
             instances: parsed_instances.clone(),
+            enums: parsed_enums,
+            format: CACHE_FORMAT,
+            docs: Default::default(),
+            data_types: Default::default(),
+            validators: Default::default(),
+            fetched_at: 0,
         };
 
         let cache_path = temp_dir().join("serialized_api.bin");
@@ -352,7 +1400,7 @@ mod tests {
     #[tokio::test]
     async fn test_backwards_compat_cache() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         let dump = download_api().await?;
-        let parsed_instances = parse_api_dump(&dump)?;
+        let (parsed_instances, _) = parse_api_dump(&dump)?;
 
         // Write in old format (raw ParsedInstances, no version)
         let cache_path = temp_dir().join("serialized_api_old.bin");
@@ -373,7 +1421,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_version_fetch() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let version = get_live_version().await?;
+        let version = ApiFetcher::new().live_version().await?;
         assert!(!version.is_empty(), "Version string should not be empty");
         println!("Live version: {}", version);
         Ok(())
@@ -387,18 +1435,25 @@ mod tests {
         fs::create_dir_all(&out_dir)?;
 
         println!("Downloading API dump...");
-        let (dump, version) = download_api_with_version().await?;
+        let (dump, version, _) = ApiFetcher::new().fetch(|_| {}).await?;
         println!("Version: {}", version);
 
-        let parsed_instances = parse_api_dump(&dump)?;
-        cache_file(&parsed_instances, &version)?;
-
+        let (parsed_instances, parsed_enums) = parse_api_dump(&dump)?;
         let out_path = out_dir.join("serialized_api.bin");
-        let cache = CachedApi {
+        let mut cache = CachedApi {
             version: version.clone(),
             instances: parsed_instances.clone(),
+            enums: parsed_enums,
+            format: CACHE_FORMAT,
+            docs: Default::default(),
+            data_types: Default::default(),
+            validators: Default::default(),
+            fetched_at: 0,
         };
-        let encoded = bincode::serialize(&cache)?;
+        // Written through the cache's encoding, which bundled_cache reads back with decode_cache
+        intern_members(cache.instances.values_mut());
+        cache_file(&cache_dir(None), &cache)?;
+        let encoded = encode_cache(&cache)?;
         fs::write(&out_path, &encoded)?;
 
         println!("Bundled cache written to: {}", out_path.display());
@@ -0,0 +1,128 @@
+// This script parses command line arguments, picking how the server talks to its client
+// (Hand rolled, editors only ever pass a handful of flags. Both "--tcp 5007" and "--tcp=5007" work)
+
+pub const USAGE: &str = "Usage: rblx-react-lsp [OPTIONS]
+
+Options:
+  --stdio              Talk over stdin/stdout(Default)
+  --tcp <PORT>         Connect to a client listening on 127.0.0.1:<PORT>(Also --socket <PORT>)
+  --listen             With --tcp, listen on the port instead and serve every client connecting
+  --pipe <PATH>        Connect to a client's named pipe(Windows) or unix domain socket
+  -V, --version        Print version and exit
+  -h, --help           Print this and exit";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Transport {
+    Stdio,
+    Tcp { port: u16, listen: bool },
+    Pipe(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Serve(Transport),
+    Version,
+    Help,
+}
+
+pub fn version() -> String {
+    format!("rblx-react-lsp {}", env!("CARGO_PKG_VERSION"))
+}
+
+// Arguments without the program name, an error message if they don't make sense together
+pub fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Command, String> {
+    let mut args = args.into_iter();
+    let mut stdio = false;
+    let mut port = None;
+    let mut listen = false;
+    let mut pipe = None;
+
+    while let Some(arg) = args.next() {
+        let (flag, inline_value) = match arg.split_once('=') {
+            Some((flag, value)) if flag.starts_with("--") => (flag.to_string(), Some(value)),
+            _ => (arg.clone(), None),
+        };
+        let mut value = |name: &str| {
+            inline_value
+                .map(str::to_string)
+                .or_else(|| args.next())
+                .ok_or_else(|| format!("{} needs a value", name))
+        };
+        match flag.as_str() {
+            "-V" | "--version" => return Ok(Command::Version),
+            "-h" | "--help" => return Ok(Command::Help),
+            "--stdio" => stdio = true,
+            "--tcp" | "--socket" => {
+                let text = value(&flag)?;
+                let parsed = text
+                    .parse::<u16>()
+                    .map_err(|_| format!("Invalid port '{}'", text))?;
+                port = Some(parsed);
+            }
+            "--listen" => listen = true,
+            "--pipe" => pipe = Some(value(&flag)?),
+            // vscode-languageclient passes it along to every server, we don't watch the client process
+            "--clientProcessId" => {
+                value(&flag)?;
+            }
+            _ => return Err(format!("Unknown argument '{}'", arg)),
+        }
+    }
+
+    let transport = match (stdio, port, pipe) {
+        (_, Some(_), Some(_)) | (true, Some(_), _) | (true, _, Some(_)) => {
+            return Err("Pick only one of --stdio, --tcp and --pipe".to_string())
+        }
+        (_, Some(port), None) => Transport::Tcp { port, listen },
+        (_, None, Some(path)) => Transport::Pipe(path),
+        (_, None, None) => Transport::Stdio,
+    };
+    if listen && !matches!(transport, Transport::Tcp { .. }) {
+        return Err("--listen only works with --tcp".to_string());
+    }
+    Ok(Command::Serve(transport))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cli::{parse_args, Command, Transport};
+
+    fn parse(args: &[&str]) -> Result<Command, String> {
+        parse_args(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn test_parse_args() {
+        assert_eq!(parse(&[]), Ok(Command::Serve(Transport::Stdio)));
+        assert_eq!(
+            parse(&["--stdio", "--clientProcessId=123"]),
+            Ok(Command::Serve(Transport::Stdio))
+        );
+        assert_eq!(
+            parse(&["--tcp", "5007"]),
+            Ok(Command::Serve(Transport::Tcp {
+                port: 5007,
+                listen: false
+            }))
+        );
+        assert_eq!(
+            parse(&["--listen", "--socket=5007"]),
+            Ok(Command::Serve(Transport::Tcp {
+                port: 5007,
+                listen: true
+            }))
+        );
+        assert_eq!(
+            parse(&["--pipe=/tmp/lsp.sock"]),
+            Ok(Command::Serve(Transport::Pipe("/tmp/lsp.sock".to_string())))
+        );
+        assert_eq!(parse(&["--tcp", "5007", "-V"]), Ok(Command::Version));
+        assert_eq!(parse(&["-h"]), Ok(Command::Help));
+
+        assert!(parse(&["--tcp"]).is_err());
+        assert!(parse(&["--tcp", "99999"]).is_err());
+        assert!(parse(&["--stdio", "--tcp", "5007"]).is_err());
+        assert!(parse(&["--listen"]).is_err());
+        assert!(parse(&["--verbose"]).is_err());
+    }
+}
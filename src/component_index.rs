@@ -0,0 +1,622 @@
+// This script indexes component modules(Modules returning a table of components) across the workspace
+// so elements like e(Components.Button, {...}) resolve to where Button is defined
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+use lazy_static::lazy_static;
+use regex::Regex;
+use tower_lsp::lsp_types::{Location, Range, Url};
+
+use crate::{
+    custom_classes::is_custom_class_file,
+    file_diagnoser::{
+        byte_offset_to_position, find_matching_brace, get_element_regions, innermost_region_at,
+        parse_props_table, split_top_level, PropKey,
+    },
+    file_manager::find_workspace_files,
+    file_refactorer::is_valid_identifier,
+    sourcemap::Sourcemap,
+    symbol_table::{
+        required_module_name, unwrap_component_wrapper, RequireContext, SymbolKind, SymbolTable,
+    },
+};
+
+lazy_static! {
+    // Matches "type Name = ", "export type Name<T> = " capturing Name
+    static ref TYPE_ALIAS_PATTERN: Regex =
+        Regex::new(r"(?m)^\s*(?:export\s+)?type\s+(\w+)\b(?:\s*<[^>]*>)?\s*=\s*").unwrap();
+    // Matches "Table.Key = value" at line start capturing Table, Key and value
+    static ref FIELD_ASSIGNMENT_PATTERN: Regex =
+        Regex::new(r"(?m)^(\w+)\.(\w+)\s*=([^\n]*)").unwrap();
+    // Matches "function Table.Key(" at line start capturing Table and Key
+    static ref FIELD_FUNCTION_PATTERN: Regex =
+        Regex::new(r"(?m)^function\s+(\w+)\.(\w+)\s*\(").unwrap();
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComponentDef {
+    pub name: String,
+    pub location: Location,
+    pub props: Vec<String>, // Inferred from props.X reads in component body and its props annotation
+    pub prop_types: HashMap<String, String>, // Prop -> annotated type
+}
+
+#[derive(Debug, Default)]
+pub struct ComponentIndex {
+    modules: HashMap<Url, (String, Vec<ComponentDef>)>, // Module name and components it exports
+    create_element_modules: HashMap<Url, String>, // Names of modules returning a createElement helper
+    sourcemap: Sourcemap, // Resolves requires to exact files, module names are the fallback
+}
+
+pub fn is_luau_file(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|ext| ext.to_str()),
+        Some("luau" | "lua")
+    ) && !is_custom_class_file(path)
+}
+
+// Name module gets required under(File stem, or folder name for init files like Rojo does)
+fn module_name(uri: &Url) -> Option<String> {
+    let path = uri.to_file_path().ok()?;
+    let stem = path.file_stem()?.to_str()?;
+    if stem == "init" {
+        return Some(path.parent()?.file_name()?.to_str()?.to_string());
+    }
+    Some(stem.to_string())
+}
+
+fn location(doc: &str, uri: &Url, range: (usize, usize)) -> Location {
+    Location {
+        uri: uri.clone(),
+        range: Range {
+            start: byte_offset_to_position(doc, range.0),
+            end: byte_offset_to_position(doc, range.1),
+        },
+    }
+}
+
+// Named type alias declared in doc, e.g. the "{ ... }" of "type Props = { ... }"
+fn type_alias<'a>(doc: &'a str, name: &str) -> Option<&'a str> {
+    let start = TYPE_ALIAS_PATTERN
+        .captures_iter(doc)
+        .find(|caps| &caps[1] == name)?
+        .get(0)?
+        .end();
+    // Alias ends at first line break outside of brackets
+    let mut depth = 0usize;
+    let end = doc[start..]
+        .char_indices()
+        .find(|&(_, c)| {
+            match c {
+                '{' | '(' | '[' => depth += 1,
+                '}' | ')' | ']' => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+            c == '\n' && depth == 0
+        })
+        .map(|(i, _)| start + i)
+        .unwrap_or(doc.len());
+    Some(doc[start..end].trim())
+}
+
+// (Prop, type) of every field in a props annotation, following aliases and & intersections
+fn annotated_prop_types(doc: &str, annotation: &str, depth: usize) -> Vec<(String, String)> {
+    let mut fields = Vec::new();
+    for (start, end) in split_top_level(annotation, &['&']) {
+        let part = annotation[start..end].trim().trim_end_matches('?');
+        if let Some(inner) = part.strip_prefix('{').and_then(|p| p.strip_suffix('}')) {
+            fields.extend(split_top_level(inner, &[',', ';']).into_iter().filter_map(
+                |(start, end)| {
+                    let (name, field_type) = inner[start..end].split_once(':')?;
+                    let name = name.trim();
+                    is_valid_identifier(name)
+                        .then(|| (name.to_string(), field_type.trim().to_string()))
+                },
+            ));
+        } else if depth > 0 && is_valid_identifier(part) {
+            // Aliases can refer to other aliases, depth keeps cyclic ones from looping forever
+            if let Some(alias) = type_alias(doc, part) {
+                fields.extend(annotated_prop_types(doc, alias, depth - 1));
+            }
+        }
+    }
+    fields
+}
+
+// Props of a function component, both read in its body and declared by its first param's annotation
+fn function_component_props(
+    doc: &str,
+    symbols: &SymbolTable,
+    function: usize,
+) -> (Vec<String>, HashMap<String, String>) {
+    let prop_types: HashMap<String, String> = symbols
+        .first_param_type(doc, function)
+        .map(|annotation| annotated_prop_types(doc, annotation, 4))
+        .unwrap_or_default()
+        .into_iter()
+        .collect();
+    let mut props = symbols.function_props(doc, function);
+    props.extend(prop_types.keys().cloned());
+    props.sort();
+    props.dedup();
+    (props, prop_types)
+}
+
+// Component a value expression refers to, falling back to the key itself for anything we can't follow
+fn component_from_value(
+    doc: &str,
+    uri: &Url,
+    symbols: &SymbolTable,
+    name: &str,
+    key_range: (usize, usize),
+    value: (usize, usize),
+) -> ComponentDef {
+    let raw = &doc[value.0..value.1];
+    let leading = raw.len() - raw.trim_start().len();
+    let (offset, text) = unwrap_component_wrapper(raw.trim());
+    let (range, function) = if text.starts_with("function") {
+        let start = value.0 + leading + offset;
+        let function = symbols
+            .functions
+            .iter()
+            .position(|f| f.keyword_start == start);
+        (key_range, function)
+    } else {
+        symbols
+            .symbols
+            .iter()
+            .rev()
+            .find(|s| s.range.0 < value.0 && s.name == text && s.function.is_some())
+            .map(|s| (s.range, s.function))
+            .unwrap_or((key_range, None))
+    };
+
+    let (props, prop_types) = function
+        .map(|f| function_component_props(doc, symbols, f))
+        .unwrap_or_default();
+    ComponentDef {
+        name: name.to_string(),
+        location: location(doc, uri, range),
+        props,
+        prop_types,
+    }
+}
+
+// Named entries of table literal starting at brace
+fn table_components(
+    doc: &str,
+    uri: &Url,
+    symbols: &SymbolTable,
+    brace_start: usize,
+) -> Vec<ComponentDef> {
+    let brace_end = find_matching_brace(doc, brace_start + 1);
+    parse_props_table(doc, brace_start, brace_end)
+        .into_iter()
+        .filter_map(|entry| {
+            let PropKey::Name(name) = &entry.key else {
+                return None;
+            };
+            let eq = doc[entry.key_range.1..entry.range.1].find('=')?;
+            let value = (entry.key_range.1 + eq + 1, entry.range.1);
+            Some(component_from_value(
+                doc,
+                uri,
+                symbols,
+                name,
+                entry.key_range,
+                value,
+            ))
+        })
+        .collect()
+}
+
+// (Start of return keyword, returned expression) of module's top level return statement
+fn top_level_return(doc: &str) -> Option<(usize, &str)> {
+    let return_start = doc
+        .match_indices("return")
+        .map(|(i, _)| i)
+        .filter(|&i| i == 0 || doc.as_bytes()[i - 1] == b'\n')
+        .filter(|&i| {
+            !doc[i + "return".len()..].starts_with(|c: char| c.is_alphanumeric() || c == '_')
+        })
+        .last()?;
+    Some((
+        return_start,
+        doc[return_start + "return".len()..].trim_start(),
+    ))
+}
+
+// Whether module returns a createElement helper(return React.createElement, or a local bound to it)
+fn exports_create_element(doc: &str, symbols: &SymbolTable) -> bool {
+    let Some((return_start, expr)) = top_level_return(doc) else {
+        return false;
+    };
+    let expr = expr
+        .lines()
+        .next()
+        .unwrap_or("")
+        .trim()
+        .trim_end_matches(';');
+    symbols.react_var_names(doc).iter().any(|react_var_name| {
+        expr == format!("{react_var_name}.createElement")
+            || symbols
+                .create_element_macros(doc, return_start, react_var_name)
+                .iter()
+                .any(|name| name == expr)
+    })
+}
+
+// Components exported through module's top level return statement
+fn exported_components(doc: &str, uri: &Url, symbols: &SymbolTable) -> Vec<ComponentDef> {
+    let Some((return_start, expr)) = top_level_return(doc) else {
+        return Vec::new();
+    };
+    let expr_start = doc.len() - expr.len();
+
+    if expr.starts_with('{') {
+        return table_components(doc, uri, symbols, expr_start);
+    }
+
+    // return Components, where Components is a local table filled in before returning
+    let name_len = expr
+        .find(|c: char| !c.is_alphanumeric() && c != '_')
+        .unwrap_or(expr.len());
+    let name = &expr[..name_len];
+    if !is_valid_identifier(name) {
+        return Vec::new();
+    }
+    let Some(table) = symbols
+        .symbols
+        .iter()
+        .rev()
+        .find(|s| s.kind == SymbolKind::Local && s.name == name && s.range.0 < return_start)
+    else {
+        return Vec::new();
+    };
+
+    let mut components = match table.value {
+        Some((start, _)) if doc[start..].starts_with('{') => {
+            table_components(doc, uri, symbols, start)
+        }
+        _ => Vec::new(),
+    };
+
+    // Components.Button = Button and function Components.Button(props)
+    let fields = |pattern: &'static Regex| {
+        pattern
+            .captures_iter(&doc[..return_start])
+            .filter(move |caps| &caps[1] == name)
+    };
+    for caps in fields(&FIELD_ASSIGNMENT_PATTERN) {
+        let key = caps.get(2).unwrap();
+        let value = caps.get(3).unwrap();
+        components.push(component_from_value(
+            doc,
+            uri,
+            symbols,
+            key.as_str(),
+            (key.start(), key.end()),
+            (value.start(), value.end()),
+        ));
+    }
+    for caps in fields(&FIELD_FUNCTION_PATTERN) {
+        let key = caps.get(2).unwrap();
+        let start = caps.get(0).unwrap().start();
+        let (props, prop_types) = symbols
+            .functions
+            .iter()
+            .position(|f| f.keyword_start == start)
+            .map(|f| function_component_props(doc, symbols, f))
+            .unwrap_or_default();
+        components.push(ComponentDef {
+            name: key.as_str().to_string(),
+            location: location(doc, uri, (key.start(), key.end())),
+            props,
+            prop_types,
+        });
+    }
+
+    components
+}
+
+impl ComponentIndex {
+    // Indexes every luau file under root from disk
+    pub fn from_workspace(root: &Path, requires: &Arc<RequireContext>) -> Self {
+        let mut index = Self::default();
+        for path in find_workspace_files(root, is_luau_file) {
+            let (Ok(text), Ok(uri)) = (fs::read_to_string(&path), Url::from_file_path(&path))
+            else {
+                continue;
+            };
+            index.index_module(&uri, &text, &SymbolTable::build(&text, requires));
+        }
+        index
+    }
+
+    // Adds modules of other index this one doesn't have yet(Open documents are newer than disk)
+    pub fn merge_missing(&mut self, other: ComponentIndex) {
+        for (uri, module) in other.modules {
+            self.modules.entry(uri).or_insert(module);
+        }
+        for (uri, name) in other.create_element_modules {
+            self.create_element_modules.entry(uri).or_insert(name);
+        }
+    }
+
+    pub fn index_module(&mut self, uri: &Url, doc: &str, symbols: &SymbolTable) {
+        let components = exported_components(doc, uri, symbols);
+        self.remove_module(uri);
+        let Some(name) = module_name(uri) else {
+            return;
+        };
+        if exports_create_element(doc, symbols) {
+            self.create_element_modules
+                .insert(uri.clone(), name.clone());
+        }
+        if !components.is_empty() {
+            self.modules.insert(uri.clone(), (name, components));
+        }
+    }
+
+    pub fn remove_module(&mut self, uri: &Url) {
+        self.modules.remove(uri);
+        self.create_element_modules.remove(uri);
+    }
+
+    // Names of indexed modules that return a createElement helper, sorted
+    pub fn create_element_modules(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.create_element_modules.values().cloned().collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    pub fn set_sourcemap(&mut self, sourcemap: Sourcemap) {
+        self.sourcemap = sourcemap;
+    }
+
+    pub fn sourcemap(&self) -> &Sourcemap {
+        &self.sourcemap
+    }
+
+    // (Uri, module name, components) of every indexed module
+    pub fn modules(&self) -> impl Iterator<Item = (&Url, &str, &[ComponentDef])> {
+        self.modules
+            .iter()
+            .map(|(uri, (name, components))| (uri, name.as_str(), components.as_slice()))
+    }
+
+    // Lowest uri wins when multiple modules share a name, so lookups stay stable
+    pub fn module_components(&self, module: &str) -> &[ComponentDef] {
+        self.modules
+            .iter()
+            .filter(|(_, (module_name, _))| module_name == module)
+            .min_by_key(|(uri, _)| uri.as_str())
+            .map(|(_, (_, components))| components.as_slice())
+            .unwrap_or_default()
+    }
+
+    // Components of module a require(...) value inside uri loads
+    fn required_components(&self, value: &str, uri: &Url) -> &[ComponentDef] {
+        let from_file = uri.to_file_path().ok();
+        if let Some(path) = from_file.and_then(|from| self.sourcemap.resolve_require(value, &from))
+        {
+            // Sourcemap knows the exact file, so a same named module elsewhere can't shadow it
+            return Url::from_file_path(path)
+                .ok()
+                .and_then(|target| self.modules.get(&target))
+                .map(|(_, components)| components.as_slice())
+                .unwrap_or_default();
+        }
+        required_module_name(value)
+            .map(|module| self.module_components(&module))
+            .unwrap_or_default()
+    }
+
+    // (Local name, module components) of every indexed component module document requires
+    pub fn required_modules<'a>(
+        &'a self,
+        doc: &str,
+        uri: &Url,
+        symbols: &SymbolTable,
+    ) -> Vec<(String, &'a [ComponentDef])> {
+        symbols
+            .symbols
+            .iter()
+            .filter(|s| s.kind == SymbolKind::Local)
+            .filter_map(|s| {
+                let components = self.required_components(symbols.value_text(doc, s)?, uri);
+                (!components.is_empty()).then(|| (s.name.clone(), components))
+            })
+            .collect()
+    }
+
+    // Component an element's first argument(Button or Components.Button) refers to
+    pub fn resolve(
+        &self,
+        doc: &str,
+        uri: &Url,
+        symbols: &SymbolTable,
+        expr: &str,
+    ) -> Option<ComponentDef> {
+        let expr = expr.trim();
+        if let Some((base, member)) = expr.split_once('.') {
+            let (base, member) = (base.trim(), member.trim());
+            if !is_valid_identifier(base) || !is_valid_identifier(member) {
+                return None;
+            }
+            return symbols
+                .symbols
+                .iter()
+                .filter(|s| s.name == base)
+                .find_map(|s| {
+                    let components = self.required_components(symbols.value_text(doc, s)?, uri);
+                    components.iter().find(|c| c.name == member)
+                })
+                .cloned();
+        }
+
+        if !is_valid_identifier(expr) {
+            return None;
+        }
+        let symbol = symbols
+            .symbols
+            .iter()
+            .filter(|s| s.name == expr && s.kind != SymbolKind::Parameter)
+            .find(|s| s.function.is_some())?;
+        let (props, prop_types) = function_component_props(doc, symbols, symbol.function?);
+        Some(ComponentDef {
+            name: symbol.name.clone(),
+            location: location(doc, uri, symbol.range),
+            props,
+            prop_types,
+        })
+    }
+
+    // Definition of component whose element's first argument is under byte offset
+    pub fn definition_at(
+        &self,
+        doc: &str,
+        uri: &Url,
+        symbols: &SymbolTable,
+        byte_offset: usize,
+    ) -> Option<Location> {
+        let regions = get_element_regions(doc, symbols.requires());
+        let region = innermost_region_at(&regions, byte_offset)?;
+        let &(start, end) = region.args.first()?;
+        if byte_offset < start || byte_offset > end {
+            return None;
+        }
+        self.resolve(doc, uri, symbols, &doc[start..end])
+            .map(|component| component.location)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tower_lsp::lsp_types::Url;
+
+    use crate::component_index::ComponentIndex;
+    use crate::file_diagnoser::byte_offset_to_position;
+    use crate::symbol_table::{required_module_name, SymbolTable};
+
+    const MODULE: &str = r#"local React = require(game.Packages.React)
+
+local function Button(props)
+    return React.createElement("TextButton", { Text = props.text, [React.Event.Activated] = props.onClick })
+end
+
+local Components = {
+    Button = Button,
+    Label = function(props)
+        return React.createElement("TextLabel", { Text = props.label })
+    end,
+}
+
+function Components.Icon(props)
+    return React.createElement("ImageLabel", { Image = props.image })
+end
+
+Components.Memo = React.memo(Button)
+Components.Input = React.forwardRef(function(props, ref)
+    return React.createElement("TextBox", { PlaceholderText = props.hint, ref = ref })
+end)
+
+return Components
+"#;
+
+    #[test]
+    fn test_required_module_name() {
+        assert_eq!(
+            required_module_name("require(script.Parent.Components)"),
+            Some("Components".to_string())
+        );
+        assert_eq!(
+            required_module_name(r#"require(script.Parent:WaitForChild("Components"))"#),
+            Some("Components".to_string())
+        );
+        assert_eq!(
+            required_module_name(r#"require("../Shared/Components/init.luau")"#),
+            Some("Components".to_string())
+        );
+        assert_eq!(required_module_name("Components.Button"), None);
+    }
+
+    #[test]
+    fn test_create_element_modules() {
+        let mut index = ComponentIndex::default();
+        let direct = "local React = require(game.Packages.React)\nreturn React.createElement\n";
+        let aliased =
+            "local React = require(game.Packages.React)\nlocal e = React.createElement\nreturn e;\n";
+        let other = "local React = require(game.Packages.React)\nreturn React\n";
+        for (uri, doc) in [
+            ("file:///project/src/e.luau", direct),
+            ("file:///project/src/CreateElement/init.luau", aliased),
+            ("file:///project/src/Other.luau", other),
+        ] {
+            let uri = Url::parse(uri).unwrap();
+            index.index_module(&uri, doc, &SymbolTable::build(doc, &Default::default()));
+        }
+        assert_eq!(index.create_element_modules(), ["CreateElement", "e"]);
+
+        index.remove_module(&Url::parse("file:///project/src/e.luau").unwrap());
+        assert_eq!(index.create_element_modules(), ["CreateElement"]);
+    }
+
+    #[test]
+    fn test_component_index() {
+        let module_uri = Url::parse("file:///project/src/Components/init.luau").unwrap();
+        let mut index = ComponentIndex::default();
+        index.index_module(
+            &module_uri,
+            MODULE,
+            &SymbolTable::build(MODULE, &Default::default()),
+        );
+
+        let doc = r#"local React = require(game.Packages.React)
+local Components = require(script.Parent.Components)
+local e = React.createElement
+return e(Components.Button, {})
+"#;
+        let uri = Url::parse("file:///project/src/App.luau").unwrap();
+        let symbols = SymbolTable::build(doc, &Default::default());
+
+        let button = index
+            .resolve(doc, &uri, &symbols, "Components.Button")
+            .unwrap();
+        assert_eq!(button.props, vec!["onClick", "text"]);
+        assert_eq!(
+            button.location.range.start,
+            byte_offset_to_position(MODULE, MODULE.find("Button(props)").unwrap())
+        );
+        let label = index.resolve(doc, &uri, &symbols, "Components.Label");
+        assert_eq!(label.unwrap().props, vec!["label"]);
+        let icon = index.resolve(doc, &uri, &symbols, "Components.Icon");
+        assert_eq!(icon.unwrap().props, vec!["image"]);
+        // memo/forwardRef wrappers resolve to what they wrap
+        let memo = index.resolve(doc, &uri, &symbols, "Components.Memo");
+        assert_eq!(
+            memo.unwrap().location.range.start,
+            button.location.range.start
+        );
+        let input = index.resolve(doc, &uri, &symbols, "Components.Input");
+        assert_eq!(input.unwrap().props, vec!["hint"]);
+        assert!(index
+            .resolve(doc, &uri, &symbols, "Components.Missing")
+            .is_none());
+
+        let offset = doc.find("Button, {}").unwrap() + 2;
+        let location = index.definition_at(doc, &uri, &symbols, offset).unwrap();
+        assert_eq!(location.uri, module_uri);
+
+        // Same file components resolve without the index
+        let module_symbols = SymbolTable::build(MODULE, &Default::default());
+        let local = ComponentIndex::default()
+            .resolve(MODULE, &module_uri, &module_symbols, "Button")
+            .unwrap();
+        assert_eq!(local.props, vec!["onClick", "text"]);
+    }
+}
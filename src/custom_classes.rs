@@ -0,0 +1,213 @@
+// This script reads user defined classes(customClasses.json or luau type stubs) that get merged into the API
+
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::api_parser::{ParsedInstance, ParsedProperty, SharedProperty};
+use crate::file_manager::find_workspace_files;
+
+pub const CUSTOM_CLASSES_FILE: &str = "customClasses.json";
+
+// customClasses.json maps class name -> this
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, rename_all = "camelCase")]
+struct CustomClass {
+    superclass: String,
+    properties: HashMap<String, String>, // Property name -> type
+    events: Vec<String>,
+}
+
+pub fn is_custom_class_file(path: &Path) -> bool {
+    let Some(file_name) = path.file_name().and_then(|name| name.to_str()) else {
+        return false;
+    };
+    file_name == CUSTOM_CLASSES_FILE
+        || file_name.ends_with(".d.luau")
+        || file_name.ends_with(".d.lua")
+}
+
+// Members are only the class' own ones, ApiManager adds inherited ones when merging
+pub fn parse_custom_classes_json(text: &str) -> Result<Vec<ParsedInstance>, serde_json::Error> {
+    let classes: HashMap<String, CustomClass> = serde_json::from_str(text)?;
+    let mut parsed: Vec<ParsedInstance> = classes
+        .into_iter()
+        .map(|(name, class)| {
+            let mut properties: Vec<SharedProperty> = class
+                .properties
+                .into_iter()
+                .map(|(name, data_type)| {
+                    Arc::new(ParsedProperty {
+                        name,
+                        data_type,
+                        default: None,
+                        tags: Vec::new(),
+                    })
+                })
+                .collect();
+            properties.sort_by(|a, b| a.name.cmp(&b.name));
+
+            ParsedInstance {
+                instance: name,
+                superclass: class.superclass,
+                properties,
+                events: class
+                    .events
+                    .into_iter()
+                    .map(|name| {
+                        Arc::new(ParsedProperty {
+                            name,
+                            data_type: "Function".to_string(),
+                            default: None,
+                            tags: Vec::new(),
+                        })
+                    })
+                    .collect(),
+                methods: Vec::new(),
+                restricted: Vec::new(),
+                tags: Vec::new(),
+            }
+        })
+        .collect();
+    parsed.sort_by(|a, b| a.instance.cmp(&b.instance));
+    Ok(parsed)
+}
+
+// Picks "declare class Name extends Super ... end" blocks out of a definitions file
+// Fields typed as RBXScriptSignal become events, methods are skipped as they can't be props
+pub fn parse_type_stubs(text: &str) -> Vec<ParsedInstance> {
+    let mut parsed = Vec::new();
+    let mut current: Option<ParsedInstance> = None;
+
+    for line in text.lines() {
+        let line = line.split("--").next().unwrap_or("").trim();
+
+        if let Some(header) = line.strip_prefix("declare class ") {
+            let mut words = header.split_whitespace();
+            let name = words.next().unwrap_or("").to_string();
+            let superclass = match (words.next(), words.next()) {
+                (Some("extends"), Some(superclass)) => superclass.to_string(),
+                _ => String::new(),
+            };
+            parsed.extend(current.take());
+            current = Some(ParsedInstance {
+                instance: name,
+                superclass,
+                properties: Vec::new(),
+                events: Vec::new(),
+                methods: Vec::new(),
+                restricted: Vec::new(),
+                tags: Vec::new(),
+            });
+            continue;
+        }
+
+        let Some(class) = current.as_mut() else {
+            continue;
+        };
+        if line == "end" {
+            parsed.extend(current.take());
+            continue;
+        }
+        if line.starts_with("function ") {
+            continue;
+        }
+
+        if let Some((name, data_type)) = line.split_once(':') {
+            let name = name.trim();
+            let data_type = data_type.trim();
+            if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+                continue;
+            }
+            if data_type.starts_with("RBXScriptSignal") {
+                class.events.push(Arc::new(ParsedProperty {
+                    name: name.to_string(),
+                    data_type: "Function".to_string(),
+                    default: None,
+                    tags: Vec::new(),
+                }));
+            } else {
+                class.properties.push(Arc::new(ParsedProperty {
+                    name: name.to_string(),
+                    data_type: data_type.to_string(),
+                    default: None,
+                    tags: Vec::new(),
+                }));
+            }
+        }
+    }
+
+    parsed.extend(current);
+    parsed.retain(|class| !class.instance.is_empty());
+    parsed
+}
+
+pub fn parse_custom_class_file(
+    path: &Path,
+    text: &str,
+) -> Result<Vec<ParsedInstance>, serde_json::Error> {
+    if path.file_name().and_then(|name| name.to_str()) == Some(CUSTOM_CLASSES_FILE) {
+        parse_custom_classes_json(text)
+    } else {
+        Ok(parse_type_stubs(text))
+    }
+}
+
+// Every custom class file under root
+pub fn find_custom_class_files(root: &Path) -> Vec<PathBuf> {
+    find_workspace_files(root, is_custom_class_file)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::custom_classes::{
+        is_custom_class_file, parse_custom_classes_json, parse_type_stubs,
+    };
+
+    #[test]
+    fn test_parse_custom_classes_json() {
+        let classes = parse_custom_classes_json(
+            r#"{ "FancyButton": { "superclass": "TextButton", "properties": { "Variant": "string" }, "events": ["Pressed"] } }"#,
+        )
+        .unwrap();
+        assert_eq!(classes.len(), 1);
+        assert_eq!(classes[0].instance, "FancyButton");
+        assert_eq!(classes[0].superclass, "TextButton");
+        assert_eq!(classes[0].properties[0].name, "Variant");
+        assert_eq!(classes[0].events[0].name, "Pressed");
+        assert!(parse_custom_classes_json("[1, 2]").is_err());
+    }
+
+    #[test]
+    fn test_parse_type_stubs() {
+        let classes = parse_type_stubs(
+            r#"
+declare class FancyButton extends TextButton
+    Variant: string -- "primary" or "secondary"
+    Pressed: RBXScriptSignal<>
+    function Flash(self): ()
+end
+
+declare class Standalone
+    Count: number
+end
+"#,
+        );
+        assert_eq!(classes.len(), 2);
+        assert_eq!(classes[0].superclass, "TextButton");
+        assert_eq!(classes[0].properties.len(), 1);
+        assert_eq!(classes[0].properties[0].data_type, "string");
+        assert_eq!(classes[0].events[0].name, "Pressed");
+        assert_eq!(classes[1].superclass, "");
+    }
+
+    #[test]
+    fn test_is_custom_class_file() {
+        assert!(is_custom_class_file(Path::new("/a/customClasses.json")));
+        assert!(is_custom_class_file(Path::new("/a/globals.d.luau")));
+        assert!(!is_custom_class_file(Path::new("/a/App.luau")));
+    }
+}
@@ -0,0 +1,180 @@
+// This script works out where in an element call the cursor is, off the symbol table's bracket pairs
+// (Brackets inside strings, comments or nested calls never count, unlike scanning the call's text did)
+
+use crate::{
+    file_diagnoser::{ElementRegion, PropEntry, PropKey},
+    symbol_table::{literal_end, SymbolTable},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum ElementContext<'a> {
+    FirstArgument, // Class name string or component the element gets created from
+    PropKey,       // Key of a props table entry, typed or about to be
+    ComputedKey((usize, usize)), // Inside a props entry's [] key, offsets of both brackets
+    PropValue(&'a PropEntry), // Value of a props entry(Calls and indexing in it included)
+    ChildKey((usize, usize)), // Key of an entry in a children table, offsets of its braces
+    Other,         // Any other argument, or a table nested in one
+}
+
+// Whether a top level "," comes between call's paren and offset
+fn past_first_argument(doc: &str, region: &ElementRegion, offset: usize) -> bool {
+    let mut depth = 0usize;
+    let mut i = region.start;
+    while i < offset {
+        if let Some(end) = literal_end(doc, i) {
+            i = end;
+            continue;
+        }
+        match doc.as_bytes()[i] {
+            b'(' | b'{' | b'[' => depth += 1,
+            b')' | b'}' | b']' => depth = depth.saturating_sub(1),
+            b',' if depth == 0 => return true,
+            _ => {}
+        }
+        i += 1;
+    }
+    false
+}
+
+// Entry whose text holds offset, the one being typed if only whitespace follows it(e.g. "Visible = |")
+fn entry_at<'a>(doc: &str, region: &'a ElementRegion, offset: usize) -> Option<&'a PropEntry> {
+    region
+        .entries
+        .iter()
+        .take_while(|entry| entry.range.0 <= offset)
+        .last()
+        .filter(|entry| offset <= entry.range.1 || doc[entry.range.1..offset].trim().is_empty())
+}
+
+// Whether offset comes after entry's "=", so it's in the value rather than the key
+fn in_value(doc: &str, entry: &PropEntry, offset: usize) -> bool {
+    entry.key != PropKey::Positional
+        && offset > entry.key_range.1
+        && doc[entry.key_range.1..offset].trim_start().starts_with('=')
+}
+
+pub(crate) fn element_context<'a>(
+    doc: &str,
+    symbols: &SymbolTable,
+    region: &'a ElementRegion,
+    offset: usize,
+) -> ElementContext<'a> {
+    let Some(paren) = region.start.checked_sub(1) else {
+        return ElementContext::Other;
+    };
+    // Innermost first, ending with the call's own paren
+    let brackets: Vec<(usize, usize)> = symbols
+        .enclosing_brackets(offset)
+        .into_iter()
+        .take_while(|&(open, _)| open >= paren)
+        .collect();
+    if brackets.last().map(|&(open, _)| open) != Some(paren) {
+        return ElementContext::Other;
+    }
+    let Some(&argument) = brackets.len().checked_sub(2).map(|i| &brackets[i]) else {
+        return match past_first_argument(doc, region, offset) {
+            true => ElementContext::Other,
+            false => ElementContext::FirstArgument,
+        };
+    };
+    let nested = &brackets[..brackets.len() - 2];
+
+    if region.props.map(|(open, _)| open) == Some(argument.0) {
+        let entry = entry_at(doc, region, offset);
+        let Some(&inner) = nested.last() else {
+            return match entry.filter(|entry| in_value(doc, entry, offset)) {
+                Some(entry) => ElementContext::PropValue(entry),
+                None => ElementContext::PropKey,
+            };
+        };
+        // "[" opening an entry is its key, any other one is indexing in a value
+        let opens_entry = entry.is_none_or(|entry| entry.range.0 == inner.0);
+        if doc.as_bytes()[inner.0] == b'[' && opens_entry {
+            return match nested.len() {
+                1 => ElementContext::ComputedKey(inner),
+                _ => ElementContext::Other,
+            };
+        }
+        if nested.iter().any(|&(open, _)| doc.as_bytes()[open] == b'{') {
+            return ElementContext::Other;
+        }
+        return match entry {
+            Some(entry) => ElementContext::PropValue(entry),
+            None => ElementContext::Other,
+        };
+    }
+
+    // Tables after the props one hold children
+    let is_children_table = doc.as_bytes()[argument.0] == b'{'
+        && region
+            .args
+            .iter()
+            .skip(2)
+            .any(|&(start, _)| start == argument.0);
+    if is_children_table && nested.is_empty() {
+        return ElementContext::ChildKey(argument);
+    }
+    ElementContext::Other
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        element_context::{element_context, ElementContext},
+        file_diagnoser::get_element_regions,
+        symbol_table::SymbolTable,
+    };
+
+    // Context at the "|" in doc, against its innermost element call
+    fn context_at(doc: &str) -> String {
+        let offset = doc.find('|').unwrap();
+        let doc = doc.replacen('|', "", 1);
+        let symbols = SymbolTable::build(&doc, &Default::default());
+        let regions = get_element_regions(&doc, &Default::default());
+        let region = regions
+            .iter()
+            .filter(|region| region.start <= offset && offset <= region.end)
+            .min_by_key(|region| region.end - region.start)
+            .unwrap();
+        match element_context(&doc, &symbols, region, offset) {
+            ElementContext::PropValue(entry) => {
+                format!("PropValue({})", &doc[entry.key_range.0..entry.key_range.1])
+            }
+            ElementContext::ComputedKey(_) => "ComputedKey".to_string(),
+            ElementContext::ChildKey(_) => "ChildKey".to_string(),
+            other => format!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_element_context() {
+        let e = "local React = require(game.React)\nlocal e = React.createElement\n";
+        let at = |call: &str| context_at(&format!("{}return {}\n", e, call));
+
+        assert_eq!(at(r#"e("Fr|")"#), "FirstArgument");
+        assert_eq!(at(r#"e(Comp|, {})"#), "FirstArgument");
+        assert_eq!(at(r#"e("Frame", |)"#), "Other");
+        assert_eq!(at(r#"e("Frame", { Vis| })"#), "PropKey");
+        assert_eq!(at(r#"e("Frame", { Visible = true, | })"#), "PropKey");
+        assert_eq!(at(r#"e("Frame", { Visible = tr| })"#), "PropValue(Visible)");
+        assert_eq!(
+            at(r#"e("Frame", { Visible = |, Size = x })"#),
+            "PropValue(Visible)"
+        );
+        assert_eq!(
+            at(r#"e("Frame", { Size = UDim2.new(0, |) })"#),
+            "PropValue(Size)"
+        );
+        assert_eq!(at(r#"e("Frame", { [React.Event.Act|] })"#), "ComputedKey");
+        assert_eq!(at(r#"e("Frame", { Text = t[|] })"#), "PropValue(Text)");
+        assert_eq!(at(r#"e("Frame", { Style = { Fo| } })"#), "Other");
+        assert_eq!(at(r#"e("Frame", {}, { Chi| })"#), "ChildKey");
+
+        // Braces in strings and comments don't close anything
+        assert_eq!(
+            at("e(\"TextLabel\", { Text = \"}\", -- {\n    Vis| })"),
+            "PropKey"
+        );
+        assert_eq!(at("e(\"TextLabel\", { Text = \"{\", Vis| })"), "PropKey");
+    }
+}
@@ -0,0 +1,270 @@
+// This script builds the createElement tree of a document, used for syncing it to the Studio plugin
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use tower_lsp::lsp_types::{notification::Notification, Range, Url};
+
+use crate::file_diagnoser::{byte_offset_to_position, get_element_regions, ElementRegion, PropKey};
+use crate::symbol_table::RequireContext;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ElementProp {
+    pub key: String,
+    pub value: String, // Source text of value, we don't evaluate anything
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ElementNode {
+    pub element: String, // Class name for host elements, source text of first argument otherwise
+    pub is_host: bool,
+    pub props: Vec<ElementProp>,
+    pub children: Vec<ElementNode>,
+    pub range: Range,
+}
+
+fn build_node(doc: &str, region: &ElementRegion) -> ElementNode {
+    let element = match (&region.class_name, region.args.first()) {
+        (Some(class_name), _) => class_name.clone(),
+        (None, Some((start, end))) => doc[*start..*end].to_string(),
+        (None, None) => String::new(),
+    };
+
+    let props = region
+        .entries
+        .iter()
+        .filter_map(|entry| {
+            let key = match &entry.key {
+                PropKey::Name(name) => name.clone(),
+                PropKey::Computed(expr) => format!("[{}]", expr),
+                PropKey::Positional => return None,
+            };
+            let value = doc[entry.key_range.1..entry.range.1]
+                .trim_start()
+                .trim_start_matches('=')
+                .trim();
+            Some(ElementProp {
+                key,
+                value: value.to_string(),
+            })
+        })
+        .collect();
+
+    ElementNode {
+        element,
+        is_host: region.class_name.is_some(),
+        props,
+        children: Vec::new(),
+        range: Range {
+            start: byte_offset_to_position(doc, region.start),
+            end: byte_offset_to_position(doc, region.end),
+        },
+    }
+}
+
+// Nests elements by containment, every call found inside another call's parens becomes its child
+pub fn get_element_tree(doc: &str, requires: &Arc<RequireContext>) -> Vec<ElementNode> {
+    let regions = get_element_regions(doc, requires); // Sorted by start, so parents always come first
+
+    let mut roots: Vec<ElementNode> = Vec::new();
+    let mut stack: Vec<(usize, ElementNode)> = Vec::new(); // (end offset, node)
+
+    let pop_into_parent = |stack: &mut Vec<(usize, ElementNode)>, roots: &mut Vec<ElementNode>| {
+        if let Some((_, node)) = stack.pop() {
+            match stack.last_mut() {
+                Some((_, parent)) => parent.children.push(node),
+                None => roots.push(node),
+            }
+        }
+    };
+
+    for region in &regions {
+        while stack.last().is_some_and(|(end, _)| region.start > *end) {
+            pop_into_parent(&mut stack, &mut roots);
+        }
+        stack.push((region.end, build_node(doc, region)));
+    }
+    while !stack.is_empty() {
+        pop_into_parent(&mut stack, &mut roots);
+    }
+
+    roots
+}
+
+// Bumped on any breaking change to TreeSyncParams/ElementNode, see schemas/tree-dump.v1.schema.json
+pub const TREE_SCHEMA_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TreeSyncParams {
+    pub schema_version: u32,
+    pub uri: Url,
+    pub version: i32,
+    pub tree: Vec<ElementNode>,
+}
+
+impl TreeSyncParams {
+    pub fn new(uri: Url, version: i32, doc: &str, requires: &Arc<RequireContext>) -> Self {
+        Self {
+            schema_version: TREE_SCHEMA_VERSION,
+            uri,
+            version,
+            tree: get_element_tree(doc, requires),
+        }
+    }
+}
+
+// Custom notification consumed by the companion Studio plugin(Through the extension)
+pub enum TreeSync {}
+
+impl Notification for TreeSync {
+    type Params = TreeSyncParams;
+    const METHOD: &'static str = "rblxReact/treeSync";
+}
+
+pub enum TreeSyncSchedule {
+    Now,
+    Later(Duration),
+    AlreadyPending,
+}
+
+// Keeps track of when each document was last synced, so typing doesn't flood the plugin
+#[derive(Debug, Default)]
+pub struct TreeSyncThrottle {
+    last_sent: HashMap<Url, Instant>,
+    pending: HashSet<Url>,
+}
+
+impl TreeSyncThrottle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn schedule(&mut self, uri: &Url, throttle: Duration) -> TreeSyncSchedule {
+        if self.pending.contains(uri) {
+            return TreeSyncSchedule::AlreadyPending;
+        }
+
+        let elapsed = self
+            .last_sent
+            .get(uri)
+            .map(|sent| sent.elapsed())
+            .unwrap_or(throttle);
+        if elapsed >= throttle {
+            self.last_sent.insert(uri.clone(), Instant::now());
+            return TreeSyncSchedule::Now;
+        }
+
+        self.pending.insert(uri.clone());
+        TreeSyncSchedule::Later(throttle - elapsed)
+    }
+
+    // Called once a delayed sync actually goes out
+    pub fn mark_sent(&mut self, uri: &Url) {
+        self.pending.remove(uri);
+        self.last_sent.insert(uri.clone(), Instant::now());
+    }
+
+    pub fn forget(&mut self, uri: &Url) {
+        self.pending.remove(uri);
+        self.last_sent.remove(uri);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use tower_lsp::lsp_types::Url;
+
+    use crate::element_tree::{
+        get_element_tree, TreeSyncParams, TreeSyncSchedule, TreeSyncThrottle, TREE_SCHEMA_VERSION,
+    };
+
+    #[test]
+    fn test_tree_dump_schema() {
+        let schema: serde_json::Value =
+            serde_json::from_str(include_str!("../schemas/tree-dump.v1.schema.json")).unwrap();
+        assert_eq!(
+            schema["properties"]["schemaVersion"]["const"],
+            TREE_SCHEMA_VERSION
+        );
+
+        let doc = "local React = require(game.React)\nreturn React.createElement(\"Frame\", { Size = 1 })\n";
+        let dump = serde_json::to_value(TreeSyncParams::new(
+            Url::parse("file:///App.luau").unwrap(),
+            3,
+            doc,
+            &Default::default(),
+        ))
+        .unwrap();
+        let has_required = |value: &serde_json::Value, schema: &serde_json::Value| {
+            schema["required"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .all(|key| value.get(key.as_str().unwrap()).is_some())
+        };
+        assert!(has_required(&dump, &schema));
+        assert!(has_required(
+            &dump["tree"][0],
+            &schema["$defs"]["elementNode"]
+        ));
+    }
+
+    #[test]
+    fn test_element_tree_nesting() {
+        let doc = r#"local React = require(game.React)
+local e = React.createElement
+return e("Frame", { Size = UDim2.fromScale(1, 1) }, {
+    Label = e("TextLabel", { Text = "Hi", [React.Event.Activated] = onClick }),
+    Button = e(Button, {}),
+})
+"#;
+        let tree = get_element_tree(doc, &Default::default());
+        assert_eq!(tree.len(), 1);
+
+        let frame = &tree[0];
+        assert_eq!(frame.element, "Frame");
+        assert_eq!(frame.props[0].key, "Size");
+        assert_eq!(frame.props[0].value, "UDim2.fromScale(1, 1)");
+
+        let children: Vec<(&str, bool)> = frame
+            .children
+            .iter()
+            .map(|c| (c.element.as_str(), c.is_host))
+            .collect();
+        assert_eq!(children, vec![("TextLabel", true), ("Button", false)]);
+        assert_eq!(frame.children[0].props[1].key, "[React.Event.Activated]");
+    }
+
+    #[test]
+    fn test_tree_sync_throttle() {
+        let uri = Url::parse("file:///test.luau").unwrap();
+        let mut throttle = TreeSyncThrottle::new();
+        let window = Duration::from_secs(60);
+
+        assert!(matches!(
+            throttle.schedule(&uri, window),
+            TreeSyncSchedule::Now
+        ));
+        assert!(matches!(
+            throttle.schedule(&uri, window),
+            TreeSyncSchedule::Later(_)
+        ));
+        assert!(matches!(
+            throttle.schedule(&uri, window),
+            TreeSyncSchedule::AlreadyPending
+        ));
+
+        throttle.mark_sent(&uri);
+        assert!(matches!(
+            throttle.schedule(&uri, Duration::ZERO),
+            TreeSyncSchedule::Now
+        ));
+    }
+}
@@ -1,62 +1,82 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
 use lazy_static::lazy_static;
 use regex::Regex;
-use tower_lsp::lsp_types::{CompletionItem, CompletionItemKind, CompletionResponse, Position};
+use serde_json::{json, Value};
+use tokio_util::sync::CancellationToken;
+use tower_lsp::lsp_types::{
+    CompletionContext, CompletionItem, CompletionItemKind, CompletionList, CompletionResponse,
+    CompletionTextEdit, CompletionTriggerKind, Diagnostic, DiagnosticSeverity, Documentation,
+    Hover, HoverContents, InsertTextFormat, MarkupContent, MarkupKind, NumberOrString, Position,
+    Range, TextEdit, Url,
+};
+
+use crate::{
+    api_manager::{is_subsequence, ApiManager},
+    component_index::{ComponentDef, ComponentIndex},
+    element_context::{element_context, ElementContext},
+    file_refactorer::is_valid_identifier,
+    hook_diagnoser::get_hook_diagnostics,
+    rules::code_description,
+    settings::Settings,
+    symbol_table::{instance_path_segments, literal_end, RequireContext, SymbolKind, SymbolTable},
+};
 
-use crate::api_manager::ApiManager;
+// Marker put into CompletionItem.data so clients(and us) can tell which server produced an item
+pub const ITEM_SOURCE: &str = "rblx-react-lsp";
 
 lazy_static! {
-    // Matches require*(**.React) where * is any number of white space and ** is any number of characters
-    static ref REACT_PATTERN: Regex = Regex::new(r#"(?i)require\s*\(\s*[^)]*\.React\s*\)"#).unwrap();
-    static ref REACT_VAR_PATTERN: Regex = Regex::new(
-        r#"(?i)\b(?:local\s+)?(\w+)\s*=\s*require\s*\(.*\.React\s*\)"#
-    ).unwrap();
-    // Matches local <macro_name> = <react_var>.createElement
-    static ref CREATE_ELEMENT_MACRO_PATTERN: Regex = Regex::new(
-        r#"(?i)\b(?:local\s+)?(\w+)\s*=\s*(\w+)\.createElement\b"#
+    // Matches require*(**.X) where * is any number of white space and ** is any number of characters
+    // (Calls like GetService("ReplicatedStorage") included), capturing **.X
+    static ref REACT_PATTERN: Regex =
+        Regex::new(r#"(?i)require\s*\(\s*((?:[^()]|\([^()]*\))*\.\w+)\s*\)"#).unwrap();
+    // Matches require*("**") capturing the string path, any quote style
+    static ref STRING_REQUIRE_PATTERN: Regex = Regex::new(r#"require\s*\(\s*["'`]([^"'`]+)["'`]\s*\)"#).unwrap();
+    // Matches <react_var>.createElement, either in a macro definition or a direct call
+    static ref CREATE_ELEMENT_CALLEE_PATTERN: Regex = Regex::new(
+        r#"\b(\w+)\.createElement\b"#
     ).unwrap();
     //static ref FIRST_QUOTES_PATTERN: Regex = Regex::new(r#""(.+)""#).unwrap();
 
     static ref FIND_QUOTES: Regex = Regex::new(r#"(?s)(?:"([^"]*?)"|'([^']*?)'|`([^`]*?)`|\[\[([^\]]*?)\]\])"#).unwrap();
 }
 
-fn has_react(doc: &str) -> bool {
-    REACT_PATTERN.is_match(doc)
+fn has_react(doc: &str, requires: &RequireContext) -> bool {
+    REACT_PATTERN
+        .captures_iter(doc)
+        .any(|caps| requires.is_react_module(&instance_path_segments(&caps[1])))
+        || STRING_REQUIRE_PATTERN
+            .captures_iter(doc)
+            .any(|caps| requires.is_react_require_path(&caps[1]))
 }
 
-// TODO: This should return warning when multiple reacts are detected
-// This method is used for checking what name was used for requiring react
-fn get_react_var_name(doc: &str) -> Option<String> {
-    if let Some(caps) = REACT_VAR_PATTERN.captures(doc) {
-        return Some(caps.get(1).unwrap().as_str().to_string());
+// Name React is used under when document calls createElement without ever requiring React
+pub(crate) fn get_unrequired_react_var(doc: &str, requires: &RequireContext) -> Option<String> {
+    if has_react(doc, requires) {
+        return None;
     }
-    None
+    CREATE_ELEMENT_CALLEE_PATTERN
+        .captures(doc)
+        .map(|caps| caps.get(1).unwrap().as_str().to_string())
 }
 
 // Find all createElement macros defined before the given byte offset
-// Returns a list of macro names that can be used as createElement
-fn get_create_element_macros(
+// Returns a list of macro names that can be used as createElement(Including helpers required from other modules)
+pub(crate) fn get_create_element_macros(
     doc: &str,
+    symbols: &SymbolTable,
     before_byte_offset: usize,
     react_var_name: &str,
 ) -> Vec<String> {
-    let mut macros = Vec::new();
-    let search_region = &doc[..before_byte_offset.min(doc.len())];
-
-    for caps in CREATE_ELEMENT_MACRO_PATTERN.captures_iter(search_region) {
-        if let (Some(macro_name), Some(var_name)) = (caps.get(1), caps.get(2)) {
-            // Check if the variable name matches the React variable name
-            if var_name.as_str() == react_var_name {
-                macros.push(macro_name.as_str().to_string());
-            }
-        }
-    }
-
+    let mut macros = symbols.create_element_macros(doc, before_byte_offset, react_var_name);
+    macros.extend(symbols.create_element_helpers(doc, before_byte_offset));
     macros
 }
 
 fn extract_name_from_span(span: &str) -> Option<String> {
     let args: Vec<&str> = span.split(',').collect();
-    if let Some(first_arg) = args.get(0) {
+    if let Some(first_arg) = args.first() {
         let trimmed = first_arg.trim();
 
         if trimmed.starts_with("[[") && trimmed.ends_with("]]") && trimmed.len() >= 4 {
@@ -65,7 +85,7 @@ fn extract_name_from_span(span: &str) -> Option<String> {
 
         if trimmed.len() >= 2 {
             let first_char = trimmed.chars().next();
-            let last_char = trimmed.chars().rev().next();
+            let last_char = trimmed.chars().next_back();
             if (first_char == Some('"') || first_char == Some('\'') || first_char == Some('`'))
                 && first_char == last_char
             {
@@ -76,6 +96,319 @@ fn extract_name_from_span(span: &str) -> Option<String> {
     None
 }
 
+// What completion_resolve needs to fill in the rest of an item later
+fn resolve_data(class_name: &str, member: &str) -> Value {
+    json!({ "source": ITEM_SOURCE, "class": class_name, "member": member })
+}
+
+fn markdown(value: String) -> Documentation {
+    Documentation::MarkupContent(MarkupContent {
+        kind: MarkupKind::Markdown,
+        value,
+    })
+}
+
+// Markdown shown for a property, event or change key, shared by completion docs and hover
+fn member_docs(
+    member: &str,
+    name: &str,
+    data_type: &str,
+    declared_in: &str,
+    class_name: &str,
+    default: Option<&str>,
+    documentation: Option<&str>,
+) -> String {
+    let mut docs = match member {
+        "event" => {
+            let mut params = vec!["rbx"];
+            params.extend(signature_params(data_type));
+            format!(
+                "```luau\n[React.Event.{}] = function({}) end\n```\n",
+                name,
+                params.join(", ")
+            )
+        }
+        "change" => format!(
+            "```luau\n[React.Change.{}] = function(rbx) end\n```\nFires when `{}: {}` changes\n",
+            name, name, data_type
+        ),
+        _ => format!("```luau\n{}: {}\n```\n", name, data_type),
+    };
+    if let Some(documentation) = documentation {
+        docs.push_str(&format!("\n{}\n", documentation));
+    }
+    if let Some(default) = default {
+        docs.push_str(&format!("\nDefaults to `{}`\n", default));
+    }
+    if declared_in != class_name {
+        docs.push_str(&format!("\nInherited from `{}`", declared_in));
+    }
+    docs
+}
+
+// Hover over a [React.Event.X] or [React.Change.X] key, or a method called on an element's instance
+pub fn get_hover(
+    doc: &str,
+    analysis: &DocumentAnalysis,
+    cursor: &Position,
+    api_manager: &ApiManager,
+) -> Option<Hover> {
+    let offset = position_to_byte_offset(doc, cursor)?;
+    let variable_name = analysis.symbols.react_var_name_at(doc, offset)?;
+
+    get_key_hover(doc, analysis, offset, &variable_name, api_manager)
+        .or_else(|| get_method_hover(doc, analysis, offset, &variable_name, api_manager))
+        .or_else(|| get_data_type_hover(doc, offset, api_manager))
+}
+
+// Class of the innermost element around offset
+fn enclosing_element_class(
+    doc: &str,
+    analysis: &DocumentAnalysis,
+    variable_name: &str,
+    offset: usize,
+) -> Option<String> {
+    let groups = extract_all_create_element_groups(doc, analysis, variable_name, offset);
+    groups
+        .into_iter()
+        .min_by_key(|region| region.end - region.start)
+        .and_then(|region| extract_name_from_span(&doc[region.start..region.end]))
+}
+
+fn member_hover(doc: &str, value: String, range: (usize, usize)) -> Hover {
+    Hover {
+        contents: HoverContents::Markup(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value,
+        }),
+        range: Some(Range {
+            start: byte_offset_to_position(doc, range.0),
+            end: byte_offset_to_position(doc, range.1),
+        }),
+    }
+}
+
+fn get_key_hover(
+    doc: &str,
+    analysis: &DocumentAnalysis,
+    offset: usize,
+    variable_name: &str,
+    api_manager: &ApiManager,
+) -> Option<Hover> {
+    let line_start = doc[..offset].rfind('\n').map(|i| i + 1).unwrap_or(0);
+    let line_end = doc[offset..]
+        .find('\n')
+        .map(|i| offset + i)
+        .unwrap_or(doc.len());
+    let key_start = line_start + doc[line_start..offset].rfind('[')?;
+    let key_end = offset + doc[offset..line_end].find(']')? + 1;
+    let key = doc[key_start + 1..key_end - 1].trim();
+    let (member, name) = if let Some(name) = key.strip_prefix(&format!("{}.Event.", variable_name))
+    {
+        ("event", name)
+    } else {
+        (
+            "change",
+            key.strip_prefix(&format!("{}.Change.", variable_name))?,
+        )
+    };
+    if !is_valid_identifier(name) {
+        return None;
+    }
+
+    // Innermost element around the key is the one it belongs to
+    let class_name = enclosing_element_class(doc, analysis, variable_name, key_start)?;
+    let (data_type, declared_in) = api_manager.member_info(&class_name, name, member == "event")?;
+    let default = match member {
+        "change" => api_manager.property_default(&class_name, name),
+        _ => None,
+    };
+
+    Some(member_hover(
+        doc,
+        member_docs(
+            member,
+            name,
+            &data_type,
+            &declared_in,
+            &class_name,
+            default.as_deref(),
+            api_manager.documentation(&declared_in, Some(name)),
+        ),
+        (key_start, key_end),
+    ))
+}
+
+// DataType.member, e.g. UDim2.fromScale or Vector2.zero(Members of values need a type, which isn't tracked)
+fn get_data_type_hover(doc: &str, offset: usize, api_manager: &ApiManager) -> Option<Hover> {
+    let (start, end) = word_range_at(doc, offset);
+    let object_end = start
+        .checked_sub(1)
+        .filter(|&i| doc[i..].starts_with('.'))?;
+    let (object_start, _) = word_range_at(doc, object_end);
+    let (type_name, name) = (&doc[object_start..object_end], &doc[start..end]);
+    if !is_valid_identifier(name) || !is_valid_identifier(type_name) {
+        return None;
+    }
+
+    let member = api_manager.data_type_member(type_name, name)?;
+    let mut docs = match &member.params {
+        Some(params) => format!(
+            "```luau\n{}.{}({})\n```\n",
+            type_name,
+            name,
+            params.join(", ")
+        ),
+        None => format!("```luau\n{}.{}\n```\n", type_name, name),
+    };
+    if !member.documentation.is_empty() {
+        docs.push_str(&format!("\n{}\n", member.documentation));
+    }
+    Some(member_hover(doc, docs, (object_start, end)))
+}
+
+// rbx:Method() where rbx is the first parameter of an element's event/change handler or ref callback
+fn get_method_hover(
+    doc: &str,
+    analysis: &DocumentAnalysis,
+    offset: usize,
+    variable_name: &str,
+    api_manager: &ApiManager,
+) -> Option<Hover> {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    let word_start = |end: usize| {
+        doc[..end]
+            .rfind(|c: char| !is_word(c))
+            .map(|i| i + 1)
+            .unwrap_or(0)
+    };
+    let start = word_start(offset);
+    let end = doc[offset..]
+        .find(|c: char| !is_word(c))
+        .map(|i| offset + i)
+        .unwrap_or(doc.len());
+    let object_end = start
+        .checked_sub(1)
+        .filter(|&i| doc[i..].starts_with(':'))?;
+    let object_start = word_start(object_end);
+    let (name, object) = (&doc[start..end], &doc[object_start..object_end]);
+    if !is_valid_identifier(name) || !is_valid_identifier(object) {
+        return None;
+    }
+
+    let param = analysis
+        .symbols
+        .resolve(object, object_start)
+        .filter(|symbol| symbol.kind == SymbolKind::Parameter)?;
+    // Only the first parameter is the instance, "= function(rbx"
+    let assigned_to = doc[..param.range.0]
+        .trim_end()
+        .strip_suffix('(')?
+        .trim_end()
+        .strip_suffix("function")?
+        .trim_end()
+        .strip_suffix('=')?
+        .trim_end();
+    let is_handler = match assigned_to.strip_suffix(']') {
+        Some(key) => {
+            let key = key[key.rfind('[')? + 1..].trim();
+            key.starts_with(&format!("{}.Event.", variable_name))
+                || key.starts_with(&format!("{}.Change.", variable_name))
+        }
+        None => assigned_to.rsplit(|c: char| !is_word(c)).next() == Some("ref"),
+    };
+    if !is_handler {
+        return None;
+    }
+
+    let class_name = enclosing_element_class(doc, analysis, variable_name, param.range.0)?;
+    let (signature, declared_in) = api_manager.method_info(&class_name, name)?;
+    Some(member_hover(
+        doc,
+        member_docs(
+            "method",
+            name,
+            &signature,
+            &declared_in,
+            &class_name,
+            None,
+            api_manager.documentation(&declared_in, Some(name)),
+        ),
+        (start, end),
+    ))
+}
+
+// Heavy fields(Detail, docs) are only computed for the item user actually selected
+pub fn resolve_completion_item(
+    mut item: CompletionItem,
+    api_manager: &ApiManager,
+) -> CompletionItem {
+    let Some(data) = item.data.as_ref() else {
+        return item;
+    };
+    let class_name = data
+        .get("class")
+        .and_then(Value::as_str)
+        .unwrap_or("")
+        .to_string();
+    let member = data.get("member").and_then(Value::as_str).unwrap_or("");
+
+    match member {
+        "property" | "event" | "change" => {
+            let is_event = member == "event";
+            let Some((data_type, declared_in)) =
+                api_manager.member_info(&class_name, &item.label, is_event)
+            else {
+                return item;
+            };
+
+            let default = match member {
+                "event" => None,
+                _ => api_manager.property_default(&class_name, &item.label),
+            };
+            let docs = member_docs(
+                member,
+                &item.label,
+                &data_type,
+                &declared_in,
+                &class_name,
+                default.as_deref(),
+                api_manager.documentation(&declared_in, Some(&item.label)),
+            );
+            // Change items complete a handler, so the default only goes in their docs
+            item.detail = Some(match default {
+                Some(default) if member == "property" => format!("{} = {}", data_type, default),
+                _ => data_type,
+            });
+            item.documentation = Some(markdown(docs));
+        }
+        "class" => {
+            let chain = api_manager.superclass_chain(&item.label);
+            if chain.len() > 1 {
+                item.detail = Some(chain.join(" < "));
+            }
+            let property_count = api_manager
+                .lookup_properties(&item.label)
+                .map(|props| props.len())
+                .unwrap_or(0);
+            let event_count = api_manager
+                .lookup_events(&item.label)
+                .map(|events| events.len())
+                .unwrap_or(0);
+            let mut docs = format!("{} properties, {} events", property_count, event_count);
+            if let Some(documentation) = api_manager.documentation(&item.label, None) {
+                docs = format!("{}\n\n{}", documentation, docs);
+            }
+            item.documentation = Some(markdown(docs));
+        }
+        _ => {}
+    }
+    item
+}
+
+// Typing either of these right after a prop name accepts the selected item
+const PROPERTY_COMMIT_CHARACTERS: [&str; 2] = ["=", ","];
+
 fn get_instance_property_diagnostics(
     instance_name: &str,
     api_manager: &ApiManager,
@@ -83,12 +416,20 @@ fn get_instance_property_diagnostics(
     let mut diagnostics: Vec<CompletionItem> = Vec::new();
 
     if let Some(parsed_instance) = api_manager.lookup_properties(instance_name) {
-        for (i, (name, data_type)) in parsed_instance.into_iter().enumerate() {
+        for (i, (name, _)) in parsed_instance.into_iter().enumerate() {
             diagnostics.push(CompletionItem {
                 label: name.clone(),
                 kind: Some(CompletionItemKind::FIELD),
-                detail: Some(data_type.clone()),
                 sort_text: Some(format!("\x01{:05}", i)),
+                data: Some(resolve_data(instance_name, "property")),
+                commit_characters: Some(
+                    PROPERTY_COMMIT_CHARACTERS
+                        .iter()
+                        .map(|c| c.to_string())
+                        .collect(),
+                ),
+                // List is ranked, so first item is the most used prop(If anything is used at all)
+                preselect: (i == 0 && api_manager.freq_of(&name) > 0).then_some(true),
 
                 ..Default::default()
             });
@@ -98,6 +439,25 @@ fn get_instance_property_diagnostics(
     diagnostics
 }
 
+// Change keys target properties(Read-only ones too), but complete as events since their value is a handler
+fn get_instance_change_items(instance_name: &str, api_manager: &ApiManager) -> Vec<CompletionItem> {
+    let Some(properties) = api_manager.lookup_changeable_properties(instance_name) else {
+        return Vec::new();
+    };
+    properties
+        .into_iter()
+        .enumerate()
+        .map(|(i, (name, _))| CompletionItem {
+            kind: Some(CompletionItemKind::EVENT),
+            sort_text: Some(format!("\x01{:05}", i)),
+            data: Some(resolve_data(instance_name, "change")),
+            preselect: (i == 0 && api_manager.freq_of(&name) > 0).then_some(true),
+            label: name,
+            ..Default::default()
+        })
+        .collect()
+}
+
 fn get_instance_events_diagnostics(
     instance_name: &str,
     api_manager: &ApiManager,
@@ -105,12 +465,12 @@ fn get_instance_events_diagnostics(
     let mut diagnostics: Vec<CompletionItem> = Vec::new();
 
     if let Some(parsed_instance) = api_manager.lookup_events(instance_name) {
-        for (i, (name, data_type)) in parsed_instance.into_iter().enumerate() {
+        for (i, (name, _)) in parsed_instance.into_iter().enumerate() {
             diagnostics.push(CompletionItem {
                 label: name.clone(),
                 kind: Some(CompletionItemKind::FIELD),
-                detail: Some(data_type.clone()),
                 sort_text: Some(format!("\x01{:05}", i)),
+                data: Some(resolve_data(instance_name, "event")),
 
                 ..Default::default()
             });
@@ -120,6 +480,737 @@ fn get_instance_events_diagnostics(
     diagnostics
 }
 
+// "name: Type" pieces of an event signature like "(input: InputObject) -> ()"(Old caches only have "Function")
+fn signature_params(signature: &str) -> Vec<&str> {
+    signature
+        .strip_prefix('(')
+        .and_then(|signature| signature.split_once(')'))
+        .map(|(params, _)| {
+            split_top_level(params, &[','])
+                .into_iter()
+                .map(|(start, end)| &params[start..end])
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+// Handlers get the instance first, then event's own arguments(Tab stop, so they're easy to drop)
+fn event_handler_params(instance_name: &str, event_name: &str, api_manager: &ApiManager) -> String {
+    let signature = api_manager
+        .member_info(instance_name, event_name, true)
+        .map(|(signature, _)| signature)
+        .unwrap_or_default();
+    let names: Vec<&str> = signature_params(&signature)
+        .into_iter()
+        .filter_map(|param| param.split(':').next())
+        .map(str::trim)
+        .collect();
+    if names.is_empty() {
+        return "rbx".to_string();
+    }
+    format!("rbx${{1:, {}}}", names.join(", "))
+}
+
+// (Key, what react-lua does with it) of props handled by React itself rather than the instance
+const SPECIAL_PROP_DOCS: [(&str, &str); 4] = [
+    ("ref", "Gets set to the created instance once mounted, pass a `createRef`/`useRef` ref or a callback. Function components need `forwardRef` to receive one"),
+    ("key", "Identifies element among its siblings, so state survives reordering"),
+    ("children", "Child elements, same as passing them as createElement's third argument"),
+    ("[{}.Tag]", "Space separated CollectionService tags applied to the created instance"),
+];
+
+// Never in the API dump, so they're merged ahead of class properties
+fn get_special_prop_items(react_var_name: &str) -> Vec<CompletionItem> {
+    SPECIAL_PROP_DOCS
+        .iter()
+        .enumerate()
+        .map(|(i, (key, docs))| CompletionItem {
+            label: key.replace("{}", react_var_name),
+            kind: Some(CompletionItemKind::PROPERTY),
+            sort_text: Some(format!("\x00{:05}", i)),
+            detail: Some("React prop".to_string()),
+            documentation: Some(markdown(docs.to_string())),
+
+            ..Default::default()
+        })
+        .collect()
+}
+
+// react-lua top level API offered after "React.", (Name, kind, signature)
+const REACT_API: [(&str, CompletionItemKind, &str); 30] = [
+    ("createElement", CompletionItemKind::FUNCTION, "createElement(component, props: {}?, ...children) -> ReactElement"),
+    ("useState", CompletionItemKind::FUNCTION, "useState<S>(initialState: S | () -> S) -> (S, (S | (S) -> S) -> ())"),
+    ("useEffect", CompletionItemKind::FUNCTION, "useEffect(effect: () -> (() -> ())?, deps: {any}?) -> ()"),
+    ("useMemo", CompletionItemKind::FUNCTION, "useMemo<T>(create: () -> T, deps: {any}?) -> T"),
+    ("useCallback", CompletionItemKind::FUNCTION, "useCallback<T>(callback: T, deps: {any}?) -> T"),
+    ("useRef", CompletionItemKind::FUNCTION, "useRef<T>(initialValue: T) -> { current: T }"),
+    ("useContext", CompletionItemKind::FUNCTION, "useContext<T>(context: Context<T>) -> T"),
+    ("useReducer", CompletionItemKind::FUNCTION, "useReducer<S, A>(reducer: (S, A) -> S, initialState: S, init: ((S) -> S)?) -> (S, (A) -> ())"),
+    ("useLayoutEffect", CompletionItemKind::FUNCTION, "useLayoutEffect(effect: () -> (() -> ())?, deps: {any}?) -> ()"),
+    ("useImperativeHandle", CompletionItemKind::FUNCTION, "useImperativeHandle<T>(ref: Ref<T>, create: () -> T, deps: {any}?) -> ()"),
+    ("useBinding", CompletionItemKind::FUNCTION, "useBinding<T>(initialValue: T) -> (Binding<T>, (T) -> ())"),
+    ("useDebugValue", CompletionItemKind::FUNCTION, "useDebugValue<T>(value: T, format: ((T) -> any)?) -> ()"),
+    ("createContext", CompletionItemKind::FUNCTION, "createContext<T>(defaultValue: T) -> Context<T>"),
+    ("createRef", CompletionItemKind::FUNCTION, "createRef<T>() -> { current: T? }"),
+    ("createBinding", CompletionItemKind::FUNCTION, "createBinding<T>(initialValue: T) -> (Binding<T>, (T) -> ())"),
+    ("joinBindings", CompletionItemKind::FUNCTION, "joinBindings<T>(bindings: { [any]: Binding<any> }) -> Binding<T>"),
+    ("forwardRef", CompletionItemKind::FUNCTION, "forwardRef(render: (props, ref) -> ReactElement?) -> Component"),
+    ("memo", CompletionItemKind::FUNCTION, "memo(component, areEqual: ((prevProps, nextProps) -> boolean)?) -> Component"),
+    ("lazy", CompletionItemKind::FUNCTION, "lazy(load: () -> Promise<{ default: Component }>) -> Component"),
+    ("cloneElement", CompletionItemKind::FUNCTION, "cloneElement(element: ReactElement, props: {}?, ...children) -> ReactElement"),
+    ("isValidElement", CompletionItemKind::FUNCTION, "isValidElement(value: any) -> boolean"),
+    ("Fragment", CompletionItemKind::CLASS, "Fragment: Component (Groups children without creating an instance)"),
+    ("StrictMode", CompletionItemKind::CLASS, "StrictMode: Component"),
+    ("Suspense", CompletionItemKind::CLASS, "Suspense: Component (props: { fallback: ReactElement? })"),
+    ("Profiler", CompletionItemKind::CLASS, "Profiler: Component (props: { id: string, onRender: (...) -> () })"),
+    ("Component", CompletionItemKind::CLASS, "Component: Class component base, Component:extend(name: string)"),
+    ("PureComponent", CompletionItemKind::CLASS, "PureComponent: Component skipping renders when props and state are shallow equal"),
+    ("Children", CompletionItemKind::MODULE, "Children: { map, forEach, count, only, toArray }"),
+    ("Event", CompletionItemKind::MODULE, "Event: Keys for instance event handlers, [React.Event.Activated]"),
+    ("Change", CompletionItemKind::MODULE, "Change: Keys for property change handlers, [React.Change.Text]"),
+];
+
+// Call snippets of binding constructors, the rest of REACT_API inserts just its name
+const REACT_API_SNIPPETS: [(&str, &str); 3] = [
+    ("useBinding", "useBinding(${1:initialValue})"),
+    ("createBinding", "createBinding(${1:initialValue})"),
+    ("joinBindings", "joinBindings({ ${1:bindings} })"),
+];
+
+fn get_react_api_items() -> Vec<CompletionItem> {
+    REACT_API
+        .iter()
+        .enumerate()
+        .map(|(i, (name, kind, signature))| {
+            let snippet = REACT_API_SNIPPETS
+                .iter()
+                .find(|(snippet_name, _)| snippet_name == name);
+            CompletionItem {
+                label: name.to_string(),
+                kind: Some(*kind),
+                sort_text: Some(format!("\x01{:05}", i)),
+                detail: Some(signature.to_string()),
+                insert_text: snippet.map(|(_, snippet)| snippet.to_string()),
+                insert_text_format: snippet.map(|_| InsertTextFormat::SNIPPET),
+
+                ..Default::default()
+            }
+        })
+        .collect()
+}
+
+// binding:map snippet when cursor is right after "binding:"(Or "binding:ma") of a known binding
+fn get_binding_method_item(
+    doc: &str,
+    symbols: &SymbolTable,
+    cursor_byte_offset: usize,
+) -> Option<CompletionItem> {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    let before_colon = doc[..cursor_byte_offset]
+        .trim_end_matches(is_word)
+        .strip_suffix(':')?;
+    let name_start = before_colon.trim_end_matches(is_word).len();
+    let name = &before_colon[name_start..];
+    if name.is_empty() || before_colon[..name_start].ends_with(['.', ':']) {
+        return None;
+    }
+    if !symbols
+        .binding_names(doc, cursor_byte_offset)
+        .iter()
+        .any(|binding| binding == name)
+    {
+        return None;
+    }
+    Some(CompletionItem {
+        label: "map".to_string(),
+        kind: Some(CompletionItemKind::METHOD),
+        sort_text: Some(format!("\x01{:05}", 0)),
+        detail: Some("map<U>(predicate: (T) -> U) -> Binding<U>".to_string()),
+        insert_text: Some("map(function(${1:value})\n\treturn $0\nend)".to_string()),
+        insert_text_format: Some(InsertTextFormat::SNIPPET),
+        ..Default::default()
+    })
+}
+
+// Cursor right after "React." or "React.use"(Not foo.React. or React.Event.)
+fn is_react_member_access(before_cursor: &str, react_var_name: &str) -> bool {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    before_cursor
+        .trim_end_matches(is_word)
+        .strip_suffix('.')
+        .and_then(|before_dot| before_dot.strip_suffix(react_var_name))
+        .is_some_and(|before_var| {
+            !before_var.ends_with(|c: char| is_word(c) || c == '.' || c == ':')
+        })
+}
+
+// (Key, offset value starts at) of entry being typed as "Key = Enum.Fo|" in props
+// Only bare names and dot chains count, cursor inside a call or table isn't a value anymore
+fn prop_value_context(props: &str, cursor: usize) -> Option<(&str, usize)> {
+    let before_cursor = &props[..cursor];
+    let (entry_start, entry_end) = *split_top_level(before_cursor, &[',', ';']).last()?;
+    if !before_cursor[entry_end..].trim().is_empty() {
+        return None; // Separator after last entry, a new key is being typed
+    }
+
+    let entry = &before_cursor[entry_start..];
+    let eq = entry.find('=')?;
+    let key = entry[..eq].trim();
+    let value = &entry[eq + 1..];
+    let typed = value.trim_start();
+    if !is_valid_identifier(key)
+        || value.starts_with('=')
+        || !typed
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '.')
+    {
+        return None;
+    }
+    Some((key, entry_start + eq + 1 + (value.len() - typed.len())))
+}
+
+// Cursor right after a dot of a chain values complete from, e.g. "Enum.", "Enum.Font." or "UDim2."
+fn is_value_access(before_cursor: &str) -> bool {
+    let is_chain = |c: char| c.is_alphanumeric() || c == '_' || c == '.';
+    let chain_start = before_cursor.trim_end_matches(is_chain).len();
+    let chain = &before_cursor[chain_start..];
+    before_cursor.ends_with('.')
+        && (chain.starts_with("Enum.")
+            || VALUE_CONSTRUCTORS
+                .iter()
+                .any(|(name, _)| chain.strip_prefix(name) == Some(".")))
+}
+
+// Datatype -> (constructor, snippet inserting it) offered as values of properties typed as it
+const VALUE_CONSTRUCTORS: [(&str, &[(&str, &str)]); 3] = [
+    (
+        "UDim2",
+        &[
+            ("UDim2.new", "UDim2.new(${1:0}, ${2:0}, ${3:0}, ${4:0})"),
+            ("UDim2.fromScale", "UDim2.fromScale(${1:0}, ${2:0})"),
+            ("UDim2.fromOffset", "UDim2.fromOffset(${1:0}, ${2:0})"),
+        ],
+    ),
+    (
+        "Color3",
+        &[
+            (
+                "Color3.fromRGB",
+                "Color3.fromRGB(${1:255}, ${2:255}, ${3:255})",
+            ),
+            ("Color3.fromHex", "Color3.fromHex(\"${1:#FFFFFF}\")"),
+            ("Color3.new", "Color3.new(${1:1}, ${2:1}, ${3:1})"),
+        ],
+    ),
+    (
+        "Font",
+        &[(
+            "Font.new",
+            "Font.new(\"rbxasset://fonts/families/${1:GothamSSm}.json\", Enum.FontWeight.${2:Regular})",
+        )],
+    ),
+];
+
+fn get_constructor_value_items(data_type: &str, api_manager: &ApiManager) -> Vec<CompletionItem> {
+    let mut constructors: Vec<(String, String)> = VALUE_CONSTRUCTORS
+        .iter()
+        .find(|(name, _)| *name == data_type)
+        .map(|(_, constructors)| {
+            constructors
+                .iter()
+                .map(|(label, snippet)| (label.to_string(), snippet.to_string()))
+                .collect()
+        })
+        .unwrap_or_default();
+    // Ones only the docs know about(e.g. UDim2.fromOffset, Vector2.zero) go after the handpicked snippets
+    if let Some(parsed) = api_manager.data_type(data_type) {
+        for constructor in &parsed.constructors {
+            let label = format!("{}.{}", data_type, constructor.name);
+            if constructors.iter().any(|(existing, _)| *existing == label) {
+                continue;
+            }
+            let snippet = match &constructor.params {
+                Some(params) => format!(
+                    "{}({})",
+                    label,
+                    params
+                        .iter()
+                        .enumerate()
+                        .map(|(i, param)| format!("${{{}:{}}}", i + 1, param))
+                        .collect::<Vec<String>>()
+                        .join(", ")
+                ),
+                None => label.clone(),
+            };
+            constructors.push((label, snippet));
+        }
+    }
+
+    constructors
+        .into_iter()
+        .map(|(label, snippet)| {
+            let documentation = label
+                .split_once('.')
+                .and_then(|(_, name)| api_manager.data_type_member(data_type, name))
+                .filter(|member| !member.documentation.is_empty())
+                .map(|member| markdown(member.documentation.clone()));
+            CompletionItem {
+                label,
+                kind: Some(CompletionItemKind::CONSTRUCTOR),
+                detail: Some(data_type.to_string()),
+                documentation,
+                insert_text: Some(snippet),
+                insert_text_format: Some(InsertTextFormat::SNIPPET),
+
+                ..Default::default()
+            }
+        })
+        .collect()
+}
+
+// Bool properties that are true unless set, everything else defaults to false
+const TRUE_BY_DEFAULT: [&str; 8] = [
+    "Active",
+    "Archivable",
+    "AutoButtonColor",
+    "AutoLocalize",
+    "Enabled",
+    "Interactable",
+    "ResetOnSpawn",
+    "Visible",
+];
+
+// Value that isn't default comes first(Writing the default out does nothing)
+fn get_bool_value_items(prop_name: &str) -> Vec<CompletionItem> {
+    let default = TRUE_BY_DEFAULT.contains(&prop_name);
+    [!default, default]
+        .into_iter()
+        .map(|value| CompletionItem {
+            label: value.to_string(),
+            kind: Some(CompletionItemKind::KEYWORD),
+            detail: Some(if value == default {
+                "bool(Default)".to_string()
+            } else {
+                "bool".to_string()
+            }),
+
+            ..Default::default()
+        })
+        .collect()
+}
+
+fn get_enum_value_items(enum_path: &str, api_manager: &ApiManager) -> Vec<CompletionItem> {
+    let Some(enum_items) = enum_path
+        .strip_prefix("Enum.")
+        .and_then(|enum_name| api_manager.lookup_enum(enum_name))
+    else {
+        return Vec::new();
+    };
+    enum_items
+        .iter()
+        .map(|item| CompletionItem {
+            label: format!("{}.{}", enum_path, item.name),
+            kind: Some(CompletionItemKind::ENUM_MEMBER),
+            detail: Some(format!("{} = {}", enum_path, item.value)),
+
+            ..Default::default()
+        })
+        .collect()
+}
+
+// FontFace(Font datatype) gets Font.new, then every legacy Enum.Font item converted through fromEnum
+fn get_font_value_items(api_manager: &ApiManager) -> Vec<CompletionItem> {
+    let mut items = get_constructor_value_items("Font", api_manager);
+    items.extend(
+        get_enum_value_items("Enum.Font", api_manager)
+            .into_iter()
+            .map(|mut item| {
+                item.label = format!("Font.fromEnum({})", item.label);
+                item.kind = Some(CompletionItemKind::CONSTRUCTOR);
+                item.detail = Some("Font".to_string());
+                item
+            }),
+    );
+    items
+}
+
+// (Start of chain, category) of "Enum.Categ|"(No category yet) or "Enum.Category.Ite|" at end of text
+fn enum_chain_at(before_cursor: &str) -> Option<(usize, Option<&str>)> {
+    let is_chain = |c: char| c.is_alphanumeric() || c == '_' || c == '.';
+    let chain_start = before_cursor.trim_end_matches(is_chain).len();
+    let rest = before_cursor[chain_start..].strip_prefix("Enum.")?;
+    if before_cursor[..chain_start].ends_with(':') {
+        return None; // Method call on something, not the Enum global
+    }
+    match rest.split_once('.') {
+        None => Some((chain_start, None)),
+        Some((category, item)) if !item.contains('.') => Some((chain_start, Some(category))),
+        Some(_) => None,
+    }
+}
+
+// Enum categories, or items of one, replacing typed chain over range
+fn get_enum_chain_items(
+    doc: &str,
+    range: (usize, usize),
+    category: Option<&str>,
+    api_manager: &ApiManager,
+) -> Vec<CompletionItem> {
+    let items = match category {
+        Some(category) => get_enum_value_items(&format!("Enum.{}", category), api_manager),
+        None => {
+            let mut names: Vec<&String> = api_manager.enum_names().collect();
+            names.sort();
+            names
+                .into_iter()
+                .map(|name| CompletionItem {
+                    label: format!("Enum.{}", name),
+                    kind: Some(CompletionItemKind::ENUM),
+
+                    ..Default::default()
+                })
+                .collect()
+        }
+    };
+    with_value_edits(doc, items, range)
+}
+
+// Items keep their order, insert_text(If any) is what gets inserted over range instead of label
+fn with_value_edits(
+    doc: &str,
+    items: Vec<CompletionItem>,
+    range: (usize, usize),
+) -> Vec<CompletionItem> {
+    let range = Range {
+        start: byte_offset_to_position(doc, range.0),
+        end: byte_offset_to_position(doc, range.1),
+    };
+    items
+        .into_iter()
+        .enumerate()
+        .map(|(i, mut item)| {
+            item.sort_text = Some(format!("\x01{:05}", i));
+            item.filter_text = Some(item.label.clone());
+            item.text_edit = Some(CompletionTextEdit::Edit(TextEdit {
+                range,
+                new_text: item
+                    .insert_text
+                    .take()
+                    .unwrap_or_else(|| item.label.clone()),
+            }));
+            item
+        })
+        .collect()
+}
+
+// Values fitting the type of a property, replacing value typed so far over range
+fn get_prop_value_items(
+    doc: &str,
+    instance_name: &str,
+    prop_name: &str,
+    range: (usize, usize),
+    api_manager: &ApiManager,
+) -> Vec<CompletionItem> {
+    let Some((data_type, _)) = api_manager.member_info(instance_name, prop_name, false) else {
+        return Vec::new();
+    };
+    let items = match data_type.as_str() {
+        "bool" => get_bool_value_items(prop_name),
+        "Font" => get_font_value_items(api_manager),
+        data_type if data_type.starts_with("Enum.") => get_enum_value_items(data_type, api_manager),
+        data_type => get_constructor_value_items(data_type, api_manager),
+    };
+    with_value_edits(doc, items, range)
+}
+
+fn get_component_prop_items(component: &ComponentDef) -> Vec<CompletionItem> {
+    component
+        .props
+        .iter()
+        .enumerate()
+        .map(|(i, name)| CompletionItem {
+            label: name.clone(),
+            kind: Some(CompletionItemKind::FIELD),
+            sort_text: Some(format!("\x01{:05}", i)),
+            // Annotated type when there is one, e.g. "() -> ()" for OnClick
+            detail: Some(
+                component
+                    .prop_types
+                    .get(name)
+                    .cloned()
+                    .unwrap_or_else(|| format!("{} prop", component.name)),
+            ),
+
+            ..Default::default()
+        })
+        .collect()
+}
+
+// Byte range of identifier around offset, so text on both sides of cursor gets replaced
+fn word_range_at(doc: &str, offset: usize) -> (usize, usize) {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    let start = doc[..offset]
+        .char_indices()
+        .rev()
+        .take_while(|&(_, c)| is_word(c))
+        .last()
+        .map(|(i, _)| i)
+        .unwrap_or(offset);
+    let end = offset
+        + doc[offset..]
+            .find(|c: char| !is_word(c))
+            .unwrap_or(doc.len() - offset);
+    (start, end)
+}
+
+// Existing entries already have their " = value", so only the key should change
+fn followed_by_assignment(doc: &str, offset: usize) -> bool {
+    let rest = doc[offset..].trim_start_matches([' ', '\t']);
+    rest.starts_with('=') && !rest.starts_with("==")
+}
+
+// Turns bare labels into snippets inserting the whole entry(e.g. "Size = $0,") over range
+// filter_prefix is whatever range covers before the label, so clients still filter by what was typed
+fn with_snippet_edits(
+    doc: &str,
+    items: Vec<CompletionItem>,
+    range: (usize, usize),
+    filter_prefix: &str,
+    entry: impl Fn(&str) -> String,
+) -> Vec<CompletionItem> {
+    let range = Range {
+        start: byte_offset_to_position(doc, range.0),
+        end: byte_offset_to_position(doc, range.1),
+    };
+    items
+        .into_iter()
+        .map(|mut item| {
+            item.text_edit = Some(CompletionTextEdit::Edit(TextEdit {
+                range,
+                new_text: entry(&item.label),
+            }));
+            item.insert_text_format = Some(InsertTextFormat::SNIPPET);
+            // Snippet already types " = ", committing with "=" or "," would add a second one
+            item.commit_characters = None;
+            if !filter_prefix.is_empty() {
+                item.filter_text = Some(format!("{}{}", filter_prefix, item.label));
+            }
+            item
+        })
+        .collect()
+}
+
+fn prop_snippets(doc: &str, cursor: usize, items: Vec<CompletionItem>) -> Vec<CompletionItem> {
+    let range = word_range_at(doc, cursor);
+    if followed_by_assignment(doc, range.1) {
+        return items;
+    }
+    with_snippet_edits(doc, items, range, "", |name| format!("{} = $0,", name))
+}
+
+// Start of module name typed right after require(, e.g. "local Button = require(But"
+fn require_argument_start(before_cursor: &str) -> Option<usize> {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    let word_start = before_cursor.trim_end_matches(is_word).len();
+    let before_call = before_cursor[..word_start]
+        .strip_suffix('(')?
+        .trim_end()
+        .strip_suffix("require")?;
+    (!before_call.ends_with(|c: char| is_word(c) || c == '.' || c == ':')).then_some(word_start)
+}
+
+// Every ModuleScript in the sourcemap by name, inserting its instance path relative to uri
+fn get_require_items(
+    doc: &str,
+    uri: &Url,
+    range: (usize, usize),
+    components: &ComponentIndex,
+) -> Vec<CompletionItem> {
+    let Ok(from_file) = uri.to_file_path() else {
+        return Vec::new();
+    };
+    let sourcemap = components.sourcemap();
+    let range = Range {
+        start: byte_offset_to_position(doc, range.0),
+        end: byte_offset_to_position(doc, range.1),
+    };
+
+    let mut modules: Vec<(&String, String)> = sourcemap
+        .module_scripts()
+        .filter(|(_, file)| *file != from_file)
+        .filter_map(|(target, _)| {
+            let path = sourcemap.require_path(target, &from_file)?;
+            Some((target.last()?, path))
+        })
+        .collect();
+    // Closest modules first
+    modules.sort_by_key(|(_, path)| path.len());
+
+    modules
+        .into_iter()
+        .enumerate()
+        .map(|(i, (name, path))| CompletionItem {
+            label: name.clone(),
+            kind: Some(CompletionItemKind::MODULE),
+            sort_text: Some(format!("\x01{:05}", i)),
+            filter_text: Some(name.clone()),
+            detail: Some(path.clone()),
+            text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+                range,
+                new_text: path,
+            })),
+
+            ..Default::default()
+        })
+        .collect()
+}
+
+// Below leading comments and requires, so a new require joins existing ones
+fn require_insert_offset(doc: &str) -> usize {
+    let mut offset = 0;
+    let mut insert_at = 0;
+    for line in doc.split_inclusive('\n') {
+        let trimmed = line.trim();
+        offset += line.len();
+        if trimmed.starts_with("--")
+            || (trimmed.starts_with("local ") && trimmed.contains("require("))
+        {
+            insert_at = offset;
+        } else if !trimmed.is_empty() {
+            break;
+        }
+    }
+    insert_at
+}
+
+// Components of indexed modules document doesn't require yet, picking one adds the require too
+// (Needs sourcemap to know the path, and module name can't clash with an existing local)
+fn get_unrequired_component_items(
+    doc: &str,
+    uri: &Url,
+    symbols: &SymbolTable,
+    components: &ComponentIndex,
+) -> Vec<CompletionItem> {
+    let Ok(from_file) = uri.to_file_path() else {
+        return Vec::new();
+    };
+    let declared: HashSet<&str> = symbols.declared_names().collect();
+    let mut modules: Vec<(&str, String, &[ComponentDef])> = components
+        .modules()
+        .filter(|(module_uri, name, _)| *module_uri != uri && !declared.contains(name))
+        .filter_map(|(module_uri, name, module)| {
+            let file = module_uri.to_file_path().ok()?;
+            let path = components.sourcemap().require_path_to(&file, &from_file)?;
+            Some((name, path, module))
+        })
+        .collect();
+    modules.sort_by(|a, b| a.0.cmp(b.0).then_with(|| a.1.cmp(&b.1)));
+
+    let position = byte_offset_to_position(doc, require_insert_offset(doc));
+    let mut items = Vec::new();
+    for (name, path, module) in modules {
+        items.extend(module.iter().map(|component| CompletionItem {
+            label: format!("{}.{}", name, component.name),
+            kind: Some(CompletionItemKind::CLASS),
+            detail: Some(format!("Component from {} (Adds require)", name)),
+            additional_text_edits: Some(vec![TextEdit {
+                range: Range {
+                    start: position,
+                    end: position,
+                },
+                new_text: format!("local {} = require({})\n", name, path),
+            }]),
+            ..Default::default()
+        }));
+    }
+    items
+}
+
+// Identifier typed as first argument, e(But) or e(Components.But)
+// Class names come after components, inserted quoted since nothing was typed as a string yet
+#[allow(clippy::too_many_arguments)]
+fn get_first_argument_items(
+    doc: &str,
+    symbols: &SymbolTable,
+    uri: &Url,
+    cursor_byte_offset: usize,
+    typed: &str,
+    api_manager: &ApiManager,
+    components: &ComponentIndex,
+    element_constants: &[String],
+) -> Vec<CompletionItem> {
+    let required = components.required_modules(doc, uri, symbols);
+    let item = |label: String, kind, detail: String| CompletionItem {
+        label,
+        kind: Some(kind),
+        detail: Some(detail),
+        ..Default::default()
+    };
+
+    let mut items = Vec::new();
+    if let Some((base, _)) = typed.split_once('.') {
+        // Only members of the module before the dot, label is what goes after it
+        for (name, module) in required.iter().filter(|(name, _)| name == base) {
+            items.extend(module.iter().map(|component| {
+                item(
+                    component.name.clone(),
+                    CompletionItemKind::CLASS,
+                    format!("Component from {}", name),
+                )
+            }));
+        }
+    } else {
+        // Locals can only be used after they're declared
+        let defined_before = |name: &String| {
+            symbols
+                .symbols
+                .iter()
+                .any(|s| s.name == *name && s.range.0 < cursor_byte_offset)
+        };
+        items.extend(
+            symbols
+                .component_names()
+                .into_iter()
+                .filter(defined_before)
+                .map(|name| item(name, CompletionItemKind::FUNCTION, "Component".to_string())),
+        );
+        for (name, module) in &required {
+            items.extend(module.iter().map(|component| {
+                item(
+                    format!("{}.{}", name, component.name),
+                    CompletionItemKind::CLASS,
+                    format!("Component from {}", name),
+                )
+            }));
+        }
+        items.extend(element_constants.iter().map(|constant| {
+            item(
+                constant.clone(),
+                CompletionItemKind::CONSTANT,
+                "Element constant".to_string(),
+            )
+        }));
+        items.extend(get_unrequired_component_items(
+            doc, uri, symbols, components,
+        ));
+        items.extend(
+            get_instance_names(typed, api_manager)
+                .into_iter()
+                .map(|class| CompletionItem {
+                    insert_text: Some(format!("\"{}\"", class.label)),
+                    ..class
+                }),
+        );
+    }
+
+    for (i, item) in items.iter_mut().enumerate() {
+        item.sort_text = Some(format!("\x01{:05}", i));
+    }
+    items
+}
+
+// Class names best matching instance_name first, sort_text keeps that order and filter_text the
+// name itself(Some clients filter on insert_text otherwise, which first argument items quote)
 fn get_instance_names(instance_name: &str, api_manager: &ApiManager) -> Vec<CompletionItem> {
     let mut diagnostics: Vec<CompletionItem> = Vec::new();
 
@@ -129,6 +1220,8 @@ fn get_instance_names(instance_name: &str, api_manager: &ApiManager) -> Vec<Comp
                 label: property.clone(),
                 kind: Some(CompletionItemKind::CLASS),
                 sort_text: Some(format!("\x01{:05}", i)),
+                filter_text: Some(property.clone()),
+                data: Some(resolve_data(&property, "class")),
 
                 ..Default::default()
             });
@@ -138,7 +1231,7 @@ fn get_instance_names(instance_name: &str, api_manager: &ApiManager) -> Vec<Comp
     diagnostics
 }
 
-fn position_to_byte_offset(doc: &str, position: &Position) -> Option<usize> {
+pub(crate) fn position_to_byte_offset(doc: &str, position: &Position) -> Option<usize> {
     let mut byte_offset = 0;
 
     for (line_index, line) in doc.split_inclusive('\n').enumerate() {
@@ -161,6 +1254,16 @@ fn position_to_byte_offset(doc: &str, position: &Position) -> Option<usize> {
     None
 }
 
+pub(crate) fn byte_offset_to_position(doc: &str, byte_offset: usize) -> Position {
+    let byte_offset = byte_offset.min(doc.len());
+    let before = &doc[..byte_offset];
+    let line = before.matches('\n').count() as u32;
+    let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+    let character = before[line_start..].encode_utf16().count() as u32;
+
+    Position { line, character }
+}
+
 fn context_is_assignment(doc: &str, cursor_byte_offset: usize) -> bool {
     if cursor_byte_offset > doc.len() {
         return false;
@@ -206,219 +1309,1034 @@ fn is_cursor_in_context(
     None
 }
 
-fn find_matching_paren(doc: &str, start: usize) -> usize {
-    let mut depth = 1;
-    for (offset, ch) in doc[start..].char_indices() {
-        match ch {
-            '(' => depth += 1,
-            ')' => {
-                depth -= 1;
-                if depth == 0 {
-                    return start + offset;
-                }
+// Byte ranges of every string and comment in doc, in order
+fn literal_ranges(doc: &str) -> Vec<(usize, usize)> {
+    let mut ranges = Vec::new();
+    let mut i = 0;
+    while let Some(ch) = doc[i..].chars().next() {
+        match literal_end(doc, i) {
+            Some(end) => {
+                ranges.push((i, end));
+                i = end;
             }
-            _ => {}
+            None => i += ch.len_utf8(),
         }
     }
-    doc.len()
+    ranges
 }
 
-fn find_matching_brace(doc: &str, start: usize) -> usize {
-    let mut depth = 1;
-    for (offset, ch) in doc[start..].char_indices() {
-        match ch {
-            '{' => depth += 1,
-            '}' => {
-                depth -= 1;
-                if depth == 0 {
-                    return start + offset;
-                }
-            }
-            _ => {}
-        }
-    }
-    doc.len()
+fn in_literal(ranges: &[(usize, usize)], offset: usize) -> bool {
+    let index = ranges.partition_point(|(start, _)| *start <= offset);
+    index > 0 && offset < ranges[index - 1].1
 }
 
-fn find_matching_bracket(doc: &str, start: usize) -> usize {
+// Offset of close balancing an open right before start, or doc.len() if there's none
+fn find_matching(doc: &str, start: usize, open: char, close: char) -> usize {
     let mut depth = 1;
-    for (offset, ch) in doc[start..].char_indices() {
-        match ch {
-            '[' => depth += 1,
-            ']' => {
-                depth -= 1;
-                if depth == 0 {
-                    return start + offset;
-                }
+    let mut i = start;
+    while let Some(ch) = doc[i..].chars().next() {
+        if let Some(end) = literal_end(doc, i) {
+            i = end;
+            continue;
+        }
+        if ch == open {
+            depth += 1;
+        } else if ch == close {
+            depth -= 1;
+            if depth == 0 {
+                return i;
             }
-            _ => {}
         }
+        i += ch.len_utf8();
     }
     doc.len()
 }
 
-fn extract_create_element_groups(doc: &str, var_name: &str) -> Vec<(usize, usize, String)> {
-    let needle = format!("{var_name}.createElement(");
-    let mut groups = Vec::new();
+pub(crate) fn find_matching_paren(doc: &str, start: usize) -> usize {
+    find_matching(doc, start, '(', ')')
+}
 
-    for start in doc.match_indices(&needle).map(|(i, _)| i + needle.len()) {
-        let end = find_matching_paren(doc, start);
-        groups.push((start, end, doc[start..end].to_string()));
-    }
+pub(crate) fn find_matching_brace(doc: &str, start: usize) -> usize {
+    find_matching(doc, start, '{', '}')
+}
 
-    groups
+fn find_matching_bracket(doc: &str, start: usize) -> usize {
+    find_matching(doc, start, '[', ']')
 }
 
-// Extract all createElement calls from both the original React variable and any macros
-// Only considers macros defined before the cursor position
-fn extract_all_create_element_groups(
+// Callee at i isn't the tail of a longer name(e.g. "e(" in "Roact.createElement(" or "obj:e(")
+fn starts_callee(doc: &str, i: usize) -> bool {
+    !doc[..i]
+        .chars()
+        .next_back()
+        .is_some_and(|c| c.is_alphanumeric() || c == '_' || c == '.' || c == ':')
+}
+
+// Finds (callee_start, args_start) of every call matching needle(e.g. "e(") that isn't part of a longer name
+pub(crate) fn find_call_sites<'a>(
+    doc: &'a str,
+    needle: &'a str,
+) -> impl Iterator<Item = (usize, usize)> + 'a {
+    let literals = literal_ranges(doc);
+    doc.match_indices(needle)
+        .filter(move |(i, _)| !in_literal(&literals, *i))
+        .filter(|(i, _)| starts_callee(doc, *i))
+        .map(move |(i, _)| (i, i + needle.len()))
+}
+
+fn get_create_element_needles(
     doc: &str,
+    symbols: &SymbolTable,
     react_var_name: &str,
     cursor_byte_offset: usize,
-) -> Vec<(usize, usize, String)> {
-    let mut all_groups = Vec::new();
-
-    // Add groups from the original React variable (e.g., React.createElement)
-    all_groups.extend(extract_create_element_groups(doc, react_var_name));
-
-    // Add groups from all macros defined before cursor position
-    let macros = get_create_element_macros(doc, cursor_byte_offset, react_var_name);
+) -> Vec<String> {
+    let mut needles = vec![format!("{react_var_name}.createElement(")];
+    // For macros, we look for macro_name( instead of macro_name.createElement(
+    let mut macros = symbols.create_element_macros(doc, cursor_byte_offset, react_var_name);
+    macros.extend(symbols.create_element_helpers(doc, cursor_byte_offset));
     for macro_name in macros {
-        // For macros, we look for macro_name( instead of macro_name.createElement(
-        let needle = format!("{macro_name}(");
-        for start in doc.match_indices(&needle).map(|(i, _)| i + needle.len()) {
-            let end = find_matching_paren(doc, start);
-            all_groups.push((start, end, doc[start..end].to_string()));
-        }
+        needles.push(format!("{macro_name}("));
     }
+    needles
+}
 
-    all_groups
+// Documents past this many lines aren't region indexed, completions only walk the top level
+// statement around the cursor instead(Big generated UI modules took hundreds of ms otherwise)
+const WINDOWED_ANALYSIS_LINES: usize = 3000;
+
+// Scans completions would otherwise redo on every request, FileManager keeps one per document
+// version(Ranged edits update it in place, anything else rebuilds it, so it always matches the text)
+#[derive(Debug)]
+pub struct DocumentAnalysis {
+    pub symbols: SymbolTable,
+    pub(crate) regions: Option<RegionIndex>, // None past WINDOWED_ANALYSIS_LINES
+    has_react: Option<bool>,                 // Filled in by the first request needing it
+    line_breaks: usize,                      // Kept up to date by edits, without recounting
 }
 
-fn get_completion_items(
-    doc: &str,
-    cursor: &Position,
-    api_manager: &ApiManager,
-) -> Vec<CompletionItem> {
+impl DocumentAnalysis {
+    pub fn new(doc: &str, requires: &Arc<RequireContext>) -> Self {
+        let symbols = SymbolTable::build(doc, requires);
+        let line_breaks = doc.bytes().filter(|&b| b == b'\n').count();
+        Self {
+            regions: (line_breaks < WINDOWED_ANALYSIS_LINES)
+                .then(|| RegionIndex::build(doc, &symbols)),
+            symbols,
+            has_react: None,
+            line_breaks,
+        }
+    }
+
+    // Catches up with doc after splice replaced removed, reusing every region and symbol the edit
+    // didn't touch(Symbols only get parsed again for the top level statements around it)
+    pub(crate) fn apply_edit(&mut self, doc: &str, splice: TextSplice, removed: &str) {
+        let inserted = &doc[splice.start..splice.start + splice.inserted_len];
+        self.line_breaks =
+            self.line_breaks - removed.matches('\n').count() + inserted.matches('\n').count();
+        if !self
+            .symbols
+            .update(doc, splice.start, removed, splice.inserted_len)
+        {
+            self.symbols = SymbolTable::build(doc, &self.symbols.requires().clone());
+        }
+        self.regions = match self.regions.take() {
+            _ if self.line_breaks >= WINDOWED_ANALYSIS_LINES => None,
+            Some(mut regions) => {
+                regions.update(doc, &self.symbols, splice, removed);
+                Some(regions)
+            }
+            None => Some(RegionIndex::build(doc, &self.symbols)),
+        };
+        self.has_react = None;
+    }
+
+    fn has_react(&mut self, doc: &str) -> bool {
+        let requires = self.symbols.requires();
+        *self
+            .has_react
+            .get_or_insert_with(|| has_react(doc, requires))
+    }
+}
+
+// Element calls whose parens are still open at offset, walking brackets from the top level
+// statement it's in(The only regions completions use, without scanning the rest of doc)
+fn enclosing_element_regions(
+    doc: &str,
+    symbols: &SymbolTable,
+    needles: &[String],
+    react_var_name: &str,
+    offset: usize,
+) -> Vec<ElementRegion> {
+    let mut open_parens = Vec::new();
+    let mut i = symbols.statement_start_at(offset);
+    while i < offset {
+        let Some(ch) = doc[i..].chars().next() else {
+            break;
+        };
+        if let Some(end) = literal_end(doc, i) {
+            i = end;
+            continue;
+        }
+        match ch {
+            '(' => open_parens.push(i + 1),
+            ')' => {
+                open_parens.pop();
+            }
+            _ => {}
+        }
+        i += ch.len_utf8();
+    }
+
+    // Direct calls only count where the cursor's React variable is the visible one
+    let direct_needle = format!("{react_var_name}.createElement(");
+    open_parens
+        .into_iter()
+        .filter_map(|start| {
+            let needle = needles.iter().find(|needle| {
+                doc[..start].ends_with(needle.as_str()) && starts_callee(doc, start - needle.len())
+            })?;
+            let callee_start = start - needle.len();
+            let is_visible = *needle != direct_needle
+                || symbols.react_var_name_at(doc, callee_start).as_deref() == Some(react_var_name);
+            is_visible.then(|| element_region(doc, callee_start, start))
+        })
+        .collect()
+}
+
+// Extract all createElement calls from both the original React variable and any macros
+// Only considers macros defined before the cursor position(Picked out of the region index, or
+// the calls around the cursor in documents too big to index)
+fn extract_all_create_element_groups(
+    doc: &str,
+    analysis: &DocumentAnalysis,
+    react_var_name: &str,
+    cursor_byte_offset: usize,
+) -> Vec<ElementRegion> {
+    let needles =
+        get_create_element_needles(doc, &analysis.symbols, react_var_name, cursor_byte_offset);
+    let Some(index) = &analysis.regions else {
+        return enclosing_element_regions(
+            doc,
+            &analysis.symbols,
+            &needles,
+            react_var_name,
+            cursor_byte_offset,
+        );
+    };
+    index
+        .regions()
+        .iter()
+        .filter(|region| {
+            needles
+                .iter()
+                .any(|needle| &doc[region.callee_start..region.start] == needle)
+        })
+        .filter(|region| region.start <= cursor_byte_offset && cursor_byte_offset <= region.end)
+        .cloned()
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum PropKey {
+    Name(String),     // Size = ...
+    Computed(String), // [React.Event.Activated] = ...
+    Positional,       // Entries without key(e.g. children in array form)
+}
+
+// All offsets are absolute byte offsets into the document
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct PropEntry {
+    pub key: PropKey,
+    pub key_range: (usize, usize),
+    pub range: (usize, usize),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ElementRegion {
+    pub callee_start: usize, // Start of "React.createElement"/"e" before the paren
+    pub start: usize,        // Right after the opening paren
+    pub end: usize,          // Closing paren
+    pub class_name: Option<String>,
+    pub args: Vec<(usize, usize)>,
+    pub props: Option<(usize, usize)>, // Opening and closing brace of props table
+    pub entries: Vec<PropEntry>,
+}
+
+// Splits text on top level separators, ignoring anything nested in brackets, strings or comments
+// Returns trimmed (start, end) spans, empty pieces(e.g. after a trailing comma) are skipped
+pub(crate) fn split_top_level(text: &str, separators: &[char]) -> Vec<(usize, usize)> {
+    let mut pieces = Vec::new();
+    let mut depth = 0usize;
+    let mut piece_start = 0;
+    let mut i = 0;
+
+    let push_piece = |pieces: &mut Vec<(usize, usize)>, start: usize, end: usize| {
+        let piece = &text[start..end];
+        let trimmed_start = start + (piece.len() - piece.trim_start().len());
+        let trimmed_end = start + piece.trim_end().len();
+        if trimmed_start < trimmed_end {
+            pieces.push((trimmed_start, trimmed_end));
+        }
+    };
+
+    while let Some(c) = text[i..].chars().next() {
+        if let Some(end) = literal_end(text, i) {
+            i = end;
+            continue;
+        }
+
+        match c {
+            '(' | '{' | '[' => depth += 1,
+            ')' | '}' | ']' => depth = depth.saturating_sub(1),
+            c if depth == 0 && separators.contains(&c) => {
+                push_piece(&mut pieces, piece_start, i);
+                piece_start = i + c.len_utf8();
+            }
+            _ => {}
+        }
+        i += c.len_utf8();
+    }
+    push_piece(&mut pieces, piece_start, text.len());
+
+    pieces
+}
+
+// Returns offset of a single "=" (Not ==, ~=, <=, >=) in entry if it's at the top level
+fn find_assignment(entry: &str, from: usize) -> Option<usize> {
+    let rest = entry[from..].trim_start();
+    let eq = from + (entry.len() - from - rest.len());
+    if rest.starts_with('=') && !rest.starts_with("==") {
+        return Some(eq);
+    }
+    None
+}
+
+fn parse_prop_entry(doc: &str, start: usize, end: usize) -> PropEntry {
+    // Comments before the key aren't part of the entry(-- note\n Text = "Hi")
+    let mut start = start;
+    while doc[start..end].starts_with("--") {
+        let comment_end = literal_end(doc, start).unwrap_or(end).min(end);
+        start =
+            comment_end + (doc[comment_end..end].len() - doc[comment_end..end].trim_start().len());
+    }
+    let entry = &doc[start..end];
+    let ident_len = entry
+        .find(|c: char| !c.is_alphanumeric() && c != '_')
+        .unwrap_or(entry.len());
+
+    if ident_len > 0 && !entry.starts_with(|c: char| c.is_ascii_digit()) {
+        if find_assignment(entry, ident_len).is_some() {
+            return PropEntry {
+                key: PropKey::Name(entry[..ident_len].to_string()),
+                key_range: (start, start + ident_len),
+                range: (start, end),
+            };
+        }
+    } else if entry.starts_with('[') {
+        let bracket_end = find_matching_bracket(entry, 1);
+        if bracket_end < entry.len() && find_assignment(entry, bracket_end + 1).is_some() {
+            return PropEntry {
+                key: PropKey::Computed(entry[1..bracket_end].trim().to_string()),
+                key_range: (start, start + bracket_end + 1),
+                range: (start, end),
+            };
+        }
+    }
+
+    PropEntry {
+        key: PropKey::Positional,
+        key_range: (start, start),
+        range: (start, end),
+    }
+}
+
+pub(crate) fn parse_props_table(doc: &str, brace_start: usize, brace_end: usize) -> Vec<PropEntry> {
+    let content_start = brace_start + 1;
+    split_top_level(&doc[content_start..brace_end], &[',', ';'])
+        .into_iter()
+        .map(|(start, end)| parse_prop_entry(doc, content_start + start, content_start + end))
+        .collect()
+}
+
+// Every createElement call in document, with its arguments and props table broken down
+fn element_region(doc: &str, callee_start: usize, start: usize) -> ElementRegion {
+    let end = find_matching_paren(doc, start);
+    let args: Vec<(usize, usize)> = split_top_level(&doc[start..end], &[','])
+        .into_iter()
+        .map(|(a, b)| (start + a, start + b))
+        .collect();
+
+    let props = args
+        .get(1)
+        .filter(|(a, _)| doc[*a..].starts_with('{'))
+        .map(|(a, _)| (*a, find_matching_brace(doc, a + 1)));
+    let entries = props
+        .map(|(a, b)| parse_props_table(doc, a, b))
+        .unwrap_or_default();
+
+    ElementRegion {
+        callee_start,
+        start,
+        end,
+        class_name: extract_name_from_span(&doc[start..end]),
+        args,
+        props,
+        entries,
+    }
+}
+
+// Needles of every call creating an element, with the React variable direct calls go through
+// (Files can require React under a different name per scope, calls only count where theirs is visible)
+fn element_call_needles(doc: &str, symbols: &SymbolTable) -> Vec<(String, Option<String>)> {
+    let mut needles = Vec::new();
+    for react_var_name in symbols.react_var_names(doc) {
+        let direct_needle = format!("{react_var_name}.createElement(");
+        for needle in get_create_element_needles(doc, symbols, &react_var_name, doc.len()) {
+            let direct = (needle == direct_needle).then(|| react_var_name.clone());
+            needles.push((needle, direct));
+        }
+    }
+    // Helpers required from other modules work without React in the file
+    for helper in symbols.create_element_helpers(doc, doc.len()) {
+        needles.push((format!("{helper}("), None));
+    }
+    needles
+}
+
+// Where an edit replaced removed bytes at start with inserted_len new ones
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct TextSplice {
+    pub start: usize,
+    pub removed_len: usize,
+    pub inserted_len: usize,
+}
+
+impl TextSplice {
+    // Offset in the old text mapped into the new one, None inside the replaced bytes
+    fn map(&self, offset: usize, old_len: usize, new_len: usize) -> Option<usize> {
+        let removed_end = self.start + self.removed_len;
+        if offset == old_len {
+            Some(new_len)
+        } else if offset <= self.start {
+            Some(offset)
+        } else if offset >= removed_end {
+            Some(offset - self.removed_len + self.inserted_len)
+        } else {
+            None
+        }
+    }
+}
+
+// Element regions of a document kept up to date edit by edit, so typing in a big file only
+// rescans the lines around the edit(Regions only depend on their own text otherwise)
+#[derive(Debug)]
+pub(crate) struct RegionIndex {
+    regions: Vec<ElementRegion>, // Sorted by start
+    needles: Vec<(String, Option<String>)>,
+    // Scopes of every symbol named like a React variable, which decide where direct calls count
+    react_scopes: Vec<(String, (usize, usize))>,
+    len: usize, // Document length regions were computed for
+}
+
+impl RegionIndex {
+    pub fn build(doc: &str, symbols: &SymbolTable) -> Self {
+        let mut index = Self {
+            regions: Vec::new(),
+            needles: element_call_needles(doc, symbols),
+            react_scopes: Self::react_scopes(doc, symbols),
+            len: doc.len(),
+        };
+        index.scan(doc, symbols, (0, doc.len()));
+        index
+    }
+
+    pub fn regions(&self) -> &[ElementRegion] {
+        &self.regions
+    }
+
+    fn react_scopes(doc: &str, symbols: &SymbolTable) -> Vec<(String, (usize, usize))> {
+        let names = symbols.react_var_names(doc);
+        symbols
+            .symbols
+            .iter()
+            .filter(|symbol| names.contains(&symbol.name))
+            .map(|symbol| (symbol.name.clone(), symbol.scope))
+            .collect()
+    }
+
+    // Adds regions of calls whose callee starts within window and aren't indexed yet
+    fn scan(&mut self, doc: &str, symbols: &SymbolTable, window: (usize, usize)) {
+        for (needle, direct) in &self.needles {
+            for (callee_start, start) in find_call_sites(doc, needle) {
+                if callee_start < window.0 || callee_start > window.1 {
+                    continue;
+                }
+                let is_visible = direct.as_ref().is_none_or(|react_var_name| {
+                    symbols.react_var_name_at(doc, callee_start).as_ref() == Some(react_var_name)
+                });
+                if is_visible && !self.regions.iter().any(|region| region.start == start) {
+                    self.regions.push(element_region(doc, callee_start, start));
+                }
+            }
+        }
+        self.regions.sort_by_key(|region| region.start);
+    }
+
+    // Brings regions up to date with doc after splice, symbols are the ones of the new doc
+    pub fn update(&mut self, doc: &str, symbols: &SymbolTable, splice: TextSplice, removed: &str) {
+        let inserted = &doc[splice.start..splice.start + splice.inserted_len];
+        let (old_len, new_len) = (self.len, doc.len());
+        // Long strings/comments(--[[ ]]) and escaped line breaks can turn any later text into
+        // code or out of it, and new callees or moved scopes change which calls count at all
+        let changes_lexing = |text: &str| text.contains(['[', ']', '-', '\\']);
+        let needles = element_call_needles(doc, symbols);
+        let react_scopes = Self::react_scopes(doc, symbols);
+        let scopes_moved =
+            react_scopes.len() != self.react_scopes.len()
+                || self.react_scopes.iter().zip(&react_scopes).any(
+                    |((old_name, old), (name, new))| {
+                        let old_mapped = splice
+                            .map(old.0, old_len, new_len)
+                            .zip(splice.map(old.1, old_len, new_len));
+                        old_name != name || old_mapped != Some(*new)
+                    },
+                );
+        if changes_lexing(removed)
+            || changes_lexing(inserted)
+            || needles != self.needles
+            || scopes_moved
+        {
+            *self = Self::build(doc, symbols);
+            return;
+        }
+
+        let removed_end = splice.start + splice.removed_len;
+        let inserted_end = splice.start + splice.inserted_len;
+        let delta = splice.inserted_len as isize - splice.removed_len as isize;
+        let shift = |offset: &mut usize| *offset = (*offset as isize + delta) as usize;
+        // Whole lines around the edit, a quote or comment typed there changes the rest of its line
+        let mut window = (
+            doc[..splice.start].rfind('\n').map(|i| i + 1).unwrap_or(0),
+            doc[inserted_end..]
+                .find('\n')
+                .map(|i| inserted_end + i)
+                .unwrap_or(new_len),
+        );
+        self.regions.retain_mut(|region| {
+            if region.end < splice.start {
+                return true;
+            }
+            // Character before the callee decides whether it's a call, so touching it counts too
+            if region.callee_start > removed_end {
+                shift(&mut region.callee_start);
+                shift(&mut region.start);
+                shift(&mut region.end);
+                for (a, b) in region.args.iter_mut() {
+                    shift(a);
+                    shift(b);
+                }
+                if let Some((a, b)) = region.props.as_mut() {
+                    shift(a);
+                    shift(b);
+                }
+                for entry in region.entries.iter_mut() {
+                    shift(&mut entry.key_range.0);
+                    shift(&mut entry.key_range.1);
+                    shift(&mut entry.range.0);
+                    shift(&mut entry.range.1);
+                }
+                return true;
+            }
+            window.0 = window.0.min(region.callee_start.min(splice.start));
+            let end = match region.end >= removed_end {
+                true => (region.end as isize + delta) as usize,
+                false => inserted_end,
+            };
+            window.1 = window.1.max(end);
+            false
+        });
+        self.len = new_len;
+        self.scan(doc, symbols, window);
+    }
+}
+
+pub(crate) fn get_element_regions(doc: &str, requires: &Arc<RequireContext>) -> Vec<ElementRegion> {
+    RegionIndex::build(doc, &SymbolTable::build(doc, requires)).regions
+}
+
+// Smallest element call(Including its callee) that contains byte offset
+pub(crate) fn innermost_region_at(
+    regions: &[ElementRegion],
+    byte_offset: usize,
+) -> Option<&ElementRegion> {
+    regions
+        .iter()
+        .filter(|region| region.callee_start <= byte_offset && byte_offset <= region.end)
+        .min_by_key(|region| region.end - region.callee_start)
+}
+
+// How many of the most used names debug dumps include
+const DEBUG_DUMP_FREQUENCIES: usize = 20;
+
+// Server's view of a document, returned by the debugDump command for bug reports
+// Offsets are bytes, cursor(When given) says which region completions there would use
+pub fn get_debug_dump(
+    doc: &str,
+    symbols: &SymbolTable,
+    cursor: Option<&Position>,
+    api_manager: &ApiManager,
+) -> Value {
+    let react_var_names = symbols.react_var_names(doc);
+    let mut macros: Vec<String> = react_var_names
+        .iter()
+        .flat_map(|name| get_create_element_macros(doc, symbols, doc.len(), name))
+        .collect();
+    macros.sort();
+    macros.dedup();
+
+    let regions = RegionIndex::build(doc, symbols).regions;
+    let region_json: Vec<Value> = regions
+        .iter()
+        .map(|region| {
+            let keys: Vec<Value> = region
+                .entries
+                .iter()
+                .map(|entry| match &entry.key {
+                    PropKey::Name(name) => json!({ "name": name, "range": entry.range }),
+                    PropKey::Computed(key) => json!({ "computed": key, "range": entry.range }),
+                    PropKey::Positional => json!({ "positional": true, "range": entry.range }),
+                })
+                .collect();
+            json!({
+                "callee": &doc[region.callee_start..region.start.saturating_sub(1)],
+                "range": [region.callee_start, region.end],
+                "element": region.args.first().map(|&(a, b)| &doc[a..b]),
+                "propsRange": region.props,
+                "className": region.class_name,
+                // Null when the name isn't an API or custom class(Components, typos)
+                "resolvedClass": region
+                    .class_name
+                    .as_ref()
+                    .filter(|name| !api_manager.superclass_chain(name).is_empty()),
+                "entries": keys,
+            })
+        })
+        .collect();
+
+    let cursor = cursor.map(|position| {
+        let byte_offset = position_to_byte_offset(doc, position);
+        let region = byte_offset
+            .and_then(|byte_offset| innermost_region_at(&regions, byte_offset))
+            .and_then(|found| {
+                regions
+                    .iter()
+                    .position(|region| region.start == found.start)
+            });
+        json!({ "byteOffset": byte_offset, "regionIndex": region })
+    });
+
+    let mut frequencies = api_manager.usage_report()["usage"].take();
+    if let Some(frequencies) = frequencies.as_array_mut() {
+        frequencies.truncate(DEBUG_DUMP_FREQUENCIES);
+    }
+
+    json!({
+        "reactVariables": react_var_names,
+        "macros": macros,
+        "regions": region_json,
+        "cursor": cursor,
+        "topFrequencies": frequencies,
+        "apiVersion": api_manager.api_version(),
+    })
+}
+
+// React treats these keys specially, they never show up in the API dump
+pub(crate) const SPECIAL_PROPS: [&str; 3] = ["ref", "key", "children"];
+
+pub const UNKNOWN_PROPERTY_CODE: &str = "unknown-property";
+pub const RESTRICTED_PROPERTY_CODE: &str = "restricted-property";
+
+pub fn get_document_diagnostics(
+    doc: &str,
+    requires: &Arc<RequireContext>,
+    api_manager: &ApiManager,
+) -> Vec<Diagnostic> {
+    get_document_diagnostics_cancellable(doc, requires, api_manager, &CancellationToken::new())
+        .unwrap_or_default()
+}
+
+// Same, giving up with None once cancel is(A newer edit of the document supersedes this run)
+pub fn get_document_diagnostics_cancellable(
+    doc: &str,
+    requires: &Arc<RequireContext>,
+    api_manager: &ApiManager,
+    cancel: &CancellationToken,
+) -> Option<Vec<Diagnostic>> {
+    let mut diagnostics = Vec::new();
+    let symbols = SymbolTable::build(doc, requires);
+
+    for region in RegionIndex::build(doc, &symbols).regions {
+        if cancel.is_cancelled() {
+            return None;
+        }
+        let Some(class_name) = region.class_name.as_ref() else {
+            continue;
+        };
+        for entry in &region.entries {
+            let PropKey::Name(key) = &entry.key else {
+                continue;
+            };
+            if SPECIAL_PROPS.contains(&key.as_str()) {
+                continue;
+            }
+            let (code, message) =
+                if let Some(reason) = api_manager.property_restriction(class_name, key) {
+                    let message = match reason.as_str() {
+                        "NotScriptable" => format!("'{}' isn't scriptable on {}", key, class_name),
+                        "ReadOnly" => format!("'{}' is read-only on {}", key, class_name),
+                        level => format!(
+                            "'{}' on {} needs {} to be set, scripts can't set it",
+                            key, class_name, level
+                        ),
+                    };
+                    (RESTRICTED_PROPERTY_CODE, message)
+                } else {
+                    // Either a real property or a class we know nothing about
+                    let Some(false) = api_manager.has_property(class_name, key) else {
+                        continue;
+                    };
+                    (
+                        UNKNOWN_PROPERTY_CODE,
+                        format!("Unknown property '{}' on {}", key, class_name),
+                    )
+                };
+
+            diagnostics.push(Diagnostic {
+                range: Range {
+                    start: byte_offset_to_position(doc, entry.key_range.0),
+                    end: byte_offset_to_position(doc, entry.key_range.1),
+                },
+                severity: Some(DiagnosticSeverity::WARNING),
+                code: Some(NumberOrString::String(code.to_string())),
+                code_description: code_description(code),
+                source: Some(ITEM_SOURCE.to_string()),
+                message,
+                ..Default::default()
+            });
+        }
+    }
+
+    if cancel.is_cancelled() {
+        return None;
+    }
+    diagnostics.extend(get_hook_diagnostics(doc, &symbols));
+    Some(diagnostics)
+}
+
+// Which detector a completion request runs, picked up front from what triggered it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CompletionTrigger {
+    Invoked, // Manually or while typing an identifier, runs every detector
+    Quote,   // Class name in first argument
+    Dot,     // Member of React, React.Event or React.Change
+    Bracket, // Start of a [React.Event.X] key
+    Colon,   // Method of a binding, binding:map
+}
+
+impl CompletionTrigger {
+    pub fn from_context(context: Option<&CompletionContext>) -> Self {
+        let character = context
+            .filter(|c| c.trigger_kind == CompletionTriggerKind::TRIGGER_CHARACTER)
+            .and_then(|c| c.trigger_character.as_deref());
+        match character {
+            Some("\"" | "'" | "`") => Self::Quote,
+            Some(".") => Self::Dot,
+            Some("[") => Self::Bracket,
+            Some(":") => Self::Colon,
+            _ => Self::Invoked,
+        }
+    }
+
+    // Cheap check on text before cursor, so triggers that can't complete anything skip analysis
+    fn can_complete(self, before_cursor: &str, react_var_name: &str) -> bool {
+        match self {
+            Self::Invoked => true,
+            Self::Quote => before_cursor.ends_with(['"', '\'', '`']),
+            Self::Dot => {
+                before_cursor.ends_with(&format!("{}.Event.", react_var_name))
+                    || before_cursor.ends_with(&format!("{}.Change.", react_var_name))
+                    || is_react_member_access(before_cursor, react_var_name)
+                    || is_value_access(before_cursor)
+            }
+            Self::Bracket => before_cursor.ends_with('['),
+            Self::Colon => before_cursor.ends_with(':'),
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn get_completion_items(
+    doc: &str,
+    analysis: &mut DocumentAnalysis,
+    uri: &Url,
+    cursor: &Position,
+    api_manager: &ApiManager,
+    components: &ComponentIndex,
+    element_constants: &[String],
+    trigger: CompletionTrigger,
+    cancel: &CancellationToken,
+) -> Vec<CompletionItem> {
     let mut diagnostics: Vec<CompletionItem> = Vec::new();
-    if !has_react(doc) {
+    let cursor_byte_offset =
+        position_to_byte_offset(doc, cursor).expect("Invalid position given for doc!");
+    // Requires don't need React in the file(It's often the first one being written)
+    if let Some(typed_start) = require_argument_start(&doc[..cursor_byte_offset])
+        .filter(|_| trigger == CompletionTrigger::Invoked)
+    {
+        return get_require_items(doc, uri, (typed_start, cursor_byte_offset), components);
+    }
+    // Files only using a helper required from elsewhere still spell event keys with React
+    let has_helpers = !analysis
+        .symbols
+        .create_element_helpers(doc, cursor_byte_offset)
+        .is_empty();
+    if !analysis.has_react(doc) && !has_helpers {
         return diagnostics;
     }
-    let variable_name = get_react_var_name(doc);
-    if variable_name.is_none() {
+    let variable_name = analysis
+        .symbols
+        .react_var_name_at(doc, cursor_byte_offset)
+        .or_else(|| has_helpers.then(|| "React".to_string()));
+    let Some(variable_name_str) = &variable_name else {
+        return diagnostics;
+    };
+    if !trigger.can_complete(&doc[..cursor_byte_offset], variable_name_str) {
         return diagnostics;
     }
-    let cursor_byte_offset =
-        position_to_byte_offset(doc, cursor).expect("Invalid position given for doc!");
-    let variable_name_str = &variable_name.unwrap();
-    let mut groups = extract_all_create_element_groups(doc, variable_name_str, cursor_byte_offset);
+    if is_react_member_access(&doc[..cursor_byte_offset], variable_name_str) {
+        return get_react_api_items();
+    }
+    // Bindings can be mapped anywhere, not just in props
+    if let Some(item) = get_binding_method_item(doc, &analysis.symbols, cursor_byte_offset) {
+        return vec![item];
+    }
+    if trigger == CompletionTrigger::Colon || cancel.is_cancelled() {
+        return diagnostics;
+    }
+    let mut regions =
+        extract_all_create_element_groups(doc, analysis, variable_name_str, cursor_byte_offset);
 
     // If we have multiple nested groups, we need to get inner most one(Which is smallest) since
     // If cursor is in multiple groups { { {|} } }, closest(smallest) takes priority
-    groups.sort_by_key(|(start, end, _)| end.saturating_sub(*start));
+    regions.sort_by_key(|region| region.end.saturating_sub(region.start));
 
-    for (start, end, group_str) in groups {
-        if cursor_byte_offset < start || cursor_byte_offset > end {
-            continue;
+    for region in &regions {
+        if cancel.is_cancelled() {
+            return Vec::new();
         }
+        let start = region.start;
+        let group_str = &doc[start..region.end];
         let local_cursor_offset = cursor_byte_offset.saturating_sub(start);
+        let first_arg = region.args.first().map_or("", |&(a, b)| &doc[a..b]);
 
-        if let Some(brace_start) = group_str.find('{') {
-            let brace_end = find_matching_brace(&group_str, brace_start + 1);
-
-            if local_cursor_offset >= brace_start && local_cursor_offset <= brace_end {
-                let brace_content = &group_str[brace_start + 1..brace_end];
-                let cursor_in_brace = local_cursor_offset.saturating_sub(brace_start + 1);
-
-                // Check if we are in nested group, if so we need to bail out
-                // Otherwise if you have nested function call inside properties table, it will provide auto complete suggestions
-                // (Which is pretty annoying)
-                let mut nested_search = 0;
-                let mut inside_nested_brace = false;
-                while let Some(rel_inner_brace) = brace_content[nested_search..].find('{') {
-                    let inner_brace_start = nested_search + rel_inner_brace;
-                    let inner_brace_end = find_matching_brace(brace_content, inner_brace_start + 1);
-
-                    if cursor_in_brace > inner_brace_start && cursor_in_brace < inner_brace_end {
-                        inside_nested_brace = true;
-                        break;
-                    }
+        // Where in the call cursor is, off bracket pairs so strings and nested calls can't fool it
+        let context = element_context(doc, &analysis.symbols, region, cursor_byte_offset);
+        let in_props = matches!(
+            context,
+            ElementContext::PropKey | ElementContext::ComputedKey(_) | ElementContext::PropValue(_)
+        );
+        if let Some((brace_start, brace_end)) = region
+            .props
+            .filter(|_| in_props && trigger != CompletionTrigger::Quote)
+        {
+            let brace_end = brace_end.min(region.end);
+            let brace_content = &doc[brace_start + 1..brace_end];
+            let cursor_in_brace = cursor_byte_offset - (brace_start + 1);
 
-                    nested_search = inner_brace_end + 1;
-                    if nested_search >= brace_content.len() {
-                        break;
-                    }
-                }
-                if inside_nested_brace {
-                    // Cursor is inside a nested calls braces, not items props
-                    // try next group in the outer loop
-                    continue;
-                }
+            let event_needle = format!("{}.Event.", variable_name_str);
+            let change_needle = format!("{}.Change.", variable_name_str);
 
-                let event_needle = format!("{}.Event.", variable_name_str);
-                let change_needle = format!("{}.Change.", variable_name_str);
-
-                let mut search_from = 0;
-                let mut handled = false;
-                while let Some(rel_bracket_start) = brace_content[search_from..].find('[') {
-                    let bracket_start = search_from + rel_bracket_start;
-                    let bracket_end = find_matching_bracket(brace_content, bracket_start + 1);
-
-                    if cursor_in_brace >= bracket_start && cursor_in_brace <= bracket_end {
-                        let bracket_content = &brace_content[bracket_start + 1..bracket_end];
-                        let cursor_in_bracket = cursor_in_brace.saturating_sub(bracket_start + 1);
-
-                        if let Some(rel_pos) = bracket_content.find(&event_needle) {
-                            // Support event auto completions
-                            let dot_offset = rel_pos + event_needle.len() - 1;
-                            if cursor_in_bracket >= dot_offset {
-                                if let Some(instance_name) = extract_name_from_span(&group_str) {
-                                    diagnostics.extend(get_instance_events_diagnostics(
-                                        &instance_name,
-                                        api_manager,
-                                    ));
-                                }
-                            }
-                        } else if let Some(rel_pos) = bracket_content.find(&change_needle) {
-                            // Support Change event
-                            let dot_offset = rel_pos + change_needle.len() - 1;
-                            if cursor_in_bracket >= dot_offset {
-                                if let Some(instance_name) = extract_name_from_span(&group_str) {
-                                    diagnostics.extend(get_instance_property_diagnostics(
-                                        &instance_name,
-                                        api_manager,
-                                    ));
-                                }
-                            }
+            if let ElementContext::ComputedKey((bracket_start, bracket_end)) = context {
+                let bracket_content = &doc[bracket_start + 1..bracket_end.min(brace_end)];
+                let cursor_in_bracket = cursor_byte_offset - (bracket_start + 1);
+
+                // Whole "[React.Event.X]" gets replaced, unless entry already has a value
+                let abs_bracket_end = if bracket_end < brace_end {
+                    bracket_end + 1
+                } else {
+                    cursor_byte_offset
+                };
+                let handler_snippets =
+                    |needle: &str, params: &dyn Fn(&str) -> String, items: Vec<CompletionItem>| {
+                        if followed_by_assignment(doc, abs_bracket_end) {
+                            return items;
                         }
+                        let filter_prefix = format!("[{}", needle);
+                        with_snippet_edits(
+                            doc,
+                            items,
+                            (bracket_start, abs_bracket_end.max(cursor_byte_offset)),
+                            &filter_prefix,
+                            |name| {
+                                format!(
+                                    "{}{}] = function({})\n$0\nend,",
+                                    filter_prefix,
+                                    name,
+                                    params(name)
+                                )
+                            },
+                        )
+                    };
 
-                        handled = true;
-                        break;
+                if let Some(rel_pos) = bracket_content.find(&event_needle) {
+                    // Support event auto completions
+                    let dot_offset = rel_pos + event_needle.len() - 1;
+                    if cursor_in_bracket >= dot_offset {
+                        if let Some(instance_name) = extract_name_from_span(group_str) {
+                            diagnostics.extend(handler_snippets(
+                                &event_needle,
+                                &|name| event_handler_params(&instance_name, name, api_manager),
+                                get_instance_events_diagnostics(&instance_name, api_manager),
+                            ));
+                        }
                     }
-
-                    search_from = bracket_end + 1;
-                    if search_from >= brace_content.len() {
-                        break;
+                } else if let Some(rel_pos) = bracket_content.find(&change_needle) {
+                    // Support Change event
+                    let dot_offset = rel_pos + change_needle.len() - 1;
+                    if cursor_in_bracket >= dot_offset {
+                        if let Some(instance_name) = extract_name_from_span(group_str) {
+                            diagnostics.extend(handler_snippets(
+                                &change_needle,
+                                &|_| "rbx".to_string(),
+                                get_instance_change_items(&instance_name, api_manager),
+                            ));
+                        }
                     }
-                }
-
-                // Cursor is in props table but not inside any bracket
-                if !handled && !context_is_assignment(doc, cursor_byte_offset) {
-                    if let Some(instance_name) = extract_name_from_span(&group_str) {
-                        diagnostics.extend(get_instance_property_diagnostics(
-                            &instance_name,
-                            api_manager,
+                } else if bracket_content[..cursor_in_bracket.min(bracket_content.len())]
+                    .trim()
+                    .is_empty()
+                {
+                    // Only "[" typed so far, offer every event as a whole handler entry
+                    if let Some(instance_name) = extract_name_from_span(group_str) {
+                        diagnostics.extend(handler_snippets(
+                            &event_needle,
+                            &|name| event_handler_params(&instance_name, name, api_manager),
+                            get_instance_events_diagnostics(&instance_name, api_manager),
                         ));
                     }
                 }
-
                 break;
             }
+
+            let value_context = prop_value_context(brace_content, cursor_in_brace)
+                .filter(|_| matches!(context, ElementContext::PropValue(_)));
+            let enum_chain = enum_chain_at(&doc[..cursor_byte_offset]);
+            let value_end = word_range_at(doc, cursor_byte_offset).1;
+            let mut value_items = Vec::new();
+            if let Some((prop_name, value_start)) = value_context {
+                // Cursor is at a prop's value, e.g. "Font = Enum.|"
+                if let Some(instance_name) = extract_name_from_span(group_str) {
+                    let value_start = brace_start + 1 + value_start;
+                    value_items = get_prop_value_items(
+                        doc,
+                        &instance_name,
+                        prop_name,
+                        (value_start, value_end),
+                        api_manager,
+                    );
+                }
+            }
+            if enum_chain.is_some() {
+                // Typed "Enum." already, so constructors and literals don't fit anymore
+                value_items.retain(|item| item.label.starts_with("Enum."));
+            }
+            if value_items.is_empty() {
+                // Enum chains complete anywhere, even when prop's type isn't known
+                if let Some((chain_start, category)) = enum_chain {
+                    value_items =
+                        get_enum_chain_items(doc, (chain_start, value_end), category, api_manager);
+                }
+            }
+
+            if value_context.is_some() || enum_chain.is_some() {
+                diagnostics.extend(value_items);
+            } else if context == ElementContext::PropKey
+                && trigger == CompletionTrigger::Invoked
+                && !context_is_assignment(doc, cursor_byte_offset)
+            {
+                // Cursor is at a key in props table
+                let mut items = get_special_prop_items(variable_name_str);
+                if let Some(instance_name) = extract_name_from_span(group_str) {
+                    items.extend(get_instance_property_diagnostics(
+                        &instance_name,
+                        api_manager,
+                    ));
+                    diagnostics.extend(prop_snippets(doc, cursor_byte_offset, items));
+                } else if first_arg == format!("{}.Fragment", variable_name_str) {
+                    // Fragments create no instance, so only key and children mean anything
+                    items.retain(|item| item.label == "key" || item.label == "children");
+                    diagnostics.extend(prop_snippets(doc, cursor_byte_offset, items));
+                } else if let Some(component) =
+                    components.resolve(doc, uri, &analysis.symbols, first_arg)
+                {
+                    // Components(e.g. e(Components.Button, {})) get props read in their body
+                    items.extend(get_component_prop_items(&component));
+                    diagnostics.extend(prop_snippets(doc, cursor_byte_offset, items));
+                }
+            }
+
+            break;
+        }
+
+        if let ElementContext::ChildKey(_) = context {
+            if trigger == CompletionTrigger::Invoked {
+                let args: Vec<(usize, usize)> = region
+                    .args
+                    .iter()
+                    .map(|&(a, b)| (a - start, b - start))
+                    .collect();
+                if let Some(item) =
+                    get_child_element_item(doc, group_str, &args, start, cursor_byte_offset)
+                {
+                    diagnostics.push(item);
+                    break;
+                }
+            }
+            continue;
         }
 
         // Cursor is in the first argument (the instance name string)
+        if context != ElementContext::FirstArgument
+            || !matches!(
+                trigger,
+                CompletionTrigger::Invoked | CompletionTrigger::Quote
+            )
+        {
+            continue;
+        }
+
+        // Cursor is in the first argument, which is an identifier rather than a class name string
+        let typed = group_str[..local_cursor_offset].trim_start();
+        if trigger == CompletionTrigger::Invoked
+            && typed
+                .chars()
+                .all(|c| c.is_alphanumeric() || c == '_' || c == '.')
+        {
+            diagnostics.extend(get_first_argument_items(
+                doc,
+                &analysis.symbols,
+                uri,
+                cursor_byte_offset,
+                typed,
+                api_manager,
+                components,
+                element_constants,
+            ));
+            break;
+        }
+
         if let Some((curr_context, _start, _end)) =
-            is_cursor_in_context(local_cursor_offset, &group_str, &FIND_QUOTES)
+            is_cursor_in_context(local_cursor_offset, group_str, &FIND_QUOTES)
         {
             diagnostics.extend(get_instance_names(curr_context.as_ref(), api_manager));
             break;
@@ -428,43 +2346,750 @@ fn get_completion_items(
     diagnostics
 }
 
+// Element snippet when cursor is at the key of a new entry in a children table(Third argument onwards)
+// args are group's top level arguments, group starting at byte offset start right after the callee's paren
+fn get_child_element_item(
+    doc: &str,
+    group_str: &str,
+    args: &[(usize, usize)],
+    start: usize,
+    cursor_byte_offset: usize,
+) -> Option<CompletionItem> {
+    let cursor = cursor_byte_offset - start;
+    let &(table_start, _) = args
+        .iter()
+        .skip(2)
+        .find(|&&(a, b)| group_str[a..].starts_with('{') && a < cursor && cursor <= b)?;
+    if cursor > find_matching_brace(group_str, table_start + 1) {
+        return None;
+    }
+    // Only a bare word may be typed so far, anything else means cursor is in an entry's value
+    let content = &group_str[table_start + 1..cursor];
+    let typed = match split_top_level(content, &[',', ';']).last() {
+        Some(&(a, b)) if b == content.len() => &content[a..],
+        _ => "",
+    };
+    if !typed.chars().all(|c| c.is_alphanumeric() || c == '_') {
+        return None;
+    }
+
+    let callee_end = start.checked_sub(1)?;
+    let callee_start = doc[..callee_end]
+        .trim_end_matches(|c: char| c.is_alphanumeric() || c == '_' || c == '.' || c == ':')
+        .len();
+    let callee = &doc[callee_start..callee_end];
+    let (range_start, range_end) = word_range_at(doc, cursor_byte_offset);
+    Some(CompletionItem {
+        label: "Child element".to_string(),
+        kind: Some(CompletionItemKind::SNIPPET),
+        detail: Some(format!("[\"Child\"] = {}(\"Frame\", {{}})", callee)),
+        sort_text: Some(format!("\x00{:05}", 0)),
+        insert_text_format: Some(InsertTextFormat::SNIPPET),
+        text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+            range: Range {
+                start: byte_offset_to_position(doc, range_start),
+                end: byte_offset_to_position(doc, range_end),
+            },
+            new_text: format!(
+                "[${{1:\"Child\"}}] = {}(\"${{2:Frame}}\", {{ $0 }}),",
+                callee
+            ),
+        })),
+        ..Default::default()
+    })
+}
+
+fn get_declared_locals(symbols: &SymbolTable) -> HashSet<String> {
+    symbols.declared_names().map(str::to_string).collect()
+}
+
+// luau-lsp already suggests locals, so showing ours too just doubles the entries(Ours are local
+// components in a first argument). true/false values stay, its keywords don't say which is the default
+fn duplicates_luau_lsp(item: &CompletionItem, locals: &HashSet<String>) -> bool {
+    item.kind == Some(CompletionItemKind::FUNCTION) && locals.contains(&item.label)
+}
+
+fn apply_coexistence(symbols: &SymbolTable, items: Vec<CompletionItem>) -> Vec<CompletionItem> {
+    let locals = get_declared_locals(symbols);
+    items
+        .into_iter()
+        .filter(|item| !duplicates_luau_lsp(item, &locals))
+        .map(|mut item| {
+            match item.data.as_mut().and_then(Value::as_object_mut) {
+                Some(data) => {
+                    data.insert("source".to_string(), json!(ITEM_SOURCE));
+                }
+                None => item.data = Some(json!({ "source": ITEM_SOURCE })),
+            }
+            item
+        })
+        .collect()
+}
+
+fn serialized_size(item: &CompletionItem) -> usize {
+    serde_json::to_vec(item)
+        .map(|bytes| bytes.len())
+        .unwrap_or(0)
+        + 1 // +1 for separating comma
+}
+
+// Keeps response under max_bytes, first by dropping heavy fields and then tail items
+// Returns true if any items had to be dropped(So response should be marked incomplete)
+fn apply_size_budget(items: &mut Vec<CompletionItem>, max_bytes: usize) -> bool {
+    if max_bytes == 0 {
+        return false;
+    }
+
+    let total: usize = items.iter().map(serialized_size).sum();
+    if total <= max_bytes {
+        return false;
+    }
+
+    for item in items.iter_mut() {
+        item.documentation = None;
+        item.detail = None;
+    }
+
+    let mut used = 0;
+    let mut keep = 0;
+    for item in items.iter() {
+        used += serialized_size(item);
+        if used > max_bytes {
+            break;
+        }
+        keep += 1;
+    }
+
+    let trimmed = keep < items.len();
+    items.truncate(keep);
+    trimmed
+}
+
+// Keeps items matching word typed at cursor, capped at max_items(0 disables the cap)
+// Returns true if anything got dropped, so client asks again as user keeps typing
+fn apply_prefix_filter(items: &mut Vec<CompletionItem>, typed: &str, max_items: usize) -> bool {
+    let before = items.len();
+    if !typed.is_empty() {
+        items.retain(|item| is_subsequence(typed, &item.label));
+    }
+    if max_items > 0 && items.len() > max_items {
+        // Cap keeps what client would list first(Each kind of item numbers its sort_text from 0)
+        items.sort_by(|a, b| {
+            let key = |item: &CompletionItem| item.sort_text.clone().unwrap_or(item.label.clone());
+            key(a).cmp(&key(b))
+        });
+        items.truncate(max_items);
+    }
+    items.len() < before
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn generate_auto_completions(
     doc: &str,
+    analysis: &mut DocumentAnalysis,
+    uri: &Url,
     cursor: &Position,
     api_manager: &ApiManager,
+    components: &ComponentIndex,
+    settings: &Settings,
+    trigger: CompletionTrigger,
+    cancel: &CancellationToken,
 ) -> Result<CompletionResponse, Box<dyn std::error::Error>> {
-    Ok(CompletionResponse::Array(get_completion_items(
+    let mut items = get_completion_items(
         doc,
+        analysis,
+        uri,
         cursor,
         api_manager,
-    )))
+        components,
+        &settings.completion.element_constants,
+        trigger,
+        cancel,
+    );
+    // Superseded by a newer request, whatever got collected is stale already
+    if cancel.is_cancelled() {
+        return Err("Completion request cancelled".into());
+    }
+    if settings.completion.coexist_with_luau_lsp {
+        items = apply_coexistence(&analysis.symbols, items);
+    }
+
+    let typed = position_to_byte_offset(doc, cursor)
+        .map(|offset| &doc[word_range_at(doc, offset).0..offset])
+        .unwrap_or("");
+    let filtered = apply_prefix_filter(&mut items, typed, settings.completion.max_items);
+    let over_budget = apply_size_budget(&mut items, settings.completion.max_response_bytes);
+    // Classes show up once the API finishes loading, so client has to ask again then
+    if filtered || over_budget || !api_manager.is_loaded() {
+        return Ok(CompletionResponse::List(CompletionList {
+            is_incomplete: true,
+            items,
+        }));
+    }
+
+    Ok(CompletionResponse::Array(items))
 }
 
 #[cfg(test)]
 mod tests {
+    use serde_json::{json, Value};
+    use tower_lsp::lsp_types::{
+        CompletionItem, CompletionItemKind, CompletionResponse, CompletionTextEdit,
+        DiagnosticSeverity, Documentation, HoverContents, InsertTextFormat, NumberOrString,
+        Position, Url,
+    };
+
+    use crate::api_manager::ApiManager;
+    use crate::api_parser::parse_data_types;
+    use crate::component_index::ComponentIndex;
     use crate::file_diagnoser::{
+        apply_prefix_filter, apply_size_budget, byte_offset_to_position, enum_chain_at,
         extract_name_from_span, find_matching_brace, find_matching_bracket, find_matching_paren,
-        get_create_element_macros, get_react_var_name,
+        generate_auto_completions, get_completion_items, get_create_element_macros, get_debug_dump,
+        get_document_diagnostics, get_document_diagnostics_cancellable, get_element_regions,
+        get_hover, require_argument_start, resolve_completion_item, split_top_level,
+        CompletionTrigger, DocumentAnalysis, RegionIndex, TextSplice, ITEM_SOURCE,
+        RESTRICTED_PROPERTY_CODE, SPECIAL_PROP_DOCS,
     };
+    use crate::settings::Settings;
+    use crate::sourcemap::Sourcemap;
+    use crate::symbol_table::{RequireContext, SymbolTable};
+    use std::collections::HashMap;
+    use std::sync::Arc;
+    use std::time::{Duration, Instant};
+    use std::{env, fs};
+    use tokio_util::sync::CancellationToken;
+
+    // Completions against a fresh analysis, like the first request after an edit
+    fn completion_items(
+        doc: &str,
+        uri: &Url,
+        cursor: &Position,
+        api_manager: &ApiManager,
+        components: &ComponentIndex,
+        element_constants: &[String],
+        trigger: CompletionTrigger,
+    ) -> Vec<CompletionItem> {
+        get_completion_items(
+            doc,
+            &mut DocumentAnalysis::new(doc, &Default::default()),
+            uri,
+            cursor,
+            api_manager,
+            components,
+            element_constants,
+            trigger,
+            &CancellationToken::new(),
+        )
+    }
+
+    #[test]
+    fn test_document_analysis_reuse() {
+        let api_manager = ApiManager::from_local_dump();
+        let uri = Url::parse("file:///App.luau").unwrap();
+        let doc = r#"local React = require(game.Packages.React)
+local e = React.createElement
+return e("Frame", { Vis })
+"#;
+        let cursor = byte_offset_to_position(doc, doc.find("Vis").unwrap() + 3);
+        let mut analysis = DocumentAnalysis::new(doc, &Default::default());
+        let complete = |analysis: &mut DocumentAnalysis| {
+            get_completion_items(
+                doc,
+                analysis,
+                &uri,
+                &cursor,
+                &api_manager,
+                &ComponentIndex::default(),
+                &[],
+                CompletionTrigger::Invoked,
+                &CancellationToken::new(),
+            )
+        };
+        let first = complete(&mut analysis);
+        assert!(first.iter().any(|item| item.label == "Visible"));
+        // Scans are kept for the next request, which gives the same items
+        assert_eq!(analysis.has_react, Some(true));
+        assert_eq!(analysis.regions.as_ref().unwrap().regions().len(), 1);
+        let second = complete(&mut analysis);
+        assert_eq!(
+            first.iter().map(|item| &item.label).collect::<Vec<_>>(),
+            second.iter().map(|item| &item.label).collect::<Vec<_>>()
+        );
+    }
+
+    // Generated UI module about lines long, components in a row then one big tree
+    fn generated_module(lines: usize) -> String {
+        let mut doc = String::from(
+            "local React = require(game.Packages.React)\nlocal e = React.createElement\n\n",
+        );
+        for i in 0..lines / 16 {
+            doc.push_str(&format!(
+                "local function Row{i}(props)\n\treturn e(\"TextLabel\", {{\n\t\tText = \"row {i}\",\n\t\tLayoutOrder = {i},\n\t}})\nend\n\n"
+            ));
+        }
+        doc.push_str("return e(\"Frame\", {}, {\n");
+        for i in 0..lines / 16 {
+            doc.push_str(&format!(
+                "\tItem{i} = e(\"TextButton\", {{\n\t\tText = \"-- {i}) (\",\n\t\tSize = UDim2.new(1, 0, 0, 20),\n\t}}, {{ Row = e(Row{i}) }}),\n"
+            ));
+        }
+        doc.push_str("\tLast = e(\"ImageLabel\", { Vis }, { e(\"\") }),\n})\n");
+        doc
+    }
+
+    #[test]
+    fn test_windowed_completions() {
+        let api_manager = ApiManager::from_local_dump();
+        let uri = Url::parse("file:///App.luau").unwrap();
+        for lines in [5000, 20000] {
+            let doc = generated_module(lines);
+            let mut windowed = DocumentAnalysis::new(&doc, &Default::default());
+            assert!(windowed.regions.is_none());
+            let mut indexed = DocumentAnalysis::new(&doc, &Default::default());
+            indexed.regions = Some(RegionIndex::build(&doc, &indexed.symbols));
+
+            let row = doc.find(&format!("row {}", lines / 32)).unwrap();
+            let cursors = [
+                doc.find("Vis").unwrap() + 3,
+                doc.rfind("e(\"\")").unwrap() + 3,
+                doc.rfind("Size =").unwrap(),
+                row + doc[row..].find("LayoutOrder").unwrap() + 6,
+                doc[..row].rfind("Text").unwrap(),
+            ];
+            for offset in cursors {
+                let cursor = byte_offset_to_position(&doc, offset);
+                let complete = |analysis: &mut DocumentAnalysis| {
+                    get_completion_items(
+                        &doc,
+                        analysis,
+                        &uri,
+                        &cursor,
+                        &api_manager,
+                        &ComponentIndex::default(),
+                        &[],
+                        CompletionTrigger::Invoked,
+                        &CancellationToken::new(),
+                    )
+                };
+                // Only the statement around cursor gets scanned, so even debug builds answer well within this
+                let started = Instant::now();
+                let items = complete(&mut windowed);
+                let elapsed = started.elapsed();
+                assert!(
+                    elapsed < Duration::from_millis(500),
+                    "{lines} lines at {offset} took {elapsed:?}"
+                );
+                assert!(!items.is_empty(), "{lines} lines at {offset}");
+                assert_eq!(items, complete(&mut indexed), "{lines} lines at {offset}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_cancelled_requests() {
+        let api_manager = ApiManager::from_local_dump();
+        let uri = Url::parse("file:///App.luau").unwrap();
+        let doc = r#"local React = require(game.Packages.React)
+local e = React.createElement
+return e("Frame", { Foo = 1, Vis })
+"#;
+        let cursor = byte_offset_to_position(doc, doc.find("Vis").unwrap() + 3);
+        let cancel = CancellationToken::new();
+        let complete = |cancel: &CancellationToken| {
+            generate_auto_completions(
+                doc,
+                &mut DocumentAnalysis::new(doc, &Default::default()),
+                &uri,
+                &cursor,
+                &api_manager,
+                &ComponentIndex::default(),
+                &Settings::default(),
+                CompletionTrigger::Invoked,
+                cancel,
+            )
+        };
+        assert!(complete(&cancel).is_ok());
+        assert_eq!(
+            get_document_diagnostics_cancellable(doc, &Default::default(), &api_manager, &cancel)
+                .map(|d| d.len()),
+            Some(1)
+        );
+
+        // Superseded requests stop instead of handing back partial results
+        cancel.cancel();
+        assert!(complete(&cancel).is_err());
+        assert!(get_document_diagnostics_cancellable(
+            doc,
+            &Default::default(),
+            &api_manager,
+            &cancel
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn test_completions_before_api_load() {
+        let uri = Url::parse("file:///App.luau").unwrap();
+        let doc = r#"local React = require(game.Packages.React)
+local e = React.createElement
+return e("Frame", {  })
+"#;
+        let cursor = byte_offset_to_position(doc, doc.find("{ ").unwrap() + 2);
+        let complete = |api_manager: &ApiManager| {
+            generate_auto_completions(
+                doc,
+                &mut DocumentAnalysis::new(doc, &Default::default()),
+                &uri,
+                &cursor,
+                api_manager,
+                &ComponentIndex::default(),
+                &Settings::default(),
+                CompletionTrigger::Invoked,
+                &CancellationToken::new(),
+            )
+            .unwrap()
+        };
+
+        // Whatever doesn't need the API still comes back, marked incomplete so it gets asked again
+        let CompletionResponse::List(list) = complete(&ApiManager::new()) else {
+            panic!("Completions without an API should be incomplete");
+        };
+        assert!(list.is_incomplete);
+        assert!(list.items.iter().all(|item| item.label != "Visible"));
+        assert!(matches!(
+            complete(&ApiManager::from_local_dump()),
+            CompletionResponse::Array(items) if items.iter().any(|item| item.label == "Visible")
+        ));
+    }
+
+    #[test]
+    fn test_max_items() {
+        let api_manager = ApiManager::from_local_dump();
+        let uri = Url::parse("file:///App.luau").unwrap();
+        let mut settings = Settings::default();
+        settings.completion.max_items = 3;
+        let complete = |doc: &str, after: &str| {
+            let cursor = byte_offset_to_position(doc, doc.find(after).unwrap() + after.len());
+            generate_auto_completions(
+                doc,
+                &mut DocumentAnalysis::new(doc, &Default::default()),
+                &uri,
+                &cursor,
+                &api_manager,
+                &ComponentIndex::default(),
+                &settings,
+                CompletionTrigger::Invoked,
+                &CancellationToken::new(),
+            )
+            .unwrap()
+        };
+
+        // Class names keep their best matches, properties their most used ones
+        let doc = "local React = require(game.Packages.React)\nreturn React.createElement(\"Fr\", {  })\n";
+        let CompletionResponse::List(classes) = complete(doc, "\"Fr") else {
+            panic!("Capped completions should be incomplete");
+        };
+        assert!(classes.is_incomplete);
+        assert_eq!(classes.items.len(), 3);
+        assert_eq!(classes.items[0].label, "Frame");
+        let CompletionResponse::List(properties) = complete(doc, "{ ") else {
+            panic!("Capped completions should be incomplete");
+        };
+        assert!(properties.is_incomplete);
+        assert_eq!(properties.items.len(), 3);
+        let uncapped = completion_items(
+            doc,
+            &uri,
+            &byte_offset_to_position(doc, doc.find("{ ").unwrap() + 2),
+            &api_manager,
+            &ComponentIndex::default(),
+            &[],
+            CompletionTrigger::Invoked,
+        );
+        let mut best = uncapped;
+        best.sort_by_key(|item| item.sort_text.clone().unwrap_or(item.label.clone()));
+        let labels = |items: &[CompletionItem]| -> Vec<String> {
+            items.iter().map(|item| item.label.clone()).collect()
+        };
+        assert_eq!(labels(&properties.items), labels(&best[..3]));
+    }
+
+    #[test]
+    fn test_region_index_updates() {
+        let mut doc = r#"local React = require(game.Packages.React)
+local e = React.createElement
+
+local function App()
+    return e("Frame", { Size = UDim2.new(1, 0, 1, 0) }, {
+        Label = e("TextLabel", { Text = "hi" }),
+    })
+end
+"#
+        .to_string();
+        let mut analysis = DocumentAnalysis::new(&doc, &Default::default());
+        // (Text to replace, replacement), each applied like a ranged didChange
+        let edits = [
+            ("Text = \"hi\"", "Text = \"hello\", TextSize = 14"),
+            ("local function App()", "local function App(props)"),
+            ("    })\nend", "    }), e(\"Frame\", {})\nend"),
+            ("e(\"TextLabel\"", "React.createElement(\"TextLabel\""),
+            ("return e(\"Frame\", ", "return e(\"Frame\" "),
+            ("e(\"Frame\" ", "e(\"Frame\", "),
+            ("\"hello\"", "\"hello -- not a comment\""),
+            ("local e = React.createElement\n", ""),
+            ("\nend\n", "\nend\nreturn App\n"),
+        ];
+        for (old, new) in edits {
+            let start = doc.find(old).unwrap();
+            doc.replace_range(start..start + old.len(), new);
+            let splice = TextSplice {
+                start,
+                removed_len: old.len(),
+                inserted_len: new.len(),
+            };
+            analysis.apply_edit(&doc, splice, old);
+            assert_eq!(
+                analysis.regions.as_ref().unwrap().regions(),
+                get_element_regions(&doc, &Default::default()),
+                "after replacing {:?}",
+                old
+            );
+        }
+    }
+
+    #[test]
+    fn test_children_completions() {
+        let api_manager = ApiManager::from_local_dump();
+        let uri = Url::parse("file:///App.luau").unwrap();
+        let complete = |doc: &str, after: &str| {
+            completion_items(
+                doc,
+                &uri,
+                &byte_offset_to_position(doc, doc.find(after).unwrap() + after.len()),
+                &api_manager,
+                &ComponentIndex::default(),
+                &[],
+                CompletionTrigger::Invoked,
+            )
+        };
+        let child_edit = |items: Vec<CompletionItem>| {
+            let item = items
+                .into_iter()
+                .find(|item| item.label == "Child element")?;
+            match item.text_edit {
+                Some(CompletionTextEdit::Edit(edit)) => Some(edit.new_text),
+                _ => None,
+            }
+        };
+
+        let doc = "local React = require(game.React)\nlocal e = React.createElement\nreturn e(\"Frame\", { Size = x }, {\n\tLabel = e(\"TextLabel\"),\n\tCh\n})\n";
+        assert_eq!(
+            child_edit(complete(doc, "\tCh")).as_deref(),
+            Some("[${1:\"Child\"}] = e(\"${2:Frame}\", { $0 }),")
+        );
+        // Props table and entry values aren't children
+        assert!(child_edit(complete(doc, "Size = x")).is_none());
+        assert!(child_edit(complete(doc, "Label = e")).is_none());
+
+        let doc = "local React = require(game.React)\nreturn React.createElement(React.Fragment, { k }, {\n\tA = React.createElement(\"Frame\", { Si }),\n\t\n})\n";
+        let fragment_props: Vec<String> = complete(doc, "{ k")
+            .into_iter()
+            .map(|item| item.label)
+            .collect();
+        assert_eq!(fragment_props, ["key", "children"]);
+        assert!(complete(doc, "{ Si")
+            .iter()
+            .any(|item| item.label == "Size"));
+        assert_eq!(
+            child_edit(complete(doc, "}),\n\t")).as_deref(),
+            Some("[${1:\"Child\"}] = React.createElement(\"${2:Frame}\", { $0 }),")
+        );
+    }
+
+    #[test]
+    fn test_imported_create_element_helpers() {
+        let api_manager = ApiManager::from_local_dump();
+        let uri = Url::parse("file:///App.luau").unwrap();
+        let doc =
+            "local el = require(script.Parent.TestCreateElement)\nreturn el(\"Frame\", { Si })\n";
+        let complete = |requires: &Arc<RequireContext>| {
+            get_completion_items(
+                doc,
+                &mut DocumentAnalysis::new(doc, requires),
+                &uri,
+                &byte_offset_to_position(doc, doc.find("Si }").unwrap() + 2),
+                &api_manager,
+                &ComponentIndex::default(),
+                &[],
+                CompletionTrigger::Invoked,
+                &CancellationToken::new(),
+            )
+        };
+
+        let mut requires = RequireContext::default();
+        assert!(get_element_regions(doc, &Arc::new(requires.clone())).is_empty());
+        assert!(complete(&Arc::new(requires.clone())).is_empty());
+
+        requires.set_create_element_modules(vec!["test*element".to_string()]);
+        let requires = Arc::new(requires);
+        assert_eq!(get_element_regions(doc, &requires).len(), 1);
+        assert!(complete(&requires).iter().any(|item| item.label == "Size"));
+    }
+
+    #[test]
+    fn test_roact_compat() {
+        let api_manager = ApiManager::from_local_dump();
+        let uri = Url::parse("file:///App.luau").unwrap();
+        let doc = "local Roact = require(game.Packages.Roact)\nreturn Roact.createElement(\"TextButton\", { Foo = 1, [Roact.Event.Act] })\n";
+        let complete = |requires: &Arc<RequireContext>| {
+            get_completion_items(
+                doc,
+                &mut DocumentAnalysis::new(doc, requires),
+                &uri,
+                &byte_offset_to_position(doc, doc.find("Act]").unwrap() + 3),
+                &api_manager,
+                &ComponentIndex::default(),
+                &[],
+                CompletionTrigger::Invoked,
+                &CancellationToken::new(),
+            )
+        };
+
+        let mut requires = RequireContext::default();
+        assert!(get_element_regions(doc, &Arc::new(requires.clone())).is_empty());
+        assert!(complete(&Arc::new(requires.clone())).is_empty());
+
+        requires.set_roact_compat(true);
+        let requires = Arc::new(requires);
+        assert_eq!(get_element_regions(doc, &requires).len(), 1);
+        let event = complete(&requires)
+            .into_iter()
+            .find(|item| item.label == "Activated")
+            .unwrap();
+        let Some(CompletionTextEdit::Edit(edit)) = event.text_edit else {
+            panic!("Expected text edit");
+        };
+        assert!(edit
+            .new_text
+            .starts_with("[Roact.Event.Activated] = function(rbx"));
+        assert!(get_document_diagnostics(doc, &requires, &api_manager)
+            .iter()
+            .any(|diagnostic| diagnostic.message.contains("Foo")));
+    }
+
+    #[test]
+    fn test_react_modules() {
+        let api_manager = ApiManager::from_local_dump();
+        let uri = Url::parse("file:///App.luau").unwrap();
+        let complete = |doc: &str, requires: &Arc<RequireContext>| {
+            get_completion_items(
+                doc,
+                &mut DocumentAnalysis::new(doc, requires),
+                &uri,
+                &byte_offset_to_position(doc, doc.find("Vis").unwrap() + 3),
+                &api_manager,
+                &ComponentIndex::default(),
+                &[],
+                CompletionTrigger::Invoked,
+                &CancellationToken::new(),
+            )
+        };
+        let doc_requiring = |path: &str| {
+            format!(
+                "local React = require({})\nreturn React.createElement(\"Frame\", {{ Vis }})\n",
+                path
+            )
+        };
+
+        // Paths going through GetService count like any other
+        let defaults = Arc::new(RequireContext::default());
+        let doc = doc_requiring("game:GetService(\"ReplicatedStorage\").Packages.React");
+        assert!(complete(&doc, &defaults)
+            .iter()
+            .any(|item| item.label == "Visible"));
+
+        let renamed = doc_requiring("game.ReplicatedStorage.Packages.React17");
+        let vendored = doc_requiring("\"@Vendor/ReactLua\"");
+        let elsewhere = doc_requiring("game.ReplicatedStorage.Other.React17");
+        assert!(complete(&renamed, &defaults).is_empty());
+        assert!(complete(&vendored, &defaults).is_empty());
+
+        let mut requires = RequireContext::default();
+        let warnings = requires.set_react_modules(&[
+            "React".to_string(),
+            "Packages.React*".to_string(),
+            "/Vendor\\.ReactLua$/".to_string(),
+            "/(/".to_string(),
+        ]);
+        assert_eq!(warnings.len(), 1);
+        let requires = Arc::new(requires);
+        assert!(complete(&renamed, &requires)
+            .iter()
+            .any(|item| item.label == "Visible"));
+        assert!(complete(&vendored, &requires)
+            .iter()
+            .any(|item| item.label == "Visible"));
+        assert!(complete(&elsewhere, &requires).is_empty());
+        assert!(complete(&doc, &requires)
+            .iter()
+            .any(|item| item.label == "Visible"));
+    }
 
     #[test]
     fn test_react_variable_name_search() {
+        let name = |doc: &str| {
+            SymbolTable::build(doc, &Default::default()).react_var_name_at(doc, doc.len())
+        };
         assert_eq!(
-            get_react_var_name(r#"local Test = require(Somewhere.Somehow.Sometime.React);"#),
+            name(r#"local Test = require(Somewhere.Somehow.Sometime.React);"#),
             Some("Test".to_string())
         );
         assert_eq!(
-            get_react_var_name(r#"local Test = require(Somewhere.Somehow.Sometime.React)"#),
+            name(r#"local Test = require(Somewhere.Somehow.Sometime.React)"#),
             Some("Test".to_string())
         );
         assert_eq!(
-            get_react_var_name(r#"local _Best123 = require(Somewhere.Somehow.Sometime.React);"#),
+            name(r#"local _Best123 = require(Somewhere.Somehow.Sometime.React);"#),
             Some("_Best123".to_string())
         );
         assert_eq!(
-            get_react_var_name(r#"local P = require(Test.React)"#),
+            name(r#"local P = require(Test.React)"#),
+            Some("P".to_string())
+        );
+        assert_eq!(
+            name(r#"local P = require("@Packages/React")"#),
             Some("P".to_string())
         );
+        assert_eq!(
+            name(r#"local P = require('../Packages/React/init.luau')"#),
+            Some("P".to_string())
+        );
+        assert_eq!(name(r#"local P = require("@Packages/ReactRoblox")"#), None);
+
+        // Nested blocks can require it under another name, or shadow it
+        let doc = "local React = require(game.React)\nlocal a\ndo\n\tlocal R = require(game.React)\n\tlocal b\nend\ndo\n\tlocal React = {}\n\tlocal c\nend\nlocal d\n";
+        let symbols = SymbolTable::build(doc, &Default::default());
+        let at = |after: &str| symbols.react_var_name_at(doc, doc.find(after).unwrap());
+        assert_eq!(at("local a"), Some("React".to_string()));
+        assert_eq!(at("local b"), Some("R".to_string()));
+        assert_eq!(at("local c"), None);
+        assert_eq!(at("local d"), Some("React".to_string()));
+
+        let api_manager = ApiManager::from_local_dump();
+        let uri = Url::parse("file:///App.luau").unwrap();
+        let doc = "local React = require(game.React)\nlocal function App()\n\tlocal R = require(game.Packages.React)\n\treturn R.createElement(\"Frame\", { Si })\nend\n";
+        let items = completion_items(
+            doc,
+            &uri,
+            &byte_offset_to_position(doc, doc.find("Si }").unwrap() + 2),
+            &api_manager,
+            &ComponentIndex::default(),
+            &[],
+            CompletionTrigger::Invoked,
+        );
+        assert!(items.iter().any(|item| item.label == "Size"));
+        assert_eq!(get_element_regions(doc, &Default::default()).len(), 1);
     }
 
     #[test]
@@ -535,6 +3160,12 @@ mod tests {
 
         let text = "{unclosed";
         assert_eq!(find_matching_brace(text, 1), text.len());
+
+        // Braces in strings and comments don't count
+        let text = "{Text = \"}\", -- }\nPlaceholderText = [[}]], --[==[ } ]==] x}";
+        assert_eq!(find_matching_brace(text, 1), text.len() - 1);
+        let text = "{Text = 'it\\'s }'}";
+        assert_eq!(find_matching_brace(text, 1), text.len() - 1);
     }
 
     #[test]
@@ -555,6 +3186,77 @@ mod tests {
         assert_eq!(find_matching_bracket(text, 1), text.len());
     }
 
+    #[test]
+    fn test_literals_ignored() {
+        let api_manager = ApiManager::from_local_dump();
+        let uri = Url::parse("file:///App.luau").unwrap();
+        let doc = "local React = require(game.React)\n-- React.createElement(\"TextLabel\", {\nreturn React.createElement(\"Frame\", { Name = \"{ [\", -- } )\n\tSi })\n";
+        let items = completion_items(
+            doc,
+            &uri,
+            &byte_offset_to_position(doc, doc.find("Si }").unwrap() + 2),
+            &api_manager,
+            &ComponentIndex::default(),
+            &[],
+            CompletionTrigger::Invoked,
+        );
+        assert!(items.iter().any(|item| item.label == "Size"));
+        assert!(!items.iter().any(|item| item.label == "Text"));
+
+        // Indexing in a value isn't a [React.Event.X] key
+        let doc = "local React = require(game.React)\nreturn React.createElement(\"TextButton\", { Text = labels[] })\n";
+        let items = completion_items(
+            doc,
+            &uri,
+            &byte_offset_to_position(doc, doc.find("[]").unwrap() + 1),
+            &api_manager,
+            &ComponentIndex::default(),
+            &[],
+            CompletionTrigger::Invoked,
+        );
+        assert!(!items.iter().any(|item| item.label == "Activated"));
+    }
+
+    #[test]
+    fn test_debug_dump() {
+        let doc = r#"local React = require(game.ReplicatedStorage.React)
+local e = React.createElement
+return e("Frame", { Size = UDim2.new(), [React.Event.Activated] = nil }, {
+    Label = e(Label, {}),
+})"#;
+        let mut api_manager = ApiManager::from_local_dump();
+        api_manager.update_freq(doc);
+        let label_start = doc.find("e(Label").unwrap();
+        let cursor = byte_offset_to_position(doc, label_start + 3);
+        let dump = get_debug_dump(
+            doc,
+            &SymbolTable::build(doc, &Default::default()),
+            Some(&cursor),
+            &api_manager,
+        );
+
+        assert_eq!(dump["reactVariables"], json!(["React"]));
+        assert_eq!(dump["macros"], json!(["e"]));
+        let regions = dump["regions"].as_array().unwrap();
+        assert_eq!(regions.len(), 2);
+        assert_eq!(regions[0]["callee"], "e");
+        assert_eq!(regions[0]["resolvedClass"], "Frame");
+        assert_eq!(regions[0]["entries"][0]["name"], "Size");
+        assert_eq!(
+            regions[0]["entries"][1]["computed"],
+            "React.Event.Activated"
+        );
+        assert_eq!(regions[0]["range"][0], doc.find("e(\"Frame").unwrap());
+        // Components aren't API classes
+        assert_eq!(regions[1]["element"], "Label");
+        assert!(regions[1]["resolvedClass"].is_null());
+        assert_eq!(dump["cursor"]["regionIndex"], 1);
+        assert!(dump["topFrequencies"]
+            .as_array()
+            .unwrap()
+            .contains(&json!({ "name": "Frame", "count": 1 })));
+    }
+
     #[test]
     fn test_create_element_macros() {
         let doc = r#"
@@ -566,18 +3268,33 @@ local frame = e("Frame", {})
 local label = create("TextLabel", {})
 "#;
 
-        let macros = get_create_element_macros(doc, doc.len(), "React");
+        let macros = get_create_element_macros(
+            doc,
+            &SymbolTable::build(doc, &Default::default()),
+            doc.len(),
+            "React",
+        );
         assert!(macros.contains(&"e".to_string()));
         assert!(macros.contains(&"create".to_string()));
         assert_eq!(macros.len(), 2);
 
         let before_create = doc.find("local create").unwrap();
-        let macros_partial = get_create_element_macros(doc, before_create, "React");
+        let macros_partial = get_create_element_macros(
+            doc,
+            &SymbolTable::build(doc, &Default::default()),
+            before_create,
+            "React",
+        );
         assert!(macros_partial.contains(&"e".to_string()));
         assert!(!macros_partial.contains(&"create".to_string()));
         assert_eq!(macros_partial.len(), 1);
 
-        let macros_wrong = get_create_element_macros(doc, doc.len(), "WrongName");
+        let macros_wrong = get_create_element_macros(
+            doc,
+            &SymbolTable::build(doc, &Default::default()),
+            doc.len(),
+            "WrongName",
+        );
         assert_eq!(macros_wrong.len(), 0);
     }
 
@@ -587,21 +3304,1236 @@ local label = create("TextLabel", {})
 local React = require(game.React)
 e = React.createElement
 "#;
-        let macros1 = get_create_element_macros(doc1, doc1.len(), "React");
+        let macros1 = get_create_element_macros(
+            doc1,
+            &SymbolTable::build(doc1, &Default::default()),
+            doc1.len(),
+            "React",
+        );
         assert!(macros1.contains(&"e".to_string()));
 
         let doc2 = r#"
 local MyReact = require(game.React)
 local create = MyReact.createElement
 "#;
-        let macros2 = get_create_element_macros(doc2, doc2.len(), "MyReact");
+        let macros2 = get_create_element_macros(
+            doc2,
+            &SymbolTable::build(doc2, &Default::default()),
+            doc2.len(),
+            "MyReact",
+        );
         assert!(macros2.contains(&"create".to_string()));
 
         let doc3 = r#"
 local React = require(game.React)
 local x = something.else
 "#;
-        let macros3 = get_create_element_macros(doc3, doc3.len(), "React");
+        let macros3 = get_create_element_macros(
+            doc3,
+            &SymbolTable::build(doc3, &Default::default()),
+            doc3.len(),
+            "React",
+        );
         assert_eq!(macros3.len(), 0);
     }
+
+    #[test]
+    fn test_coexistence_filtering() {
+        let api_manager = ApiManager::from_local_dump();
+        let uri = Url::parse("file:///App.luau").unwrap();
+        let doc = r#"local React = require(game.React)
+local e = React.createElement
+local function Button(props)
+    return e("TextButton", { Visible =  })
+end
+return e(Bu)
+"#;
+        let complete = |after: &str, coexist: bool| {
+            let mut settings = Settings::default();
+            settings.completion.coexist_with_luau_lsp = coexist;
+            let cursor = byte_offset_to_position(doc, doc.find(after).unwrap() + after.len());
+            let response = generate_auto_completions(
+                doc,
+                &mut DocumentAnalysis::new(doc, &Default::default()),
+                &uri,
+                &cursor,
+                &api_manager,
+                &ComponentIndex::default(),
+                &settings,
+                CompletionTrigger::Invoked,
+                &CancellationToken::new(),
+            )
+            .unwrap();
+            match response {
+                CompletionResponse::Array(items) => items,
+                CompletionResponse::List(list) => list.items,
+            }
+        };
+        let has = |items: &[CompletionItem], label: &str| items.iter().any(|i| i.label == label);
+
+        // luau-lsp offers the local component itself
+        let first_argument = complete("e(Bu", false);
+        assert!(has(&first_argument, "Button"));
+        let values = complete("Visible = ", false);
+        assert!(has(&values, "true") && has(&values, "false"));
+
+        let first_argument = complete("e(Bu", true);
+        assert!(!has(&first_argument, "Button"));
+        assert!(has(&first_argument, "TextButton"));
+        assert!(first_argument
+            .iter()
+            .all(|i| i.data.as_ref().unwrap()["source"] == ITEM_SOURCE));
+
+        // Bool values are kept with their default marked, non default one first
+        let values = complete("Visible = ", true);
+        let labels: Vec<&str> = values.iter().map(|i| i.label.as_str()).collect();
+        assert_eq!(labels, ["false", "true"]);
+        assert_eq!(values[1].detail.as_deref(), Some("bool(Default)"));
+        assert!(values
+            .iter()
+            .all(|i| i.data.as_ref().unwrap()["source"] == ITEM_SOURCE));
+    }
+
+    #[test]
+    fn test_split_top_level() {
+        let text = r#"Text = "a, b", Size = f(1, 2), -- c, d
+    [React.Event.Activated] = g, "#;
+        let pieces: Vec<&str> = split_top_level(text, &[','])
+            .into_iter()
+            .map(|(a, b)| &text[a..b])
+            .collect();
+        assert_eq!(
+            pieces,
+            vec![
+                r#"Text = "a, b""#,
+                "Size = f(1, 2)",
+                "-- c, d\n    [React.Event.Activated] = g"
+            ]
+        );
+
+        // Block comments and long strings can span lines and hold separators too
+        let text = "Text = [[a, {b]], --[==[ c,\n d ]==] Size = 1, Name = 'x'";
+        let pieces: Vec<&str> = split_top_level(text, &[','])
+            .into_iter()
+            .map(|(a, b)| &text[a..b])
+            .collect();
+        assert_eq!(
+            pieces,
+            vec![
+                "Text = [[a, {b]]",
+                "--[==[ c,\n d ]==] Size = 1",
+                "Name = 'x'"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unknown_property_diagnostics() {
+        let api_manager = ApiManager::from_local_dump();
+        let doc = r#"local React = require(game.React)
+local e = React.createElement
+return e("TextLabel", {
+    Text = "Hi",
+    Txet = "Typo",
+    key = "Special",
+    [React.Event.Activated] = function() end,
+}, {
+    Child = e("Frame", { Colour = 1 }),
+    Other = e("NotAClass", { Anything = 1 }),
+})
+"#;
+        let diagnostics = get_document_diagnostics(doc, &Default::default(), &api_manager);
+        assert!(diagnostics.iter().all(|d| d
+            .code_description
+            .as_ref()
+            .is_some_and(|c| c.href.as_str() == "rblx-react-lsp://rules/unknown-property")));
+        let messages: Vec<String> = diagnostics.into_iter().map(|d| d.message).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "Unknown property 'Txet' on TextLabel",
+                "Unknown property 'Colour' on Frame"
+            ]
+        );
+
+        // Comments before an entry don't hide its key
+        let doc = r#"local React = require(game.React)
+return React.createElement("TextLabel", {
+    -- note
+    Txet = "hi",
+    --[[ block
+    ]] Colour = 1,
+})
+"#;
+        let diagnostics = get_document_diagnostics(doc, &Default::default(), &api_manager);
+        let messages: Vec<&str> = diagnostics.iter().map(|d| d.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "Unknown property 'Txet' on TextLabel",
+                "Unknown property 'Colour' on TextLabel"
+            ]
+        );
+        let key_start = doc.find("Txet").unwrap();
+        assert_eq!(
+            diagnostics[0].range.start,
+            byte_offset_to_position(doc, key_start)
+        );
+    }
+
+    #[test]
+    fn test_restricted_property_diagnostics() {
+        let api_manager = ApiManager::from_local_dump();
+        assert!(api_manager
+            .lookup_properties("Frame")
+            .unwrap()
+            .iter()
+            .all(|(name, _)| name != "RobloxLocked"));
+
+        let doc = r#"local React = require(game.React)
+return React.createElement("Frame", { RobloxLocked = true, Visible = true })
+"#;
+        let diagnostics = get_document_diagnostics(doc, &Default::default(), &api_manager);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].code,
+            Some(NumberOrString::String(RESTRICTED_PROPERTY_CODE.to_string()))
+        );
+        assert_eq!(
+            diagnostics[0].message,
+            "'RobloxLocked' on Frame needs PluginSecurity to be set, scripts can't set it"
+        );
+    }
+
+    #[test]
+    fn test_diagnostic_levels() {
+        let api_manager = ApiManager::from_local_dump();
+        let doc = r#"local React = require(game.React)
+return React.createElement("Frame", { RobloxLocked = true, Colour = 1 })
+"#;
+        let severities = |settings: &Value| -> Vec<Option<DiagnosticSeverity>> {
+            Settings::from_value(settings)
+                .diagnostics
+                .apply(get_document_diagnostics(
+                    doc,
+                    &Default::default(),
+                    &api_manager,
+                ))
+                .into_iter()
+                .map(|d| d.severity)
+                .collect()
+        };
+
+        assert_eq!(
+            severities(&json!({})),
+            vec![
+                Some(DiagnosticSeverity::WARNING),
+                Some(DiagnosticSeverity::WARNING)
+            ]
+        );
+        assert_eq!(
+            severities(&json!({ "diagnostics": { "unknownProperty": "error" } })),
+            vec![
+                Some(DiagnosticSeverity::WARNING),
+                Some(DiagnosticSeverity::ERROR)
+            ]
+        );
+        assert_eq!(
+            severities(&json!({
+                "diagnostics": { "unknownProperty": "off", "restrictedProperty": "hint" }
+            })),
+            vec![Some(DiagnosticSeverity::HINT)]
+        );
+    }
+
+    #[test]
+    fn test_size_budget() {
+        let items: Vec<CompletionItem> = (0..10)
+            .map(|i| CompletionItem {
+                label: format!("Property{}", i),
+                detail: Some("A fairly long detail string that eats into the budget".to_string()),
+                ..Default::default()
+            })
+            .collect();
+
+        let mut untouched = items.clone();
+        assert!(!apply_size_budget(&mut untouched, 0));
+        assert!(!apply_size_budget(&mut untouched, 100_000));
+        assert!(untouched.iter().all(|i| i.detail.is_some()));
+
+        // Fits once details are gone
+        let mut stripped = items.clone();
+        assert!(!apply_size_budget(&mut stripped, 400));
+        assert_eq!(stripped.len(), 10);
+        assert!(stripped.iter().all(|i| i.detail.is_none()));
+
+        // Still too big, so tail gets dropped
+        let mut truncated = items;
+        assert!(apply_size_budget(&mut truncated, 100));
+        assert!(!truncated.is_empty() && truncated.len() < 10);
+        assert_eq!(truncated[0].label, "Property0");
+    }
+
+    #[test]
+    fn test_excluded_properties() {
+        let mut api_manager = ApiManager::from_local_dump();
+        let has = |api_manager: &ApiManager, class: &str, prop: &str| {
+            api_manager
+                .lookup_properties(class)
+                .unwrap()
+                .iter()
+                .any(|(name, _)| name == prop)
+        };
+        assert!(has(&api_manager, "Frame", "Selectable"));
+
+        let excluded = [("GuiObject".to_string(), vec!["Selectable".to_string()])];
+        api_manager.set_excluded_properties(excluded.into_iter().collect());
+        assert!(!has(&api_manager, "Frame", "Selectable"));
+        assert!(has(&api_manager, "Frame", "Visible"));
+
+        // Hidden from completions, but still not an unknown property
+        let doc = r#"local React = require(game.React)
+return React.createElement("Frame", { Selectable = true })
+"#;
+        assert!(get_document_diagnostics(doc, &Default::default(), &api_manager).is_empty());
+        assert_eq!(
+            api_manager.api_info()["excludedProperties"]["GuiObject"][0],
+            "Selectable"
+        );
+    }
+
+    #[test]
+    fn test_completion_resolve() {
+        let api_manager = ApiManager::from_local_dump();
+        let doc =
+            "local React = require(game.React)\nreturn React.createElement(\"Frame\", { Si })\n";
+        let cursor = byte_offset_to_position(doc, doc.find("Si }").unwrap() + 2);
+        let uri = Url::parse("file:///App.luau").unwrap();
+        let items = completion_items(
+            doc,
+            &uri,
+            &cursor,
+            &api_manager,
+            &ComponentIndex::default(),
+            &[],
+            CompletionTrigger::Invoked,
+        );
+        let size = items.into_iter().find(|item| item.label == "Size").unwrap();
+        assert!(size.detail.is_none() && size.documentation.is_none());
+
+        let resolved = resolve_completion_item(size, &api_manager);
+        assert_eq!(resolved.detail.as_deref(), Some("UDim2"));
+        let Some(Documentation::MarkupContent(docs)) = resolved.documentation else {
+            panic!("Expected markdown documentation");
+        };
+        assert!(docs.value.contains("Inherited from `GuiObject`"));
+    }
+
+    #[test]
+    fn test_api_documentation() {
+        let mut api_manager = ApiManager::from_local_dump();
+        api_manager.set_docs(HashMap::from([
+            ("Frame".to_string(), "A plain rectangle.".to_string()),
+            ("GuiObject.Size".to_string(), "How big it is.".to_string()),
+            (
+                "GuiObject.InputBegan".to_string(),
+                "Input started.".to_string(),
+            ),
+        ]));
+        let resolve = |label: &str, member: &str| {
+            let item = CompletionItem {
+                label: label.to_string(),
+                data: Some(json!({ "class": "Frame", "member": member })),
+                ..Default::default()
+            };
+            match resolve_completion_item(item, &api_manager).documentation {
+                Some(Documentation::MarkupContent(docs)) => docs.value,
+                _ => panic!("Expected markdown documentation"),
+            }
+        };
+
+        // Looked up where the member is declared
+        assert!(resolve("Size", "property").contains("How big it is."));
+        assert!(resolve("Frame", "class").starts_with("A plain rectangle."));
+        assert!(!resolve("Position", "property").contains("How big it is."));
+
+        let doc = "local React = require(game.React)\nreturn React.createElement(\"Frame\", {\n\t[React.Event.InputBegan] = onInput,\n})\n";
+        let cursor = byte_offset_to_position(doc, doc.find("InputBegan").unwrap());
+        let Some(HoverContents::Markup(markup)) = get_hover(
+            doc,
+            &DocumentAnalysis::new(doc, &Default::default()),
+            &cursor,
+            &api_manager,
+        )
+        .map(|hover| hover.contents) else {
+            panic!("Expected markdown hover");
+        };
+        assert!(markup.value.contains("Input started."));
+    }
+
+    #[test]
+    fn test_data_type_members() {
+        let mut api_manager = ApiManager::from_local_dump();
+        let api_docs = r#"{
+            "@roblox/global/UDim2.fromScale": { "documentation": "Scale only.",
+                "params": [{ "name": "xScale" }, { "name": "yScale" }] },
+            "@roblox/global/Vector2.new": { "documentation": "Makes one.",
+                "params": [{ "name": "x" }, { "name": "y" }] },
+            "@roblox/global/Vector2.zero": { "documentation": "(0, 0)" }
+        }"#;
+        api_manager.set_data_types(parse_data_types(api_docs, &HashMap::new()).unwrap());
+        let uri = Url::parse("file:///App.luau").unwrap();
+        let doc = "local React = require(game.React)\nreturn React.createElement(\"Frame\", { AnchorPoint = , Size = , Position = UDim2.fromScale(1, 1) })\n";
+        let items = |after: &str| {
+            let cursor = byte_offset_to_position(doc, doc.find(after).unwrap() + after.len());
+            completion_items(
+                doc,
+                &uri,
+                &cursor,
+                &api_manager,
+                &ComponentIndex::default(),
+                &[],
+                CompletionTrigger::Invoked,
+            )
+        };
+
+        // Datatypes without handpicked snippets get theirs from the docs
+        let anchors = items("AnchorPoint = ");
+        let edits: Vec<&str> = anchors
+            .iter()
+            .map(|item| match &item.text_edit {
+                Some(CompletionTextEdit::Edit(edit)) => edit.new_text.as_str(),
+                _ => panic!("Expected constructor to insert its snippet"),
+            })
+            .collect();
+        assert_eq!(edits, ["Vector2.new(${1:x}, ${2:y})", "Vector2.zero"]);
+        // Handpicked ones keep their snippet and get the docs
+        let sizes = items("Size = ");
+        assert_eq!(sizes.len(), 3);
+        assert!(matches!(
+            &sizes[1].documentation,
+            Some(Documentation::MarkupContent(docs)) if docs.value == "Scale only."
+        ));
+
+        let cursor = byte_offset_to_position(doc, doc.find("fromScale").unwrap() + 2);
+        let Some(HoverContents::Markup(markup)) = get_hover(
+            doc,
+            &DocumentAnalysis::new(doc, &Default::default()),
+            &cursor,
+            &api_manager,
+        )
+        .map(|hover| hover.contents) else {
+            panic!("Expected markdown hover");
+        };
+        assert!(markup.value.contains("UDim2.fromScale(xScale, yScale)"));
+        assert!(markup.value.contains("Scale only."));
+    }
+
+    #[test]
+    fn test_event_hover() {
+        let api_manager = ApiManager::from_local_dump();
+        let doc = "local React = require(game.React)\nreturn React.createElement(\"TextButton\", {\n\t[React.Event.InputBegan] = onInput,\n\t[React.Change.Text] = onText,\n\tText = \"[x]\",\n})\n";
+        let hover = |at: &str| {
+            let cursor = byte_offset_to_position(doc, doc.find(at).unwrap() + 2);
+            get_hover(
+                doc,
+                &DocumentAnalysis::new(doc, &Default::default()),
+                &cursor,
+                &api_manager,
+            )
+            .map(|hover| match hover.contents {
+                HoverContents::Markup(markup) => (markup.value, hover.range.unwrap()),
+                _ => panic!("Expected markdown hover"),
+            })
+        };
+
+        let (docs, range) = hover("InputBegan").unwrap();
+        assert!(docs.contains("function(rbx, input: InputObject)"));
+        assert!(docs.contains("Inherited from `GuiObject`"));
+        assert_eq!(
+            range.start,
+            byte_offset_to_position(doc, doc.find("[React.Event").unwrap())
+        );
+        let (docs, _) = hover("Text]").unwrap();
+        assert!(docs.contains("`Text: string`"));
+        assert!(hover("x]").is_none());
+    }
+
+    #[test]
+    fn test_method_hover() {
+        let api_manager = ApiManager::from_local_dump();
+        let doc = r#"local React = require(game.React)
+return React.createElement("TextBox", {
+    [React.Event.Focused] = function(rbx)
+        rbx:ReleaseFocus()
+    end,
+    ref = function(box)
+        box:CaptureFocus()
+    end,
+    Text = function(other) other:CaptureFocus() end,
+})
+"#;
+        let hover = |at: &str| {
+            let cursor = byte_offset_to_position(doc, doc.find(at).unwrap() + 2);
+            get_hover(
+                doc,
+                &DocumentAnalysis::new(doc, &Default::default()),
+                &cursor,
+                &api_manager,
+            )
+            .map(|hover| match hover.contents {
+                HoverContents::Markup(markup) => markup.value,
+                _ => panic!("Expected markdown hover"),
+            })
+        };
+
+        let docs = hover("ReleaseFocus").unwrap();
+        assert!(docs.contains("ReleaseFocus: (submitted: bool) -> ()"));
+        assert!(hover("CaptureFocus")
+            .unwrap()
+            .contains("CaptureFocus: () -> ()"));
+        // Not a handler, so other's class is unknown
+        assert!(hover("CaptureFocus() end").is_none());
+    }
+
+    #[test]
+    fn test_component_prop_completions() {
+        let api_manager = ApiManager::from_local_dump();
+        let doc = r#"local React = require(game.React)
+local function Card(props)
+    return React.createElement("Frame", { Visible = props.visible, Size = props.size })
+end
+return React.createElement(Card, {  })
+"#;
+        let uri = Url::parse("file:///App.luau").unwrap();
+        let cursor = byte_offset_to_position(doc, doc.find("{  })").unwrap() + 2);
+        let items = completion_items(
+            doc,
+            &uri,
+            &cursor,
+            &api_manager,
+            &ComponentIndex::default(),
+            &[],
+            CompletionTrigger::Invoked,
+        );
+        let labels: Vec<&str> = items.iter().map(|item| item.label.as_str()).collect();
+        assert_eq!(
+            labels,
+            vec!["ref", "key", "children", "[React.Tag]", "size", "visible"]
+        );
+        assert_eq!(items[4].detail.as_deref(), Some("Card prop"));
+        let Some(CompletionTextEdit::Edit(edit)) = &items[3].text_edit else {
+            panic!("Expected text edit");
+        };
+        assert_eq!(edit.new_text, "[React.Tag] = $0,");
+
+        // Annotated props(Through an alias and intersection) come with their types
+        let doc = r#"local React = require(game.React)
+type Base = { LayoutOrder: number? }
+export type Props = Base & {
+    Text: string,
+    OnClick: () -> (),
+}
+local function Button(props: Props)
+    return React.createElement("TextButton", { Text = props.Text, Visible = props.visible })
+end
+return React.createElement(Button, {  })
+"#;
+        let cursor = byte_offset_to_position(doc, doc.find("{  })").unwrap() + 2);
+        let items = completion_items(
+            doc,
+            &uri,
+            &cursor,
+            &api_manager,
+            &ComponentIndex::default(),
+            &[],
+            CompletionTrigger::Invoked,
+        );
+        let detail = |label: &str| {
+            items
+                .iter()
+                .find(|item| item.label == label)
+                .and_then(|item| item.detail.clone())
+        };
+        assert_eq!(detail("LayoutOrder").as_deref(), Some("number?"));
+        assert_eq!(detail("OnClick").as_deref(), Some("() -> ()"));
+        assert_eq!(detail("Text").as_deref(), Some("string"));
+        assert_eq!(detail("visible").as_deref(), Some("Button prop"));
+    }
+
+    #[test]
+    fn test_snippet_edits() {
+        let api_manager = ApiManager::from_local_dump();
+        let uri = Url::parse("file:///App.luau").unwrap();
+        let complete = |doc: &str, after: &str, label: &str| {
+            let cursor = byte_offset_to_position(doc, doc.find(after).unwrap() + after.len());
+            completion_items(
+                doc,
+                &uri,
+                &cursor,
+                &api_manager,
+                &ComponentIndex::default(),
+                &[],
+                CompletionTrigger::Invoked,
+            )
+            .into_iter()
+            .find(|item| item.label == label)
+            .unwrap()
+        };
+
+        let doc =
+            "local React = require(game.React)\nreturn React.createElement(\"Frame\", { Si })\n";
+        let size = complete(doc, "Si", "Size");
+        let Some(CompletionTextEdit::Edit(edit)) = size.text_edit else {
+            panic!("Expected text edit");
+        };
+        assert_eq!(edit.new_text, "Size = $0,");
+        assert_eq!(
+            edit.range.start,
+            byte_offset_to_position(doc, doc.find("Si").unwrap())
+        );
+        assert_eq!(edit.range.end.character - edit.range.start.character, 2);
+
+        let doc = "local React = require(game.React)\nreturn React.createElement(\"Frame\", { [React.Event.Inp] })\n";
+        let event = complete(doc, "Inp", "InputBegan");
+        let Some(CompletionTextEdit::Edit(edit)) = event.text_edit else {
+            panic!("Expected text edit");
+        };
+        assert_eq!(
+            edit.new_text,
+            "[React.Event.InputBegan] = function(rbx${1:, input})\n$0\nend,"
+        );
+        assert_eq!(
+            event.filter_text.as_deref(),
+            Some("[React.Event.InputBegan")
+        );
+        assert_eq!(edit.range.end.character - edit.range.start.character, 17);
+
+        // Parameters come from the event's signature, rbx alone when it has none
+        let doc = "local React = require(game.React)\nreturn React.createElement(\"TextButton\", { [React.Event.Act] })\n";
+        let handler = |event: &str| match complete(doc, "Act", event).text_edit {
+            Some(CompletionTextEdit::Edit(edit)) => edit.new_text,
+            _ => panic!("Expected text edit"),
+        };
+        assert_eq!(
+            handler("Activated"),
+            "[React.Event.Activated] = function(rbx${1:, inputObject, clickCount})\n$0\nend,"
+        );
+        assert_eq!(
+            handler("MouseButton1Click"),
+            "[React.Event.MouseButton1Click] = function(rbx)\n$0\nend,"
+        );
+
+        // Renaming key of an existing entry keeps its value
+        let doc = "local React = require(game.React)\nreturn React.createElement(\"Frame\", { Si = 1 })\n";
+        assert!(complete(doc, "Si", "Size").text_edit.is_none());
+    }
+
+    #[test]
+    fn test_completion_triggers() {
+        let api_manager = ApiManager::from_local_dump();
+        let uri = Url::parse("file:///App.luau").unwrap();
+        let labels = |doc: &str, after: &str, trigger| -> Vec<String> {
+            let cursor = byte_offset_to_position(doc, doc.find(after).unwrap() + after.len());
+            completion_items(
+                doc,
+                &uri,
+                &cursor,
+                &api_manager,
+                &ComponentIndex::default(),
+                &[],
+                trigger,
+            )
+            .into_iter()
+            .map(|item| item.label)
+            .collect()
+        };
+
+        let doc = "local React = require(game.React)\nreturn React.createElement(\"Fr\", { Text = \"\" })\n";
+        assert!(labels(doc, "\"Fr", CompletionTrigger::Quote).is_empty());
+        assert!(labels(doc, "(\"", CompletionTrigger::Quote).contains(&"Frame".to_string()));
+        // Quote typed as a prop value isn't a class name
+        assert!(labels(doc, "Text = \"", CompletionTrigger::Quote).is_empty());
+
+        let doc = "local React = require(game.React)\nreturn React.createElement(\"Frame\", { [React.Event.] })\n";
+        assert!(labels(doc, "Event.", CompletionTrigger::Dot).contains(&"InputBegan".to_string()));
+        assert!(
+            labels(doc, "React.", CompletionTrigger::Dot).contains(&"createElement".to_string())
+        );
+
+        // Events show right away on typing the bracket
+        let doc =
+            "local React = require(game.React)\nreturn React.createElement(\"Frame\", { [] })\n";
+        assert!(labels(doc, "{ [", CompletionTrigger::Bracket).contains(&"InputBegan".to_string()));
+        assert!(labels(doc, "{ ", CompletionTrigger::Bracket).is_empty());
+    }
+
+    #[test]
+    fn test_react_api_completions() {
+        let api_manager = ApiManager::from_local_dump();
+        let uri = Url::parse("file:///App.luau").unwrap();
+        let items = |doc: &str, after: &str, trigger| -> Vec<CompletionItem> {
+            let cursor = byte_offset_to_position(doc, doc.find(after).unwrap() + after.len());
+            completion_items(
+                doc,
+                &uri,
+                &cursor,
+                &api_manager,
+                &ComponentIndex::default(),
+                &[],
+                trigger,
+            )
+        };
+
+        let doc = "local React = require(game.React)\nlocal count, setCount = React.\n";
+        let api = items(doc, "React.", CompletionTrigger::Dot);
+        let use_state = api.iter().find(|item| item.label == "useState").unwrap();
+        assert!(use_state
+            .detail
+            .as_ref()
+            .unwrap()
+            .starts_with("useState<S>("));
+        assert!(api.iter().any(|item| item.label == "Fragment"));
+
+        let doc = "local React = require(game.React)\nReact.useEff\nfoo.React.\n";
+        assert!(items(doc, "React.useEff", CompletionTrigger::Invoked)
+            .iter()
+            .any(|item| item.label == "useEffect"));
+        assert!(items(doc, "foo.React.", CompletionTrigger::Dot).is_empty());
+    }
+
+    #[test]
+    fn test_binding_completions() {
+        let api_manager = ApiManager::from_local_dump();
+        let uri = Url::parse("file:///App.luau").unwrap();
+        let items = |doc: &str, after: &str, trigger| -> Vec<CompletionItem> {
+            let cursor = byte_offset_to_position(doc, doc.find(after).unwrap() + after.len());
+            completion_items(
+                doc,
+                &uri,
+                &cursor,
+                &api_manager,
+                &ComponentIndex::default(),
+                &[],
+                trigger,
+            )
+        };
+
+        let doc = "local React = require(game.React)\nlocal b = React.\n";
+        let api = items(doc, "React.", CompletionTrigger::Dot);
+        let join = api
+            .iter()
+            .find(|item| item.label == "joinBindings")
+            .unwrap();
+        assert_eq!(
+            join.insert_text.as_deref(),
+            Some("joinBindings({ ${1:bindings} })")
+        );
+        assert_eq!(join.insert_text_format, Some(InsertTextFormat::SNIPPET));
+        let use_state = api.iter().find(|item| item.label == "useState").unwrap();
+        assert_eq!(use_state.insert_text, None);
+
+        let doc = "local React = require(game.React)\nlocal size, setSize = React.createBinding(0)\nlocal scaled = size:map(function(v) return v * 2 end)\nlocal other = {}\nsize:\nscaled:ma\nother:\nsetSize:\n";
+        let labels = |after: &str, trigger| -> Vec<String> {
+            items(doc, after, trigger)
+                .into_iter()
+                .map(|item| item.label)
+                .collect()
+        };
+        assert_eq!(labels("\nsize:", CompletionTrigger::Colon), ["map"]);
+        assert_eq!(labels("scaled:ma", CompletionTrigger::Invoked), ["map"]);
+        assert!(labels("other:", CompletionTrigger::Colon).is_empty());
+        assert!(labels("setSize:", CompletionTrigger::Colon).is_empty());
+    }
+
+    #[test]
+    fn test_enum_value_completions() {
+        let api_manager = ApiManager::from_local_dump();
+        let uri = Url::parse("file:///App.luau").unwrap();
+        let items = |doc: &str, after: &str, trigger| -> Vec<CompletionItem> {
+            let cursor = byte_offset_to_position(doc, doc.find(after).unwrap() + after.len());
+            completion_items(
+                doc,
+                &uri,
+                &cursor,
+                &api_manager,
+                &ComponentIndex::default(),
+                &[],
+                trigger,
+            )
+        };
+
+        let doc = "local React = require(game.React)\nReact.createElement(\"Frame\", { Size = UDim2.new(), AutomaticSize = Enum.Au })\n";
+        let sizes = items(doc, "Enum.Au", CompletionTrigger::Invoked);
+        let labels: Vec<&str> = sizes.iter().map(|item| item.label.as_str()).collect();
+        assert_eq!(labels[0], "Enum.AutomaticSize.None");
+        assert!(labels.contains(&"Enum.AutomaticSize.XY"));
+        let value_start = doc.find("Enum.Au").unwrap();
+        match &sizes[0].text_edit {
+            Some(CompletionTextEdit::Edit(edit)) => {
+                assert_eq!(edit.range.start, byte_offset_to_position(doc, value_start));
+                assert_eq!(
+                    edit.range.end,
+                    byte_offset_to_position(doc, value_start + 7)
+                );
+                assert_eq!(edit.new_text, "Enum.AutomaticSize.None");
+            }
+            _ => panic!("Expected enum item to replace typed value"),
+        }
+
+        let doc = "local React = require(game.React)\nReact.createElement(\"TextLabel\", {\n\tFont = Enum.\n})\n";
+        let fonts = items(doc, "Enum.", CompletionTrigger::Dot);
+        assert!(fonts
+            .iter()
+            .any(|item| item.label == "Enum.Font.GothamBold"));
+
+        // Types without known values and values that went past a name get nothing
+        let doc = "local React = require(game.React)\nReact.createElement(\"Frame\", { Name = U, Visible = f(x })\n";
+        assert!(items(doc, "Name = U", CompletionTrigger::Invoked).is_empty());
+        assert!(items(doc, "f(x", CompletionTrigger::Invoked).is_empty());
+    }
+
+    #[test]
+    fn test_enum_chain_completions() {
+        assert_eq!(enum_chain_at("x = Enum."), Some((4, None)));
+        assert_eq!(enum_chain_at("f(Enum.Font.Go"), Some((2, Some("Font"))));
+        assert_eq!(enum_chain_at("x = foo.Enum."), None);
+        assert_eq!(enum_chain_at("Enum.Font.GothamBold."), None);
+
+        let api_manager = ApiManager::from_local_dump();
+        let uri = Url::parse("file:///App.luau").unwrap();
+        let doc = "local React = require(game.React)\nReact.createElement(\"Frame\", { Name = tostring(Enum.), Visible = Enum.SortOrder. })\n";
+        let labels = |after: &str| -> Vec<String> {
+            let cursor = byte_offset_to_position(doc, doc.find(after).unwrap() + after.len());
+            completion_items(
+                doc,
+                &uri,
+                &cursor,
+                &api_manager,
+                &ComponentIndex::default(),
+                &[],
+                CompletionTrigger::Dot,
+            )
+            .into_iter()
+            .map(|item| item.label)
+            .collect()
+        };
+
+        // Inside a call and on a prop that isn't an enum, so nothing to infer from
+        let categories = labels("tostring(Enum.");
+        assert!(categories.contains(&"Enum.AutomaticSize".to_string()));
+        assert!(categories.windows(2).all(|pair| pair[0] < pair[1]));
+        let orders = labels("Enum.SortOrder.");
+        assert!(orders.contains(&"Enum.SortOrder.LayoutOrder".to_string()));
+        assert!(orders
+            .iter()
+            .all(|label| label.starts_with("Enum.SortOrder.")));
+    }
+
+    #[test]
+    fn test_constructor_value_completions() {
+        let api_manager = ApiManager::from_local_dump();
+        let uri = Url::parse("file:///App.luau").unwrap();
+        let doc = "local React = require(game.React)\nReact.createElement(\"TextLabel\", { Size = , Position = UDim2., TextColor3 = Color3.fromH })\n";
+        let items = |after: &str, trigger| -> Vec<CompletionItem> {
+            let cursor = byte_offset_to_position(doc, doc.find(after).unwrap() + after.len());
+            completion_items(
+                doc,
+                &uri,
+                &cursor,
+                &api_manager,
+                &ComponentIndex::default(),
+                &[],
+                trigger,
+            )
+        };
+
+        let sizes = items("Size = ", CompletionTrigger::Invoked);
+        assert_eq!(sizes.len(), 3);
+        assert_eq!(sizes[0].label, "UDim2.new");
+        assert_eq!(sizes[0].insert_text_format, Some(InsertTextFormat::SNIPPET));
+        match &sizes[0].text_edit {
+            Some(CompletionTextEdit::Edit(edit)) => {
+                assert_eq!(edit.new_text, "UDim2.new(${1:0}, ${2:0}, ${3:0}, ${4:0})")
+            }
+            _ => panic!("Expected constructor to insert its snippet"),
+        }
+
+        // Dot after the datatype replaces what's typed with the whole call
+        let positions = items("UDim2.", CompletionTrigger::Dot);
+        assert!(positions.iter().any(|item| item.label == "UDim2.fromScale"));
+
+        let colors = items("TextColor3 = Color3.fromH", CompletionTrigger::Invoked);
+        assert_eq!(colors[0].label, "Color3.fromRGB");
+        let hex = colors
+            .iter()
+            .find(|item| item.label == "Color3.fromHex")
+            .unwrap();
+        match &hex.text_edit {
+            Some(CompletionTextEdit::Edit(edit)) => {
+                let value_start = doc.find("Color3.fromH").unwrap();
+                assert_eq!(edit.range.start, byte_offset_to_position(doc, value_start));
+                assert_eq!(edit.new_text, "Color3.fromHex(\"${1:#FFFFFF}\")");
+            }
+            _ => panic!("Expected constructor to insert its snippet"),
+        }
+    }
+
+    #[test]
+    fn test_font_value_completions() {
+        let api_manager = ApiManager::from_local_dump();
+        let uri = Url::parse("file:///App.luau").unwrap();
+        let doc = "local React = require(game.React)\nReact.createElement(\"TextLabel\", { FontFace = , Font = })\n";
+        let labels = |after: &str| -> Vec<String> {
+            let cursor = byte_offset_to_position(doc, doc.find(after).unwrap() + after.len());
+            completion_items(
+                doc,
+                &uri,
+                &cursor,
+                &api_manager,
+                &ComponentIndex::default(),
+                &[],
+                CompletionTrigger::Invoked,
+            )
+            .into_iter()
+            .map(|item| item.label)
+            .collect()
+        };
+
+        let faces = labels("FontFace = ");
+        assert_eq!(faces[0], "Font.new");
+        assert!(faces.contains(&"Font.fromEnum(Enum.Font.GothamBold)".to_string()));
+        // Legacy property only takes the enum
+        let fonts = labels(", Font = ");
+        assert!(fonts.contains(&"Enum.Font.GothamBold".to_string()));
+        assert!(!fonts.contains(&"Font.new".to_string()));
+    }
+
+    #[test]
+    fn test_bool_value_completions() {
+        let api_manager = ApiManager::from_local_dump();
+        let uri = Url::parse("file:///App.luau").unwrap();
+        let doc = "local React = require(game.React)\nReact.createElement(\"TextButton\", { Visible = , TextScaled = t })\n";
+        let labels = |after: &str| -> Vec<String> {
+            let cursor = byte_offset_to_position(doc, doc.find(after).unwrap() + after.len());
+            completion_items(
+                doc,
+                &uri,
+                &cursor,
+                &api_manager,
+                &ComponentIndex::default(),
+                &[],
+                CompletionTrigger::Invoked,
+            )
+            .into_iter()
+            .map(|item| item.label)
+            .collect()
+        };
+
+        assert_eq!(labels("Visible = "), ["false", "true"]);
+        assert_eq!(labels("TextScaled = t"), ["true", "false"]);
+    }
+
+    #[test]
+    fn test_require_completions() {
+        assert_eq!(require_argument_start("local B = require(But"), Some(18));
+        assert_eq!(require_argument_start("local B = require ("), Some(19));
+        assert_eq!(require_argument_start("local B = foo.require("), None);
+        assert_eq!(require_argument_start("local B = print(But"), None);
+
+        let root = env::temp_dir().join("rblx_react_lsp_tests/require_completions");
+        fs::create_dir_all(&root).unwrap();
+        let sourcemap_file = root.join("sourcemap.json");
+        fs::write(
+            &sourcemap_file,
+            r#"{ "name": "Place", "className": "DataModel", "children": [
+                { "name": "ReplicatedStorage", "className": "ReplicatedStorage", "children": [
+                    { "name": "App", "className": "ModuleScript", "filePaths": ["App.luau"] },
+                    { "name": "Button", "className": "ModuleScript", "filePaths": ["Button.luau"] }
+                ] },
+                { "name": "ServerScriptService", "className": "ServerScriptService", "children": [
+                    { "name": "Main", "className": "Script", "filePaths": ["Main.server.luau"] }
+                ] }
+            ] }"#,
+        )
+        .unwrap();
+        let mut components = ComponentIndex::default();
+        components.set_sourcemap(Sourcemap::load(&[sourcemap_file]).0);
+
+        let uri = Url::from_file_path(root.join("App.luau")).unwrap();
+        let doc = "local Button = require(Bu)\n";
+        let cursor = byte_offset_to_position(doc, doc.find("Bu)").unwrap() + 2);
+        let items = completion_items(
+            doc,
+            &uri,
+            &cursor,
+            &ApiManager::from_local_dump(),
+            &components,
+            &[],
+            CompletionTrigger::Invoked,
+        );
+        // Scripts aren't requirable and a module doesn't require itself
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].label, "Button");
+        let Some(CompletionTextEdit::Edit(edit)) = &items[0].text_edit else {
+            panic!("Expected text edit");
+        };
+        assert_eq!(edit.new_text, "script.Parent.Button");
+        assert_eq!(
+            (edit.range.start.character, edit.range.end.character),
+            (23, 25)
+        );
+
+        fs::remove_dir_all(&root).ok();
+    }
+
+    #[test]
+    fn test_unrequired_module_components() {
+        let root = env::temp_dir().join("rblx_react_lsp_tests/unrequired_components");
+        fs::create_dir_all(&root).unwrap();
+        let sourcemap_file = root.join("sourcemap.json");
+        fs::write(
+            &sourcemap_file,
+            r#"{ "name": "Place", "className": "DataModel", "children": [
+                { "name": "ReplicatedStorage", "className": "ReplicatedStorage", "children": [
+                    { "name": "App", "className": "ModuleScript", "filePaths": ["App.luau"] },
+                    { "name": "Components", "className": "ModuleScript", "filePaths": ["Components.luau"] }
+                ] }
+            ] }"#,
+        )
+        .unwrap();
+        let module = "local function Button(props) end\nreturn { Button = Button }\n";
+        let mut components = ComponentIndex::default();
+        components.set_sourcemap(Sourcemap::load(&[sourcemap_file]).0);
+        components.index_module(
+            &Url::from_file_path(root.join("Components.luau")).unwrap(),
+            module,
+            &SymbolTable::build(module, &Default::default()),
+        );
+
+        let uri = Url::from_file_path(root.join("App.luau")).unwrap();
+        let doc =
+            "--!strict\nlocal React = require(game.React)\n\nreturn React.createElement(Bu, {})\n";
+        let cursor = byte_offset_to_position(doc, doc.find("(Bu").unwrap() + 3);
+        let items = completion_items(
+            doc,
+            &uri,
+            &cursor,
+            &ApiManager::from_local_dump(),
+            &components,
+            &[],
+            CompletionTrigger::Invoked,
+        );
+        let button = items
+            .iter()
+            .find(|item| item.label == "Components.Button")
+            .unwrap();
+        let edit = &button.additional_text_edits.as_ref().unwrap()[0];
+        assert_eq!(
+            edit.new_text,
+            "local Components = require(script.Parent.Components)\n"
+        );
+        assert_eq!(edit.range.start.line, 2);
+
+        // Already required modules come through the local instead
+        let doc = "local React = require(game.React)\nlocal Components = require(script.Parent.Components)\nreturn React.createElement(Bu, {})\n";
+        let cursor = byte_offset_to_position(doc, doc.find("(Bu").unwrap() + 3);
+        let items = completion_items(
+            doc,
+            &uri,
+            &cursor,
+            &ApiManager::from_local_dump(),
+            &components,
+            &[],
+            CompletionTrigger::Invoked,
+        );
+        let buttons: Vec<&CompletionItem> = items
+            .iter()
+            .filter(|item| item.label == "Components.Button")
+            .collect();
+        assert_eq!(buttons.len(), 1);
+        assert!(buttons[0].additional_text_edits.is_none());
+
+        fs::remove_dir_all(&root).ok();
+    }
+
+    #[test]
+    fn test_prefix_filter() {
+        let items: Vec<CompletionItem> = ["Size", "Position", "SizeConstraint", "Visible"]
+            .iter()
+            .enumerate()
+            .map(|(i, label)| CompletionItem {
+                label: label.to_string(),
+                sort_text: Some(format!("\x01{:05}", i)),
+                ..Default::default()
+            })
+            .collect();
+        let labels = |items: &[CompletionItem]| -> Vec<String> {
+            items.iter().map(|item| item.label.clone()).collect()
+        };
+
+        let mut all = items.clone();
+        assert!(!apply_prefix_filter(&mut all, "", 0));
+        assert_eq!(all.len(), 4);
+
+        let mut size = items.clone();
+        assert!(apply_prefix_filter(&mut size, "sz", 0));
+        assert_eq!(labels(&size), vec!["Size", "SizeConstraint"]);
+
+        let mut capped = items.clone();
+        assert!(apply_prefix_filter(&mut capped, "", 2));
+        assert_eq!(labels(&capped), vec!["Size", "Position"]);
+
+        // Cap goes by ranking, not by the order kinds of items got collected in
+        let mut ranked = items;
+        ranked[3].sort_text = Some(format!("\x00{:05}", 0));
+        ranked[2].label = "Active".to_string();
+        ranked[2].sort_text = None;
+        assert!(apply_prefix_filter(&mut ranked, "", 3));
+        assert_eq!(labels(&ranked), vec!["Visible", "Size", "Position"]);
+    }
+
+    #[test]
+    fn test_commit_characters_and_preselect() {
+        let mut api_manager = ApiManager::from_local_dump();
+        api_manager.update_freq("Visible = true, Visible = false");
+        let uri = Url::parse("file:///App.luau").unwrap();
+        let complete = |doc: &str, after: &str| {
+            let cursor = byte_offset_to_position(doc, doc.find(after).unwrap() + after.len());
+            completion_items(
+                doc,
+                &uri,
+                &cursor,
+                &api_manager,
+                &ComponentIndex::default(),
+                &[],
+                CompletionTrigger::Invoked,
+            )
+        };
+
+        let doc = "local React = require(game.React)\nreturn React.createElement(\"Frame\", { Vi = 1 })\n";
+        let items = complete(doc, "Vi");
+        let properties = &items[SPECIAL_PROP_DOCS.len()..];
+        assert_eq!(properties[0].label, "Visible");
+        assert_eq!(properties[0].preselect, Some(true));
+        assert!(items
+            .iter()
+            .filter(|item| item.label != "Visible")
+            .all(|item| item.preselect.is_none()));
+        assert_eq!(
+            properties[0].commit_characters,
+            Some(vec!["=".to_string(), ",".to_string()])
+        );
+
+        // New entries get inserted as snippets, which already include the " = "
+        let doc =
+            "local React = require(game.React)\nreturn React.createElement(\"Frame\", { Vi })\n";
+        assert!(complete(doc, "Vi")[SPECIAL_PROP_DOCS.len()]
+            .commit_characters
+            .is_none());
+    }
+
+    #[test]
+    fn test_change_completions() {
+        let api_manager = ApiManager::from_local_dump();
+        let uri = Url::parse("file:///App.luau").unwrap();
+        let doc = "local R = require(game.React)\nreturn R.createElement(\"TextBox\", { [R.Change.Te] })\n";
+        let cursor = byte_offset_to_position(doc, doc.find("Te]").unwrap() + 2);
+        let text = completion_items(
+            doc,
+            &uri,
+            &cursor,
+            &api_manager,
+            &ComponentIndex::default(),
+            &[],
+            CompletionTrigger::Invoked,
+        )
+        .into_iter()
+        .find(|item| item.label == "Text")
+        .unwrap();
+        assert_eq!(text.kind, Some(CompletionItemKind::EVENT));
+        let Some(CompletionTextEdit::Edit(edit)) = &text.text_edit else {
+            panic!("Expected text edit");
+        };
+        assert_eq!(edit.new_text, "[R.Change.Text] = function(rbx)\n$0\nend,");
+
+        let resolved = resolve_completion_item(text, &api_manager);
+        assert_eq!(resolved.detail.as_deref(), Some("string"));
+        let Some(Documentation::MarkupContent(docs)) = resolved.documentation else {
+            panic!("Expected markdown documentation");
+        };
+        assert!(docs.value.contains("[React.Change.Text]"));
+    }
+
+    #[test]
+    fn test_first_argument_identifiers() {
+        let api_manager = ApiManager::from_local_dump();
+        let module = "local function Button(props) end\nreturn { Button = Button }\n";
+        let module_uri = Url::parse("file:///project/Components.luau").unwrap();
+        let mut components = ComponentIndex::default();
+        components.index_module(
+            &module_uri,
+            module,
+            &SymbolTable::build(module, &Default::default()),
+        );
+
+        let uri = Url::parse("file:///project/App.luau").unwrap();
+        let labels = |doc: &str, after: &str| -> Vec<String> {
+            let cursor = byte_offset_to_position(doc, doc.find(after).unwrap() + after.len());
+            completion_items(
+                doc,
+                &uri,
+                &cursor,
+                &api_manager,
+                &components,
+                &["Portal".to_string()],
+                CompletionTrigger::Invoked,
+            )
+            .into_iter()
+            .map(|item| item.label)
+            .collect()
+        };
+
+        let doc = r#"local React = require(game.React)
+local Components = require(script.Parent.Components)
+local function Card(props) end
+local Header = function(props) end
+return React.createElement(Ca, {}), React.createElement(Components.Bu, {}), React.createElement("Fr", {})
+local function Later(props) end
+"#;
+        let items = |doc: &str, after: &str| -> Vec<CompletionItem> {
+            let cursor = byte_offset_to_position(doc, doc.find(after).unwrap() + after.len());
+            completion_items(
+                doc,
+                &uri,
+                &cursor,
+                &api_manager,
+                &components,
+                &["Portal".to_string()],
+                CompletionTrigger::Invoked,
+            )
+        };
+        let first_argument = items(doc, "(Ca");
+        let labels_of = |items: &[CompletionItem]| -> Vec<String> {
+            items.iter().map(|item| item.label.clone()).collect()
+        };
+        // Components declared after the element(Later) aren't in scope yet
+        assert_eq!(
+            labels_of(&first_argument[..4]),
+            vec!["Card", "Header", "Components.Button", "Portal"]
+        );
+        assert_eq!(first_argument[0].kind, Some(CompletionItemKind::FUNCTION));
+        let canvas = first_argument
+            .iter()
+            .find(|item| item.label == "CanvasGroup")
+            .unwrap();
+        assert_eq!(canvas.kind, Some(CompletionItemKind::CLASS));
+        assert_eq!(canvas.insert_text.as_deref(), Some("\"CanvasGroup\""));
+        assert_eq!(canvas.filter_text.as_deref(), Some("CanvasGroup"));
+        // Only GUI classes by default(completion.classBases)
+        assert!(first_argument.iter().all(|item| item.label != "Camera"));
+        assert_eq!(labels(doc, "Components.Bu"), vec!["Button"]);
+        // Quoted first argument stays a class name
+        assert!(labels(doc, "\"Fr").contains(&"Frame".to_string()));
+    }
 }
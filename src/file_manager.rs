@@ -1,27 +1,46 @@
 use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 use tower_lsp::lsp_types::{TextDocumentContentChangeEvent, Url};
 
+use crate::{
+    file_diagnoser::{position_to_byte_offset, DocumentAnalysis, TextSplice},
+    symbol_table::{RequireContext, SymbolTable},
+};
+
 #[derive(Debug)]
 struct TextDoc {
     text: String,
     ver: i32,
+    analysis: DocumentAnalysis, // Rebuilt whenever text changes, so it's always for ver
 }
 
 #[derive(Debug)]
 pub struct FileManager {
     curr_files: HashMap<Url, TextDoc>,
+    requires: Arc<RequireContext>, // Every analysis gets built with these
 }
 
 impl FileManager {
     pub fn new() -> Self {
         Self {
             curr_files: HashMap::new(),
+            requires: Arc::default(),
         }
     }
 
     pub fn on_opened_file(&mut self, uri: Url, text: String, ver: i32) {
-        self.curr_files.insert(uri, TextDoc { text: text, ver });
+        let analysis = DocumentAnalysis::new(&text, &self.requires);
+        self.curr_files.insert(
+            uri,
+            TextDoc {
+                text,
+                ver,
+                analysis,
+            },
+        );
     }
 
     pub fn on_changed_file(
@@ -31,10 +50,36 @@ impl FileManager {
         ver: i32,
     ) {
         if let Some(doc) = self.curr_files.get_mut(uri) {
+            let mut splices = Vec::new();
             for change in changed {
-                doc.text = change.text.clone();
+                let Some(range) = change.range else {
+                    doc.text = change.text.clone();
+                    splices.clear();
+                    continue;
+                };
+                // Positions past the end clamp to it, like clients expect
+                let offset = |position| {
+                    position_to_byte_offset(&doc.text, position).unwrap_or(doc.text.len())
+                };
+                let start = offset(&range.start);
+                let end = offset(&range.end).max(start);
+                let removed = doc.text[start..end].to_string();
+                doc.text.replace_range(start..end, &change.text);
+                splices.push((
+                    TextSplice {
+                        start,
+                        removed_len: removed.len(),
+                        inserted_len: change.text.len(),
+                    },
+                    removed,
+                ));
             }
             doc.ver = ver;
+            // Single keystrokes update the analysis, batches and full replacements rebuild it
+            match (splices.as_slice(), changed.len()) {
+                ([(splice, removed)], 1) => doc.analysis.apply_edit(&doc.text, *splice, removed),
+                _ => doc.analysis = DocumentAnalysis::new(&doc.text, &self.requires),
+            }
         }
     }
 
@@ -45,4 +90,259 @@ impl FileManager {
     pub fn get_text(&self, uri: &Url) -> Option<&str> {
         self.curr_files.get(uri).map(|doc| doc.text.as_str())
     }
+
+    pub fn get_symbols(&self, uri: &Url) -> Option<&SymbolTable> {
+        self.curr_files.get(uri).map(|doc| &doc.analysis.symbols)
+    }
+
+    // Text plus its analysis, which requests fill in and reuse until the document changes
+    pub fn get_analysis(&mut self, uri: &Url) -> Option<(&str, &mut DocumentAnalysis)> {
+        self.curr_files
+            .get_mut(uri)
+            .map(|doc| (doc.text.as_str(), &mut doc.analysis))
+    }
+
+    pub fn requires(&self) -> &Arc<RequireContext> {
+        &self.requires
+    }
+
+    // Settings analysis depends on changed(Roact compat, React aliases), so every one gets rebuilt
+    pub fn set_requires(&mut self, requires: RequireContext) {
+        self.requires = Arc::new(requires);
+        for doc in self.curr_files.values_mut() {
+            doc.analysis = DocumentAnalysis::new(&doc.text, &self.requires);
+        }
+    }
+
+    pub fn get_version(&self, uri: &Url) -> Option<i32> {
+        self.curr_files.get(uri).map(|doc| doc.ver)
+    }
+
+    // (Uri, text, version) of every open document
+    pub fn open_documents(&self) -> impl Iterator<Item = (&Url, &str, i32)> {
+        self.curr_files
+            .iter()
+            .map(|(uri, doc)| (uri, doc.text.as_str(), doc.ver))
+    }
+}
+
+// What a frequency scan of a document reads, whole text the first time and changed lines after
+// (Region holds the changed lines as they were last scan and as they are now)
+#[derive(Debug, PartialEq)]
+pub enum FreqScan {
+    Document(String),
+    Region { old: String, new: String },
+}
+
+// Debounces frequency scans per document, remembering the text each one last scanned
+#[derive(Debug, Default)]
+pub struct FreqScanQueue {
+    latest: HashMap<Url, u64>, // Generation of the newest scheduled scan
+    scanned: HashMap<Url, String>,
+    generation: u64,
+}
+
+impl FreqScanQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Generation a scan scheduled now runs under, earlier ones for uri get skipped
+    pub fn schedule(&mut self, uri: &Url) -> u64 {
+        self.generation += 1;
+        self.latest.insert(uri.clone(), self.generation);
+        self.generation
+    }
+
+    // What to scan if generation is still the newest one(Latest version wins), None otherwise
+    pub fn take(&mut self, uri: &Url, generation: u64, text: &str) -> Option<FreqScan> {
+        if self.latest.get(uri) != Some(&generation) {
+            return None;
+        }
+        self.latest.remove(uri);
+        let scan = match self.scanned.get(uri) {
+            Some(previous) => {
+                let (old, new) = changed_lines(previous, text);
+                FreqScan::Region {
+                    old: old.to_string(),
+                    new: new.to_string(),
+                }
+            }
+            None => FreqScan::Document(text.to_string()),
+        };
+        self.scanned.insert(uri.clone(), text.to_string());
+        Some(scan)
+    }
+
+    pub fn forget(&mut self, uri: &Url) {
+        self.latest.remove(uri);
+        self.scanned.remove(uri);
+    }
+}
+
+// Whole lines differing between previous and text(Common prefix and suffix cut off), in both of them
+// (Empty if unchanged)
+fn changed_lines<'a, 'b>(previous: &'a str, text: &'b str) -> (&'a str, &'b str) {
+    if previous == text {
+        return ("", "");
+    }
+    let (old, new) = (previous.as_bytes(), text.as_bytes());
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old
+        .iter()
+        .rev()
+        .zip(new.iter().rev())
+        .take(old.len().min(new.len()) - prefix)
+        .take_while(|(a, b)| a == b)
+        .count();
+    // Newlines are ascii, so line boundaries are always char boundaries
+    let start = new[..prefix]
+        .iter()
+        .rposition(|&b| b == b'\n')
+        .map(|i| i + 1)
+        .unwrap_or(0);
+    // Both end at the same spot within the common suffix
+    let tail = new[new.len() - suffix..]
+        .iter()
+        .position(|&b| b == b'\n')
+        .unwrap_or(suffix);
+    let old_end = old.len() - suffix + tail;
+    let new_end = new.len() - suffix + tail;
+    (&previous[start..old_end], &text[start..new_end])
+}
+
+// Every file under root matching predicate, skipping hidden folders(.git etc) and node_modules
+pub fn find_workspace_files(root: &Path, matches: impl Fn(&Path) -> bool) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    let mut dirs = vec![root.to_path_buf()];
+
+    while let Some(dir) = dirs.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            if path.is_dir() {
+                if !name.starts_with('.') && name != "node_modules" {
+                    dirs.push(path);
+                }
+            } else if matches(&path) {
+                files.push(path);
+            }
+        }
+    }
+
+    files.sort();
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use tower_lsp::lsp_types::{Position, Range, TextDocumentContentChangeEvent, Url};
+
+    use crate::file_manager::{FileManager, FreqScan, FreqScanQueue};
+
+    #[test]
+    fn test_ranged_changes() {
+        let uri = Url::parse("file:///test.luau").unwrap();
+        let mut file_manager = FileManager::new();
+        file_manager.on_opened_file(
+            uri.clone(),
+            "local React = require(game.React)\nreturn React.createElement(\"Frame\")\n"
+                .to_string(),
+            1,
+        );
+        let change =
+            |start: (u32, u32), end: (u32, u32), text: &str| TextDocumentContentChangeEvent {
+                range: Some(Range {
+                    start: Position::new(start.0, start.1),
+                    end: Position::new(end.0, end.1),
+                }),
+                range_length: None,
+                text: text.to_string(),
+            };
+
+        file_manager.on_changed_file(&uri, &[change((1, 34), (1, 34), ", {}")], 2);
+        assert_eq!(
+            file_manager.get_text(&uri),
+            Some("local React = require(game.React)\nreturn React.createElement(\"Frame\", {})\n")
+        );
+        let (text, analysis) = file_manager.get_analysis(&uri).unwrap();
+        assert!(analysis.regions.as_ref().unwrap().regions()[0]
+            .props
+            .is_some());
+        assert_eq!(
+            &text[analysis.regions.as_ref().unwrap().regions()[0].start..],
+            "\"Frame\", {})\n"
+        );
+
+        // Batches apply in order, positions past the end clamp to it
+        file_manager.on_changed_file(
+            &uri,
+            &[
+                change((0, 6), (0, 11), "R"),
+                change((1, 7), (1, 12), "R"),
+                change((9, 0), (9, 0), "-- end"),
+            ],
+            3,
+        );
+        assert_eq!(
+            file_manager.get_text(&uri),
+            Some("local R = require(game.React)\nreturn R.createElement(\"Frame\", {})\n-- end")
+        );
+        assert_eq!(file_manager.get_version(&uri), Some(3));
+        let (_, analysis) = file_manager.get_analysis(&uri).unwrap();
+        assert_eq!(analysis.regions.as_ref().unwrap().regions().len(), 1);
+    }
+
+    #[test]
+    fn test_freq_scan_queue() {
+        let uri = Url::parse("file:///test.luau").unwrap();
+        let mut queue = FreqScanQueue::new();
+
+        let first = queue.schedule(&uri);
+        assert_eq!(
+            queue.take(&uri, first, "local a = 1\nlocal b = 2\n"),
+            Some(FreqScan::Document("local a = 1\nlocal b = 2\n".to_string()))
+        );
+
+        // Only the newest scheduled scan runs, reading just the lines that changed since the last one
+        let stale = queue.schedule(&uri);
+        let latest = queue.schedule(&uri);
+        assert_eq!(queue.take(&uri, stale, "local a = 1\nlocal b = 3\n"), None);
+        assert_eq!(
+            queue.take(
+                &uri,
+                latest,
+                "local a = 1\nlocal b = Visible\nlocal c = Size\n"
+            ),
+            Some(FreqScan::Region {
+                old: "local b = 2".to_string(),
+                new: "local b = Visible\nlocal c = Size".to_string()
+            })
+        );
+        assert_eq!(queue.take(&uri, latest, "local a = 1\n"), None);
+
+        let unchanged = queue.schedule(&uri);
+        assert_eq!(
+            queue.take(
+                &uri,
+                unchanged,
+                "local a = 1\nlocal b = Visible\nlocal c = Size\n"
+            ),
+            Some(FreqScan::Region {
+                old: String::new(),
+                new: String::new()
+            })
+        );
+
+        queue.forget(&uri);
+        let reopened = queue.schedule(&uri);
+        assert!(matches!(
+            queue.take(&uri, reopened, "local a = 1\n"),
+            Some(FreqScan::Document(_))
+        ));
+    }
 }
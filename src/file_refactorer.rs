@@ -0,0 +1,1446 @@
+// This script handles edits the server can offer on top of its analysis(Rename, code actions)
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use serde_json::json;
+
+use tower_lsp::lsp_types::{
+    AnnotatedTextEdit, ChangeAnnotation, CodeAction, CodeActionKind, CodeActionOrCommand, Command,
+    Diagnostic, DocumentChanges, NumberOrString, OneOf, OptionalVersionedTextDocumentIdentifier,
+    Position, Range, TextDocumentEdit, TextEdit, Url, WorkspaceEdit,
+};
+
+use crate::api_manager::ApiManager;
+use crate::file_diagnoser::{
+    byte_offset_to_position, get_document_diagnostics, get_element_regions,
+    get_unrequired_react_var, innermost_region_at, position_to_byte_offset, DocumentAnalysis,
+    ElementRegion, PropKey, RegionIndex, ITEM_SOURCE, RESTRICTED_PROPERTY_CODE,
+    UNKNOWN_PROPERTY_CODE,
+};
+use crate::settings::{PropsOrder, Settings};
+use crate::symbol_table::{RequireContext, SymbolTable};
+
+pub const SET_CURSOR_COMMAND: &str = "rblx-react-lsp.setCursor";
+pub const FIX_ALL_COMMAND: &str = "rblx-react-lsp.fixAll";
+pub const NEW_COMPONENT_COMMAND: &str = "rblx-react-lsp.newComponent";
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+// Reserved words can't name anything(Contextual ones like "type" and "continue" still can)
+const LUAU_KEYWORDS: [&str; 21] = [
+    "and", "break", "do", "else", "elseif", "end", "false", "for", "function", "if", "in", "local",
+    "nil", "not", "or", "repeat", "return", "then", "true", "until", "while",
+];
+
+// Luau names are ASCII only, [A-Za-z_][A-Za-z0-9_]*
+pub(crate) fn is_valid_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    let valid = match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' => {
+            chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        }
+        _ => false,
+    };
+    valid && !LUAU_KEYWORDS.contains(&name)
+}
+
+// Returns (start, end) byte range of identifier touching the given offset
+fn identifier_at(doc: &str, byte_offset: usize) -> Option<(usize, usize)> {
+    let byte_offset = byte_offset.min(doc.len());
+    let start = doc[..byte_offset]
+        .char_indices()
+        .rev()
+        .take_while(|(_, c)| is_ident_char(*c))
+        .last()
+        .map(|(i, _)| i)
+        .unwrap_or(byte_offset);
+    let end = doc[byte_offset..]
+        .char_indices()
+        .find(|(_, c)| !is_ident_char(*c))
+        .map(|(i, _)| byte_offset + i)
+        .unwrap_or(doc.len());
+
+    if start == end {
+        return None;
+    }
+    Some((start, end))
+}
+
+// Only identifiers the server actually analyzes can be renamed
+fn get_renameable_symbol(
+    doc: &str,
+    symbols: &SymbolTable,
+    position: &Position,
+) -> Option<(String, usize, usize)> {
+    let byte_offset = position_to_byte_offset(doc, position)?;
+    let (start, end) = identifier_at(doc, byte_offset)?;
+    let name = &doc[start..end];
+
+    let react_var_names = symbols.react_var_names(doc);
+    if react_var_names.is_empty() {
+        return None;
+    }
+    let is_known = react_var_names.iter().any(|react_var_name| {
+        name == react_var_name
+            || symbols
+                .create_element_macros(doc, doc.len(), react_var_name)
+                .iter()
+                .any(|m| m == name)
+    }) || symbols.component_names().iter().any(|c| c == name);
+
+    if !is_known || !symbols.usages(doc, name).contains(&(start, end)) {
+        return None;
+    }
+    Some((name.to_string(), start, end))
+}
+
+fn byte_range(doc: &str, start: usize, end: usize) -> Range {
+    Range {
+        start: byte_offset_to_position(doc, start),
+        end: byte_offset_to_position(doc, end),
+    }
+}
+
+pub fn prepare_rename(doc: &str, symbols: &SymbolTable, position: &Position) -> Option<Range> {
+    let (_, start, end) = get_renameable_symbol(doc, symbols, position)?;
+    Some(byte_range(doc, start, end))
+}
+
+pub fn rename(
+    doc: &str,
+    symbols: &SymbolTable,
+    uri: &Url,
+    position: &Position,
+    new_name: &str,
+) -> Option<WorkspaceEdit> {
+    let (name, _, _) = get_renameable_symbol(doc, symbols, position)?;
+
+    let edits: Vec<TextEdit> = symbols
+        .usages(doc, &name)
+        .into_iter()
+        .map(|(start, end)| TextEdit {
+            range: byte_range(doc, start, end),
+            new_text: new_name.to_string(),
+        })
+        .collect();
+
+    Some(single_file_edit(uri, edits))
+}
+
+fn single_file_edit(uri: &Url, edits: Vec<TextEdit>) -> WorkspaceEdit {
+    let mut changes = HashMap::new();
+    changes.insert(uri.clone(), edits);
+    WorkspaceEdit {
+        changes: Some(changes),
+        ..Default::default()
+    }
+}
+
+// Range covering a props entry together with its separator, so removing it leaves the table well formed
+fn entry_removal_range(doc: &str, region: &ElementRegion, index: usize) -> (usize, usize) {
+    let (start, end) = region.entries[index].range;
+    if let Some(next) = region.entries.get(index + 1) {
+        // Up to the separator and whitespace after it, comments before the next entry stay
+        let gap = &doc[end..next.range.0];
+        let Some(separator) = gap.find([',', ';']) else {
+            return (start, next.range.0);
+        };
+        let after = &gap[separator + 1..];
+        return (
+            start,
+            end + separator + 1 + after.len() - after.trim_start().len(),
+        );
+    }
+
+    // Last entry, eat its trailing separator and the whitespace left before it instead
+    let after = &doc[end..];
+    let after_trimmed = after.trim_start_matches([' ', '\t']);
+    if !after_trimmed.starts_with([',', ';']) && index > 0 {
+        // No trailing separator, so the previous entry's separator has to go
+        return (region.entries[index - 1].range.1, end);
+    }
+
+    let mut removal_end = end;
+    if after_trimmed.starts_with([',', ';']) {
+        removal_end += after.len() - after_trimmed.len() + 1;
+    }
+    let table_start = region.props.map(|(open, _)| open + 1).unwrap_or(start);
+    let mut removal_start = table_start + doc[table_start..start].trim_end().len();
+    // A line comment before the entry would swallow whatever follows it, so its newline stays
+    let line_start = doc[..removal_start].rfind('\n').map_or(0, |i| i + 1);
+    if doc[line_start..removal_start].contains("--") {
+        if let Some(newline) = doc[removal_start..start].rfind('\n') {
+            removal_start += newline + 1;
+            let rest = &doc[removal_end..];
+            let blank = rest.len() - rest.trim_start_matches([' ', '\t']).len();
+            if rest[blank..].starts_with('\n') {
+                removal_end += blank + 1;
+            }
+        }
+    }
+    (removal_start, removal_end)
+}
+
+// Removes the entry of an unknown or restricted property
+fn remove_property_action(
+    doc: &str,
+    uri: &Url,
+    diagnostic: &Diagnostic,
+    regions: &[ElementRegion],
+) -> Option<CodeAction> {
+    let key_start = position_to_byte_offset(doc, &diagnostic.range.start)?;
+    let (region, index) = regions.iter().find_map(|region| {
+        region
+            .entries
+            .iter()
+            .position(|entry| entry.key_range.0 == key_start)
+            .map(|index| (region, index))
+    })?;
+    let PropKey::Name(key) = &region.entries[index].key else {
+        return None;
+    };
+
+    let (start, end) = entry_removal_range(doc, region, index);
+    Some(CodeAction {
+        title: if is_our_diagnostic(diagnostic, RESTRICTED_PROPERTY_CODE) {
+            format!("Remove restricted property '{}'", key)
+        } else {
+            format!("Remove unknown property '{}'", key)
+        },
+        kind: Some(CodeActionKind::QUICKFIX),
+        diagnostics: Some(vec![diagnostic.clone()]),
+        edit: Some(single_file_edit(
+            uri,
+            vec![TextEdit {
+                range: byte_range(doc, start, end),
+                new_text: String::new(),
+            }],
+        )),
+        ..Default::default()
+    })
+}
+
+// Levenshtein distance between two strings(Case insensitive, so casing typos are cheap)
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.to_lowercase().chars().collect();
+    let b: Vec<char> = b.to_lowercase().chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut curr = vec![0; b.len() + 1];
+
+    for (i, ca) in a.iter().enumerate() {
+        curr[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let cost = if ca == cb { 0 } else { 1 };
+            curr[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(curr[j] + 1);
+        }
+        std::mem::swap(&mut prev, &mut curr);
+    }
+    prev[b.len()]
+}
+
+// Closest real property to a misspelled key, candidates come ranked from ApiManager so ties favor common props
+fn closest_property(key: &str, properties: &[(String, String)]) -> Option<String> {
+    let max_distance = (key.chars().count() / 4).clamp(1, 3);
+    properties
+        .iter()
+        .map(|(name, _)| (edit_distance(key, name), name))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, name)| name.clone())
+}
+
+// Rename of a misspelled key to its closest property, (Suggestion, edit)
+fn did_you_mean_edit(
+    doc: &str,
+    diagnostic: &Diagnostic,
+    regions: &[ElementRegion],
+    api_manager: &ApiManager,
+) -> Option<(String, TextEdit)> {
+    let key_start = position_to_byte_offset(doc, &diagnostic.range.start)?;
+    let (region, entry) = regions.iter().find_map(|region| {
+        region
+            .entries
+            .iter()
+            .find(|entry| entry.key_range.0 == key_start)
+            .map(|entry| (region, entry))
+    })?;
+    let PropKey::Name(key) = &entry.key else {
+        return None;
+    };
+    let properties = api_manager.lookup_properties(region.class_name.as_ref()?)?;
+    let suggestion = closest_property(key, &properties)?;
+    let edit = TextEdit {
+        range: byte_range(doc, entry.key_range.0, entry.key_range.1),
+        new_text: suggestion.clone(),
+    };
+    Some((suggestion, edit))
+}
+
+fn did_you_mean_action(
+    doc: &str,
+    uri: &Url,
+    diagnostic: &Diagnostic,
+    regions: &[ElementRegion],
+    api_manager: &ApiManager,
+) -> Option<CodeAction> {
+    let (suggestion, edit) = did_you_mean_edit(doc, diagnostic, regions, api_manager)?;
+    Some(CodeAction {
+        title: format!("Change to {}", suggestion),
+        kind: Some(CodeActionKind::QUICKFIX),
+        diagnostics: Some(vec![diagnostic.clone()]),
+        edit: Some(single_file_edit(uri, vec![edit])),
+        is_preferred: Some(true),
+        ..Default::default()
+    })
+}
+
+// Edits fixing every auto fixable diagnostic in doc(Only did you mean renames, removals lose code)
+pub fn fix_all_edits(
+    doc: &str,
+    requires: &Arc<RequireContext>,
+    api_manager: &ApiManager,
+) -> Vec<TextEdit> {
+    let regions = get_element_regions(doc, requires);
+    get_document_diagnostics(doc, requires, api_manager)
+        .iter()
+        .filter(|diagnostic| is_our_diagnostic(diagnostic, UNKNOWN_PROPERTY_CODE))
+        .filter_map(|diagnostic| did_you_mean_edit(doc, diagnostic, &regions, api_manager))
+        .map(|(_, edit)| edit)
+        .collect()
+}
+
+// Combines (uri, open version, edits) of many documents into one edit
+// Fixes are plain text edits(No create/rename/delete), so documentChanges support only decides the shape,
+// versioned ones let client reject edits computed against an outdated document
+pub fn combined_workspace_edit(
+    documents: Vec<(Url, Option<i32>, Vec<TextEdit>)>,
+    document_changes: bool,
+) -> WorkspaceEdit {
+    if !document_changes {
+        return WorkspaceEdit {
+            changes: Some(
+                documents
+                    .into_iter()
+                    .map(|(uri, _, edits)| (uri, edits))
+                    .collect(),
+            ),
+            ..Default::default()
+        };
+    }
+    WorkspaceEdit {
+        document_changes: Some(DocumentChanges::Edits(
+            documents
+                .into_iter()
+                .map(|(uri, version, edits)| TextDocumentEdit {
+                    text_document: OptionalVersionedTextDocumentIdentifier { uri, version },
+                    edits: edits.into_iter().map(OneOf::Left).collect(),
+                })
+                .collect(),
+        )),
+        ..Default::default()
+    }
+}
+
+// Same as combined_workspace_edit, but clients supporting change annotations ask before applying it
+// (Needs documentChanges, annotations only exist on TextDocumentEdit)
+pub fn previewable_workspace_edit(
+    documents: Vec<(Url, Option<i32>, Vec<TextEdit>)>,
+    label: &str,
+    document_changes: bool,
+    change_annotations: bool,
+) -> WorkspaceEdit {
+    if !(document_changes && change_annotations) {
+        return combined_workspace_edit(documents, document_changes);
+    }
+    let annotation_id = label.to_string();
+    WorkspaceEdit {
+        document_changes: Some(DocumentChanges::Edits(
+            documents
+                .into_iter()
+                .map(|(uri, version, edits)| TextDocumentEdit {
+                    text_document: OptionalVersionedTextDocumentIdentifier { uri, version },
+                    edits: edits
+                        .into_iter()
+                        .map(|text_edit| {
+                            OneOf::Right(AnnotatedTextEdit {
+                                text_edit,
+                                annotation_id: annotation_id.clone(),
+                            })
+                        })
+                        .collect(),
+                })
+                .collect(),
+        )),
+        change_annotations: Some(HashMap::from([(
+            annotation_id,
+            ChangeAnnotation {
+                label: label.to_string(),
+                needs_confirmation: Some(true),
+                description: None,
+            },
+        )])),
+        ..Default::default()
+    }
+}
+
+fn line_start_of(doc: &str, byte_offset: usize) -> usize {
+    doc[..byte_offset].rfind('\n').map(|i| i + 1).unwrap_or(0)
+}
+
+fn indentation_of_line(doc: &str, byte_offset: usize) -> &str {
+    let line = &doc[line_start_of(doc, byte_offset)..];
+    &line[..line.len() - line.trim_start_matches([' ', '\t']).len()]
+}
+
+// Tabs if document already indents with them, 4 spaces otherwise
+fn indent_unit(doc: &str) -> &'static str {
+    if doc.lines().any(|line| line.starts_with('\t')) {
+        "\t"
+    } else {
+        "    "
+    }
+}
+
+// Moves text that started at from_indent over to to_indent(First line is expected to be unindented)
+fn reindent(text: &str, from_indent: &str, to_indent: &str) -> String {
+    text.lines()
+        .enumerate()
+        .map(|(i, line)| {
+            if i == 0 {
+                return line.to_string();
+            }
+            match line.strip_prefix(from_indent) {
+                Some(rest) => format!("{}{}", to_indent, rest),
+                None if line.trim().is_empty() => String::new(),
+                None => line.to_string(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// Where a new top level definition can go, start of the function enclosing offset(Or its statement line)
+fn top_level_insert_offset(doc: &str, byte_offset: usize) -> usize {
+    let mut line_start = line_start_of(doc, byte_offset);
+    loop {
+        let line = &doc[line_start..];
+        let is_function = line.starts_with("local function ")
+            || line.starts_with("function ")
+            || (line.starts_with("local ")
+                && line
+                    .lines()
+                    .next()
+                    .is_some_and(|l| l.contains("= function")));
+        if is_function {
+            return line_start;
+        }
+        if line_start == 0 {
+            return line_start_of(doc, byte_offset);
+        }
+        line_start = line_start_of(doc, line_start - 1);
+    }
+}
+
+fn unique_name(doc: &str, symbols: &SymbolTable, base: &str) -> String {
+    let mut name = base.to_string();
+    let mut suffix = 2;
+    while !symbols.usages(doc, &name).is_empty() {
+        name = format!("{}{}", base, suffix);
+        suffix += 1;
+    }
+    name
+}
+
+fn extract_component_action(
+    doc: &str,
+    symbols: &SymbolTable,
+    uri: &Url,
+    byte_offset: usize,
+    regions: &[ElementRegion],
+) -> Option<CodeAction> {
+    let region = innermost_region_at(regions, byte_offset)?;
+    if region.end >= doc.len() {
+        return None; // Unclosed call, nothing sensible to extract
+    }
+    let call_end = region.end + 1;
+    let callee = doc[region.callee_start..region.start - 1].trim_end();
+
+    let name = unique_name(doc, symbols, "NewComponent");
+    let unit = indent_unit(doc);
+    let body = reindent(
+        &doc[region.callee_start..call_end],
+        indentation_of_line(doc, region.callee_start),
+        unit,
+    );
+    let definition = format!(
+        "local function {}(props)\n{}return {}\nend\n\n",
+        name, unit, body
+    );
+
+    let insert_at = byte_offset_to_position(doc, top_level_insert_offset(doc, region.callee_start));
+    Some(CodeAction {
+        title: "Extract element into component".to_string(),
+        kind: Some(CodeActionKind::REFACTOR_EXTRACT),
+        edit: Some(single_file_edit(
+            uri,
+            vec![
+                TextEdit {
+                    range: Range {
+                        start: insert_at,
+                        end: insert_at,
+                    },
+                    new_text: definition,
+                },
+                TextEdit {
+                    range: byte_range(doc, region.callee_start, call_end),
+                    new_text: format!("{}({}, {{}})", callee, name),
+                },
+            ],
+        )),
+        ..Default::default()
+    })
+}
+
+fn wrap_element_action(
+    doc: &str,
+    uri: &Url,
+    region: &ElementRegion,
+    title: &str,
+    parent_args: &str,
+) -> Option<CodeAction> {
+    if region.end >= doc.len() {
+        return None;
+    }
+    let call_end = region.end + 1;
+    let callee = doc[region.callee_start..region.start - 1].trim_end();
+
+    let indent = indentation_of_line(doc, region.callee_start);
+    let inner_indent = format!("{}{}", indent, indent_unit(doc));
+    let body = reindent(&doc[region.callee_start..call_end], indent, &inner_indent);
+
+    Some(CodeAction {
+        title: title.to_string(),
+        kind: Some(CodeActionKind::REFACTOR_REWRITE),
+        edit: Some(single_file_edit(
+            uri,
+            vec![TextEdit {
+                range: byte_range(doc, region.callee_start, call_end),
+                new_text: format!(
+                    "{}({}, {{\n{}{},\n{}}})",
+                    callee, parent_args, inner_indent, body, indent
+                ),
+            }],
+        )),
+        ..Default::default()
+    })
+}
+
+fn wrap_element_actions(
+    doc: &str,
+    symbols: &SymbolTable,
+    uri: &Url,
+    byte_offset: usize,
+    regions: &[ElementRegion],
+) -> Vec<CodeAction> {
+    let Some(region) = innermost_region_at(regions, byte_offset) else {
+        return Vec::new();
+    };
+
+    let mut actions = Vec::new();
+    actions.extend(wrap_element_action(
+        doc,
+        uri,
+        region,
+        "Wrap in Frame",
+        "\"Frame\", {}",
+    ));
+    if let Some(react_var_name) = symbols.react_var_name_at(doc, region.callee_start) {
+        actions.extend(wrap_element_action(
+            doc,
+            uri,
+            region,
+            "Wrap in Fragment",
+            &format!("{}.Fragment, nil", react_var_name),
+        ));
+    }
+    actions
+}
+
+// Whether a table argument only holds child elements(e.g. e("Frame", { Label = e("TextLabel") }))
+fn is_children_table(region: &ElementRegion, regions: &[ElementRegion]) -> bool {
+    !region.entries.is_empty()
+        && region.entries.iter().all(|entry| {
+            regions.iter().any(|child| {
+                child.callee_start >= entry.key_range.1 && child.callee_start < entry.range.1
+            })
+        })
+}
+
+fn insert_props_table_action(
+    doc: &str,
+    uri: &Url,
+    byte_offset: usize,
+    regions: &[ElementRegion],
+) -> Option<CodeAction> {
+    let region = innermost_region_at(regions, byte_offset)?;
+    let first_arg = region.args.first()?;
+
+    // (Insert offset, replaced byte count, text, cursor column offset into text)
+    let (start, end, new_text, cursor) = match region.args.get(1) {
+        None => (first_arg.1, first_arg.1, ", {  }", 4),
+        Some(&(a, b)) if &doc[a..b] == "nil" => (a, b, "{  }", 2),
+        Some(&(a, _)) if is_children_table(region, regions) => (a, a, "{  }, ", 2),
+        Some(_) => return None,
+    };
+
+    let range = byte_range(doc, start, end);
+    let cursor = Position {
+        line: range.start.line,
+        character: range.start.character + cursor,
+    };
+    Some(CodeAction {
+        title: "Insert props table".to_string(),
+        kind: Some(CodeActionKind::QUICKFIX),
+        edit: Some(single_file_edit(
+            uri,
+            vec![TextEdit {
+                range,
+                new_text: new_text.to_string(),
+            }],
+        )),
+        // Edits can't move the cursor, so ask server to reveal it inside the new table afterwards
+        command: Some(Command {
+            title: "Move cursor into props table".to_string(),
+            command: SET_CURSOR_COMMAND.to_string(),
+            arguments: Some(vec![json!(uri), json!(cursor)]),
+        }),
+        ..Default::default()
+    })
+}
+
+// Inserts "local <react_var_name> = require(...)" at top of file, but below leading comments and --!strict style directives
+fn react_require_edit(doc: &str, react_var_name: &str, settings: &Settings) -> TextEdit {
+    let mut insert_at = 0;
+    for line in doc.split_inclusive('\n') {
+        if !line.trim_start().starts_with("--") {
+            break;
+        }
+        insert_at += line.len();
+    }
+
+    let position = byte_offset_to_position(doc, insert_at);
+    // Legacy code calling Roact.createElement wants Roact itself
+    let module = if settings.roact.enabled && react_var_name == "Roact" {
+        "Roact"
+    } else {
+        settings.react_require.module_name()
+    };
+    TextEdit {
+        range: Range {
+            start: position,
+            end: position,
+        },
+        new_text: format!(
+            "local {} = require({}.{})\n",
+            react_var_name, settings.react_require.path, module
+        ),
+    }
+}
+
+// Offered when createElement is used but React was never required(e.g. pasted code)
+fn add_react_require_action(
+    doc: &str,
+    symbols: &SymbolTable,
+    uri: &Url,
+    settings: &Settings,
+) -> Option<CodeAction> {
+    let react_var_name = get_unrequired_react_var(doc, symbols.requires())?;
+    Some(CodeAction {
+        title: format!("Add {} require", react_var_name),
+        kind: Some(CodeActionKind::QUICKFIX),
+        edit: Some(single_file_edit(
+            uri,
+            vec![react_require_edit(doc, &react_var_name, settings)],
+        )),
+        ..Default::default()
+    })
+}
+
+// Edits adding a typed function component skeleton called name to doc, None if name isn't an identifier
+// Uses the file's createElement macro(Or React variable), adding the React require when there's none
+// Files already returning something get the component above that return instead of a second one
+pub fn new_component_edits(
+    doc: &str,
+    symbols: &SymbolTable,
+    name: &str,
+    settings: &Settings,
+) -> Option<Vec<TextEdit>> {
+    if !is_valid_identifier(name) {
+        return None;
+    }
+    let mut edits = Vec::new();
+    let react_var_name = match symbols.react_var_name_at(doc, doc.len()) {
+        Some(react_var_name) => react_var_name,
+        None => {
+            let react_var_name = if settings.roact.enabled {
+                "Roact"
+            } else {
+                "React"
+            };
+            edits.push(react_require_edit(doc, react_var_name, settings));
+            react_var_name.to_string()
+        }
+    };
+    let create_element = symbols
+        .create_element_macros(doc, doc.len(), &react_var_name)
+        .into_iter()
+        .next()
+        .unwrap_or_else(|| format!("{}.createElement", react_var_name));
+    // Props is likely taken by another component in the same file
+    let props_type = if doc
+        .lines()
+        .any(|line| line.trim_start().starts_with("type Props "))
+    {
+        format!("{}Props", name)
+    } else {
+        "Props".to_string()
+    };
+
+    // Module's return is the last unindented line starting with it(Table returns can span lines)
+    let module_return = doc
+        .split_inclusive('\n')
+        .scan(0, |line_start, line| {
+            let start = *line_start;
+            *line_start += line.len();
+            Some((start, line))
+        })
+        .filter(|(_, line)| line.starts_with("return") && !line[6..].starts_with(is_ident_char))
+        .last()
+        .map(|(start, _)| start);
+    let skeleton = format!(
+        "type {props_type} = {{}}\n\nlocal function {name}(props: {props_type})\n{}return {create_element}(\"Frame\", {{}})\nend\n",
+        indent_unit(doc)
+    );
+    let (range, new_text) = match module_return {
+        Some(start) => ((start, start), format!("{}\n", skeleton)),
+        // Replaces trailing whitespace so skeleton sits one blank line below existing code
+        None => {
+            let end = doc.trim_end().len();
+            let separator = if end > 0 { "\n\n" } else { "" };
+            (
+                (end, doc.len()),
+                format!("{}{}\nreturn {}\n", separator, skeleton, name),
+            )
+        }
+    };
+    edits.push(TextEdit {
+        range: Range {
+            start: byte_offset_to_position(doc, range.0),
+            end: byte_offset_to_position(doc, range.1),
+        },
+        new_text,
+    });
+    Some(edits)
+}
+
+pub const SOURCE_SORT_PROPS: CodeActionKind = CodeActionKind::new("source.sortProps");
+
+// Only keyed tables without comments between entries get sorted
+// (Positional entries like children arrays have meaningful order, and comments wouldn't move with their entry)
+fn is_sortable_table(doc: &str, region: &ElementRegion) -> bool {
+    let Some((open, close)) = region.props else {
+        return false;
+    };
+    if region.entries.len() < 2
+        || region
+            .entries
+            .iter()
+            .any(|entry| entry.key == PropKey::Positional)
+    {
+        return false;
+    }
+
+    let mut gap_start = open + 1;
+    for entry in &region.entries {
+        if doc[gap_start..entry.range.0].contains("--") {
+            return false;
+        }
+        gap_start = entry.range.1;
+    }
+    !doc[gap_start..close].contains("--")
+}
+
+fn sorted_entry_order(
+    region: &ElementRegion,
+    api_manager: &ApiManager,
+    order: PropsOrder,
+) -> Vec<usize> {
+    let ranking: Vec<String> = match order {
+        PropsOrder::Frequency => region
+            .class_name
+            .as_ref()
+            .and_then(|name| api_manager.lookup_properties(name))
+            .map(|props| props.into_iter().map(|(name, _)| name).collect())
+            .unwrap_or_default(),
+        PropsOrder::Alphabetical => Vec::new(),
+    };
+
+    let mut indices: Vec<usize> = (0..region.entries.len()).collect();
+    indices.sort_by_key(|&i| match &region.entries[i].key {
+        PropKey::Name(name) => (
+            0,
+            ranking.iter().position(|n| n == name).unwrap_or(usize::MAX),
+            name.to_lowercase(),
+        ),
+        PropKey::Computed(key) => (1, 0, key.to_lowercase()), // Events and change handlers stay at the bottom
+        PropKey::Positional => (2, 0, String::new()),
+    });
+    indices
+}
+
+// Copies doc[start..end], replacing every props table directly inside of it with its sorted version
+fn rewrite_span(
+    doc: &str,
+    start: usize,
+    end: usize,
+    regions: &[ElementRegion],
+    api_manager: &ApiManager,
+    order: PropsOrder,
+) -> String {
+    let mut tables: Vec<(&ElementRegion, usize, usize)> = regions
+        .iter()
+        .filter_map(|region| region.props.map(|(open, close)| (region, open, close)))
+        .filter(|&(_, open, close)| open >= start && close < end)
+        .collect();
+    tables.sort_by_key(|&(_, open, _)| open);
+
+    let mut text = String::new();
+    let mut cursor = start;
+    for (region, open, close) in tables {
+        if open < cursor {
+            continue; // Nested in a table we already rewrote
+        }
+        text.push_str(&doc[cursor..open]);
+        text.push_str(&sorted_table_text(doc, region, regions, api_manager, order));
+        cursor = close + 1;
+    }
+    text.push_str(&doc[cursor..end]);
+    text
+}
+
+// Entries get swapped between the original slots, so separators and whitespace stay exactly as they were
+fn sorted_table_text(
+    doc: &str,
+    region: &ElementRegion,
+    regions: &[ElementRegion],
+    api_manager: &ApiManager,
+    order: PropsOrder,
+) -> String {
+    let Some((open, close)) = region.props else {
+        return String::new();
+    };
+    if !is_sortable_table(doc, region) {
+        return format!(
+            "{{{}}}",
+            rewrite_span(doc, open + 1, close, regions, api_manager, order)
+        );
+    }
+
+    let mut text = String::from("{");
+    let mut cursor = open + 1;
+    for (slot, &source) in
+        region
+            .entries
+            .iter()
+            .zip(&sorted_entry_order(region, api_manager, order))
+    {
+        let (start, end) = region.entries[source].range;
+        text.push_str(&doc[cursor..slot.range.0]);
+        text.push_str(&rewrite_span(doc, start, end, regions, api_manager, order));
+        cursor = slot.range.1;
+    }
+    text.push_str(&doc[cursor..close]);
+    text.push('}');
+    text
+}
+
+fn sort_props_action(
+    doc: &str,
+    uri: &Url,
+    regions: &[ElementRegion],
+    api_manager: &ApiManager,
+    order: PropsOrder,
+) -> Option<CodeAction> {
+    let mut edits = Vec::new();
+    let mut cursor = 0;
+    for region in regions {
+        let Some((open, close)) = region.props else {
+            continue;
+        };
+        if open < cursor || close >= doc.len() {
+            continue;
+        }
+        let text = sorted_table_text(doc, region, regions, api_manager, order);
+        if text != doc[open..=close] {
+            edits.push(TextEdit {
+                range: byte_range(doc, open, close + 1),
+                new_text: text,
+            });
+        }
+        cursor = close + 1;
+    }
+    if edits.is_empty() {
+        return None;
+    }
+
+    let title = match order {
+        PropsOrder::Alphabetical => "Sort props alphabetically",
+        PropsOrder::Frequency => "Sort props by usage",
+    };
+    Some(CodeAction {
+        title: title.to_string(),
+        kind: Some(SOURCE_SORT_PROPS),
+        edit: Some(single_file_edit(uri, edits)),
+        ..Default::default()
+    })
+}
+
+fn is_our_diagnostic(diagnostic: &Diagnostic, code: &str) -> bool {
+    diagnostic.source.as_deref() == Some(ITEM_SOURCE)
+        && diagnostic.code == Some(NumberOrString::String(code.to_string()))
+}
+
+pub fn get_code_actions(
+    doc: &str,
+    analysis: &DocumentAnalysis,
+    uri: &Url,
+    range: &Range,
+    diagnostics: &[Diagnostic],
+    api_manager: &ApiManager,
+    settings: &Settings,
+) -> Vec<CodeActionOrCommand> {
+    let symbols = &analysis.symbols;
+    // Big documents don't keep regions around, those get scanned just for this request
+    let scanned;
+    let regions = match &analysis.regions {
+        Some(index) => index.regions(),
+        None => {
+            scanned = RegionIndex::build(doc, symbols);
+            scanned.regions()
+        }
+    };
+    let mut actions = Vec::new();
+    let cursor = position_to_byte_offset(doc, &range.start).unwrap_or(doc.len());
+
+    for diagnostic in diagnostics {
+        if is_our_diagnostic(diagnostic, UNKNOWN_PROPERTY_CODE) {
+            if let Some(action) = did_you_mean_action(doc, uri, diagnostic, regions, api_manager) {
+                actions.push(CodeActionOrCommand::CodeAction(action));
+            }
+        }
+        if is_our_diagnostic(diagnostic, UNKNOWN_PROPERTY_CODE)
+            || is_our_diagnostic(diagnostic, RESTRICTED_PROPERTY_CODE)
+        {
+            if let Some(action) = remove_property_action(doc, uri, diagnostic, regions) {
+                actions.push(CodeActionOrCommand::CodeAction(action));
+            }
+        }
+    }
+
+    if let Some(action) = add_react_require_action(doc, symbols, uri, settings) {
+        actions.push(CodeActionOrCommand::CodeAction(action));
+    }
+    if let Some(action) = insert_props_table_action(doc, uri, cursor, regions) {
+        actions.push(CodeActionOrCommand::CodeAction(action));
+    }
+    if let Some(action) = extract_component_action(doc, symbols, uri, cursor, regions) {
+        actions.push(CodeActionOrCommand::CodeAction(action));
+    }
+    for action in wrap_element_actions(doc, symbols, uri, cursor, regions) {
+        actions.push(CodeActionOrCommand::CodeAction(action));
+    }
+    if let Some(action) =
+        sort_props_action(doc, uri, regions, api_manager, settings.sort_props.order)
+    {
+        actions.push(CodeActionOrCommand::CodeAction(action));
+    }
+
+    actions
+}
+
+#[cfg(test)]
+mod tests {
+    use tower_lsp::lsp_types::{CodeActionOrCommand, DocumentChanges, OneOf, Position, Range, Url};
+
+    use crate::{
+        api_manager::ApiManager,
+        file_diagnoser::{
+            byte_offset_to_position, get_document_diagnostics, position_to_byte_offset,
+            DocumentAnalysis,
+        },
+        file_refactorer::{
+            combined_workspace_edit, edit_distance, fix_all_edits, get_code_actions,
+            is_valid_identifier, new_component_edits, prepare_rename, previewable_workspace_edit,
+            rename,
+        },
+        settings::{PropsOrder, Settings, SortPropsSettings},
+        symbol_table::SymbolTable,
+    };
+
+    const DOC: &str = r#"local React = require(game.Packages.React)
+local e = React.createElement
+
+local function Button(props)
+    return e("TextButton", { Text = "React" }) -- React button
+end
+
+return e(Button, {})
+"#;
+
+    fn position_of(needle: &str, nth: usize) -> Position {
+        let offset = DOC.match_indices(needle).nth(nth).unwrap().0;
+        byte_offset_to_position(DOC, offset)
+    }
+
+    fn position_of_in(doc: &str, needle: &str) -> Position {
+        byte_offset_to_position(doc, doc.find(needle).unwrap())
+    }
+
+    fn rename_count(needle: &str, nth: usize) -> Option<usize> {
+        let uri = Url::parse("file:///test.luau").unwrap();
+        let symbols = SymbolTable::build(DOC, &Default::default());
+        let edit = rename(DOC, &symbols, &uri, &position_of(needle, nth), "Renamed")?;
+        Some(edit.changes.unwrap().get(&uri).unwrap().len())
+    }
+
+    #[test]
+    fn test_rename_react_var() {
+        // Declaration and createElement usage, not the module path, string or comment
+        assert_eq!(rename_count("React", 0), Some(2));
+    }
+
+    #[test]
+    fn test_rename_macro_and_component() {
+        assert_eq!(rename_count("e(", 1), Some(3));
+        assert_eq!(rename_count("Button", 0), Some(2));
+    }
+
+    #[test]
+    fn test_prepare_rename_rejects_unknown() {
+        let symbols = SymbolTable::build(DOC, &Default::default());
+        assert!(prepare_rename(DOC, &symbols, &position_of("props", 0)).is_none());
+        assert!(prepare_rename(DOC, &symbols, &position_of("TextButton", 0)).is_none());
+        assert!(prepare_rename(DOC, &symbols, &position_of("Button", 0)).is_some());
+    }
+
+    #[test]
+    fn test_valid_identifier() {
+        assert!(is_valid_identifier("_Roact2"));
+        assert!(!is_valid_identifier("2Roact"));
+        assert!(!is_valid_identifier("my var"));
+        assert!(!is_valid_identifier(""));
+        assert!(!is_valid_identifier("café"));
+        assert!(!is_valid_identifier("名前"));
+
+        assert!(!is_valid_identifier("end"));
+        assert!(!is_valid_identifier("local"));
+        assert!(!is_valid_identifier("nil"));
+        assert!(is_valid_identifier("type"));
+        assert!(is_valid_identifier("ending"));
+    }
+
+    // Applies the single edit of the first code action offered for doc's diagnostics
+    fn apply_first_action(doc: &str) -> String {
+        apply_action(doc, 0)
+    }
+
+    fn apply_action(doc: &str, index: usize) -> String {
+        apply_action_at(doc, index, Position::default())
+    }
+
+    // Applies all edits of the nth code action offered at cursor
+    fn apply_action_at(doc: &str, index: usize, cursor: Position) -> String {
+        apply_action_with(doc, index, cursor, &Settings::default())
+    }
+
+    fn apply_action_with(doc: &str, index: usize, cursor: Position, settings: &Settings) -> String {
+        let uri = Url::parse("file:///test.luau").unwrap();
+        let api_manager = ApiManager::from_local_dump();
+        let diagnostics = get_document_diagnostics(doc, &Default::default(), &api_manager);
+        let range = Range {
+            start: cursor,
+            end: cursor,
+        };
+        let actions = get_code_actions(
+            doc,
+            &DocumentAnalysis::new(doc, &Default::default()),
+            &uri,
+            &range,
+            &diagnostics,
+            &api_manager,
+            settings,
+        );
+
+        let Some(CodeActionOrCommand::CodeAction(action)) = actions.get(index) else {
+            panic!("Expected a code action");
+        };
+        let changes = action.edit.as_ref().unwrap().changes.as_ref().unwrap();
+        let mut edits: Vec<(usize, usize, &str)> = changes
+            .get(&uri)
+            .unwrap()
+            .iter()
+            .map(|edit| {
+                (
+                    position_to_byte_offset(doc, &edit.range.start).unwrap(),
+                    position_to_byte_offset(doc, &edit.range.end).unwrap(),
+                    edit.new_text.as_str(),
+                )
+            })
+            .collect();
+
+        // Apply back to front so earlier offsets stay valid
+        edits.sort_by_key(|(start, _, _)| std::cmp::Reverse(*start));
+        let mut result = doc.to_string();
+        for (start, end, text) in edits {
+            result.replace_range(start..end, text);
+        }
+        result
+    }
+
+    #[test]
+    fn test_remove_unknown_property() {
+        let doc = r#"local React = require(game.React)
+return React.createElement("Frame", {
+    Visible = true,
+    Qwerty = 1,
+    Size = UDim2.new(1, 0, 1, 0),
+})
+"#;
+        assert_eq!(
+            apply_first_action(doc),
+            r#"local React = require(game.React)
+return React.createElement("Frame", {
+    Visible = true,
+    Size = UDim2.new(1, 0, 1, 0),
+})
+"#
+        );
+
+        let doc = r#"local React = require(game.React)
+return React.createElement("Frame", { Visible = true, Qwerty = "a, b" })
+"#;
+        assert_eq!(
+            apply_first_action(doc),
+            r#"local React = require(game.React)
+return React.createElement("Frame", { Visible = true })
+"#
+        );
+
+        // Comments around the entry stay where they are
+        let doc = r#"local React = require(game.React)
+return React.createElement("Frame", {
+    -- note
+    Qwerty = 1, -- trailing
+    -- about size
+    Size = UDim2.new(1, 0, 1, 0),
+})
+"#;
+        assert_eq!(
+            apply_first_action(doc),
+            r#"local React = require(game.React)
+return React.createElement("Frame", {
+    -- note
+    -- trailing
+    -- about size
+    Size = UDim2.new(1, 0, 1, 0),
+})
+"#
+        );
+        let doc = r#"local React = require(game.React)
+return React.createElement("Frame", { -- note
+    Qwerty = 1,
+})
+"#;
+        assert_eq!(
+            apply_first_action(doc),
+            r#"local React = require(game.React)
+return React.createElement("Frame", { -- note
+})
+"#
+        );
+    }
+
+    #[test]
+    fn test_did_you_mean_property() {
+        let doc = r#"local React = require(game.React)
+return React.createElement("Frame", { BackgroundColour3 = Color3.new(), sizE = 1 })
+"#;
+        // Did you mean comes first, removal second
+        assert_eq!(
+            apply_first_action(doc),
+            r#"local React = require(game.React)
+return React.createElement("Frame", { BackgroundColor3 = Color3.new(), sizE = 1 })
+"#
+        );
+        assert_eq!(
+            apply_action(doc, 1),
+            r#"local React = require(game.React)
+return React.createElement("Frame", { sizE = 1 })
+"#
+        );
+        assert_eq!(
+            apply_action(doc, 2),
+            r#"local React = require(game.React)
+return React.createElement("Frame", { BackgroundColour3 = Color3.new(), Size = 1 })
+"#
+        );
+    }
+
+    #[test]
+    fn test_fix_all() {
+        let api_manager = ApiManager::from_local_dump();
+        let doc = r#"local React = require(game.React)
+return React.createElement("Frame", { BackgroundColour3 = Color3.new(), sizE = 1, Nonsense = 2 })
+"#;
+        // Nonsense has no close property, so it's left for the user
+        let edits = fix_all_edits(doc, &Default::default(), &api_manager);
+        let texts: Vec<&str> = edits.iter().map(|edit| edit.new_text.as_str()).collect();
+        assert_eq!(texts, vec!["BackgroundColor3", "Size"]);
+
+        let uri = Url::parse("file:///test.luau").unwrap();
+        let edit = combined_workspace_edit(vec![(uri.clone(), Some(3), edits.clone())], true);
+        let Some(DocumentChanges::Edits(document_edits)) = edit.document_changes else {
+            panic!("Expected document changes");
+        };
+        assert_eq!(document_edits[0].text_document.version, Some(3));
+        assert_eq!(document_edits[0].edits.len(), 2);
+
+        let edit = combined_workspace_edit(vec![(uri.clone(), Some(3), edits.clone())], false);
+        assert!(edit.document_changes.is_none());
+        assert_eq!(edit.changes.unwrap()[&uri].len(), 2);
+
+        // Annotated edits only when the client understands them, otherwise the plain shape
+        let edit = previewable_workspace_edit(
+            vec![(uri.clone(), Some(3), edits.clone())],
+            "Preview",
+            true,
+            true,
+        );
+        let Some(DocumentChanges::Edits(document_edits)) = edit.document_changes else {
+            panic!("Expected document changes");
+        };
+        assert!(matches!(document_edits[0].edits[0], OneOf::Right(_)));
+        assert_eq!(
+            edit.change_annotations.unwrap()["Preview"].needs_confirmation,
+            Some(true)
+        );
+        let edit = previewable_workspace_edit(vec![(uri, Some(3), edits)], "Preview", true, false);
+        assert!(edit.change_annotations.is_none());
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("BackgroundColour3", "BackgroundColor3"), 1);
+        assert_eq!(edit_distance("textcolor3", "TextColor3"), 0);
+        assert_eq!(edit_distance("", "Size"), 4);
+    }
+
+    #[test]
+    fn test_extract_component() {
+        let doc = r#"local React = require(game.React)
+local e = React.createElement
+
+local function App(props)
+    return e("Frame", {}, {
+        Label = e("TextLabel", {
+            Text = "Hi",
+        }),
+    })
+end
+"#;
+        let cursor = position_of_in(doc, "TextLabel");
+        assert_eq!(
+            apply_action_at(doc, 0, cursor),
+            r#"local React = require(game.React)
+local e = React.createElement
+
+local function NewComponent(props)
+    return e("TextLabel", {
+        Text = "Hi",
+    })
+end
+
+local function App(props)
+    return e("Frame", {}, {
+        Label = e(NewComponent, {}),
+    })
+end
+"#
+        );
+    }
+
+    #[test]
+    fn test_wrap_element() {
+        let doc = r#"local React = require(game.React)
+local e = React.createElement
+
+local function App(props)
+    return e("TextLabel", {
+        Text = "Hi",
+    })
+end
+"#;
+        let cursor = position_of_in(doc, "TextLabel");
+        assert_eq!(
+            apply_action_at(doc, 1, cursor),
+            r#"local React = require(game.React)
+local e = React.createElement
+
+local function App(props)
+    return e("Frame", {}, {
+        e("TextLabel", {
+            Text = "Hi",
+        }),
+    })
+end
+"#
+        );
+        assert_eq!(
+            apply_action_at(doc, 2, cursor),
+            r#"local React = require(game.React)
+local e = React.createElement
+
+local function App(props)
+    return e(React.Fragment, nil, {
+        e("TextLabel", {
+            Text = "Hi",
+        }),
+    })
+end
+"#
+        );
+    }
+
+    #[test]
+    fn test_insert_props_table() {
+        let cases = [
+            (r#"e("Frame")"#, r#"e("Frame", {  })"#, 0),
+            (r#"e("Frame", nil, {})"#, r#"e("Frame", {  }, {})"#, 0),
+            // Children keys are still flagged as unknown props, so removal fix comes first
+            (
+                r#"e("Frame", { Label = e("TextLabel", {}) })"#,
+                r#"e("Frame", {  }, { Label = e("TextLabel", {}) })"#,
+                1,
+            ),
+        ];
+        let header = "local React = require(game.React)\nlocal e = React.createElement\nreturn ";
+        for (before, after, index) in cases {
+            let doc = format!("{}{}\n", header, before);
+            let cursor = position_of_in(&doc, "Frame");
+            assert_eq!(
+                apply_action_at(&doc, index, cursor),
+                format!("{}{}\n", header, after)
+            );
+        }
+    }
+
+    #[test]
+    fn test_sort_props() {
+        let doc = r#"local React = require(game.React)
+local e = React.createElement
+return e("Frame", {
+    Visible = true,
+    [React.Event.MouseEnter] = onEnter,
+    AnchorPoint = Vector2.new(0.5, 0.5),
+}, {
+    Label = e("TextLabel", { Text = "Hi", Font = Enum.Font.Gotham }),
+})
+"#;
+        let settings = Settings {
+            sort_props: SortPropsSettings {
+                order: PropsOrder::Alphabetical,
+            },
+            ..Default::default()
+        };
+        // Only refactors on offer at that cursor are extract, wrap x2, then sorting
+        let cursor = position_of_in(doc, "Frame");
+        assert_eq!(
+            apply_action_with(doc, 3, cursor, &settings),
+            r#"local React = require(game.React)
+local e = React.createElement
+return e("Frame", {
+    AnchorPoint = Vector2.new(0.5, 0.5),
+    Visible = true,
+    [React.Event.MouseEnter] = onEnter,
+}, {
+    Label = e("TextLabel", { Font = Enum.Font.Gotham, Text = "Hi" }),
+})
+"#
+        );
+    }
+
+    #[test]
+    fn test_add_react_require() {
+        let doc = "--!strict\nlocal e = React.createElement\nreturn e(\"Frame\")\n";
+        assert_eq!(
+            apply_action(doc, 0),
+            "--!strict\nlocal React = require(game:GetService(\"ReplicatedStorage\").Packages.React)\nlocal e = React.createElement\nreturn e(\"Frame\")\n"
+        );
+    }
+
+    fn apply_new_component(doc: &str, name: &str) -> String {
+        let edits = new_component_edits(
+            doc,
+            &SymbolTable::build(doc, &Default::default()),
+            name,
+            &Settings::default(),
+        )
+        .unwrap();
+        let mut result = doc.to_string();
+        for edit in edits.iter().rev() {
+            // End of document isn't a position inside it
+            let start = position_to_byte_offset(doc, &edit.range.start).unwrap_or(doc.len());
+            let end = position_to_byte_offset(doc, &edit.range.end).unwrap_or(doc.len());
+            result.replace_range(start..end, &edit.new_text);
+        }
+        result
+    }
+
+    #[test]
+    fn test_new_component() {
+        assert_eq!(
+            apply_new_component("", "Card"),
+            r#"local React = require(game:GetService("ReplicatedStorage").Packages.React)
+type Props = {}
+
+local function Card(props: Props)
+    return React.createElement("Frame", {})
+end
+
+return Card
+"#
+        );
+
+        // File's macro and indentation get used, the existing return stays the only one
+        let doc = "local React = require(game.Packages.React)\nlocal e = React.createElement\n\ntype Props = {}\n\nreturn {\n\tButton = Button,\n}\n";
+        assert_eq!(
+            apply_new_component(doc, "Card"),
+            "local React = require(game.Packages.React)\nlocal e = React.createElement\n\ntype Props = {}\n\ntype CardProps = {}\n\nlocal function Card(props: CardProps)\n\treturn e(\"Frame\", {})\nend\n\nreturn {\n\tButton = Button,\n}\n"
+        );
+
+        // Code without a return gets the component appended below it
+        let doc = "local R = require(game.Packages.React)\nlocal x = 1\n\n\n";
+        assert!(apply_new_component(doc, "Card")
+            .ends_with("local x = 1\n\ntype Props = {}\n\nlocal function Card(props: Props)\n    return R.createElement(\"Frame\", {})\nend\n\nreturn Card\n"));
+        assert!(new_component_edits(
+            "",
+            &SymbolTable::default(),
+            "not valid",
+            &Settings::default()
+        )
+        .is_none());
+    }
+}
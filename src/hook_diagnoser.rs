@@ -0,0 +1,285 @@
+// This script checks hook calls, warning when useEffect's dependency table misses something its body reads
+// (Same idea as eslint's exhaustive-deps, just over the symbol table instead of a full parse)
+
+use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, NumberOrString, Range};
+
+use crate::{
+    file_diagnoser::{
+        byte_offset_to_position, find_call_sites, find_matching_brace, find_matching_paren,
+        split_top_level, ITEM_SOURCE,
+    },
+    rules::code_description,
+    symbol_table::{Symbol, SymbolKind, SymbolTable},
+};
+
+pub const MISSING_DEPENDENCY_CODE: &str = "missing-effect-dependency";
+pub const MISSING_DEPENDENCY_TABLE_CODE: &str = "missing-effect-dependency-table";
+
+// Hooks taking (effect, dependencies)
+const EFFECT_HOOKS: [&str; 2] = ["useEffect", "useLayoutEffect"];
+
+// Hook results that never change between renders, (hook, position in "local a, b = hook(...)")
+const STABLE_HOOK_RESULTS: [(&str, usize); 5] = [
+    ("useState", 1),
+    ("useReducer", 1),
+    ("useRef", 0),
+    ("useBinding", 0),
+    ("useBinding", 1),
+];
+
+fn diagnostic(doc: &str, range: (usize, usize), code: &str, message: String) -> Diagnostic {
+    Diagnostic {
+        range: Range {
+            start: byte_offset_to_position(doc, range.0),
+            end: byte_offset_to_position(doc, range.1),
+        },
+        severity: Some(DiagnosticSeverity::WARNING),
+        code: Some(NumberOrString::String(code.to_string())),
+        code_description: code_description(code),
+        source: Some(ITEM_SOURCE.to_string()),
+        message,
+        ..Default::default()
+    }
+}
+
+// Whether symbol holds a hook result that stays the same every render(e.g. useState's setter)
+fn is_stable(
+    doc: &str,
+    symbols: &SymbolTable,
+    react_var_names: &[String],
+    symbol: &Symbol,
+) -> bool {
+    if symbol.kind != SymbolKind::Local {
+        return false;
+    }
+    let index = symbols
+        .symbols
+        .iter()
+        .position(|s| std::ptr::eq(s, symbol))
+        .unwrap_or(0);
+    // Only the first name of "local a, b = ..." gets the value, the second is right after it
+    let (value_symbol, position) = match symbol.value {
+        Some(_) => (symbol, 0),
+        None => match index.checked_sub(1).map(|i| &symbols.symbols[i]) {
+            Some(previous) if previous.kind == SymbolKind::Local && previous.value.is_some() => {
+                (previous, 1)
+            }
+            _ => return false,
+        },
+    };
+    let Some((callee, _)) = symbols
+        .value_text(doc, value_symbol)
+        .and_then(|value| value.split_once('('))
+    else {
+        return false;
+    };
+    STABLE_HOOK_RESULTS.iter().any(|&(hook, stable_position)| {
+        stable_position == position
+            && react_var_names
+                .iter()
+                .any(|name| callee.trim() == format!("{}.{}", name, hook))
+    })
+}
+
+// Identifier at span plus the member chain after it(e.g. "props.text" out of props.text:upper())
+fn member_path(doc: &str, span: (usize, usize)) -> String {
+    let mut end = span.1;
+    while let Some(rest) = doc[end..].strip_prefix('.') {
+        let len = rest
+            .find(|c: char| !c.is_alphanumeric() && c != '_')
+            .unwrap_or(rest.len());
+        if len == 0 {
+            break;
+        }
+        end += 1 + len;
+    }
+    // Method calls(a.b:c()) stop the chain, the object is what gets read
+    doc[span.0..end].to_string()
+}
+
+fn is_covered(path: &str, dependencies: &[String]) -> bool {
+    dependencies.iter().any(|dependency| {
+        path == dependency
+            || path
+                .strip_prefix(dependency.as_str())
+                .is_some_and(|rest| rest.starts_with('.'))
+    })
+}
+
+// Paths effect function reads that come from the component(Locals, functions and props declared outside it)
+fn effect_reads(
+    doc: &str,
+    symbols: &SymbolTable,
+    react_var_names: &[String],
+    effect: (usize, usize),
+) -> Vec<String> {
+    let mut paths: Vec<String> = Vec::new();
+    for span in symbols.identifiers_in(effect) {
+        let name = &doc[span.0..span.1];
+        let Some(symbol) = symbols.resolve(name, span.0) else {
+            continue;
+        };
+        // Declared inside the effect itself, or at module level where it can't change per render
+        let inside_effect = symbol.range.0 >= effect.0 && symbol.range.0 < effect.1;
+        let module_level = symbol.kind != SymbolKind::Parameter && symbol.scope.1 >= doc.len();
+        if inside_effect || module_level {
+            continue;
+        }
+        if react_var_names.iter().any(|react| react == name)
+            || is_stable(doc, symbols, react_var_names, symbol)
+        {
+            continue;
+        }
+        // Assigning a component local inside an effect doesn't read it
+        let rest = doc[span.1..].trim_start_matches([' ', '\t']);
+        if rest.starts_with('=') && !rest.starts_with("==") {
+            continue;
+        }
+        let path = member_path(doc, span);
+        if !paths.contains(&path) {
+            paths.push(path);
+        }
+    }
+    paths
+}
+
+pub fn get_hook_diagnostics(doc: &str, symbols: &SymbolTable) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let react_var_names = symbols.react_var_names(doc);
+
+    for react_var_name in &react_var_names {
+        for hook in EFFECT_HOOKS {
+            let needle = format!("{}.{}(", react_var_name, hook);
+            for (callee_start, start) in find_call_sites(doc, &needle) {
+                let end = find_matching_paren(doc, start);
+                let args: Vec<(usize, usize)> = split_top_level(&doc[start..end], &[','])
+                    .into_iter()
+                    .map(|(a, b)| (start + a, start + b))
+                    .collect();
+                let Some(&effect) = args
+                    .first()
+                    .filter(|&&(a, _)| doc[a..].starts_with("function"))
+                else {
+                    continue;
+                };
+                let callee_end = start - 1;
+
+                let Some(&(table_start, table_end)) = args.get(1) else {
+                    diagnostics.push(diagnostic(
+                        doc,
+                        (callee_start, callee_end),
+                        MISSING_DEPENDENCY_TABLE_CODE,
+                        format!(
+                            "{} has no dependency table, so it runs after every render. Pass {{}} to run it once",
+                            hook
+                        ),
+                    ));
+                    continue;
+                };
+                // Dependencies held in a variable can't be checked
+                if !doc[table_start..].starts_with('{') {
+                    continue;
+                }
+                let inner_end = find_matching_brace(doc, table_start + 1).min(table_end);
+                let inner = &doc[table_start + 1..inner_end];
+                let dependencies: Vec<String> = split_top_level(inner, &[',', ';'])
+                    .into_iter()
+                    .map(|(a, b)| inner[a..b].chars().filter(|c| !c.is_whitespace()).collect())
+                    .collect();
+
+                let mut missing: Vec<String> = effect_reads(doc, symbols, &react_var_names, effect)
+                    .into_iter()
+                    .filter(|path| !is_covered(path, &dependencies))
+                    .collect();
+                // props.a.b is already covered by reporting props.a
+                let shorter = missing.clone();
+                missing.retain(|path| {
+                    !shorter
+                        .iter()
+                        .any(|other| other != path && is_covered(path, std::slice::from_ref(other)))
+                });
+                if missing.is_empty() {
+                    continue;
+                }
+
+                let plural = if missing.len() == 1 { "y" } else { "ies" };
+                diagnostics.push(diagnostic(
+                    doc,
+                    (table_start, table_end),
+                    MISSING_DEPENDENCY_CODE,
+                    format!(
+                        "{} is missing dependenc{}: {}",
+                        hook,
+                        plural,
+                        missing.join(", ")
+                    ),
+                ));
+            }
+        }
+    }
+
+    diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use tower_lsp::lsp_types::NumberOrString;
+
+    use crate::hook_diagnoser::{
+        get_hook_diagnostics, MISSING_DEPENDENCY_CODE, MISSING_DEPENDENCY_TABLE_CODE,
+    };
+    use crate::symbol_table::SymbolTable;
+
+    const DOC: &str = r#"local React = require(game.Packages.React)
+local Theme = require(script.Parent.Theme)
+
+local function Counter(props)
+    local count, setCount = React.useState(0)
+    local ref = React.useRef(nil)
+    local step = props.step or 1
+
+    React.useEffect(function()
+        local doubled = count * 2
+        setCount(doubled + step)
+        ref.current = Theme.color
+        print(props.label.text, props.label)
+    end, { count })
+
+    React.useEffect(function()
+        print(count, props.label)
+    end, { count, props.label })
+
+    React.useLayoutEffect(function()
+        print("mounted")
+    end)
+
+    React.useEffect(function()
+        print(count)
+    end, deps)
+end
+"#;
+
+    #[test]
+    fn test_effect_dependencies() {
+        let diagnostics = get_hook_diagnostics(DOC, &SymbolTable::build(DOC, &Default::default()));
+        let codes: Vec<&NumberOrString> = diagnostics
+            .iter()
+            .filter_map(|diagnostic| diagnostic.code.as_ref())
+            .collect();
+        assert_eq!(
+            codes,
+            [
+                &NumberOrString::String(MISSING_DEPENDENCY_CODE.to_string()),
+                &NumberOrString::String(MISSING_DEPENDENCY_TABLE_CODE.to_string())
+            ]
+        );
+        // Setters, refs, module locals and the effect's own locals aren't dependencies
+        assert_eq!(
+            diagnostics[0].message,
+            "useEffect is missing dependencies: step, props.label"
+        );
+        assert!(diagnostics[1]
+            .message
+            .starts_with("useLayoutEffect has no dependency table"));
+    }
+}
@@ -0,0 +1,210 @@
+// This script wraps the LSP service to move fields shared by every completion item into CompletionList.itemDefaults
+// (tower-lsp's CompletionList type predates LSP 3.17, so we do it on the raw json response instead)
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, RwLock};
+use std::task::{Context, Poll};
+
+use serde_json::{Map, Value};
+use tower::Service;
+use tower_lsp::jsonrpc::{Request, Response};
+
+// Item fields that map 1:1 onto an itemDefaults entry of the same name
+const PLAIN_DEFAULTS: [&str; 4] = [
+    "commitCharacters",
+    "insertTextFormat",
+    "insertTextMode",
+    "data",
+];
+
+fn supported_item_defaults(initialize_params: &Value) -> Vec<String> {
+    initialize_params
+        .pointer("/capabilities/textDocument/completion/completionList/itemDefaults")
+        .and_then(Value::as_array)
+        .map(|defaults| {
+            defaults
+                .iter()
+                .filter_map(Value::as_str)
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+// Value of field if every item has exactly the same one
+fn shared_value<'a>(items: &'a [Value], pointer: &str) -> Option<&'a Value> {
+    let first = items.first()?.pointer(pointer)?;
+    items
+        .iter()
+        .all(|item| item.pointer(pointer) == Some(first))
+        .then_some(first)
+}
+
+pub fn hoist_item_defaults(result: &mut Value, supported: &[String]) {
+    // Plain arrays can't carry defaults, so convert to a list first
+    if result.is_array() {
+        let items = result.take();
+        *result = serde_json::json!({ "isIncomplete": false, "items": items });
+    }
+    let Some(list) = result.as_object_mut() else {
+        return;
+    };
+    let Some(Value::Array(items)) = list.get_mut("items") else {
+        return;
+    };
+    if items.len() < 2 {
+        return;
+    }
+
+    let mut defaults = Map::new();
+    for field in PLAIN_DEFAULTS {
+        if !supported.iter().any(|s| s == field) {
+            continue;
+        }
+        if let Some(value) = shared_value(items, &format!("/{}", field)).cloned() {
+            for item in items.iter_mut() {
+                item.as_object_mut().map(|item| item.remove(field));
+            }
+            defaults.insert(field.to_string(), value);
+        }
+    }
+
+    // Same replace range everywhere, so items only need to carry their new text
+    if supported.iter().any(|s| s == "editRange") {
+        if let Some(range) = shared_value(items, "/textEdit/range").cloned() {
+            for item in items.iter_mut() {
+                let Some(item) = item.as_object_mut() else {
+                    continue;
+                };
+                if let Some(Value::Object(edit)) = item.remove("textEdit") {
+                    let new_text = edit.get("newText").cloned().unwrap_or_default();
+                    if item.get("label") != Some(&new_text) {
+                        item.insert("textEditText".to_string(), new_text);
+                    }
+                }
+            }
+            defaults.insert("editRange".to_string(), range);
+        }
+    }
+
+    if !defaults.is_empty() {
+        list.insert("itemDefaults".to_string(), Value::Object(defaults));
+    }
+}
+
+#[derive(Clone)]
+pub struct ItemDefaultsService<S> {
+    inner: S,
+    supported: Arc<RwLock<Vec<String>>>,
+}
+
+impl<S> ItemDefaultsService<S> {
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner,
+            supported: Arc::new(RwLock::new(Vec::new())),
+        }
+    }
+}
+
+impl<S> Service<Request> for ItemDefaultsService<S>
+where
+    S: Service<Request, Response = Option<Response>>,
+    S::Future: Send + 'static,
+{
+    type Response = Option<Response>;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        let is_completion = req.method() == "textDocument/completion";
+        if req.method() == "initialize" {
+            if let Some(params) = req.params() {
+                *self.supported.write().unwrap() = supported_item_defaults(params);
+            }
+        }
+
+        let supported = self.supported.read().unwrap().clone();
+        let fut = self.inner.call(req);
+
+        Box::pin(async move {
+            let response = fut.await?;
+            if !is_completion || supported.is_empty() {
+                return Ok(response);
+            }
+
+            Ok(response.map(|response| {
+                let (id, body) = response.into_parts();
+                let body = body.map(|mut result| {
+                    hoist_item_defaults(&mut result, &supported);
+                    result
+                });
+                Response::from_parts(id, body)
+            }))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::item_defaults::{hoist_item_defaults, supported_item_defaults};
+
+    #[test]
+    fn test_supported_item_defaults() {
+        let params = json!({ "capabilities": { "textDocument": { "completion": {
+            "completionList": { "itemDefaults": ["commitCharacters", "editRange"] }
+        }}}});
+        assert_eq!(
+            supported_item_defaults(&params),
+            vec!["commitCharacters", "editRange"]
+        );
+        assert!(supported_item_defaults(&json!({ "capabilities": {} })).is_empty());
+    }
+
+    #[test]
+    fn test_hoist_item_defaults() {
+        let range =
+            json!({ "start": { "line": 0, "character": 1 }, "end": { "line": 0, "character": 2 } });
+        let mut result = json!([
+            { "label": "Size", "commitCharacters": ["="], "insertTextFormat": 2,
+              "textEdit": { "range": range, "newText": "Size = $0," } },
+            { "label": "Visible", "commitCharacters": ["="], "insertTextFormat": 1,
+              "textEdit": { "range": range, "newText": "Visible" } },
+        ]);
+        let supported: Vec<String> = ["commitCharacters", "insertTextFormat", "editRange"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+
+        hoist_item_defaults(&mut result, &supported);
+        assert_eq!(
+            result,
+            json!({
+                "isIncomplete": false,
+                "itemDefaults": { "commitCharacters": ["="], "editRange": range },
+                "items": [
+                    { "label": "Size", "insertTextFormat": 2, "textEditText": "Size = $0," },
+                    { "label": "Visible", "insertTextFormat": 1 },
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn test_hoist_needs_support() {
+        let original = json!({ "isIncomplete": true, "items": [
+            { "label": "A", "commitCharacters": ["="] },
+            { "label": "B", "commitCharacters": ["="] },
+        ]});
+        let mut result = original.clone();
+        hoist_item_defaults(&mut result, &[]);
+        assert_eq!(result, original);
+    }
+}
@@ -0,0 +1,151 @@
+// This script reads require aliases out of .luaurc files, so string requires(require("@Packages/React"))
+// get recognized as React too. Aliases only matter when their name doesn't already say React(e.g. "@UI")
+
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::file_manager::find_workspace_files;
+use crate::symbol_table::RequireContext;
+
+pub const LUAURC_FILE: &str = ".luaurc";
+
+#[derive(Deserialize, Debug, Default)]
+struct Luaurc {
+    #[serde(default)]
+    aliases: HashMap<String, String>,
+}
+
+pub fn is_luaurc_file(path: &Path) -> bool {
+    path.file_name().and_then(|name| name.to_str()) == Some(LUAURC_FILE)
+}
+
+pub fn find_luaurc_files(root: &Path) -> Vec<PathBuf> {
+    find_workspace_files(root, is_luaurc_file)
+}
+
+// Last segment of a require path, without extension or trailing init(So "../Packages/React/init.luau" is React)
+pub(crate) fn module_name(path: &str) -> &str {
+    let mut segments = path
+        .trim_end_matches('/')
+        .rsplit('/')
+        .map(|segment| {
+            segment
+                .strip_suffix(".luau")
+                .or_else(|| segment.strip_suffix(".lua"))
+                .unwrap_or(segment)
+        })
+        .filter(|segment| !segment.is_empty());
+    match segments.next() {
+        Some("init") => segments.next().unwrap_or(""),
+        Some(name) => name,
+        None => "",
+    }
+}
+
+// Segments of a string require path, without extensions, alias @ or trailing init
+// ("@Packages/React/init.luau" is Packages, React)
+pub(crate) fn string_path_segments(path: &str) -> Vec<&str> {
+    let mut segments: Vec<&str> = path
+        .split('/')
+        .map(|segment| {
+            segment
+                .strip_suffix(".luau")
+                .or_else(|| segment.strip_suffix(".lua"))
+                .unwrap_or(segment)
+                .trim_start_matches('@')
+        })
+        .filter(|segment| !matches!(*segment, "" | "." | ".."))
+        .collect();
+    if segments.last() == Some(&"init") {
+        segments.pop();
+    }
+    segments
+}
+
+// Names of aliases pointing at React(As requires counts it), plus a message for each file that failed to load
+pub fn load_react_aliases(
+    requires: &RequireContext,
+    paths: &[PathBuf],
+) -> (Vec<String>, Vec<String>) {
+    let mut aliases = Vec::new();
+    let mut warnings = Vec::new();
+    for path in paths {
+        let luaurc = match fs::read_to_string(path) {
+            Ok(text) => serde_json::from_str::<Luaurc>(&text).map_err(|e| e.to_string()),
+            Err(e) => Err(e.to_string()),
+        };
+        match luaurc {
+            Ok(luaurc) => aliases.extend(
+                luaurc
+                    .aliases
+                    .into_iter()
+                    .filter(|(_, target)| requires.is_react_module(&string_path_segments(target)))
+                    .map(|(name, _)| name.to_lowercase()),
+            ),
+            Err(e) => warnings.push(format!("Failed to load {}: {}", path.display(), e)),
+        }
+    }
+    aliases.sort();
+    aliases.dedup();
+    (aliases, warnings)
+}
+
+// Path of require("...") with a string literal argument
+pub(crate) fn string_require_path(value: &str) -> Option<&str> {
+    let inner = value
+        .trim()
+        .strip_prefix("require")?
+        .trim_start()
+        .strip_prefix('(')?
+        .strip_suffix(')')?
+        .trim();
+    let quote = inner
+        .chars()
+        .next()
+        .filter(|c| matches!(c, '"' | '\'' | '`'))?;
+    inner.strip_prefix(quote)?.strip_suffix(quote)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs};
+
+    use crate::luaurc::{find_luaurc_files, load_react_aliases, string_require_path};
+    use crate::symbol_table::RequireContext;
+
+    #[test]
+    fn test_react_aliases() {
+        assert_eq!(
+            string_require_path(r#"require("@Packages/React")"#),
+            Some("@Packages/React")
+        );
+        assert_eq!(string_require_path("require(script.React)"), None);
+        let mut requires = RequireContext::default();
+        assert!(requires.is_react_require_path("@Packages/React"));
+        assert!(requires.is_react_require_path("../Packages/React/init.luau"));
+        assert!(requires.is_react_require_path("@react"));
+        assert!(!requires.is_react_require_path("./ReactRoblox"));
+
+        let root = env::temp_dir().join("rblx_react_lsp_tests/luaurc");
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        fs::write(
+            root.join(".luaurc"),
+            r#"{ "aliases": { "UiLib": "Packages/React/", "Shared": "src/shared" } }"#,
+        )
+        .unwrap();
+
+        let (aliases, warnings) = load_react_aliases(&requires, &find_luaurc_files(&root));
+        assert!(warnings.is_empty());
+        assert_eq!(aliases, ["uilib"]);
+
+        assert!(!requires.is_react_require_path("@UiLib"));
+        requires.set_react_aliases(aliases);
+        assert!(requires.is_react_require_path("@UiLib"));
+        assert!(!requires.is_react_require_path("@Shared"));
+
+        fs::remove_dir_all(&root).ok();
+    }
+}
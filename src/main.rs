@@ -1,50 +1,858 @@
+mod api_fetcher;
 mod api_manager;
 mod api_parser;
+mod cli;
+mod component_index;
+mod custom_classes;
+mod element_context;
+mod element_tree;
 mod file_diagnoser;
 mod file_manager;
+mod file_refactorer;
+mod hook_diagnoser;
+mod item_defaults;
+mod luaurc;
+mod request_cancel;
+mod roact_migration;
+mod rules;
+mod settings;
+mod sourcemap;
+mod symbol_table;
+mod type_exporter;
 
-use std::{path::PathBuf, sync::Arc};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    path::PathBuf,
+    sync::Arc,
+    time::Duration,
+};
 
-use serde_json::Value;
-use tokio::sync::Mutex;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver};
+use tokio::sync::{Mutex, RwLock};
+use tokio_util::sync::CancellationToken;
 use tower_lsp::{
-    jsonrpc::Result,
+    jsonrpc::{Error, Result},
     lsp_types::{
-        CompletionOptions, CompletionParams, CompletionResponse, DidChangeTextDocumentParams,
-        DidCloseTextDocumentParams, DidOpenTextDocumentParams, ExecuteCommandOptions,
-        ExecuteCommandParams, InitializeParams, InitializeResult, InitializedParams,
-        MessageActionItem, MessageType, ServerCapabilities, TextDocumentSyncCapability,
-        TextDocumentSyncKind,
+        notification::{Notification, Progress},
+        request::{ShowDocument, WorkDoneProgressCreate},
+        ClientCapabilities, CodeActionKind, CodeActionOptions, CodeActionParams,
+        CodeActionProviderCapability, CodeActionResponse, CompletionItem, CompletionOptions,
+        CompletionParams, CompletionResponse, Diagnostic, DidChangeConfigurationParams,
+        DidChangeTextDocumentParams, DidChangeWatchedFilesParams,
+        DidChangeWatchedFilesRegistrationOptions, DidCloseTextDocumentParams,
+        DidOpenTextDocumentParams, ExecuteCommandOptions, ExecuteCommandParams, FileSystemWatcher,
+        GotoDefinitionParams, GotoDefinitionResponse, Hover, HoverParams, HoverProviderCapability,
+        InitializeParams, InitializeResult, InitializedParams, MessageActionItem, MessageType,
+        NumberOrString, OneOf, Position, PrepareRenameResponse, ProgressParams,
+        ProgressParamsValue, Range, Registration, RenameOptions, RenameParams, ServerCapabilities,
+        ShowDocumentParams, TextDocumentPositionParams, TextDocumentSyncCapability,
+        TextDocumentSyncKind, TextEdit, Url, WorkDoneProgress, WorkDoneProgressBegin,
+        WorkDoneProgressCancelParams, WorkDoneProgressCreateParams, WorkDoneProgressEnd,
+        WorkDoneProgressReport, WorkspaceEdit,
     },
     Client, LanguageServer, LspService, Server,
 };
 
 use crate::{
-    api_manager::ApiManager,
-    api_parser::{create_api_file_readable, get_live_version},
-    file_diagnoser::generate_auto_completions,
-    file_manager::FileManager,
+    api_fetcher::{ApiFetcher, FetcherConfig},
+    api_manager::{ApiManager, ApiStage},
+    api_parser::{
+        cache_file_size, clear_cache_files, create_api_file_readable, load_usage_counts,
+        save_usage_counts, BUNDLED_VERSION, LOCAL_VERSION_PREFIX,
+    },
+    cli::{parse_args, version, Command, Transport, USAGE},
+    component_index::{is_luau_file, ComponentIndex},
+    custom_classes::{
+        find_custom_class_files, is_custom_class_file, parse_custom_class_file, CUSTOM_CLASSES_FILE,
+    },
+    element_tree::{TreeSync, TreeSyncParams, TreeSyncSchedule, TreeSyncThrottle},
+    file_diagnoser::{
+        generate_auto_completions, get_debug_dump, get_document_diagnostics,
+        get_document_diagnostics_cancellable, get_element_regions, get_hover,
+        position_to_byte_offset, resolve_completion_item, CompletionTrigger,
+    },
+    file_manager::{find_workspace_files, FileManager, FreqScan, FreqScanQueue},
+    file_refactorer::{
+        combined_workspace_edit, fix_all_edits, get_code_actions, is_valid_identifier,
+        new_component_edits, prepare_rename, previewable_workspace_edit, rename, FIX_ALL_COMMAND,
+        NEW_COMPONENT_COMMAND, SET_CURSOR_COMMAND, SOURCE_SORT_PROPS,
+    },
+    item_defaults::ItemDefaultsService,
+    luaurc::{find_luaurc_files, is_luaurc_file, load_react_aliases, LUAURC_FILE},
+    request_cancel::{request_cancel, CancelRequestService},
+    roact_migration::{migrate_roact_edits, MIGRATE_ROACT_COMMAND},
+    rules::{rule_docs_markdown, RULE_DOCS_COMMAND},
+    settings::Settings,
+    sourcemap::{find_sourcemap_files, is_sourcemap_file, Sourcemap, DEFAULT_SOURCEMAP},
+    symbol_table::{RequireContext, SymbolTable},
+    type_exporter::{export_types, TypeExportFormat},
 };
 
 #[derive(Debug)]
 struct Backend {
     client: Client,
     file_manager: Arc<Mutex<FileManager>>,
-    api_manager: Arc<Mutex<ApiManager>>,
+    api_manager: Arc<RwLock<ApiManager>>,
+    api_fetcher: Arc<Mutex<ApiFetcher>>, // Rebuilt when download settings change
+    // Locked after api_manager and before settings
+    components: Arc<Mutex<ComponentIndex>>,
+    settings: Arc<Mutex<Settings>>,
+    tree_sync: Arc<Mutex<TreeSyncThrottle>>,
+    freq_scans: Arc<Mutex<FreqScanQueue>>,
+    // Shared by running API downloads/loads, cancelled(And replaced) when user cancels them
+    api_cancel: Arc<Mutex<CancellationToken>>,
+    audit_cancel: Arc<Mutex<CancellationToken>>, // Same, for auditWorkspace
+    // Latest completion request's token, cancelled when the next one comes in
+    completion_cancel: Arc<Mutex<CancellationToken>>,
+    diagnostic_cancels: Arc<Mutex<HashMap<Url, CancellationToken>>>, // Same, per document's diagnostics pass
+    api_load: Arc<Mutex<ApiLoadState>>,                              // Locked after api_manager
+    workspace_root: Arc<Mutex<Option<PathBuf>>>,
+    client_capabilities: Arc<Mutex<ClientCapabilities>>, // Set once in initialize
+    // Stops persist_usage_counts' saving, at shutdown or once the connection closes without one
+    persist_cancel: CancellationToken,
+}
+
+// Whether an API load is running, and whether completions went out without an API meanwhile
+#[derive(Debug, Default)]
+struct ApiLoadState {
+    loading: bool,
+    waiting: bool, // Client gets ApiLoaded once the load finishes
+    retried: bool, // Completions retry a failed startup load once, commands are for the rest
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ApiLoadedParams {
+    version: String,
+}
+
+// Sent when an API load finishes after completions were answered without one, so client asks again
+enum ApiLoaded {}
+
+impl Notification for ApiLoaded {
+    type Params = ApiLoadedParams;
+    const METHOD: &'static str = "rblxReact/apiLoaded";
+}
+
+const FREQ_SCAN_DELAY: Duration = Duration::from_millis(300);
+const FREQ_SAVE_INTERVAL: Duration = Duration::from_secs(300);
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(120);
+const LOAD_TIMEOUT: Duration = Duration::from_secs(30);
+const GEN_METADATA_PROGRESS: &str = "rblx-react-lsp/genMetadata";
+const AUDIT_WORKSPACE_PROGRESS: &str = "rblx-react-lsp/auditWorkspace";
+
+impl Backend {
+    async fn apply_settings(&self, settings: Settings) {
+        let mut api_manager = self.api_manager.write().await;
+        api_manager.set_excluded_properties(settings.completion.excluded_properties.clone());
+        api_manager.set_deterministic_ranking(settings.completion.deterministic_ranking);
+        api_manager.set_sorting(settings.completion.sorting);
+        api_manager.set_member_settings(settings.completion.members);
+        api_manager.set_class_bases(settings.completion.class_bases.clone());
+        api_manager.set_pinned_version(settings.api.pinned().map(str::to_string));
+        api_manager.set_max_age(settings.api.max_age());
+        api_manager.set_dump_path(settings.api.dump_path());
+        api_manager.set_cache_dir(settings.api.cache_dir());
+        drop(api_manager);
+        let config = settings.api.fetcher_config();
+        let mut api_fetcher = self.api_fetcher.lock().await;
+        if *api_fetcher.config() != config {
+            match ApiFetcher::with_config(config) {
+                Ok(fetcher) => *api_fetcher = fetcher,
+                Err(e) => {
+                    self.client
+                        .show_message(
+                            MessageType::ERROR,
+                            format!(
+                                "Invalid API download settings, keeping previous ones: {}",
+                                e
+                            ),
+                        )
+                        .await
+                }
+            }
+        }
+        drop(api_fetcher);
+        let mut file_manager = self.file_manager.lock().await;
+        let mut requires = RequireContext::clone(file_manager.requires());
+        requires.set_roact_compat(settings.roact.enabled);
+        for warning in requires.set_react_modules(&settings.react_require.modules) {
+            self.client.log_message(MessageType::WARNING, warning).await;
+        }
+        *self.settings.lock().await = settings;
+        requires.set_create_element_modules(
+            create_element_modules(&self.components, &self.settings).await,
+        );
+        file_manager.set_requires(requires);
+    }
+
+    // Cancels the document's running diagnostics pass, handing out the token of the next one
+    async fn supersede_diagnostics(&self, uri: &Url) -> CancellationToken {
+        let cancel = CancellationToken::new();
+        let previous = self
+            .diagnostic_cancels
+            .lock()
+            .await
+            .insert(uri.clone(), cancel.clone());
+        if let Some(previous) = previous {
+            previous.cancel();
+        }
+        cancel
+    }
+
+    // Completion came in before any API got loaded, retrying the load if nothing is running it
+    async fn load_api_on_demand(&self) {
+        let mut api_load = self.api_load.lock().await;
+        api_load.waiting = true;
+        if api_load.loading || api_load.retried {
+            return;
+        }
+        api_load.loading = true;
+        api_load.retried = true;
+        drop(api_load);
+
+        let client = self.client.clone();
+        let api_manager = self.api_manager.clone();
+        let api_fetcher = self.api_fetcher.lock().await.clone();
+        let api_cancel = self.api_cancel.clone();
+        let api_load = self.api_load.clone();
+        tokio::spawn(async move {
+            let cancel = api_cancel.lock().await.clone();
+            let loaded =
+                load_api_with_progress(&client, &api_manager, &api_fetcher, &cancel, &api_load)
+                    .await;
+            if let Err(e) = loaded {
+                client
+                    .log_message(
+                        MessageType::ERROR,
+                        format!("Failed to load Roblox API on demand ({})", e),
+                    )
+                    .await;
+            }
+        });
+    }
+
+    async fn cancel_api_operations(&self) {
+        let mut cancel = self.api_cancel.lock().await;
+        cancel.cancel();
+        *cancel = CancellationToken::new();
+    }
+
+    // Not part of tower-lsp's LanguageServer trait, registered as custom method in main
+    async fn work_done_progress_cancel(&self, params: WorkDoneProgressCancelParams) {
+        if params.token == NumberOrString::String(GEN_METADATA_PROGRESS.to_string()) {
+            self.cancel_api_operations().await;
+        } else if params.token == NumberOrString::String(AUDIT_WORKSPACE_PROGRESS.to_string()) {
+            self.audit_cancel.lock().await.cancel();
+        }
+    }
+
+    // Re-reads given custom class files(Missing ones get removed) and republishes diagnostics of
+    // open documents using any class that changed
+    async fn reload_custom_classes(&self, paths: Vec<PathBuf>) {
+        let file_manager = self.file_manager.lock().await;
+        let mut api_manager = self.api_manager.write().await;
+        let mut affected = Vec::new();
+
+        for path in paths {
+            let classes = match std::fs::read_to_string(&path) {
+                Ok(text) => match parse_custom_class_file(&path, &text) {
+                    Ok(classes) => classes,
+                    Err(e) => {
+                        // Keep previous classes around until file is valid again
+                        self.client
+                            .log_message(
+                                MessageType::WARNING,
+                                format!("Failed to parse {}: {}", path.display(), e),
+                            )
+                            .await;
+                        continue;
+                    }
+                },
+                Err(_) => Vec::new(),
+            };
+            affected.extend(api_manager.set_custom_classes(path, classes));
+        }
+        if affected.is_empty() {
+            return;
+        }
+        // Republishing only reads, so completions can go on meanwhile
+        let api_manager = api_manager.downgrade();
+
+        let requires = file_manager.requires();
+        for (uri, text, version) in file_manager.open_documents() {
+            let uses_affected = get_element_regions(text, requires).iter().any(|region| {
+                region
+                    .class_name
+                    .as_ref()
+                    .is_some_and(|name| affected.contains(name))
+            });
+            if uses_affected {
+                self.publish_diagnostics(
+                    uri.clone(),
+                    get_document_diagnostics(text, requires, &api_manager),
+                    Some(version),
+                )
+                .await;
+            }
+        }
+    }
+
+    // Re-reads component modules changed on disk(Open documents are kept up to date by did_change)
+    async fn reindex_component_modules(&self, paths: Vec<PathBuf>) {
+        let mut file_manager = self.file_manager.lock().await;
+        let mut components = self.components.lock().await;
+        for path in paths {
+            let Ok(uri) = Url::from_file_path(&path) else {
+                continue;
+            };
+            if file_manager.get_text(&uri).is_some() {
+                continue;
+            }
+            match std::fs::read_to_string(&path) {
+                Ok(text) => components.index_module(
+                    &uri,
+                    &text,
+                    &SymbolTable::build(&text, file_manager.requires()),
+                ),
+                Err(_) => components.remove_module(&uri),
+            }
+        }
+        drop(components);
+        sync_create_element_modules(&mut file_manager, &self.components, &self.settings).await;
+    }
+
+    // Re-reads React aliases out of every .luaurc, then refreshes open documents since they may now require React
+    async fn reload_luaurc(&self) {
+        let Some(root) = self.workspace_root.lock().await.clone() else {
+            return;
+        };
+        let requires = self.file_manager.lock().await.requires().clone();
+        let (aliases, warnings) = load_react_aliases(&requires, &find_luaurc_files(&root));
+        for warning in warnings {
+            self.client.log_message(MessageType::WARNING, warning).await;
+        }
+        let mut file_manager = self.file_manager.lock().await;
+        let mut requires = RequireContext::clone(file_manager.requires());
+        requires.set_react_aliases(aliases);
+        file_manager.set_requires(requires);
+        drop(file_manager);
+        self.republish_diagnostics().await;
+    }
+
+    // Rediagnoses every open document, after something all of them depend on changed
+    async fn republish_diagnostics(&self) {
+        let file_manager = self.file_manager.lock().await;
+        let api_manager = self.api_manager.read().await;
+        for (uri, text, version) in file_manager.open_documents() {
+            self.publish_diagnostics(
+                uri.clone(),
+                get_document_diagnostics(text, file_manager.requires(), &api_manager),
+                Some(version),
+            )
+            .await;
+        }
+    }
+
+    // Publishes with each rule's configured severity, leaving out rules turned off
+    async fn publish_diagnostics(
+        &self,
+        uri: Url,
+        diagnostics: Vec<Diagnostic>,
+        version: Option<i32>,
+    ) {
+        let diagnostics = self.settings.lock().await.diagnostics.apply(diagnostics);
+        self.client
+            .publish_diagnostics(uri, diagnostics, version)
+            .await;
+    }
+
+    // Re-merges every sourcemap(Cheap next to reading modules, so no per file updates)
+    async fn reload_sourcemaps(&self) {
+        let Some(root) = self.workspace_root.lock().await.clone() else {
+            return;
+        };
+        let files = self.settings.lock().await.sourcemap.files.clone();
+        let paths = if files.is_empty() {
+            find_sourcemap_files(&root)
+        } else {
+            files.iter().map(|file| root.join(file)).collect()
+        };
+
+        let (sourcemap, warnings) = Sourcemap::load(&paths);
+        for warning in warnings {
+            self.client.log_message(MessageType::WARNING, warning).await;
+        }
+        self.components.lock().await.set_sourcemap(sourcemap);
+    }
+
+    // (Uri, open version, text) of just uri if given, otherwise open documents plus every other
+    // luau file in workspace. Files on disk are read off the runtime without holding file_manager,
+    // stopping early once cancel is
+    async fn workspace_documents(
+        &self,
+        uri: Option<Url>,
+        cancel: &CancellationToken,
+    ) -> Vec<(Url, Option<i32>, String)> {
+        let mut texts: Vec<(Url, Option<i32>, String)> = Vec::new();
+        {
+            let file_manager = self.file_manager.lock().await;
+            match &uri {
+                Some(uri) => texts.extend(
+                    file_manager
+                        .get_text(uri)
+                        .map(|text| (uri.clone(), file_manager.get_version(uri), text.to_string())),
+                ),
+                None => texts.extend(
+                    file_manager
+                        .open_documents()
+                        .map(|(uri, text, version)| (uri.clone(), Some(version), text.to_string())),
+                ),
+            }
+        }
+        let open: HashSet<Url> = texts.iter().map(|(uri, _, _)| uri.clone()).collect();
+        let workspace_root = self.workspace_root.lock().await.clone();
+
+        let cancel = cancel.clone();
+        let read = tokio::task::spawn_blocking(move || {
+            let files: Vec<(Url, PathBuf)> = match uri {
+                Some(uri) => uri
+                    .to_file_path()
+                    .ok()
+                    .filter(|_| !open.contains(&uri))
+                    .map(|path| (uri, path))
+                    .into_iter()
+                    .collect(),
+                None => workspace_root
+                    .map(|root| find_workspace_files(&root, is_luau_file))
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter_map(|path| Some((Url::from_file_path(&path).ok()?, path)))
+                    .filter(|(uri, _)| !open.contains(uri))
+                    .collect(),
+            };
+            files
+                .into_iter()
+                .take_while(|_| !cancel.is_cancelled())
+                .filter_map(|(uri, path)| Some((uri, None, std::fs::read_to_string(path).ok()?)))
+                .collect::<Vec<_>>()
+        })
+        .await;
+        texts.extend(read.unwrap_or_default());
+
+        texts
+    }
+
+    // (Uri, open version, edits) of every document with auto fixable diagnostics
+    async fn fix_all_documents(&self, uri: Option<Url>) -> Vec<(Url, Option<i32>, Vec<TextEdit>)> {
+        let texts = self
+            .workspace_documents(uri, &CancellationToken::new())
+            .await;
+        let requires = self.file_manager.lock().await.requires().clone();
+        let api_manager = self.api_manager.read().await;
+        texts
+            .into_iter()
+            .map(|(uri, version, text)| {
+                (uri, version, fix_all_edits(&text, &requires, &api_manager))
+            })
+            .filter(|(_, _, edits)| !edits.is_empty())
+            .collect()
+    }
+
+    // Updates usage counts from document's latest text once typing pauses for FREQ_SCAN_DELAY
+    // (Off the edit path, and after the first scan only changed lines get read)
+    async fn schedule_freq_scan(&self, uri: &Url) {
+        let generation = self.freq_scans.lock().await.schedule(uri);
+        let file_manager = self.file_manager.clone();
+        let api_manager = self.api_manager.clone();
+        let freq_scans = self.freq_scans.clone();
+        let uri = uri.clone();
+
+        tokio::spawn(async move {
+            tokio::time::sleep(FREQ_SCAN_DELAY).await;
+            let scan = {
+                let file_manager = file_manager.lock().await;
+                let Some(text) = file_manager.get_text(&uri) else {
+                    return;
+                };
+                freq_scans.lock().await.take(&uri, generation, text)
+            };
+            let (old, new) = match scan {
+                Some(FreqScan::Document(text)) => (String::new(), text),
+                Some(FreqScan::Region { old, new }) => (old, new),
+                None => return,
+            };
+            // Scan only reads, completions just wait for the merge
+            let (removed, added) = {
+                let api_manager = api_manager.read().await;
+                (
+                    api_manager.freq_updates(&old),
+                    api_manager.freq_updates(&new),
+                )
+            };
+            if !removed.is_empty() || !added.is_empty() {
+                api_manager.write().await.apply_freq_updates(removed, added);
+            }
+        });
+    }
+
+    // Restores usage counts an earlier session saved for root, then saves them every
+    // FREQ_SAVE_INTERVAL they changed(Shutdown saves the last ones)
+    fn persist_usage_counts(&self, root: PathBuf) {
+        let api_manager = self.api_manager.clone();
+        let cancel = self.persist_cancel.clone();
+        tokio::spawn(async move {
+            let restore_root = root.clone();
+            let cache_dir = api_manager.read().await.cache_dir().to_path_buf();
+            let restored =
+                tokio::task::spawn_blocking(move || load_usage_counts(&cache_dir, &restore_root))
+                    .await
+                    .ok()
+                    .flatten()
+                    .unwrap_or_default();
+            api_manager.write().await.restore_freq(restored.clone());
+
+            let mut saved = restored;
+            let mut interval = tokio::time::interval(FREQ_SAVE_INTERVAL);
+            interval.tick().await; // First one fires right away
+            loop {
+                tokio::select! {
+                    _ = interval.tick() => {}
+                    _ = cancel.cancelled() => return,
+                }
+                let (counts, cache_dir) = {
+                    let api_manager = api_manager.read().await;
+                    (
+                        api_manager.freq_lookup.clone(),
+                        api_manager.cache_dir().to_path_buf(),
+                    )
+                };
+                if counts != saved {
+                    write_usage_counts(cache_dir, root.clone(), counts.clone()).await;
+                    saved = counts;
+                }
+            }
+        });
+    }
+
+    async fn save_usage_counts(&self) {
+        let Some(root) = self.workspace_root.lock().await.clone() else {
+            return;
+        };
+        let (counts, cache_dir) = {
+            let api_manager = self.api_manager.read().await;
+            (
+                api_manager.freq_lookup.clone(),
+                api_manager.cache_dir().to_path_buf(),
+            )
+        };
+        write_usage_counts(cache_dir, root, counts).await;
+    }
+
+    // Sends element tree of document to the Studio plugin, throttled per document
+    async fn sync_tree(&self, uri: &Url, text: &str, requires: &Arc<RequireContext>, version: i32) {
+        let settings = self.settings.lock().await;
+        if !settings.tree_sync.enabled {
+            return;
+        }
+        let throttle = Duration::from_millis(settings.tree_sync.throttle_ms);
+        drop(settings);
+
+        let schedule = self.tree_sync.lock().await.schedule(uri, throttle);
+        match schedule {
+            TreeSyncSchedule::Now => {
+                self.client
+                    .send_notification::<TreeSync>(TreeSyncParams::new(
+                        uri.clone(),
+                        version,
+                        text,
+                        requires,
+                    ))
+                    .await;
+            }
+            TreeSyncSchedule::Later(delay) => {
+                // Trailing sync picks up whatever the latest text is once throttle window passes
+                let client = self.client.clone();
+                let file_manager = self.file_manager.clone();
+                let tree_sync = self.tree_sync.clone();
+                let uri = uri.clone();
+
+                tokio::spawn(async move {
+                    tokio::time::sleep(delay).await;
+
+                    let params = {
+                        let file_manager = file_manager.lock().await;
+                        file_manager.get_text(&uri).map(|text| {
+                            let version = file_manager.get_version(&uri).unwrap_or(version);
+                            TreeSyncParams::new(uri.clone(), version, text, file_manager.requires())
+                        })
+                    };
+                    tree_sync.lock().await.mark_sent(&uri);
+
+                    if let Some(params) = params {
+                        client.send_notification::<TreeSync>(params).await;
+                    }
+                });
+            }
+            TreeSyncSchedule::AlreadyPending => {}
+        }
+    }
+}
+
+// Usage counts file io is blocking, so it runs off the runtime(Failing to save is only logged)
+async fn write_usage_counts(cache_dir: PathBuf, root: PathBuf, counts: HashMap<String, usize>) {
+    let saved =
+        tokio::task::spawn_blocking(move || save_usage_counts(&cache_dir, &root, &counts)).await;
+    if let Ok(Err(e)) = saved {
+        eprintln!("Failed to save usage counts ({})", e);
+    }
+}
+
+// createElement helper modules from settings plus indexed modules returning one
+async fn create_element_modules(
+    components: &Mutex<ComponentIndex>,
+    settings: &Mutex<Settings>,
+) -> Vec<String> {
+    let components = components.lock().await;
+    let mut modules = settings.lock().await.create_element.helper_modules.clone();
+    modules.extend(components.create_element_modules());
+    modules
+}
+
+// Hands the analyses the current createElement helper modules(Only rebuilding them when those changed)
+async fn sync_create_element_modules(
+    file_manager: &mut FileManager,
+    components: &Mutex<ComponentIndex>,
+    settings: &Mutex<Settings>,
+) {
+    let modules = create_element_modules(components, settings).await;
+    if file_manager.requires().create_element_modules() != modules.as_slice() {
+        let mut requires = RequireContext::clone(file_manager.requires());
+        requires.set_create_element_modules(modules);
+        file_manager.set_requires(requires);
+    }
+}
+
+// Starts cancellable progress in editor, false if client doesn't support server initiated progress
+async fn begin_progress(client: &Client, token: &str, title: &str) -> bool {
+    let token = NumberOrString::String(token.to_string());
+    let created = client
+        .send_request::<WorkDoneProgressCreate>(WorkDoneProgressCreateParams {
+            token: token.clone(),
+        })
+        .await;
+    if created.is_err() {
+        return false;
+    }
+
+    client
+        .send_notification::<Progress>(ProgressParams {
+            token,
+            value: ProgressParamsValue::WorkDone(WorkDoneProgress::Begin(WorkDoneProgressBegin {
+                title: title.to_string(),
+                cancellable: Some(true),
+                message: None,
+                percentage: Some(0),
+            })),
+        })
+        .await;
+    true
+}
+
+async fn report_progress(client: &Client, token: &str, message: &str, percentage: Option<u32>) {
+    client
+        .send_notification::<Progress>(ProgressParams {
+            token: NumberOrString::String(token.to_string()),
+            value: ProgressParamsValue::WorkDone(WorkDoneProgress::Report(
+                WorkDoneProgressReport {
+                    cancellable: Some(true),
+                    message: Some(message.to_string()),
+                    percentage,
+                },
+            )),
+        })
+        .await;
+}
+
+async fn end_progress(client: &Client, token: &str, message: Option<String>) {
+    client
+        .send_notification::<Progress>(ProgressParams {
+            token: NumberOrString::String(token.to_string()),
+            value: ProgressParamsValue::WorkDone(WorkDoneProgress::End(WorkDoneProgressEnd {
+                message,
+            })),
+        })
+        .await;
+}
+
+// Set to 1/true to force deterministic ranking regardless of settings(Test harnesses)
+const DETERMINISTIC_RANKING_ENV: &str = "RBLX_REACT_LSP_DETERMINISTIC_RANKING";
+
+fn startup_api_manager() -> ApiManager {
+    let forced = std::env::var(DETERMINISTIC_RANKING_ENV)
+        .is_ok_and(|value| value == "1" || value.eq_ignore_ascii_case("true"));
+    ApiManager::new().with_forced_deterministic_ranking(forced)
+}
+
+// Fetcher for env var config until settings arrive, plain defaults if that config is broken
+fn startup_fetcher() -> ApiFetcher {
+    ApiFetcher::with_config(FetcherConfig::from_env()).unwrap_or_else(|e| {
+        eprintln!("Invalid API download env vars, using defaults: {}", e);
+        ApiFetcher::new()
+    })
+}
+
+// Stage callback for ApiManager operations, plus the end report_api_stages reads them from
+fn stage_channel() -> (impl FnMut(ApiStage) + Send, UnboundedReceiver<ApiStage>) {
+    let (sender, receiver) = unbounded_channel();
+    let on_stage = move |stage| {
+        let _ = sender.send(stage);
+    };
+    (on_stage, receiver)
+}
+
+// Forwards stages to editor until the operation sending them is done, returns whether progress is shown
+// Unless eager, progress only begins with the first stage so loads that just read the cache don't flash a bar
+// (Caller ends it with end_api_progress, once it knows how the operation went)
+async fn report_api_stages(
+    client: &Client,
+    title: &str,
+    eager: bool,
+    mut stages: UnboundedReceiver<ApiStage>,
+) -> bool {
+    let mut has_progress = None;
+    if eager {
+        has_progress = Some(begin_progress(client, GEN_METADATA_PROGRESS, title).await);
+    }
+    let mut last_report = None;
+    while let Some(stage) = stages.recv().await {
+        let shown = match has_progress {
+            Some(shown) => shown,
+            None => {
+                *has_progress.insert(begin_progress(client, GEN_METADATA_PROGRESS, title).await)
+            }
+        };
+        let report = (stage.message(), stage.percentage());
+        if shown && last_report != Some(report) {
+            last_report = Some(report);
+            report_progress(client, GEN_METADATA_PROGRESS, report.0, report.1).await;
+        }
+    }
+    has_progress == Some(true)
+}
+
+// Ends progress report_api_stages showed, failures say why so an empty completion list isn't a mystery
+async fn end_api_progress<T>(
+    client: &Client,
+    shown: bool,
+    result: &std::result::Result<T, Box<dyn std::error::Error + Send + Sync>>,
+    done: String,
+) {
+    if !shown {
+        return;
+    }
+    let message = match result {
+        Ok(_) => done,
+        Err(e) => format!("Failed: {}", e),
+    };
+    end_progress(client, GEN_METADATA_PROGRESS, Some(message)).await;
+}
+
+// Loads API the way startup does(Cache, download or bundled snapshot) showing its progress,
+// then has client ask again for completions answered while it ran
+async fn load_api_with_progress(
+    client: &Client,
+    api_manager: &RwLock<ApiManager>,
+    fetcher: &ApiFetcher,
+    cancel: &CancellationToken,
+    api_load: &Mutex<ApiLoadState>,
+) -> std::result::Result<String, Box<dyn std::error::Error + Send + Sync>> {
+    // Stale caches get revalidated in here, which may download a new dump
+    let (on_stage, stages) = stage_channel();
+    let (loaded, shown) = tokio::join!(
+        ApiManager::load_api(api_manager, fetcher, cancel, LOAD_TIMEOUT, on_stage),
+        report_api_stages(client, "Loading Roblox API…", true, stages),
+    );
+    let done = match &loaded {
+        Ok(version) if version == BUNDLED_VERSION => {
+            "Using the bundled snapshot, download failed".to_string()
+        }
+        Ok(version) => match version.strip_prefix(LOCAL_VERSION_PREFIX) {
+            Some(path) => format!("Loaded from {}", path),
+            None => format!("Loaded {}", version),
+        },
+        Err(_) => String::new(),
+    };
+    end_api_progress(client, shown, &loaded, done).await;
+
+    let mut state = api_load.lock().await;
+    state.loading = false;
+    let waiting = std::mem::take(&mut state.waiting);
+    drop(state);
+    if let (true, Ok(version)) = (waiting, &loaded) {
+        client
+            .send_notification::<ApiLoaded>(ApiLoadedParams {
+                version: version.clone(),
+            })
+            .await;
+    }
+    loaded
+}
+
+// Downloads and caches latest API dump, showing its progress in editor when it can
+async fn download_api_with_progress(
+    client: &Client,
+    api_manager: &RwLock<ApiManager>,
+    fetcher: &ApiFetcher,
+    cancel: &CancellationToken,
+) -> std::result::Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    // Callback runs inside the download, so stages are forwarded to client from here
+    let (on_stage, stages) = stage_channel();
+    let (result, shown) = tokio::join!(
+        ApiManager::download_api(api_manager, fetcher, cancel, DOWNLOAD_TIMEOUT, on_stage),
+        report_api_stages(client, "Downloading Roblox API dump", false, stages),
+    );
+    end_api_progress(client, shown, &result, "Downloaded".to_string()).await;
+    result
 }
 
 #[tower_lsp::async_trait]
 impl LanguageServer for Backend {
-    async fn initialize(&self, _: InitializeParams) -> Result<InitializeResult> {
+    async fn initialize(&self, params: InitializeParams) -> Result<InitializeResult> {
+        if let Some(options) = params.initialization_options {
+            self.apply_settings(Settings::from_value(&options)).await;
+        }
+        *self.workspace_root.lock().await = params.root_uri.and_then(|uri| uri.to_file_path().ok());
+        *self.client_capabilities.lock().await = params.capabilities;
+
         Ok(InitializeResult {
             capabilities: ServerCapabilities {
                 text_document_sync: Some(TextDocumentSyncCapability::Kind(
-                    TextDocumentSyncKind::FULL,
+                    TextDocumentSyncKind::INCREMENTAL,
                 )),
                 execute_command_provider: Some(ExecuteCommandOptions {
                     commands: vec![
                         "rblx-react-lsp.genMetadata".to_string(),
                         "rblx-react-lsp.readCache".to_string(),
+                        "rblx-react-lsp.clearCache".to_string(),
+                        "rblx-react-lsp.apiInfo".to_string(),
+                        "rblx-react-lsp.apiVersion".to_string(),
+                        "rblx-react-lsp.stats".to_string(),
+                        "rblx-react-lsp.exportTypes".to_string(),
+                        "rblx-react-lsp.loadLocalDump".to_string(),
+                        "rblx-react-lsp.dumpTree".to_string(),
+                        "rblx-react-lsp.debugDump".to_string(),
+                        "rblx-react-lsp.usageReport".to_string(),
+                        "rblx-react-lsp.auditWorkspace".to_string(),
+                        RULE_DOCS_COMMAND.to_string(),
+                        FIX_ALL_COMMAND.to_string(),
+                        NEW_COMPONENT_COMMAND.to_string(),
+                        MIGRATE_ROACT_COMMAND.to_string(),
+                        SET_CURSOR_COMMAND.to_string(),
                     ],
                     work_done_progress_options: Default::default(),
                 }),
@@ -55,9 +863,29 @@ impl LanguageServer for Backend {
                         "`".to_string(),
                         "'".to_string(),
                         "[".to_string(),
+                        ":".to_string(),
                     ]),
+                    resolve_provider: Some(true),
                     ..Default::default()
                 }),
+                code_action_provider: Some(CodeActionProviderCapability::Options(
+                    CodeActionOptions {
+                        code_action_kinds: Some(vec![
+                            CodeActionKind::QUICKFIX,
+                            CodeActionKind::REFACTOR_EXTRACT,
+                            CodeActionKind::REFACTOR_REWRITE,
+                            SOURCE_SORT_PROPS,
+                        ]),
+                        work_done_progress_options: Default::default(),
+                        resolve_provider: None,
+                    },
+                )),
+                definition_provider: Some(OneOf::Left(true)),
+                hover_provider: Some(HoverProviderCapability::Simple(true)),
+                rename_provider: Some(OneOf::Right(RenameOptions {
+                    prepare_provider: Some(true),
+                    work_done_progress_options: Default::default(),
+                })),
                 ..Default::default()
             },
             server_info: None,
@@ -69,10 +897,47 @@ impl LanguageServer for Backend {
         let api_manager_for_update = self.api_manager.clone();
         let client = self.client.clone();
         let client_for_update = self.client.clone();
+        let api_cancel = self.api_cancel.clone();
+        let api_fetcher = self.api_fetcher.lock().await.clone();
+        let api_load = self.api_load.clone();
+        // Set before spawning, so completions coming in meanwhile wait on this load
+        api_load.lock().await.loading = true;
+        if let Some(root) = self.workspace_root.lock().await.clone() {
+            self.persist_usage_counts(root);
+        }
 
         tokio::spawn(async move {
-            let mut api_manager = api_manager.lock().await;
-            match api_manager.load_api().await {
+            let cancel = api_cancel.lock().await.clone();
+            let pinned_version = api_manager
+                .read()
+                .await
+                .pinned_version()
+                .map(str::to_string);
+            let loaded =
+                load_api_with_progress(&client, &api_manager, &api_fetcher, &cancel, &api_load)
+                    .await;
+            match loaded {
+                Ok(cached_version) if cached_version == BUNDLED_VERSION => {
+                    // Update check would just fail again, genMetadata is how to get the real one
+                    client
+                        .show_message(
+                            MessageType::WARNING,
+                            "Couldn't download the Roblox API, using the snapshot bundled with the server. Run 'RBLX React: Generate and Cache API Metadata' once online for the latest one.",
+                        )
+                        .await;
+                }
+                Ok(cached_version) if cached_version.starts_with(LOCAL_VERSION_PREFIX) => {
+                    // Local dumps are picked on purpose, so there's no update to offer
+                    client
+                        .log_message(
+                            MessageType::INFO,
+                            format!(
+                                "API loaded from {}.",
+                                &cached_version[LOCAL_VERSION_PREFIX.len()..]
+                            ),
+                        )
+                        .await;
+                }
                 Ok(cached_version) => {
                     client
                         .log_message(MessageType::INFO, "API loaded from cache.")
@@ -80,12 +945,23 @@ impl LanguageServer for Backend {
 
                     // Check for updates in the background without blocking completions
                     tokio::spawn(async move {
-                        match get_live_version().await {
-                            Ok(live_version) if live_version != cached_version => {
+                        // Pinned setups only ever want their pinned version
+                        let (target_version, prompt) = match pinned_version {
+                            Some(version) => (
+                                Ok(version),
+                                "Pinned Roblox API version isn't cached. Download it now?",
+                            ),
+                            None => (
+                                api_fetcher.live_version().await,
+                                "Roblox API update available. Update now?",
+                            ),
+                        };
+                        match target_version {
+                            Ok(target_version) if target_version != cached_version => {
                                 let response = client_for_update
                                     .show_message_request(
                                         MessageType::INFO,
-                                        "Roblox API update available. Update now?",
+                                        prompt,
                                         Some(vec![
                                             MessageActionItem {
                                                 title: "Yes".to_string(),
@@ -108,8 +984,15 @@ impl LanguageServer for Backend {
                                             )
                                             .await;
 
-                                        let mut mgr = api_manager_for_update.lock().await;
-                                        match mgr.download_api().await {
+                                        let cancel = api_cancel.lock().await.clone();
+                                        let result = download_api_with_progress(
+                                            &client_for_update,
+                                            &api_manager_for_update,
+                                            &api_fetcher,
+                                            &cancel,
+                                        )
+                                        .await;
+                                        match result {
                                             Ok(_) => {
                                                 client_for_update
                                                     .show_message(
@@ -140,7 +1023,7 @@ impl LanguageServer for Backend {
                         .show_message(
                             MessageType::WARNING,
                             format!(
-                                "No API cache found, run 'RBLX React: Generate and Cache API Metadata' to enable completions. ({})",
+                                "Failed to load Roblox API, run 'RBLX React: Generate and Cache API Metadata' to enable completions. ({})",
                                 e
                             ),
                         )
@@ -149,52 +1032,253 @@ impl LanguageServer for Backend {
             }
         });
 
+        // Custom classes can be merged before API is loaded, ApiManager re-applies them once it is
+        let workspace_root = self.workspace_root.lock().await.clone();
+        if let Some(root) = workspace_root {
+            self.reload_luaurc().await;
+            self.reload_custom_classes(find_custom_class_files(&root))
+                .await;
+            self.reload_sourcemaps().await;
+
+            // Reading every module can take a while on big projects, so index off the async runtime
+            let file_manager = self.file_manager.clone();
+            let components = self.components.clone();
+            let settings = self.settings.clone();
+            let requires = file_manager.lock().await.requires().clone();
+            tokio::spawn(async move {
+                let scan = move || ComponentIndex::from_workspace(&root, &requires);
+                if let Ok(index) = tokio::task::spawn_blocking(scan).await {
+                    let mut file_manager = file_manager.lock().await;
+                    components.lock().await.merge_missing(index);
+                    sync_create_element_modules(&mut file_manager, &components, &settings).await;
+                }
+            });
+        }
+        let registration = |id: &str, patterns: &[&str]| Registration {
+            id: id.to_string(),
+            method: "workspace/didChangeWatchedFiles".to_string(),
+            register_options: serde_json::to_value(DidChangeWatchedFilesRegistrationOptions {
+                watchers: patterns
+                    .iter()
+                    .map(|pattern| FileSystemWatcher {
+                        glob_pattern: format!("**/{}", pattern),
+                        kind: None,
+                    })
+                    .collect(),
+            })
+            .ok(),
+        };
+        let registered = self
+            .client
+            .register_capability(vec![
+                registration(
+                    "rblx-react-lsp/customClasses",
+                    &[CUSTOM_CLASSES_FILE, "*.d.luau", "*.d.lua"],
+                ),
+                registration("rblx-react-lsp/componentModules", &["*.luau", "*.lua"]),
+                registration(
+                    "rblx-react-lsp/sourcemaps",
+                    &[DEFAULT_SOURCEMAP, "*.sourcemap.json"],
+                ),
+                registration("rblx-react-lsp/luaurc", &[LUAURC_FILE]),
+            ])
+            .await;
+        if registered.is_err() {
+            self.client
+                .log_message(
+                    MessageType::LOG,
+                    "Client can't watch files, custom class, component module, sourcemap and .luaurc changes need a restart",
+                )
+                .await;
+        }
+
         self.client
             .log_message(MessageType::INFO, "Server initialized!")
             .await;
     }
 
+    async fn did_change_watched_files(&self, params: DidChangeWatchedFilesParams) {
+        let paths: Vec<PathBuf> = params
+            .changes
+            .into_iter()
+            .filter_map(|change| change.uri.to_file_path().ok())
+            .collect();
+        let sourcemap_changed = paths.iter().any(|path| is_sourcemap_file(path));
+        let luaurc_changed = paths.iter().any(|path| is_luaurc_file(path));
+        let (custom_classes, modules): (Vec<PathBuf>, Vec<PathBuf>) = paths
+            .into_iter()
+            .filter(|path| is_custom_class_file(path) || is_luau_file(path))
+            .partition(|path| is_custom_class_file(path));
+        self.reload_custom_classes(custom_classes).await;
+        self.reindex_component_modules(modules).await;
+        if sourcemap_changed {
+            self.reload_sourcemaps().await;
+        }
+        if luaurc_changed {
+            self.reload_luaurc().await;
+        }
+    }
+
+    async fn did_change_configuration(&self, params: DidChangeConfigurationParams) {
+        let settings = Settings::from_value(&params.settings);
+        let current = self.settings.lock().await.clone();
+        let sourcemaps_changed = settings.sourcemap != current.sourcemap;
+        let react_modules_changed = settings.roact != current.roact
+            || settings.react_require.modules != current.react_require.modules;
+        let diagnostics_changed = settings.diagnostics != current.diagnostics;
+        self.apply_settings(settings).await;
+        if sourcemaps_changed {
+            self.reload_sourcemaps().await;
+        }
+        if react_modules_changed {
+            // Aliases count by the module they point at(Roact ones only in compatibility mode)
+            self.reload_luaurc().await;
+        } else if diagnostics_changed {
+            self.republish_diagnostics().await;
+        }
+    }
+
     async fn did_open(&self, params: DidOpenTextDocumentParams) {
         let mut file_manager = self.file_manager.lock().await;
-        let mut api_manager = self.api_manager.lock().await;
-        api_manager.update_freq(&params.text_document.text);
+        let api_manager = self.api_manager.read().await;
+        let diagnostics = get_document_diagnostics(
+            &params.text_document.text,
+            file_manager.requires(),
+            &api_manager,
+        );
         file_manager.on_opened_file(
-            params.text_document.uri,
+            params.text_document.uri.clone(),
             params.text_document.text,
             params.text_document.version,
         );
+        let uri = &params.text_document.uri;
+        if let Some((doc, symbols)) = file_manager
+            .get_text(uri)
+            .zip(file_manager.get_symbols(uri))
+        {
+            self.components.lock().await.index_module(uri, doc, symbols);
+            sync_create_element_modules(&mut file_manager, &self.components, &self.settings).await;
+        }
+
+        drop(api_manager);
+        self.publish_diagnostics(
+            params.text_document.uri.clone(),
+            diagnostics,
+            Some(params.text_document.version),
+        )
+        .await;
+        self.schedule_freq_scan(&params.text_document.uri).await;
+
+        if let Some(doc) = file_manager.get_text(&params.text_document.uri) {
+            let requires = file_manager.requires();
+            self.sync_tree(
+                &params.text_document.uri,
+                doc,
+                requires,
+                params.text_document.version,
+            )
+            .await;
+        }
     }
 
     async fn did_change(&self, params: DidChangeTextDocumentParams) {
+        // Before waiting on file_manager, so a pass for the previous text lets go of it
+        let cancel = self.supersede_diagnostics(&params.text_document.uri).await;
         let mut file_manager = self.file_manager.lock().await;
-        let mut api_manager = self.api_manager.lock().await;
         file_manager.on_changed_file(
             &params.text_document.uri,
             &params.content_changes,
             params.text_document.version,
         );
-        if let Some(doc) = file_manager.get_text(&params.text_document.uri) {
-            api_manager.update_freq(doc);
+        let uri = &params.text_document.uri;
+        if let Some((doc, symbols)) = file_manager
+            .get_text(uri)
+            .zip(file_manager.get_symbols(uri))
+        {
+            self.schedule_freq_scan(uri).await;
+            self.components.lock().await.index_module(uri, doc, symbols);
+            sync_create_element_modules(&mut file_manager, &self.components, &self.settings).await;
+        }
+        if let Some(doc) = file_manager.get_text(uri) {
+            let requires = file_manager.requires();
+            let api_manager = self.api_manager.read().await;
+            let diagnostics =
+                get_document_diagnostics_cancellable(doc, requires, &api_manager, &cancel);
+            drop(api_manager);
+            // A newer change publishes its own
+            let Some(diagnostics) = diagnostics else {
+                return;
+            };
+            self.publish_diagnostics(
+                params.text_document.uri.clone(),
+                diagnostics,
+                Some(params.text_document.version),
+            )
+            .await;
+            self.sync_tree(uri, doc, requires, params.text_document.version)
+                .await;
         }
     }
 
     async fn did_close(&self, params: DidCloseTextDocumentParams) {
         let mut file_manager = self.file_manager.lock().await;
         file_manager.on_closed_file(&params.text_document.uri);
+        self.tree_sync
+            .lock()
+            .await
+            .forget(&params.text_document.uri);
+        self.freq_scans
+            .lock()
+            .await
+            .forget(&params.text_document.uri);
+        if let Some(cancel) = self
+            .diagnostic_cancels
+            .lock()
+            .await
+            .remove(&params.text_document.uri)
+        {
+            cancel.cancel();
+        }
+        self.client
+            .publish_diagnostics(params.text_document.uri, vec![], None)
+            .await;
     }
 
     async fn completion(&self, params: CompletionParams) -> Result<Option<CompletionResponse>> {
-        let file_manager = self.file_manager.lock().await;
-        let api_manager = self.api_manager.lock().await;
+        // A newer keystroke's request supersedes this one, cancelled before it waits on any lock
+        let cancel = request_cancel();
+        {
+            let mut latest = self.completion_cancel.lock().await;
+            latest.cancel();
+            *latest = cancel.clone();
+        }
+        let mut file_manager = self.file_manager.lock().await;
+        let api_manager = self.api_manager.read().await;
+        if !api_manager.is_loaded() {
+            self.load_api_on_demand().await;
+        }
+        let components = self.components.lock().await;
+        let settings = self.settings.lock().await;
         let text_document = params.text_document_position;
 
-        let file_text = file_manager.get_text(&text_document.text_document.uri);
-        if let Some(text) = file_text {
-            if let Ok(diagnose_results) =
-                generate_auto_completions(text, &text_document.position, &api_manager)
-            {
+        let uri = &text_document.text_document.uri;
+        if let Some((text, analysis)) = file_manager.get_analysis(uri) {
+            if let Ok(diagnose_results) = generate_auto_completions(
+                text,
+                analysis,
+                uri,
+                &text_document.position,
+                &api_manager,
+                &components,
+                &settings,
+                CompletionTrigger::from_context(params.context.as_ref()),
+                &cancel,
+            ) {
                 return Ok(Some(diagnose_results));
             }
+            if cancel.is_cancelled() {
+                return Err(tower_lsp::jsonrpc::Error::request_cancelled());
+            }
         } else {
             self.client
                 .log_message(MessageType::LOG, "Could not find file!")
@@ -204,6 +1288,105 @@ impl LanguageServer for Backend {
         Ok(Some(CompletionResponse::Array(vec![])))
     }
 
+    async fn completion_resolve(&self, item: CompletionItem) -> Result<CompletionItem> {
+        let api_manager = self.api_manager.read().await;
+        Ok(resolve_completion_item(item, &api_manager))
+    }
+
+    async fn code_action(&self, params: CodeActionParams) -> Result<Option<CodeActionResponse>> {
+        let mut file_manager = self.file_manager.lock().await;
+        let api_manager = self.api_manager.read().await;
+        let settings = self.settings.lock().await;
+        let actions = file_manager
+            .get_analysis(&params.text_document.uri)
+            .map(|(text, analysis)| {
+                get_code_actions(
+                    text,
+                    analysis,
+                    &params.text_document.uri,
+                    &params.range,
+                    &params.context.diagnostics,
+                    &api_manager,
+                    &settings,
+                )
+            })
+            .unwrap_or_default();
+
+        Ok(Some(actions))
+    }
+
+    async fn hover(&self, params: HoverParams) -> Result<Option<Hover>> {
+        let mut file_manager = self.file_manager.lock().await;
+        let api_manager = self.api_manager.read().await;
+        let text_document = params.text_document_position_params;
+
+        Ok(file_manager
+            .get_analysis(&text_document.text_document.uri)
+            .and_then(|(text, analysis)| {
+                get_hover(text, analysis, &text_document.position, &api_manager)
+            }))
+    }
+
+    async fn goto_definition(
+        &self,
+        params: GotoDefinitionParams,
+    ) -> Result<Option<GotoDefinitionResponse>> {
+        let file_manager = self.file_manager.lock().await;
+        let components = self.components.lock().await;
+        let text_document = params.text_document_position_params;
+
+        let uri = &text_document.text_document.uri;
+        let location = file_manager
+            .get_text(uri)
+            .zip(file_manager.get_symbols(uri))
+            .and_then(|(text, symbols)| {
+                let offset = position_to_byte_offset(text, &text_document.position)?;
+                components.definition_at(text, uri, symbols, offset)
+            });
+
+        Ok(location.map(GotoDefinitionResponse::Scalar))
+    }
+
+    async fn prepare_rename(
+        &self,
+        params: TextDocumentPositionParams,
+    ) -> Result<Option<PrepareRenameResponse>> {
+        let file_manager = self.file_manager.lock().await;
+        let uri = &params.text_document.uri;
+        let range = file_manager
+            .get_text(uri)
+            .zip(file_manager.get_symbols(uri))
+            .and_then(|(text, symbols)| prepare_rename(text, symbols, &params.position));
+
+        Ok(range.map(PrepareRenameResponse::Range))
+    }
+
+    async fn rename(&self, params: RenameParams) -> Result<Option<WorkspaceEdit>> {
+        if !is_valid_identifier(&params.new_name) {
+            return Err(Error::invalid_params(format!(
+                "'{}' is not a valid identifier",
+                params.new_name
+            )));
+        }
+
+        let file_manager = self.file_manager.lock().await;
+        let text_document = params.text_document_position;
+
+        let uri = &text_document.text_document.uri;
+        Ok(file_manager
+            .get_text(uri)
+            .zip(file_manager.get_symbols(uri))
+            .and_then(|(text, symbols)| {
+                rename(
+                    text,
+                    symbols,
+                    uri,
+                    &text_document.position,
+                    &params.new_name,
+                )
+            }))
+    }
+
     async fn execute_command(
         &self,
         params: ExecuteCommandParams,
@@ -214,33 +1397,102 @@ impl LanguageServer for Backend {
                     .show_message(MessageType::INFO, "Downloading Roblox API dump...")
                     .await;
 
-                let mut api_manager = self.api_manager.lock().await;
-                match api_manager.download_api().await {
+                let cancel = self.api_cancel.lock().await.clone();
+                let api_fetcher = self.api_fetcher.lock().await.clone();
+                let result = download_api_with_progress(
+                    &self.client,
+                    &self.api_manager,
+                    &api_fetcher,
+                    &cancel,
+                )
+                .await;
+
+                match result {
                     Ok(_) => {
                         self.client
                             .show_message(MessageType::INFO, "Roblox API loaded successfully")
                             .await;
                     }
                     Err(e) => {
-                        self.client
-                            .show_message(
-                                MessageType::ERROR,
-                                format!("Failed to download API: {}", e),
-                            )
-                            .await;
+                        // Popups get dismissed, log keeps the classified reason around
+                        let message = format!("Failed to download API: {}", e);
+                        self.client.log_message(MessageType::ERROR, &message).await;
+                        self.client.show_message(MessageType::ERROR, message).await;
                     }
                 }
             }
 
+            "rblx-react-lsp.clearCache" => {
+                // Optional dir a readCache export went to, which gets removed too
+                let readable_dir = params
+                    .arguments
+                    .first()
+                    .and_then(Value::as_str)
+                    .map(PathBuf::from);
+                let cache_dir = self.api_manager.read().await.cache_dir().to_path_buf();
+                let removed =
+                    clear_cache_files(&cache_dir, readable_dir.as_deref()).map_err(|e| Error {
+                        message: format!("Failed to clear API cache: {}", e),
+                        ..Error::internal_error()
+                    })?;
+                // Open documents refill them as they change, the saved ones go right away
+                self.api_manager.write().await.freq_lookup.clear();
+                self.save_usage_counts().await;
+
+                let cancel = self.api_cancel.lock().await.clone();
+                let api_fetcher = self.api_fetcher.lock().await.clone();
+                let result = download_api_with_progress(
+                    &self.client,
+                    &self.api_manager,
+                    &api_fetcher,
+                    &cancel,
+                )
+                .await;
+                if let Err(e) = result {
+                    let message =
+                        format!("Cleared API cache, but downloading it again failed: {}", e);
+                    self.client.log_message(MessageType::ERROR, &message).await;
+                    self.client.show_message(MessageType::ERROR, &message).await;
+                    return Err(Error {
+                        message,
+                        ..Error::internal_error()
+                    });
+                }
+
+                let api_info = self.api_manager.read().await.api_info();
+                return Ok(Some(json!({
+                    "removed": removed,
+                    "bytesWritten": cache_file_size(&cache_dir),
+                    "classCount": api_info["classCount"],
+                    "version": api_info["version"],
+                })));
+            }
+
             "rblx-react-lsp.readCache" => {
                 let args = params.arguments;
-                if let Some(Value::String(path_str)) = args.get(0) {
+                // { "class": Name } returns just that class, small enough for a virtual document
+                if let Some(class_name) = args
+                    .first()
+                    .and_then(|arg| arg.get("class"))
+                    .and_then(Value::as_str)
+                {
+                    let api_manager = self.api_manager.read().await;
+                    return match api_manager.class_json(class_name) {
+                        Some(class) => Ok(Some(class)),
+                        None => Err(Error::invalid_params(format!(
+                            "Unknown class '{}'",
+                            class_name
+                        ))),
+                    };
+                }
+                if let Some(Value::String(path_str)) = args.first() {
                     let path = PathBuf::from(path_str);
                     if path.exists() {
                         self.client
                             .show_message(MessageType::INFO, "Loading API from cache...")
                             .await;
-                        match create_api_file_readable(path).await {
+                        let api_fetcher = self.api_fetcher.lock().await.clone();
+                        match create_api_file_readable(&api_fetcher, path).await {
                             Ok(_) => {
                                 self.client
                                     .show_message(MessageType::INFO, "Cache loaded successfully")
@@ -270,6 +1522,493 @@ impl LanguageServer for Backend {
                 }
             }
 
+            "rblx-react-lsp.apiInfo" => {
+                let api_manager = self.api_manager.read().await;
+                return Ok(Some(api_manager.api_info()));
+            }
+
+            "rblx-react-lsp.exportTypes" => {
+                let path = params
+                    .arguments
+                    .first()
+                    .and_then(Value::as_str)
+                    .map(PathBuf::from)
+                    .ok_or_else(|| Error::invalid_params("Expected output path"))?;
+                let format_name = params
+                    .arguments
+                    .get(1)
+                    .and_then(|options| options.get("format"))
+                    .and_then(Value::as_str)
+                    .unwrap_or("definitions");
+                let format = TypeExportFormat::from_name(format_name).ok_or_else(|| {
+                    Error::invalid_params(format!("Unknown format '{}'", format_name))
+                })?;
+
+                let (types, class_count) = export_types(&*self.api_manager.read().await, format);
+                if class_count == 0 {
+                    return Err(Error::invalid_params("No Roblox API loaded to export"));
+                }
+                // Big exports take a moment to write, so that happens off the runtime
+                let bytes_written = types.len();
+                let write_path = path.clone();
+                let written =
+                    tokio::task::spawn_blocking(move || std::fs::write(write_path, types))
+                        .await
+                        .unwrap_or_else(|e| Err(std::io::Error::other(e)));
+                written.map_err(|e| Error {
+                    message: format!("Failed to write {}: {}", path.display(), e),
+                    ..Error::internal_error()
+                })?;
+                return Ok(Some(json!({
+                    "path": path,
+                    "format": format_name,
+                    "classCount": class_count,
+                    "bytesWritten": bytes_written,
+                })));
+            }
+
+            "rblx-react-lsp.stats" => {
+                let (mut stats, cache_dir) = {
+                    let api_manager = self.api_manager.read().await;
+                    (api_manager.stats(), api_manager.cache_dir().to_path_buf())
+                };
+                stats["cacheFileBytes"] = json!(cache_file_size(&cache_dir));
+                stats["trackedDocuments"] =
+                    json!(self.file_manager.lock().await.open_documents().count());
+                return Ok(Some(stats));
+            }
+
+            "rblx-react-lsp.apiVersion" => {
+                // Checked without holding the lock, completions shouldn't wait on the network
+                let api_fetcher = self.api_fetcher.lock().await.clone();
+                let latest_version = api_fetcher.live_version().await.ok();
+                let api_manager = self.api_manager.read().await;
+                return Ok(Some(api_manager.version_info(latest_version.as_deref())));
+            }
+
+            "rblx-react-lsp.loadLocalDump" => {
+                // Path argument loads that file once, without one it reloads api.dumpPath
+                let path = match params.arguments.first().and_then(Value::as_str) {
+                    Some(path) => Some(PathBuf::from(path)),
+                    None => self.settings.lock().await.api.dump_path(),
+                }
+                .ok_or_else(|| Error::invalid_params("Expected API dump path"))?;
+
+                let cancel = self.api_cancel.lock().await.clone();
+                let (on_stage, stages) = stage_channel();
+                let (result, shown) = tokio::join!(
+                    ApiManager::load_dump_file(
+                        &self.api_manager,
+                        path.clone(),
+                        &cancel,
+                        LOAD_TIMEOUT,
+                        on_stage
+                    ),
+                    report_api_stages(&self.client, "Loading Roblox API dump", false, stages),
+                );
+                end_api_progress(&self.client, shown, &result, "Loaded".to_string()).await;
+                match result {
+                    Ok(_) => {
+                        self.client
+                            .show_message(
+                                MessageType::INFO,
+                                format!("Roblox API loaded from {}", path.display()),
+                            )
+                            .await;
+                    }
+                    Err(e) => {
+                        self.client
+                            .show_message(MessageType::ERROR, format!("Failed to load API: {}", e))
+                            .await;
+                    }
+                }
+            }
+
+            "rblx-react-lsp.dumpTree" => {
+                let uri = params
+                    .arguments
+                    .first()
+                    .and_then(|v| serde_json::from_value::<Url>(v.clone()).ok())
+                    .ok_or_else(|| Error::invalid_params("Expected document uri"))?;
+                let file_manager = self.file_manager.lock().await;
+                let text = file_manager
+                    .get_text(&uri)
+                    .ok_or_else(|| Error::invalid_params(format!("Document not open: {}", uri)))?;
+                let version = file_manager.get_version(&uri).unwrap_or(0);
+                let dump = TreeSyncParams::new(uri.clone(), version, text, file_manager.requires());
+                return Ok(serde_json::to_value(dump).ok());
+            }
+
+            "rblx-react-lsp.debugDump" => {
+                let uri = params
+                    .arguments
+                    .first()
+                    .and_then(|v| serde_json::from_value::<Url>(v.clone()).ok())
+                    .ok_or_else(|| Error::invalid_params("Expected document uri"))?;
+                let cursor = params
+                    .arguments
+                    .get(1)
+                    .and_then(|v| serde_json::from_value::<Position>(v.clone()).ok());
+                let file_manager = self.file_manager.lock().await;
+                let (text, symbols) = file_manager
+                    .get_text(&uri)
+                    .zip(file_manager.get_symbols(&uri))
+                    .ok_or_else(|| Error::invalid_params(format!("Document not open: {}", uri)))?;
+                let api_manager = self.api_manager.read().await;
+                let mut dump = get_debug_dump(text, symbols, cursor.as_ref(), &api_manager);
+                dump["uri"] = json!(uri);
+                dump["version"] = json!(file_manager.get_version(&uri));
+                return Ok(Some(dump));
+            }
+
+            "rblx-react-lsp.auditWorkspace" => {
+                let cancel = {
+                    let mut cancel = self.audit_cancel.lock().await;
+                    *cancel = CancellationToken::new();
+                    cancel.clone()
+                };
+                let documents = self.workspace_documents(None, &cancel).await;
+                let requires = self.file_manager.lock().await.requires().clone();
+                let total = documents.len();
+                let has_progress =
+                    begin_progress(&self.client, AUDIT_WORKSPACE_PROGRESS, "Auditing workspace")
+                        .await;
+
+                let mut files_with_problems = 0;
+                let mut problems = 0;
+                let mut by_code: BTreeMap<String, usize> = BTreeMap::new();
+                let mut last_percentage = None;
+                let mut audited = 0;
+                for (uri, version, text) in documents {
+                    if cancel.is_cancelled() {
+                        break;
+                    }
+                    let diagnostics =
+                        get_document_diagnostics(&text, &requires, &*self.api_manager.read().await);
+                    // Rules turned off don't count as problems either
+                    let diagnostics = self.settings.lock().await.diagnostics.apply(diagnostics);
+                    if !diagnostics.is_empty() {
+                        files_with_problems += 1;
+                        problems += diagnostics.len();
+                        for diagnostic in &diagnostics {
+                            if let Some(NumberOrString::String(code)) = &diagnostic.code {
+                                *by_code.entry(code.clone()).or_default() += 1;
+                            }
+                        }
+                    }
+                    self.client
+                        .publish_diagnostics(uri, diagnostics, version)
+                        .await;
+                    audited += 1;
+
+                    let percentage = (audited * 100 / total) as u32;
+                    if has_progress && last_percentage != Some(percentage) {
+                        last_percentage = Some(percentage);
+                        report_progress(
+                            &self.client,
+                            AUDIT_WORKSPACE_PROGRESS,
+                            &format!("{}/{} files", audited, total),
+                            Some(percentage),
+                        )
+                        .await;
+                    }
+                    // Lets the cancel notification through between files
+                    tokio::task::yield_now().await;
+                }
+                if has_progress {
+                    end_progress(&self.client, AUDIT_WORKSPACE_PROGRESS, None).await;
+                }
+
+                let cancelled = audited < total;
+                self.client
+                    .log_message(
+                        MessageType::INFO,
+                        format!(
+                            "Audited {} of {} files, {} problem(s) in {} file(s)",
+                            audited, total, problems, files_with_problems
+                        ),
+                    )
+                    .await;
+                return Ok(Some(json!({
+                    "files": audited,
+                    "filesWithProblems": files_with_problems,
+                    "problems": problems,
+                    "byCode": by_code,
+                    "cancelled": cancelled,
+                })));
+            }
+
+            "rblx-react-lsp.usageReport" => {
+                let api_manager = self.api_manager.read().await;
+                return Ok(Some(api_manager.usage_report()));
+            }
+
+            RULE_DOCS_COMMAND => {
+                let code = params
+                    .arguments
+                    .first()
+                    .and_then(Value::as_str)
+                    .unwrap_or("");
+                return match rule_docs_markdown(code) {
+                    Some(docs) => Ok(Some(Value::String(docs))),
+                    None => Err(Error::invalid_params(format!("Unknown rule '{}'", code))),
+                };
+            }
+
+            NEW_COMPONENT_COMMAND => {
+                let name = params
+                    .arguments
+                    .first()
+                    .and_then(Value::as_str)
+                    .ok_or_else(|| Error::invalid_params("Expected component name"))?
+                    .to_string();
+                let uri = params
+                    .arguments
+                    .get(1)
+                    .and_then(|v| serde_json::from_value::<Url>(v.clone()).ok())
+                    .ok_or_else(|| Error::invalid_params("Expected target uri"))?;
+                // Closed files are read from disk, edits then go against that text
+                let (doc, symbols, version) = {
+                    let file_manager = self.file_manager.lock().await;
+                    match file_manager
+                        .get_text(&uri)
+                        .zip(file_manager.get_symbols(&uri))
+                    {
+                        Some((text, symbols)) => (
+                            text.to_string(),
+                            symbols.clone(),
+                            file_manager.get_version(&uri),
+                        ),
+                        None => {
+                            let text = uri
+                                .to_file_path()
+                                .ok()
+                                .and_then(|path| std::fs::read_to_string(path).ok())
+                                .ok_or_else(|| {
+                                    Error::invalid_params(format!("Can't read {}", uri))
+                                })?;
+                            let symbols = SymbolTable::build(&text, file_manager.requires());
+                            (text, symbols, None)
+                        }
+                    }
+                };
+                let settings = self.settings.lock().await.clone();
+                let edits =
+                    new_component_edits(&doc, &symbols, &name, &settings).ok_or_else(|| {
+                        Error::invalid_params(format!("'{}' isn't a valid component name", name))
+                    })?;
+
+                let workspace = self.client_capabilities.lock().await.workspace.clone();
+                let document_changes = workspace
+                    .as_ref()
+                    .and_then(|w| w.workspace_edit.as_ref())
+                    .and_then(|e| e.document_changes)
+                    == Some(true);
+                let edit = combined_workspace_edit(vec![(uri, version, edits)], document_changes);
+                // Same fallback as fixAll, clients that can't apply edits get it back instead
+                if workspace.and_then(|w| w.apply_edit) != Some(true) {
+                    return Ok(Some(json!({ "applied": false, "edit": edit })));
+                }
+                let applied = match self.client.apply_edit(edit).await {
+                    Ok(response) => response.applied,
+                    Err(e) => {
+                        self.client
+                            .show_message(
+                                MessageType::ERROR,
+                                format!("Failed to insert component: {}", e),
+                            )
+                            .await;
+                        false
+                    }
+                };
+                return Ok(Some(json!({ "applied": applied })));
+            }
+
+            MIGRATE_ROACT_COMMAND => {
+                let uri = params
+                    .arguments
+                    .first()
+                    .and_then(|v| serde_json::from_value::<Url>(v.clone()).ok());
+                let mut notes: Vec<String> = Vec::new();
+                let mut documents = Vec::new();
+                let requires = self.file_manager.lock().await.requires().clone();
+                let texts = self
+                    .workspace_documents(uri, &CancellationToken::new())
+                    .await;
+                for (uri, version, text) in texts {
+                    let (edits, file_notes) = migrate_roact_edits(&text, &requires);
+                    let file = uri
+                        .path()
+                        .rsplit('/')
+                        .next()
+                        .unwrap_or_default()
+                        .to_string();
+                    notes.extend(
+                        file_notes
+                            .into_iter()
+                            .map(|note| format!("{}: {}", file, note)),
+                    );
+                    if !edits.is_empty() {
+                        documents.push((uri, version, edits));
+                    }
+                }
+                let edit_count: usize = documents.iter().map(|(_, _, edits)| edits.len()).sum();
+                let files = documents.len();
+                if edit_count == 0 {
+                    self.client
+                        .show_message(MessageType::INFO, "No Roact code found to migrate")
+                        .await;
+                    return Ok(Some(
+                        json!({ "files": 0, "edits": 0, "applied": false, "notes": notes }),
+                    ));
+                }
+
+                let workspace = self.client_capabilities.lock().await.workspace.clone();
+                let workspace_edit = workspace.as_ref().and_then(|w| w.workspace_edit.as_ref());
+                let edit = previewable_workspace_edit(
+                    documents,
+                    "Migrate Roact to React",
+                    workspace_edit.and_then(|e| e.document_changes) == Some(true),
+                    workspace_edit.is_some_and(|e| e.change_annotation_support.is_some()),
+                );
+                // Same fallback as fixAll
+                if workspace.and_then(|w| w.apply_edit) != Some(true) {
+                    return Ok(Some(json!({
+                        "files": files,
+                        "edits": edit_count,
+                        "applied": false,
+                        "notes": notes,
+                        "edit": edit,
+                    })));
+                }
+                let applied = match self.client.apply_edit(edit).await {
+                    Ok(response) => response.applied,
+                    Err(e) => {
+                        self.client
+                            .show_message(
+                                MessageType::ERROR,
+                                format!("Failed to migrate Roact code: {}", e),
+                            )
+                            .await;
+                        false
+                    }
+                };
+                if applied && !notes.is_empty() {
+                    self.client
+                        .show_message(
+                            MessageType::WARNING,
+                            format!(
+                                "Migrated to React, {} place(s) still need a manual look, see the output log",
+                                notes.len()
+                            ),
+                        )
+                        .await;
+                    self.client
+                        .log_message(MessageType::INFO, notes.join("\n"))
+                        .await;
+                }
+                return Ok(Some(json!({
+                    "files": files,
+                    "edits": edit_count,
+                    "applied": applied,
+                    "notes": notes,
+                })));
+            }
+
+            FIX_ALL_COMMAND => {
+                let uri = params
+                    .arguments
+                    .first()
+                    .and_then(|v| serde_json::from_value::<Url>(v.clone()).ok());
+                let documents = self.fix_all_documents(uri).await;
+                let fixes: usize = documents.iter().map(|(_, _, edits)| edits.len()).sum();
+                let files = documents.len();
+                if fixes == 0 {
+                    self.client
+                        .show_message(MessageType::INFO, "No auto-fixable problems found")
+                        .await;
+                    return Ok(Some(json!({ "fixes": 0, "files": 0, "applied": false })));
+                }
+
+                let workspace = self.client_capabilities.lock().await.workspace.clone();
+                let document_changes = workspace
+                    .as_ref()
+                    .and_then(|w| w.workspace_edit.as_ref())
+                    .and_then(|e| e.document_changes)
+                    == Some(true);
+                let edit = combined_workspace_edit(documents, document_changes);
+
+                // Client can't apply edits itself, so hand edit back as command result instead
+                if workspace.and_then(|w| w.apply_edit) != Some(true) {
+                    return Ok(Some(json!({
+                        "fixes": fixes,
+                        "files": files,
+                        "applied": false,
+                        "edit": edit,
+                    })));
+                }
+                let applied = match self.client.apply_edit(edit).await {
+                    Ok(response) if response.applied => true,
+                    Ok(response) => {
+                        self.client
+                            .show_message(
+                                MessageType::WARNING,
+                                format!(
+                                    "Fixes were rejected: {}",
+                                    response.failure_reason.unwrap_or_default()
+                                ),
+                            )
+                            .await;
+                        false
+                    }
+                    Err(e) => {
+                        self.client
+                            .show_message(
+                                MessageType::ERROR,
+                                format!("Failed to apply fixes: {}", e),
+                            )
+                            .await;
+                        false
+                    }
+                };
+                if applied {
+                    self.client
+                        .show_message(
+                            MessageType::INFO,
+                            format!("Applied {} fixes in {} files", fixes, files),
+                        )
+                        .await;
+                }
+                return Ok(Some(
+                    json!({ "fixes": fixes, "files": files, "applied": applied }),
+                ));
+            }
+
+            SET_CURSOR_COMMAND => {
+                // Editors without window/showDocument support just leave the cursor where it was
+                let mut args = params.arguments.into_iter();
+                let uri = args
+                    .next()
+                    .and_then(|v| serde_json::from_value::<Url>(v).ok());
+                let position = args
+                    .next()
+                    .and_then(|v| serde_json::from_value::<Position>(v).ok());
+                if let (Some(uri), Some(position)) = (uri, position) {
+                    let _ = self
+                        .client
+                        .send_request::<ShowDocument>(ShowDocumentParams {
+                            uri,
+                            external: None,
+                            take_focus: Some(true),
+                            selection: Some(Range {
+                                start: position,
+                                end: position,
+                            }),
+                        })
+                        .await;
+                }
+            }
+
             unknown => {
                 self.client
                     .log_message(
@@ -284,25 +2023,112 @@ impl LanguageServer for Backend {
     }
 
     async fn shutdown(&self) -> Result<()> {
+        self.save_usage_counts().await;
+        self.persist_cancel.cancel();
         Ok(())
     }
 }
 
+// Runs a fresh server(Own documents, settings and API) over one client connection
+async fn serve<I, O>(input: I, output: O)
+where
+    I: AsyncRead + Unpin,
+    O: AsyncWrite,
+{
+    let persist_cancel = CancellationToken::new();
+    let (service, socket) = LspService::build(|client| Backend {
+        client,
+        file_manager: Arc::new(Mutex::new(FileManager::new())),
+        api_manager: Arc::new(RwLock::new(startup_api_manager())),
+        api_fetcher: Arc::new(Mutex::new(startup_fetcher())),
+        components: Arc::new(Mutex::new(ComponentIndex::default())),
+        settings: Arc::new(Mutex::new(Settings::default())),
+        tree_sync: Arc::new(Mutex::new(TreeSyncThrottle::new())),
+        freq_scans: Arc::new(Mutex::new(FreqScanQueue::new())),
+        api_cancel: Arc::new(Mutex::new(CancellationToken::new())),
+        audit_cancel: Arc::new(Mutex::new(CancellationToken::new())),
+        completion_cancel: Arc::new(Mutex::new(CancellationToken::new())),
+        diagnostic_cancels: Arc::new(Mutex::new(HashMap::new())),
+        api_load: Arc::new(Mutex::new(ApiLoadState::default())),
+        workspace_root: Arc::new(Mutex::new(None)),
+        client_capabilities: Arc::new(Mutex::new(ClientCapabilities::default())),
+        persist_cancel: persist_cancel.clone(),
+    })
+    .custom_method(
+        "window/workDoneProgress/cancel",
+        Backend::work_done_progress_cancel,
+    )
+    .finish();
+    let service = CancelRequestService::new(ItemDefaultsService::new(service));
+    Server::new(input, output, socket).serve(service).await;
+    persist_cancel.cancel();
+}
+
+#[cfg(unix)]
+async fn serve_pipe(path: &str) -> std::io::Result<()> {
+    let (read, write) = tokio::net::UnixStream::connect(path).await?.into_split();
+    serve(read, write).await;
+    Ok(())
+}
+
+#[cfg(windows)]
+async fn serve_pipe(path: &str) -> std::io::Result<()> {
+    let pipe = tokio::net::windows::named_pipe::ClientOptions::new().open(path)?;
+    let (read, write) = tokio::io::split(pipe);
+    serve(read, write).await;
+    Ok(())
+}
+
+async fn serve_transport(transport: Transport) -> std::io::Result<()> {
+    match transport {
+        Transport::Stdio => serve(tokio::io::stdin(), tokio::io::stdout()).await,
+        Transport::Tcp {
+            port,
+            listen: false,
+        } => {
+            let (read, write) = TcpStream::connect(("127.0.0.1", port)).await?.into_split();
+            serve(read, write).await;
+        }
+        Transport::Tcp { port, listen: true } => {
+            let listener = TcpListener::bind(("127.0.0.1", port)).await?;
+            eprintln!("Listening on {}", listener.local_addr()?);
+            // Every client gets its own session, so they're served side by side
+            loop {
+                let (stream, _) = listener.accept().await?;
+                let (read, write) = stream.into_split();
+                tokio::spawn(serve(read, write));
+            }
+        }
+        Transport::Pipe(path) => serve_pipe(&path).await?,
+    }
+    Ok(())
+}
+
 #[tokio::main]
 async fn main() {
-    let stdin = tokio::io::stdin();
-    let stdout = tokio::io::stdout();
-
     // Incase abort happens(Since we set panic level to abort)
     // Might as well add something so info gets logged
     std::panic::set_hook(Box::new(|info| {
         eprintln!("LSP panicked: {}", info);
     }));
 
-    let (service, socket) = LspService::new(|client| Backend {
-        client,
-        file_manager: Arc::new(Mutex::new(FileManager::new())),
-        api_manager: Arc::new(Mutex::new(ApiManager::new())),
-    });
-    Server::new(stdin, stdout, socket).serve(service).await;
+    let transport = match parse_args(std::env::args().skip(1)) {
+        Ok(Command::Serve(transport)) => transport,
+        Ok(Command::Version) => {
+            println!("{}", version());
+            return;
+        }
+        Ok(Command::Help) => {
+            println!("{}\n\n{}", version(), USAGE);
+            return;
+        }
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, USAGE);
+            std::process::exit(2);
+        }
+    };
+    if let Err(e) = serve_transport(transport).await {
+        eprintln!("Failed to connect to client ({})", e);
+        std::process::exit(1);
+    }
 }
@@ -0,0 +1,144 @@
+// This script wraps the LSP service so handlers can see $/cancelRequest while they're still scanning
+// (tower-lsp drops a cancelled handler's future, but only at its next await, synchronous work keeps going until then)
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+
+use tokio_util::sync::CancellationToken;
+use tower::Service;
+use tower_lsp::jsonrpc::{Id, Request, Response};
+
+tokio::task_local! {
+    static REQUEST_CANCEL: CancellationToken;
+}
+
+// Token of the request being handled, cancelled once the client cancels it
+// (Never cancelled outside of a request, e.g. in notifications)
+pub fn request_cancel() -> CancellationToken {
+    REQUEST_CANCEL
+        .try_with(CancellationToken::clone)
+        .unwrap_or_default()
+}
+
+#[derive(Clone)]
+pub struct CancelRequestService<S> {
+    inner: S,
+    pending: Arc<Mutex<HashMap<Id, CancellationToken>>>,
+}
+
+impl<S> CancelRequestService<S> {
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner,
+            pending: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+}
+
+impl<S> Service<Request> for CancelRequestService<S>
+where
+    S: Service<Request, Response = Option<Response>>,
+    S::Future: Send + 'static,
+{
+    type Response = Option<Response>;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        // Still passed on, so tower-lsp answers the request with its cancelled error
+        if req.method() == "$/cancelRequest" {
+            let id = req
+                .params()
+                .and_then(|params| params.get("id"))
+                .and_then(|id| serde_json::from_value::<Id>(id.clone()).ok());
+            if let Some(token) = id.and_then(|id| self.pending.lock().unwrap().remove(&id)) {
+                token.cancel();
+            }
+        }
+
+        let Some(id) = req.id().cloned() else {
+            return Box::pin(self.inner.call(req));
+        };
+        let token = CancellationToken::new();
+        self.pending
+            .lock()
+            .unwrap()
+            .insert(id.clone(), token.clone());
+        let pending = self.pending.clone();
+        let fut = REQUEST_CANCEL.scope(token, self.inner.call(req));
+
+        Box::pin(async move {
+            let response = fut.await;
+            pending.lock().unwrap().remove(&id);
+            response
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+
+    use serde_json::json;
+    use tower::Service;
+    use tower_lsp::jsonrpc::{Request, Response};
+
+    use crate::request_cancel::{request_cancel, CancelRequestService};
+
+    // Requests wait until they're cancelled, notifications finish right away
+    struct UntilCancelled;
+
+    impl Service<Request> for UntilCancelled {
+        type Response = Option<Response>;
+        type Error = ();
+        type Future = Pin<Box<dyn Future<Output = Result<Option<Response>, ()>> + Send>>;
+
+        fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), ()>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, req: Request) -> Self::Future {
+            Box::pin(async move {
+                let id = req.id().cloned();
+                if let Some(id) = id {
+                    request_cancel().cancelled().await;
+                    return Ok(Some(Response::from_ok(id, json!("cancelled"))));
+                }
+                Ok(None)
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cancel_request() {
+        let mut service = CancelRequestService::new(UntilCancelled);
+        let completion = Request::build("textDocument/completion").id(1).finish();
+        let pending = tokio::spawn(service.call(completion));
+
+        // Unknown ids are ignored, the matching one gets its token cancelled
+        let other = Request::build("$/cancelRequest")
+            .params(json!({ "id": 2 }))
+            .finish();
+        assert_eq!(service.call(other).await, Ok(None));
+        assert!(!pending.is_finished());
+        let cancel = Request::build("$/cancelRequest")
+            .params(json!({ "id": 1 }))
+            .finish();
+        assert_eq!(service.call(cancel).await, Ok(None));
+        let response = pending.await.unwrap().unwrap().unwrap();
+        assert_eq!(response.into_parts().1, Ok(json!("cancelled")));
+        assert!(service.pending.lock().unwrap().is_empty());
+
+        // Outside a request nothing ever cancels it
+        assert!(!request_cancel().is_cancelled());
+    }
+}
@@ -0,0 +1,237 @@
+// This script rewrites legacy Roact code into react-lua, for the migrateRoact command
+// (Works off the symbol table, so strings, comments and shadowing locals named Roact stay untouched)
+
+use std::sync::Arc;
+
+use tower_lsp::lsp_types::{Range, TextEdit};
+
+use crate::{
+    file_diagnoser::byte_offset_to_position,
+    symbol_table::{required_module_name, RequireContext, SymbolKind, SymbolTable},
+};
+
+pub const MIGRATE_ROACT_COMMAND: &str = "rblx-react-lsp.migrateRoact";
+
+// Roact members React itself has no equivalent for, renamed anyway but reported back
+// (mount/update/unmount and portals moved to ReactRoblox, the rest are gone)
+const MANUAL_MEMBERS: [(&str, &str); 6] = [
+    (
+        "mount",
+        "use ReactRoblox.createRoot(container):render(element)",
+    ),
+    ("update", "render again through the ReactRoblox root"),
+    ("unmount", "use root:unmount() of the ReactRoblox root"),
+    ("Portal", "use ReactRoblox.createPortal(children, target)"),
+    (
+        "oneChild",
+        "no React equivalent, index the children table directly",
+    ),
+    ("setGlobalConfig", "no React equivalent, remove it"),
+];
+
+fn ident_len(text: &str) -> usize {
+    text.find(|c: char| !c.is_alphanumeric() && c != '_')
+        .unwrap_or(text.len())
+}
+
+// Edits turning doc's Roact usage into React, plus notes on what still needs a manual look
+// Empty when doc never requires Roact or already has a React local the rename would clash with
+pub fn migrate_roact_edits(
+    doc: &str,
+    requires: &Arc<RequireContext>,
+) -> (Vec<TextEdit>, Vec<String>) {
+    let symbols = SymbolTable::build(doc, requires);
+    let Some(roact) = symbols.symbols.iter().find(|symbol| {
+        symbol.kind == SymbolKind::Local
+            && symbols
+                .value_text(doc, symbol)
+                .and_then(required_module_name)
+                .is_some_and(|module| module.eq_ignore_ascii_case("Roact"))
+    }) else {
+        return (Vec::new(), Vec::new());
+    };
+    if symbols.declared_names().any(|name| name == "React") {
+        return (
+            Vec::new(),
+            vec!["File already has a React local, migrate it by hand".to_string()],
+        );
+    }
+
+    let mut edits: Vec<(usize, usize, String)> = Vec::new();
+    let mut notes = Vec::new();
+    // Require path's last Roact segment(require(Packages.Roact), require("@Packages/Roact"))
+    if let Some((value_start, value_end)) = roact.value {
+        if let Some(i) = doc[value_start..value_end].rfind("Roact") {
+            let start = value_start + i;
+            edits.push((start, start + "Roact".len(), "React".to_string()));
+        }
+    }
+    // Calls go through the file's macro(local e = Roact.createElement) when it has one
+    let macro_symbol = symbols.symbols.iter().find(|symbol| {
+        symbols
+            .create_element_macros(doc, doc.len(), &roact.name)
+            .contains(&symbol.name)
+    });
+
+    for (start, end) in symbols.usages(doc, &roact.name) {
+        // Another local named Roact shadows it here
+        if symbols
+            .resolve(&roact.name, start)
+            .is_some_and(|symbol| symbol.range != roact.range)
+        {
+            continue;
+        }
+        let member_start = end + 1;
+        let member = doc[end..]
+            .strip_prefix('.')
+            .map(|rest| &rest[..ident_len(rest)])
+            .unwrap_or("");
+        let member_end = member_start + member.len();
+
+        match member {
+            "createElement" => {
+                let in_macro_definition = macro_symbol
+                    .and_then(|symbol| symbol.value)
+                    .is_some_and(|(a, b)| start >= a && start < b);
+                match macro_symbol {
+                    Some(symbol) if !in_macro_definition => {
+                        edits.push((start, member_end, symbol.name.clone()))
+                    }
+                    _ => edits.push((start, end, "React".to_string())),
+                }
+            }
+            // Fragment is an element type in React, its children become the third argument
+            "createFragment" if doc[member_end..].starts_with('(') => {
+                let args_empty = doc[member_end + 1..].trim_start().starts_with(')');
+                let replacement = if args_empty {
+                    "React.createElement(React.Fragment"
+                } else {
+                    "React.createElement(React.Fragment, nil, "
+                };
+                let args_start = if args_empty {
+                    member_end + 1
+                } else {
+                    member_end
+                        + 1
+                        + (doc[member_end + 1..].len() - doc[member_end + 1..].trim_start().len())
+                };
+                edits.push((start, args_start, replacement.to_string()));
+            }
+            // [Roact.Ref] and [Roact.Children] keys are plain ref and children props
+            // (props[Roact.Children] indexing becomes props.children)
+            "Ref" | "Children"
+                if doc[..start].ends_with('[') && doc[member_end..].starts_with(']') =>
+            {
+                let key = if member == "Ref" { "ref" } else { "children" };
+                let rest = doc[member_end + 1..].trim_start();
+                let is_key = rest.starts_with('=') && !rest.starts_with("==");
+                let key = if is_key {
+                    key.to_string()
+                } else {
+                    format!(".{}", key)
+                };
+                edits.push((start - 1, member_end + 1, key));
+            }
+            _ => {
+                if let Some((_, hint)) = MANUAL_MEMBERS.iter().find(|(name, _)| *name == member) {
+                    let line = byte_offset_to_position(doc, start).line + 1;
+                    notes.push(format!(
+                        "Line {}: {}.{}, {}",
+                        line, roact.name, member, hint
+                    ));
+                }
+                edits.push((start, end, "React".to_string()));
+            }
+        }
+    }
+
+    edits.sort_by_key(|(start, _, _)| *start);
+    let edits = edits
+        .into_iter()
+        .map(|(start, end, new_text)| TextEdit {
+            range: Range {
+                start: byte_offset_to_position(doc, start),
+                end: byte_offset_to_position(doc, end),
+            },
+            new_text,
+        })
+        .collect();
+    (edits, notes)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{file_diagnoser::position_to_byte_offset, roact_migration::migrate_roact_edits};
+
+    fn migrate(doc: &str) -> (String, Vec<String>) {
+        let (edits, notes) = migrate_roact_edits(doc, &Default::default());
+        let mut result = doc.to_string();
+        for edit in edits.iter().rev() {
+            let start = position_to_byte_offset(doc, &edit.range.start).unwrap();
+            let end = position_to_byte_offset(doc, &edit.range.end).unwrap();
+            result.replace_range(start..end, &edit.new_text);
+        }
+        (result, notes)
+    }
+
+    #[test]
+    fn test_migrate_roact() {
+        let doc = r#"local Roact = require(game.ReplicatedStorage.Packages.Roact)
+local e = Roact.createElement
+
+local Button = Roact.Component:extend("Button")
+
+function Button:render()
+    local label = "Roact.createElement stays in strings"
+    return Roact.createFragment({
+        Frame = Roact.createElement("Frame", {
+            [Roact.Ref] = self.ref,
+            [Roact.Event.Activated] = self.props.onActivated,
+            [Roact.Change.AbsoluteSize] = nil,
+        }, self.props[Roact.Children]),
+    })
+end
+
+Roact.mount(e(Button), game.Players.LocalPlayer.PlayerGui)
+return Button
+"#;
+        let (migrated, notes) = migrate(doc);
+        assert_eq!(
+            migrated,
+            r#"local React = require(game.ReplicatedStorage.Packages.React)
+local e = React.createElement
+
+local Button = React.Component:extend("Button")
+
+function Button:render()
+    local label = "Roact.createElement stays in strings"
+    return React.createElement(React.Fragment, nil, {
+        Frame = e("Frame", {
+            ref = self.ref,
+            [React.Event.Activated] = self.props.onActivated,
+            [React.Change.AbsoluteSize] = nil,
+        }, self.props.children),
+    })
+end
+
+React.mount(e(Button), game.Players.LocalPlayer.PlayerGui)
+return Button
+"#
+        );
+        assert_eq!(notes.len(), 1);
+        assert!(notes[0].starts_with("Line 17: Roact.mount, use ReactRoblox.createRoot"));
+
+        // Files without Roact, or with React already around, are left alone
+        assert!(
+            migrate_roact_edits("local React = require(game.React)\n", &Default::default())
+                .0
+                .is_empty()
+        );
+        let (edits, notes) = migrate_roact_edits(
+            "local Roact = require(game.Roact)\nlocal React = require(game.React)\n",
+            &Default::default(),
+        );
+        assert!(edits.is_empty());
+        assert_eq!(notes.len(), 1);
+    }
+}
@@ -0,0 +1,95 @@
+// This script holds docs of every diagnostic rule, diagnostics link to them through code_description
+// Links use our own scheme, clients show them by running RULE_DOCS_COMMAND with the rule code
+
+use tower_lsp::lsp_types::{CodeDescription, Url};
+
+use crate::file_diagnoser::{RESTRICTED_PROPERTY_CODE, UNKNOWN_PROPERTY_CODE};
+use crate::hook_diagnoser::{MISSING_DEPENDENCY_CODE, MISSING_DEPENDENCY_TABLE_CODE};
+
+pub const RULE_DOCS_COMMAND: &str = "rblx-react-lsp.ruleDocs";
+const RULE_DOCS_BASE: &str = "rblx-react-lsp://rules/";
+
+#[derive(Debug)]
+pub struct Rule {
+    pub code: &'static str,
+    pub title: &'static str,
+    pub docs: &'static str, // Markdown body, what triggers it and how to deal with it
+}
+
+pub const RULES: [Rule; 4] = [
+    Rule {
+    code: UNKNOWN_PROPERTY_CODE,
+    title: "Unknown property",
+    docs: "Prop key isn't a property of the created class(Or any of its superclasses) in the loaded API dump. \
+Usually a typo, quick fixes offer renaming it to the closest property or removing the entry.\n\n\
+`ref`, `key`, `children` and `[React.Event.X]`/`[React.Change.X]` keys are never reported. \
+Classes missing from the dump aren't checked at all, declare your own through `customClasses.json` or a `*.d.luau` stub. \
+If a real property is reported, the cached API is probably outdated, run `rblx-react-lsp.genMetadata`.",
+    },
+    Rule {
+        code: RESTRICTED_PROPERTY_CODE,
+        title: "Restricted property",
+        docs: "Prop key is a real property, but scripts can't set it. Either it's read-only(e.g. `AbsoluteSize`), tagged `NotScriptable`, \
+or writing it needs a security level scripts don't have(e.g. `PluginSecurity`, `RobloxScriptSecurity`). \
+React would error when applying it, so remove the entry. Read-only ones can still be watched with `[React.Change.X]`.\n\n\
+These properties never show up in completions(Read-only ones do with `completion.members.includeReadOnly`).",
+    },
+    Rule {
+        code: MISSING_DEPENDENCY_CODE,
+        title: "Missing effect dependency",
+        docs: "`useEffect`/`useLayoutEffect` body reads a local or prop of the component that its dependency table doesn't list, \
+so the effect keeps seeing the value from the render it was created in. Add it to the table(`props` covers `props.text` too).\n\n\
+Module level locals, locals declared inside the effect, `useState`/`useReducer` setters, `useRef` refs and `useBinding` results never change between renders, \
+so they aren't required. Dependency tables held in a variable aren't checked.",
+    },
+    Rule {
+        code: MISSING_DEPENDENCY_TABLE_CODE,
+        title: "Missing effect dependency table",
+        docs: "Without a dependency table the effect runs after every render. \
+Pass `{}` to run it only on mount, or list what it reads to rerun when those change.",
+    },
+];
+
+pub fn find_rule(code: &str) -> Option<&'static Rule> {
+    RULES.iter().find(|rule| rule.code == code)
+}
+
+pub fn rule_docs_url(code: &str) -> Option<Url> {
+    Url::parse(&format!("{}{}", RULE_DOCS_BASE, code)).ok()
+}
+
+// Accepts either the code or its docs url, so clients can pass whatever link got clicked
+pub fn rule_docs_markdown(code_or_url: &str) -> Option<String> {
+    let code = code_or_url
+        .strip_prefix(RULE_DOCS_BASE)
+        .unwrap_or(code_or_url);
+    let rule = find_rule(code)?;
+    Some(format!(
+        "# {} (`{}`)\n\n{}",
+        rule.title, rule.code, rule.docs
+    ))
+}
+
+pub fn code_description(code: &str) -> Option<CodeDescription> {
+    find_rule(code)?;
+    Some(CodeDescription {
+        href: rule_docs_url(code)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rules::{code_description, rule_docs_markdown, RULES};
+
+    #[test]
+    fn test_rule_docs() {
+        for rule in &RULES {
+            let href = code_description(rule.code).unwrap().href;
+            let docs = rule_docs_markdown(href.as_str()).unwrap();
+            assert_eq!(rule_docs_markdown(rule.code), Some(docs.clone()));
+            assert!(docs.contains(rule.code));
+        }
+        assert_eq!(code_description("not-a-rule"), None);
+        assert_eq!(rule_docs_markdown("not-a-rule"), None);
+    }
+}
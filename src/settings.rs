@@ -0,0 +1,323 @@
+// This script holds user configurable settings(Sent via initializationOptions or didChangeConfiguration)
+
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Duration;
+use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, NumberOrString};
+
+use crate::api_fetcher::FetcherConfig;
+use crate::file_diagnoser::{RESTRICTED_PROPERTY_CODE, UNKNOWN_PROPERTY_CODE};
+use crate::hook_diagnoser::{MISSING_DEPENDENCY_CODE, MISSING_DEPENDENCY_TABLE_CODE};
+
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Settings {
+    pub completion: CompletionSettings,
+    pub tree_sync: TreeSyncSettings,
+    pub sort_props: SortPropsSettings,
+    pub react_require: ReactRequireSettings,
+    pub sourcemap: SourcemapSettings,
+    pub roact: RoactSettings,
+    pub create_element: CreateElementSettings,
+    pub api: ApiSettings,
+    pub diagnostics: DiagnosticSettings,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default, rename_all = "camelCase")]
+pub struct CompletionSettings {
+    // When running next to luau-lsp, drop generic items it already provides (e.g. plain locals)
+    pub coexist_with_luau_lsp: bool,
+    // Max serialized size of a completion response in bytes, 0 disables the budget
+    pub max_response_bytes: usize,
+    // Max items per response after filtering by typed word, 0 disables the cap
+    pub max_items: usize,
+    // Class name -> property names never offered for that class(And its subclasses)
+    pub excluded_properties: HashMap<String, Vec<String>>,
+    // Ranks purely by name instead of usage frequency(Also forced by RBLX_REACT_LSP_DETERMINISTIC_RANKING=1)
+    pub deterministic_ranking: bool,
+    // How properties, events and class names are ordered
+    pub sorting: CompletionSorting,
+    // Extra identifiers offered as createElement's first argument(e.g. class constants element factories take)
+    pub element_constants: Vec<String>,
+    // Which tagged members get offered, untagged ones always are
+    pub members: MemberSettings,
+    // Class names are only offered if they descend from one of these, empty offers every creatable class
+    pub class_bases: Vec<String>,
+}
+
+impl Default for CompletionSettings {
+    fn default() -> Self {
+        Self {
+            coexist_with_luau_lsp: false,
+            max_response_bytes: 512 * 1024,
+            max_items: 100,
+            excluded_properties: HashMap::new(),
+            deterministic_ranking: false,
+            sorting: CompletionSorting::default(),
+            element_constants: Vec::new(),
+            members: MemberSettings::default(),
+            class_bases: vec!["GuiBase2d".to_string(), "UIBase".to_string()],
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum CompletionSorting {
+    #[default]
+    Frequency, // Most used first(Best match first for class names), adapts to what gets typed
+    Alphabetical,
+    ApiOrder, // Own members before inherited ones, classes followed by their subclasses
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(default, rename_all = "camelCase")]
+pub struct MemberSettings {
+    pub include_deprecated: bool,
+    pub include_read_only: bool, // Change keys still offer them, those only read
+    pub include_hidden: bool,    // Hidden ones work fine, Studio just doesn't list them
+}
+
+impl Default for MemberSettings {
+    fn default() -> Self {
+        Self {
+            include_deprecated: false,
+            include_read_only: false,
+            include_hidden: true,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default, rename_all = "camelCase")]
+pub struct TreeSyncSettings {
+    // Streams element tree of changed documents through rblxReact/treeSync notifications
+    pub enabled: bool,
+    // Min time between two syncs of the same document
+    pub throttle_ms: u64,
+}
+
+impl Default for TreeSyncSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            throttle_ms: 250,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum PropsOrder {
+    Alphabetical,
+    #[default]
+    Frequency, // Same ranking completions use
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default, rename_all = "camelCase")]
+pub struct SortPropsSettings {
+    // Order used by the "source.sortProps" code action
+    pub order: PropsOrder,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ReactRequireSettings {
+    // Instance path React lives under, the "Add React require" fix inserts require(<path>.React)
+    pub path: String,
+    // Modules treated as React, module names or trailing path segments(A * matches anything)
+    // like "React17" or "Packages.React*", and "/.../" regexes matched against the dotted path
+    pub modules: Vec<String>,
+}
+
+impl ReactRequireSettings {
+    // Module the "Add React require" fix requires, first listed plain name(React if there's none)
+    pub fn module_name(&self) -> &str {
+        self.modules
+            .iter()
+            .filter(|module| !module.starts_with('/') && !module.contains('*'))
+            .find_map(|module| module.rsplit(['.', '/']).next())
+            .filter(|name| !name.is_empty())
+            .unwrap_or("React")
+    }
+}
+
+impl Default for ReactRequireSettings {
+    fn default() -> Self {
+        Self {
+            path: "game:GetService(\"ReplicatedStorage\").Packages".to_string(),
+            modules: vec!["React".to_string()],
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default, rename_all = "camelCase")]
+pub struct SourcemapSettings {
+    // Sourcemaps relative to workspace root in precedence order, empty discovers every one
+    pub files: Vec<String>,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(default, rename_all = "camelCase")]
+pub struct RoactSettings {
+    // Treats require(...Roact) like React, so legacy Roact.createElement/Roact.Event code gets analyzed too
+    pub enabled: bool,
+}
+
+#[derive(Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default, rename_all = "camelCase")]
+pub struct CreateElementSettings {
+    // Module names(A * matches anything) whose require returns a createElement helper, e.g. "e" or "*CreateElement"
+    // Workspace modules returning one get recognized without being listed
+    pub helper_modules: Vec<String>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ApiSettings {
+    // Version hash downloads stick to instead of the latest one(e.g. "version-0123456789abcdef")
+    pub pinned_version: Option<String>,
+    // Cache older than this gets revalidated when loaded, 0 never does
+    pub max_age_hours: u64,
+    // Download endpoints and network setup, unset ones come from RBLX_REACT_LSP_* env vars or defaults
+    pub setup_url: Option<String>, // Replaces https://setup.rbxcdn.com
+    pub client_version_url: Option<String>,
+    pub tracker_url: Option<String>, // Replaces the client tracker's raw GitHub url
+    pub proxy: Option<String>,
+    pub ca_certificate: Option<String>, // PEM file path
+    // API-Dump.json parsed instead of downloading anything(Air gapped setups, beta dumps)
+    pub dump_path: Option<String>,
+    // Directory the parsed API cache lives in, defaults to the platform cache directory
+    pub cache_dir: Option<String>,
+}
+
+impl Default for ApiSettings {
+    fn default() -> Self {
+        Self {
+            pinned_version: None,
+            max_age_hours: 24,
+            setup_url: None,
+            client_version_url: None,
+            tracker_url: None,
+            proxy: None,
+            ca_certificate: None,
+            dump_path: None,
+            cache_dir: None,
+        }
+    }
+}
+
+impl ApiSettings {
+    // Blank counts as not pinned, so clients can clear it with ""
+    pub fn pinned(&self) -> Option<&str> {
+        self.pinned_version
+            .as_deref()
+            .map(str::trim)
+            .filter(|version| !version.is_empty())
+    }
+
+    pub fn dump_path(&self) -> Option<PathBuf> {
+        self.dump_path
+            .as_deref()
+            .map(str::trim)
+            .filter(|path| !path.is_empty())
+            .map(PathBuf::from)
+    }
+
+    pub fn cache_dir(&self) -> Option<PathBuf> {
+        self.cache_dir
+            .as_deref()
+            .map(str::trim)
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+    }
+
+    pub fn fetcher_config(&self) -> FetcherConfig {
+        let set = |value: &Option<String>| {
+            value
+                .as_deref()
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+                .map(str::to_string)
+        };
+        FetcherConfig {
+            setup_url: set(&self.setup_url),
+            client_version_url: set(&self.client_version_url),
+            tracker_url: set(&self.tracker_url),
+            proxy: set(&self.proxy),
+            ca_certificate: set(&self.ca_certificate),
+        }
+        .or(FetcherConfig::from_env())
+    }
+
+    pub fn max_age(&self) -> Option<Duration> {
+        (self.max_age_hours > 0).then(|| Duration::from_secs(self.max_age_hours * 60 * 60))
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum DiagnosticLevel {
+    Error,
+    #[default]
+    Warning,
+    Information,
+    Hint,
+    Off, // Never published
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(default, rename_all = "camelCase")]
+pub struct DiagnosticSettings {
+    // Severity each rule gets published with, so teams can turn them on one at a time
+    pub unknown_property: DiagnosticLevel,
+    pub restricted_property: DiagnosticLevel,
+    pub missing_effect_dependency: DiagnosticLevel,
+    pub missing_effect_dependency_table: DiagnosticLevel,
+}
+
+impl DiagnosticSettings {
+    fn level(&self, code: &str) -> DiagnosticLevel {
+        match code {
+            UNKNOWN_PROPERTY_CODE => self.unknown_property,
+            RESTRICTED_PROPERTY_CODE => self.restricted_property,
+            MISSING_DEPENDENCY_CODE => self.missing_effect_dependency,
+            MISSING_DEPENDENCY_TABLE_CODE => self.missing_effect_dependency_table,
+            _ => DiagnosticLevel::Warning,
+        }
+    }
+
+    // Drops diagnostics of rules turned off and gives the rest their configured severity
+    pub fn apply(&self, diagnostics: Vec<Diagnostic>) -> Vec<Diagnostic> {
+        diagnostics
+            .into_iter()
+            .filter_map(|mut diagnostic| {
+                let level = match &diagnostic.code {
+                    Some(NumberOrString::String(code)) => self.level(code),
+                    _ => DiagnosticLevel::Warning,
+                };
+                diagnostic.severity = Some(match level {
+                    DiagnosticLevel::Error => DiagnosticSeverity::ERROR,
+                    DiagnosticLevel::Warning => DiagnosticSeverity::WARNING,
+                    DiagnosticLevel::Information => DiagnosticSeverity::INFORMATION,
+                    DiagnosticLevel::Hint => DiagnosticSeverity::HINT,
+                    DiagnosticLevel::Off => return None,
+                });
+                Some(diagnostic)
+            })
+            .collect()
+    }
+}
+
+impl Settings {
+    // Client can either send settings as is, or nested under our section name
+    pub fn from_value(value: &Value) -> Self {
+        let section = value.get("rblxReactLsp").unwrap_or(value);
+        serde_json::from_value(section.clone()).unwrap_or_default()
+    }
+}
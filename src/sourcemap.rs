@@ -0,0 +1,302 @@
+// This script reads Rojo sourcemaps, mapping instance paths(ReplicatedStorage.Packages.Button) to files
+// Multi place projects have one per *.project.json, they get merged with earlier ones winning conflicts
+
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::file_diagnoser::split_top_level;
+use crate::file_manager::find_workspace_files;
+use crate::file_refactorer::is_valid_identifier;
+
+pub const DEFAULT_SOURCEMAP: &str = "sourcemap.json";
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct SourcemapNode {
+    name: String,
+    #[serde(default)]
+    class_name: String,
+    #[serde(default)]
+    file_paths: Vec<PathBuf>, // Relative to sourcemap's folder
+    #[serde(default)]
+    children: Vec<SourcemapNode>,
+}
+
+#[derive(Debug, Default)]
+pub struct Sourcemap {
+    files: HashMap<Vec<String>, PathBuf>, // Instance path(Without game) -> script file
+    origins: HashMap<Vec<String>, PathBuf>, // Instance path -> sourcemap that mapped it
+    instances: HashMap<PathBuf, Vec<String>>, // Script file -> instance path
+    module_scripts: Vec<Vec<String>>, // Requirable instance paths under DataModel, in precedence order
+}
+
+pub fn is_sourcemap_file(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name == DEFAULT_SOURCEMAP || name.ends_with(".sourcemap.json"))
+}
+
+// Every sourcemap under root in precedence order, sourcemap.json files(Default place) first
+pub fn find_sourcemap_files(root: &Path) -> Vec<PathBuf> {
+    let mut files = find_workspace_files(root, is_sourcemap_file);
+    files.sort_by_key(|path| {
+        let is_default = path.file_name().and_then(|name| name.to_str()) == Some(DEFAULT_SOURCEMAP);
+        (!is_default, path.clone())
+    });
+    files
+}
+
+fn is_script_path(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|ext| ext.to_str()),
+        Some("luau" | "lua")
+    )
+}
+
+// Instance names of require(...) argument, e.g. ["game", "ReplicatedStorage", "Shared"]
+// Each is tagged with whether it's a plain member access(Only those can mean .Parent)
+fn require_segments(value: &str) -> Option<Vec<(String, bool)>> {
+    let inner = value
+        .trim()
+        .strip_prefix("require")?
+        .trim_start()
+        .strip_prefix('(')?
+        .strip_suffix(')')?;
+
+    split_top_level(inner, &['.', ':'])
+        .into_iter()
+        .map(|(start, end)| {
+            let piece = &inner[start..end];
+            match piece.split_once('(') {
+                // GetService("X"), WaitForChild("X"), FindFirstChild("X")
+                Some((_, arg)) => {
+                    let name = arg
+                        .trim_end_matches(')')
+                        .trim()
+                        .trim_matches(['"', '\'', '`']);
+                    (!name.is_empty()).then(|| (name.to_string(), false))
+                }
+                None => Some((piece.to_string(), true)),
+            }
+        })
+        .collect()
+}
+
+impl Sourcemap {
+    // Merges sourcemaps in precedence order, returning a message for each file that failed to load
+    // and each instance two sourcemaps disagree on
+    pub fn load(paths: &[PathBuf]) -> (Self, Vec<String>) {
+        let mut sourcemap = Self::default();
+        let mut warnings = Vec::new();
+
+        for path in paths {
+            let root = match fs::read_to_string(path) {
+                Ok(text) => serde_json::from_str::<SourcemapNode>(&text).map_err(|e| e.to_string()),
+                Err(e) => Err(e.to_string()),
+            };
+            match root {
+                Ok(root) => {
+                    let base = path.parent().unwrap_or(Path::new(""));
+                    // Places start at DataModel, packages at their own root instance
+                    let is_place = root.class_name == "DataModel";
+                    let instance_path = if is_place {
+                        Vec::new()
+                    } else {
+                        vec![root.name.clone()]
+                    };
+                    let origin = (path.as_path(), is_place);
+                    sourcemap.merge(&root, instance_path, base, origin, &mut warnings);
+                }
+                Err(e) => warnings.push(format!("Failed to load {}: {}", path.display(), e)),
+            }
+        }
+
+        (sourcemap, warnings)
+    }
+
+    fn merge(
+        &mut self,
+        node: &SourcemapNode,
+        instance_path: Vec<String>,
+        base: &Path,
+        origin: (&Path, bool), // Sourcemap file and whether it's rooted at DataModel
+        warnings: &mut Vec<String>,
+    ) {
+        if let Some(file) = node.file_paths.iter().find(|file| is_script_path(file)) {
+            let file = base.join(file);
+            match self.files.get(&instance_path) {
+                Some(existing) if *existing != file => warnings.push(format!(
+                    "{} is {} in {} but {} in {}, keeping the first",
+                    instance_path.join("."),
+                    existing.display(),
+                    self.origins[&instance_path].display(),
+                    file.display(),
+                    origin.0.display()
+                )),
+                Some(_) => {}
+                None => {
+                    self.files.insert(instance_path.clone(), file.clone());
+                    self.origins
+                        .insert(instance_path.clone(), origin.0.to_path_buf());
+                    // Package roots aren't reachable from game, so only places can be required by path
+                    if origin.1 && node.class_name == "ModuleScript" {
+                        self.module_scripts.push(instance_path.clone());
+                    }
+                }
+            }
+            // Packages show up in both place and package sourcemap, place one comes first
+            self.instances
+                .entry(file)
+                .or_insert_with(|| instance_path.clone());
+        }
+
+        for child in &node.children {
+            let mut child_path = instance_path.clone();
+            child_path.push(child.name.clone());
+            self.merge(child, child_path, base, origin, warnings);
+        }
+    }
+
+    // Script file a require(game...) or require(script...) call inside from_file points at
+    pub fn resolve_require(&self, value: &str, from_file: &Path) -> Option<&Path> {
+        let mut segments = require_segments(value)?.into_iter();
+        let mut path = match segments.next()?.0.as_str() {
+            "game" => Vec::new(),
+            "script" => self.instances.get(from_file)?.clone(),
+            _ => return None,
+        };
+        for (name, is_member) in segments {
+            if is_member && name == "Parent" {
+                path.pop()?;
+            } else {
+                path.push(name);
+            }
+        }
+        self.files.get(&path).map(PathBuf::as_path)
+    }
+
+    // (Instance path, file) of every ModuleScript a require could load
+    pub fn module_scripts(&self) -> impl Iterator<Item = (&[String], &Path)> {
+        self.module_scripts
+            .iter()
+            .filter_map(|path| Some((path.as_slice(), self.files.get(path)?.as_path())))
+    }
+
+    // Path requiring file from from_file, if file is a ModuleScript reachable from game
+    pub fn require_path_to(&self, file: &Path, from_file: &Path) -> Option<String> {
+        let target = self.instances.get(file)?;
+        if !self.module_scripts.contains(target) {
+            return None;
+        }
+        self.require_path(target, from_file)
+    }
+
+    // Inverse of resolve_require, script relative within the same service and game rooted otherwise
+    pub fn require_path(&self, target: &[String], from_file: &Path) -> Option<String> {
+        let member = |name: &String| {
+            if is_valid_identifier(name) {
+                format!(".{}", name)
+            } else {
+                format!(":WaitForChild(\"{}\")", name)
+            }
+        };
+
+        if let Some(from) = self.instances.get(from_file) {
+            if !from.is_empty() && from.first() == target.first() {
+                let common = from.iter().zip(target).take_while(|(a, b)| a == b).count();
+                let mut path = "script".to_string();
+                path.push_str(&".Parent".repeat(from.len() - common));
+                path.extend(target[common..].iter().map(member));
+                return Some(path);
+            }
+        }
+
+        let (service, rest) = target.split_first()?;
+        let mut path = format!("game:GetService(\"{}\")", service);
+        path.extend(rest.iter().map(member));
+        Some(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs};
+
+    use crate::sourcemap::{find_sourcemap_files, Sourcemap};
+
+    #[test]
+    fn test_sourcemap_merge() {
+        let root = env::temp_dir().join("rblx_react_lsp_tests/sourcemaps");
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("packages")).unwrap();
+
+        fs::write(
+            root.join("sourcemap.json"),
+            r#"{ "name": "Place", "className": "DataModel", "children": [
+                { "name": "ReplicatedStorage", "className": "ReplicatedStorage", "children": [
+                    { "name": "App", "className": "ModuleScript", "filePaths": ["src/App.luau"] },
+                    { "name": "Components", "className": "ModuleScript", "filePaths": ["src/Components/init.luau"] }
+                ] }
+            ] }"#,
+        )
+        .unwrap();
+        fs::write(
+            root.join("packages/ui.sourcemap.json"),
+            r#"{ "name": "Place", "className": "DataModel", "children": [
+                { "name": "ReplicatedStorage", "className": "ReplicatedStorage", "children": [
+                    { "name": "Components", "className": "ModuleScript", "filePaths": ["Components.luau"] },
+                    { "name": "Theme", "className": "ModuleScript", "filePaths": ["Theme.luau"] }
+                ] }
+            ] }"#,
+        )
+        .unwrap();
+
+        let files = find_sourcemap_files(&root);
+        assert_eq!(files[0], root.join("sourcemap.json"));
+
+        let (sourcemap, warnings) = Sourcemap::load(&files);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].starts_with("ReplicatedStorage.Components is"));
+
+        let app = root.join("src/App.luau");
+        assert_eq!(
+            sourcemap.resolve_require("require(script.Parent.Components)", &app),
+            Some(root.join("src/Components/init.luau").as_path())
+        );
+        assert_eq!(
+            sourcemap.resolve_require(
+                r#"require(game:GetService("ReplicatedStorage"):WaitForChild("Theme"))"#,
+                &app
+            ),
+            Some(root.join("packages/Theme.luau").as_path())
+        );
+        assert_eq!(
+            sourcemap.resolve_require("require(script.Missing)", &app),
+            None
+        );
+
+        // Suggested paths resolve back to the same file
+        let theme = root.join("packages/Theme.luau");
+        let (target, _) = sourcemap
+            .module_scripts()
+            .find(|(_, file)| *file == theme)
+            .unwrap();
+        let path = sourcemap.require_path(target, &app).unwrap();
+        assert_eq!(path, "script.Parent.Theme");
+        assert_eq!(
+            sourcemap.resolve_require(&format!("require({})", path), &app),
+            Some(theme.as_path())
+        );
+        assert_eq!(
+            sourcemap.require_path(
+                &["ServerStorage".to_string(), "Data Store".to_string()],
+                &app
+            ),
+            Some("game:GetService(\"ServerStorage\"):WaitForChild(\"Data Store\")".to_string())
+        );
+
+        fs::remove_dir_all(&root).ok();
+    }
+}
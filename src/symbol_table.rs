@@ -0,0 +1,1278 @@
+// This script builds a per document table of locals, parameters and assignments
+// (Token based, so strings and comments never get mistaken for code like the old regex lookups did)
+
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::sync::Arc;
+
+use crate::luaurc::{module_name, string_path_segments, string_require_path};
+
+lazy_static! {
+    // Matches require*(**.X) assignment values, capturing the instance path(React is checked against it)
+    static ref REACT_REQUIRE_PATTERN: Regex =
+        Regex::new(r#"(?is)^require\s*\((.*\.\w+)\s*\)$"#).unwrap();
+    // Matches start of React.memo(...)/React.forwardRef(...) up to the wrapped component
+    static ref COMPONENT_WRAPPER_PATTERN: Regex =
+        Regex::new(r#"^(?:[A-Za-z_]\w*\.)?(?:memo|forwardRef)\s*\(\s*"#).unwrap();
+}
+
+#[derive(Debug, Clone)]
+enum ReactModule {
+    // Trailing segments of the require path, each may use *("React17", "Packages.React*")
+    Path(Vec<String>),
+    // Matched against the whole path, segments joined by dots
+    Regex(Regex),
+}
+
+// Which requires count as React or a createElement helper, from settings and .luaurc files
+// (Every table gets built with one, so sessions never see each other's)
+#[derive(Debug, Clone, Default)]
+pub struct RequireContext {
+    roact_compat: bool,              // require(...Roact) counts as React
+    react_modules: Vec<ReactModule>, // reactRequire.modules, empty means only ones named React
+    react_aliases: Vec<String>,      // Lowercased names of .luaurc aliases pointing at React
+    // Module name patterns(A * matches anything) whose require returns a createElement helper,
+    // from settings plus workspace modules returning one
+    create_element_modules: Vec<String>,
+}
+
+impl RequireContext {
+    pub fn set_roact_compat(&mut self, enabled: bool) {
+        self.roact_compat = enabled;
+    }
+
+    // Sets modules counted as React, "/.../" entries are regexes, anything else a require path
+    // Returns a message for each entry that isn't a valid regex
+    pub fn set_react_modules(&mut self, patterns: &[String]) -> Vec<String> {
+        let mut modules = Vec::new();
+        let mut warnings = Vec::new();
+        for pattern in patterns {
+            let regex = pattern
+                .strip_prefix('/')
+                .and_then(|rest| rest.strip_suffix('/'))
+                .filter(|regex| !regex.is_empty());
+            let module = match regex {
+                Some(regex) => match Regex::new(regex) {
+                    Ok(regex) => ReactModule::Regex(regex),
+                    Err(e) => {
+                        warnings.push(format!("Invalid React module regex {}: {}", pattern, e));
+                        continue;
+                    }
+                },
+                None if pattern.contains('/') => ReactModule::Path(
+                    string_path_segments(pattern)
+                        .into_iter()
+                        .map(str::to_string)
+                        .collect(),
+                ),
+                None => ReactModule::Path(
+                    instance_path_segments(pattern)
+                        .into_iter()
+                        .map(str::to_string)
+                        .collect(),
+                ),
+            };
+            modules.push(module);
+        }
+        self.react_modules = modules;
+        warnings
+    }
+
+    pub fn set_react_aliases(&mut self, aliases: Vec<String>) {
+        self.react_aliases = aliases;
+    }
+
+    pub fn set_create_element_modules(&mut self, patterns: Vec<String>) {
+        self.create_element_modules = patterns;
+    }
+
+    pub fn create_element_modules(&self) -> &[String] {
+        &self.create_element_modules
+    }
+
+    // Whether a required module's path is React(Or ends in Roact when compatibility mode is on)
+    pub(crate) fn is_react_module(&self, segments: &[&str]) -> bool {
+        let Some(name) = segments.last() else {
+            return false;
+        };
+        let listed = if self.react_modules.is_empty() {
+            name.eq_ignore_ascii_case("React")
+        } else {
+            self.react_modules.iter().any(|module| match module {
+                ReactModule::Path(patterns) => {
+                    !patterns.is_empty()
+                        && patterns.len() <= segments.len()
+                        && patterns
+                            .iter()
+                            .rev()
+                            .zip(segments.iter().rev())
+                            .all(|(pattern, segment)| matches_module_pattern(pattern, segment))
+                }
+                ReactModule::Regex(regex) => regex.is_match(&segments.join(".")),
+            })
+        };
+        listed || (self.roact_compat && name.eq_ignore_ascii_case("Roact"))
+    }
+
+    // Whether a string require path loads React, by its segments or through a .luaurc alias
+    pub(crate) fn is_react_require_path(&self, path: &str) -> bool {
+        if self.is_react_module(&string_path_segments(path)) {
+            return true;
+        }
+        let Some(alias) = path.strip_prefix('@') else {
+            return false;
+        };
+        let alias = alias.trim_end_matches('/').to_lowercase();
+        self.react_aliases.contains(&alias)
+    }
+
+    fn is_create_element_module(&self, name: &str) -> bool {
+        self.create_element_modules
+            .iter()
+            .any(|pattern| matches_module_pattern(pattern, name))
+    }
+}
+
+// Segments of an instance path, game:GetService("ReplicatedStorage").Packages:WaitForChild("React")
+// is ReplicatedStorage, Packages, React after game
+pub(crate) fn instance_path_segments(path: &str) -> Vec<&str> {
+    path.split(['.', ':'])
+        .map(|segment| match segment.split_once('(') {
+            Some((_, args)) => args.split([',', ')']).next().unwrap_or(""),
+            None => segment,
+        })
+        .map(|segment| segment.trim().trim_matches(['"', '\'', '`']))
+        .filter(|segment| !segment.is_empty())
+        .collect()
+}
+
+// Last path segment of require(script.Parent.Components), require(x:WaitForChild("Components"))
+// or require("./Components")
+pub(crate) fn required_module_name(value: &str) -> Option<String> {
+    if let Some(path) = string_require_path(value) {
+        let name = module_name(path);
+        return (!name.is_empty()).then(|| name.to_string());
+    }
+    let inner = value
+        .trim()
+        .strip_prefix("require")?
+        .trim_start()
+        .strip_prefix('(')?
+        .strip_suffix(')')?;
+    let last = inner.rsplit(['.', ':']).next()?.trim();
+    let name = match last.split_once('(') {
+        Some((_, arg)) => arg.trim_end_matches(')').trim().trim_matches(['"', '\'']),
+        None => last,
+    };
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+fn matches_module_pattern(pattern: &str, name: &str) -> bool {
+    let (pattern, name) = (pattern.to_lowercase(), name.to_lowercase());
+    match pattern.split_once('*') {
+        Some((prefix, suffix)) => {
+            name.len() >= prefix.len() + suffix.len()
+                && name.starts_with(prefix)
+                && name.ends_with(suffix)
+        }
+        None => pattern == name,
+    }
+}
+
+// Component wrapped by memo/forwardRef call(Offset into text, text), expression itself otherwise
+// Only the first argument counts, so memo's areEqual stays out
+pub(crate) fn unwrap_component_wrapper(text: &str) -> (usize, &str) {
+    let Some(wrapper) = COMPONENT_WRAPPER_PATTERN.find(text) else {
+        return (0, text);
+    };
+    let inner = &text[wrapper.end()..];
+    let first_arg = if inner.starts_with("function") {
+        inner
+    } else {
+        inner.split([',', ')']).next().unwrap_or("").trim()
+    };
+    (wrapper.end(), first_arg)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TokenKind {
+    Ident,
+    Str,
+    Number,
+    Punct,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Token {
+    kind: TokenKind,
+    start: usize,
+    end: usize,
+    line_break_before: bool,
+}
+
+// Level of long bracket starting at offset("[[" is 0, "[==[" is 2), None if it isn't one
+pub(crate) fn long_bracket_level(bytes: &[u8], offset: usize) -> Option<usize> {
+    if bytes.get(offset) != Some(&b'[') {
+        return None;
+    }
+    let mut level = 0;
+    while bytes.get(offset + 1 + level) == Some(&b'=') {
+        level += 1;
+    }
+    (bytes.get(offset + 1 + level) == Some(&b'[')).then_some(level)
+}
+
+// Offset right after the closing bracket of a long string/comment(Or end of doc if unclosed)
+pub(crate) fn long_bracket_end(doc: &str, content_start: usize, level: usize) -> usize {
+    let closing = format!("]{}]", "=".repeat(level));
+    doc[content_start..]
+        .find(&closing)
+        .map(|i| content_start + i + closing.len())
+        .unwrap_or(doc.len())
+}
+
+// End of the string literal or comment starting at offset, None if there's neither
+// (Unfinished quoted strings end at their line, so one stray quote doesn't eat the whole doc)
+pub(crate) fn literal_end(doc: &str, offset: usize) -> Option<usize> {
+    let rest = &doc[offset..];
+    let bytes = rest.as_bytes();
+    match *bytes.first()? {
+        quote @ (b'"' | b'\'' | b'`') => {
+            let mut j = 1;
+            while j < bytes.len() {
+                match bytes[j] {
+                    b'\\' => j += 2,
+                    c if c == quote => return Some(offset + j + 1),
+                    b'\n' if quote != b'`' => return Some(offset + j),
+                    _ => j += 1,
+                }
+            }
+            Some(doc.len())
+        }
+        b'-' if rest.starts_with("--") => {
+            Some(match long_bracket_level(doc.as_bytes(), offset + 2) {
+                Some(level) => long_bracket_end(doc, offset + 4 + level, level),
+                None => offset + rest.find('\n').unwrap_or(rest.len()),
+            })
+        }
+        b'[' => long_bracket_level(doc.as_bytes(), offset)
+            .map(|level| long_bracket_end(doc, offset + 2 + level, level)),
+        _ => None,
+    }
+    .map(|end| end.min(doc.len()))
+}
+
+// Tokens starting within range, range starting at a token(Or a line break) so lexing matches whole doc's
+fn tokenize(doc: &str, range: (usize, usize)) -> Vec<Token> {
+    const DOUBLE_PUNCT: [&str; 6] = ["==", "~=", "<=", ">=", "..", "::"];
+
+    let bytes = doc.as_bytes();
+    let mut tokens = Vec::new();
+    let mut i = range.0;
+    let mut line_break = range.0 > 0;
+
+    while i < range.1 {
+        let c = bytes[i];
+        let start = i;
+
+        if c == b'\n' {
+            line_break = true;
+            i += 1;
+            continue;
+        }
+        if c.is_ascii_whitespace() {
+            i += 1;
+            continue;
+        }
+
+        let kind = if let Some(end) = literal_end(doc, i) {
+            i = end;
+            // Comments
+            if doc[start..].starts_with("--") {
+                continue;
+            }
+            TokenKind::Str
+        } else if c.is_ascii_digit() {
+            while i < bytes.len()
+                && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'.' || bytes[i] == b'_')
+            {
+                i += 1;
+            }
+            TokenKind::Number
+        } else if c.is_ascii_alphabetic() || c == b'_' || c >= 0x80 {
+            while i < bytes.len()
+                && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_' || bytes[i] >= 0x80)
+            {
+                i += 1;
+            }
+            TokenKind::Ident
+        } else {
+            if doc[i..].starts_with("...") {
+                i += 3;
+            } else if DOUBLE_PUNCT.iter().any(|p| doc[i..].starts_with(p)) {
+                i += 2;
+            } else {
+                i += doc[i..].chars().next().map(char::len_utf8).unwrap_or(1);
+            }
+            TokenKind::Punct
+        };
+
+        tokens.push(Token {
+            kind,
+            start,
+            end: i,
+            line_break_before: line_break,
+        });
+        line_break = false;
+    }
+
+    tokens
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SymbolKind {
+    Local,         // local name = ...
+    LocalFunction, // local function name()
+    Function,      // function name()
+    Assignment,    // name = ...(Without local, either a global or reassigned local)
+    Parameter,
+}
+
+#[derive(Debug, Clone)]
+pub struct Symbol {
+    pub name: String,
+    pub kind: SymbolKind,
+    pub range: (usize, usize),         // Name itself
+    pub value: Option<(usize, usize)>, // Assigned expression
+    pub scope: (usize, usize),         // Where symbol is visible
+    pub function: Option<usize>,       // Index into functions, if symbol holds a function literal
+}
+
+#[derive(Debug, Clone)]
+pub struct FunctionInfo {
+    pub keyword_start: usize, // Start of "function" keyword
+    pub params: Vec<String>,
+    pub param_types: Vec<Option<(usize, usize)>>, // Type annotation of each param(Text after ":")
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SymbolTable {
+    pub symbols: Vec<Symbol>,
+    pub functions: Vec<FunctionInfo>,
+    // Every identifier that isn't a member access(e.g. "React" in React.createElement but not "createElement")
+    identifiers: Vec<(usize, usize)>,
+    // Starts of top level statements(Outside of every block and bracket), in order
+    statements: Vec<usize>,
+    // Open and close offsets of every "(", "{" and "[" pair in code, sorted by open
+    // (Unclosed ones close at end of doc, strings and comments never hold any)
+    brackets: Vec<(usize, usize)>,
+    requires: Arc<RequireContext>, // What the React and createElement helper lookups go by
+}
+
+const STATEMENT_KEYWORDS: [&str; 13] = [
+    "local", "return", "end", "else", "elseif", "until", "do", "then", "for", "while", "repeat",
+    "break", "continue",
+];
+// Tokens an expression can't end on, so a line break after one of these doesn't end the statement
+const BINARY_OPERATORS: [&str; 19] = [
+    "+", "-", "*", "/", "//", "%", "^", "..", "==", "~=", "<", ">", "<=", ">=", "and", "or", "not",
+    "=", ",",
+];
+// The "if" in "x = if a then b else c" is an expression without an end
+const EXPRESSION_PREFIXES: [&str; 24] = [
+    "=", ",", "(", "{", "[", "return", "and", "or", "not", "..", "+", "-", "*", "/", "%", "^",
+    "==", "~=", "<", ">", "<=", ">=", "#", "in",
+];
+
+struct Parser<'a> {
+    doc: &'a str,
+    tokens: Vec<Token>,
+    table: SymbolTable,
+    // Open blocks, each with symbols declared in it(Their scope ends with the block)
+    blocks: Vec<Vec<usize>>,
+    limit: usize, // Tokens from here on are only looked ahead at(A window's next statement)
+}
+
+impl<'a> Parser<'a> {
+    fn text(&self, index: usize) -> &'a str {
+        self.tokens
+            .get(index)
+            .map(|t| &self.doc[t.start..t.end])
+            .unwrap_or("")
+    }
+
+    fn is_ident(&self, index: usize) -> bool {
+        self.tokens
+            .get(index)
+            .is_some_and(|t| t.kind == TokenKind::Ident)
+    }
+
+    fn declare(
+        &mut self,
+        name_token: usize,
+        kind: SymbolKind,
+        value: Option<(usize, usize)>,
+    ) -> usize {
+        let token = self.tokens[name_token];
+        self.table.symbols.push(Symbol {
+            name: self.doc[token.start..token.end].to_string(),
+            kind,
+            range: (token.start, token.end),
+            value,
+            scope: (token.start, self.doc.len()),
+            function: None,
+        });
+        let index = self.table.symbols.len() - 1;
+        if let Some(block) = self.blocks.last_mut() {
+            block.push(index);
+        }
+        index
+    }
+
+    fn close_block(&mut self, end: usize) {
+        if let Some(block) = self.blocks.pop() {
+            for symbol in block {
+                self.table.symbols[symbol].scope.1 = end;
+            }
+        }
+    }
+
+    // Parses "(a, b, ...)" after a function keyword/name, opening the function body block
+    // Returns index of first token after the parameter list
+    fn function_body(&mut self, keyword: usize, paren: usize) -> usize {
+        let mut params = Vec::new();
+        let mut param_types = Vec::new();
+        let mut param_tokens = Vec::new();
+        let mut annotation_start: Option<usize> = None;
+        let mut i = paren + 1;
+        let mut depth = 1;
+        if self.text(paren) == "(" {
+            while i < self.tokens.len() && depth > 0 {
+                match self.text(i) {
+                    "(" | "{" | "[" => depth += 1,
+                    ")" | "}" | "]" => depth -= 1,
+                    _ => {}
+                }
+                // Annotation ends at next top level "," or the closing paren
+                if depth == 0 || (depth == 1 && self.text(i) == ",") {
+                    if let (Some(start), Some(last)) =
+                        (annotation_start.take(), param_types.last_mut())
+                    {
+                        *last = Some((start, self.tokens[i].start));
+                    }
+                }
+                let prev = self.text(i - 1);
+                if depth == 1 && self.is_ident(i) && (prev == "(" || prev == ",") {
+                    params.push(self.text(i).to_string());
+                    param_types.push(None);
+                    param_tokens.push(i);
+                }
+                if depth == 1 && self.text(i) == ":" && param_tokens.last() == Some(&(i - 1)) {
+                    annotation_start = Some(self.tokens[i].end);
+                }
+                i += 1;
+            }
+        } else {
+            i = paren;
+        }
+
+        self.table.functions.push(FunctionInfo {
+            keyword_start: self.tokens[keyword].start,
+            params,
+            param_types,
+        });
+        self.blocks.push(Vec::new());
+        for token in param_tokens {
+            let t = self.tokens[token];
+            self.table.identifiers.push((t.start, t.end));
+            self.declare(token, SymbolKind::Parameter, None);
+        }
+        i
+    }
+
+    // Spans of comma separated expressions starting at token index, up to where the statement ends
+    fn expression_list(&self, first: usize) -> Vec<(usize, usize)> {
+        let mut spans = Vec::new();
+        let mut depth = 0usize;
+        let mut blocks = 0usize; // function bodies(And blocks inside of them) within expression
+        let mut span_start: Option<usize> = None;
+        let mut last_end = 0;
+        let mut i = first;
+
+        while i < self.tokens.len() {
+            let token = self.tokens[i];
+            let text = self.text(i);
+            let prev = if i > first { self.text(i - 1) } else { "=" };
+
+            if depth == 0 && blocks == 0 {
+                let continues = BINARY_OPERATORS.contains(&prev)
+                    || [".", ":", "..", "and", "or"].contains(&text)
+                    || (BINARY_OPERATORS.contains(&text)
+                        && text != ","
+                        && text != "="
+                        && text != "not");
+                if (token.line_break_before && i > first && !continues)
+                    || text == ";"
+                    || (STATEMENT_KEYWORDS.contains(&text) && i > first)
+                    || [")", "}", "]", "="].contains(&text)
+                {
+                    break;
+                }
+                if text == "," {
+                    if let Some(start) = span_start.take() {
+                        spans.push((start, last_end));
+                    }
+                    i += 1;
+                    continue;
+                }
+            }
+
+            match text {
+                "(" | "{" | "[" => depth += 1,
+                ")" | "}" | "]" => depth = depth.saturating_sub(1),
+                "function" | "do" | "repeat" => blocks += 1,
+                "if" if blocks > 0 && !EXPRESSION_PREFIXES.contains(&prev) => blocks += 1,
+                "end" | "until" => blocks = blocks.saturating_sub(1),
+                _ => {}
+            }
+            span_start.get_or_insert(token.start);
+            last_end = token.end;
+            i += 1;
+        }
+
+        if let Some(start) = span_start {
+            spans.push((start, last_end));
+        }
+        spans
+    }
+
+    fn parse(mut self) -> Parsed {
+        let mut pending_links = Vec::new();
+        let mut depth = 0usize;
+        let mut open_brackets: Vec<usize> = Vec::new();
+        let mut i = 0;
+
+        while i < self.limit {
+            let text = self.text(i);
+            let prev = if i > 0 { self.text(i - 1) } else { "" };
+
+            if self.is_ident(i) && prev != "." && prev != ":" {
+                let token = self.tokens[i];
+                self.table.identifiers.push((token.start, token.end));
+            }
+
+            let starts_statement = match text {
+                "local" | "function" | "return" | "if" | "do" | "while" | "for" | "repeat" => {
+                    !EXPRESSION_PREFIXES.contains(&prev)
+                }
+                _ => {
+                    self.is_ident(i)
+                        && self.tokens[i].line_break_before
+                        && !BINARY_OPERATORS.contains(&prev)
+                        && prev != "."
+                        && prev != ":"
+                }
+            };
+            if starts_statement && depth == 0 && self.blocks.is_empty() {
+                self.table.statements.push(self.tokens[i].start);
+            }
+            match text {
+                "(" | "{" | "[" => {
+                    depth += 1;
+                    open_brackets.push(self.tokens[i].start);
+                }
+                ")" | "}" | "]" => {
+                    depth = depth.saturating_sub(1);
+                    // A closer of another kind(Typo or half typed code) leaves the open one be
+                    let opener = match text {
+                        ")" => b'(',
+                        "}" => b'{',
+                        _ => b'[',
+                    };
+                    if let Some(&open) = open_brackets
+                        .last()
+                        .filter(|&&open| self.doc.as_bytes()[open] == opener)
+                    {
+                        open_brackets.pop();
+                        self.table.brackets.push((open, self.tokens[i].start));
+                    }
+                }
+                _ => {}
+            }
+
+            match text {
+                "local" if self.text(i + 1) == "function" && self.is_ident(i + 2) => {
+                    // Declared before body so recursive calls resolve to it
+                    let symbol = self.declare(i + 2, SymbolKind::LocalFunction, None);
+                    let token = self.tokens[i + 2];
+                    self.table.identifiers.push((token.start, token.end));
+                    self.table.symbols[symbol].function = Some(self.table.functions.len());
+                    i = self.function_body(i + 1, i + 3);
+                    continue;
+                }
+                "local" => {
+                    // Names(Skipping type annotations) up to the "="
+                    let mut names = Vec::new();
+                    let mut j = i + 1;
+                    let mut expect_name = true;
+                    while j < self.tokens.len() {
+                        let t = self.text(j);
+                        if t == "=" || (self.tokens[j].line_break_before && !expect_name) {
+                            break;
+                        }
+                        if expect_name && self.is_ident(j) {
+                            names.push(j);
+                            expect_name = false;
+                        } else if t == "," {
+                            expect_name = true;
+                        }
+                        j += 1;
+                    }
+
+                    let values = if self.text(j) == "=" {
+                        self.expression_list(j + 1)
+                    } else {
+                        Vec::new()
+                    };
+                    for (n, name) in names.into_iter().enumerate() {
+                        let token = self.tokens[name];
+                        self.table.identifiers.push((token.start, token.end));
+                        let symbol = self.declare(name, SymbolKind::Local, values.get(n).copied());
+                        pending_links.push(symbol);
+                    }
+
+                    // Continue right after "=" so function literals in values still get parsed
+                    // (Without one, j is already the next statement and mustn't be skipped)
+                    i = if self.text(j) == "=" { j + 1 } else { j };
+                    continue;
+                }
+                "function" => {
+                    let paren = if self.is_ident(i + 1) {
+                        // function name() or function a.b:c(), only plain names become symbols
+                        let mut j = i + 1;
+                        while self.text(j + 1) == "." || self.text(j + 1) == ":" {
+                            j += 2;
+                        }
+                        if j == i + 1 {
+                            let symbol = self.declare(i + 1, SymbolKind::Function, None);
+                            self.table.symbols[symbol].function = Some(self.table.functions.len());
+                        }
+                        let token = self.tokens[i + 1];
+                        self.table.identifiers.push((token.start, token.end));
+                        j + 1
+                    } else {
+                        i + 1
+                    };
+                    i = self.function_body(i, paren);
+                    continue;
+                }
+                "if" if !EXPRESSION_PREFIXES.contains(&prev) => self.blocks.push(Vec::new()),
+                "do" | "repeat" => self.blocks.push(Vec::new()),
+                "end" | "until" => {
+                    let end = self.tokens[i].end;
+                    self.close_block(end);
+                }
+                _ if self.is_ident(i)
+                    && self.text(i + 1) == "="
+                    && ![".", ":", ",", "local"].contains(&prev) =>
+                {
+                    let values = self.expression_list(i + 2);
+                    let symbol = self.declare(i, SymbolKind::Assignment, values.first().copied());
+                    pending_links.push(symbol);
+                }
+                _ => {}
+            }
+            i += 1;
+        }
+
+        let end = self.doc.len();
+        let unclosed = !self.blocks.is_empty() || !open_brackets.is_empty();
+        while !self.blocks.is_empty() {
+            self.close_block(end);
+        }
+        self.table
+            .brackets
+            .extend(open_brackets.into_iter().map(|open| (open, end)));
+        self.table.brackets.sort_unstable();
+        Parsed {
+            table: self.table,
+            pending_links,
+            unclosed,
+        }
+    }
+}
+
+// What a parse leaves for the caller, links wait until every earlier symbol is in the table
+struct Parsed {
+    table: SymbolTable,
+    pending_links: Vec<usize>,
+    unclosed: bool, // Blocks or brackets still open at the last token
+}
+
+// Swaps spans starting within old window for new ones, shifting the ones after it
+fn splice_spans(
+    spans: &mut Vec<(usize, usize)>,
+    new: Vec<(usize, usize)>,
+    window: (usize, usize),
+    shift: impl Fn(usize) -> usize,
+) {
+    let after: Vec<(usize, usize)> = spans
+        .iter()
+        .filter(|&&(start, _)| start >= window.1)
+        .map(|&(start, end)| (shift(start), shift(end)))
+        .collect();
+    spans.retain(|&(start, _)| start < window.0);
+    spans.extend(new);
+    spans.extend(after);
+}
+
+impl SymbolTable {
+    pub(crate) fn requires(&self) -> &Arc<RequireContext> {
+        &self.requires
+    }
+
+    pub fn build(doc: &str, requires: &Arc<RequireContext>) -> Self {
+        let tokens = tokenize(doc, (0, doc.len()));
+        let parsed = Parser {
+            doc,
+            limit: tokens.len(),
+            tokens,
+            table: SymbolTable::default(),
+            blocks: Vec::new(),
+        }
+        .parse();
+        let mut table = parsed.table;
+        table.requires = requires.clone();
+        for symbol in parsed.pending_links {
+            table.link_function_value(doc, symbol);
+        }
+        table
+    }
+
+    // Catches up with doc after an edit at start replaced removed with inserted_len bytes, parsing
+    // only the top level statements around it. False(Table left as it was) when that might not
+    // match a full build, e.g. the edit opened a block or long comment reaching past them
+    pub fn update(&mut self, doc: &str, start: usize, removed: &str, inserted_len: usize) -> bool {
+        // Long strings/comments, backtick strings and escaped line breaks can reach past any statement
+        let changes_lexing = |text: &str| text.contains(['[', ']', '-', '\\', '`']);
+        if changes_lexing(removed) || changes_lexing(&doc[start..start + inserted_len]) {
+            return false;
+        }
+        let old_len = doc.len() + removed.len() - inserted_len;
+        let removed_end = start + removed.len();
+        let delta = inserted_len as isize - removed.len() as isize;
+        let shift = |offset: usize| (offset as isize + delta) as usize;
+
+        // One untouched statement before the edit, so the first one parsed starts just like it did
+        let before = self.statements.partition_point(|&s| s < start);
+        let window_start = before.checked_sub(2).map_or(0, |i| self.statements[i]);
+        // First statement past the edited line anchors the window, it has to still start one
+        // after parsing(The next one ends the window)
+        let line_end = doc[start + inserted_len..]
+            .find('\n')
+            .map_or(doc.len(), |i| start + inserted_len + i);
+        let anchor = self
+            .statements
+            .iter()
+            .position(|&s| s > removed_end && shift(s) > line_end);
+        let old_window_end = anchor
+            .and_then(|i| self.statements.get(i + 1).copied())
+            .unwrap_or(old_len);
+        let window_end = shift(old_window_end);
+
+        // Brackets left open before the window would have swallowed it
+        let spanning = self.brackets[..self
+            .brackets
+            .partition_point(|&(open, _)| open < window_start)]
+            .iter()
+            .any(|&(_, close)| close >= window_start);
+        if spanning {
+            return false;
+        }
+        // Plus the next statement's first token, so values running on past the window show up
+        let tokens = tokenize(doc, (window_start, (window_end + 1).min(doc.len())));
+        let limit = tokens.partition_point(|token| token.start < window_end);
+        if tokens[..limit]
+            .last()
+            .is_some_and(|token| token.end > window_end)
+        {
+            return false;
+        }
+        let parsed = Parser {
+            doc,
+            tokens,
+            table: SymbolTable::default(),
+            blocks: Vec::new(),
+            limit,
+        }
+        .parse();
+        let anchored =
+            anchor.is_none_or(|i| parsed.table.statements.contains(&shift(self.statements[i])));
+        let reaches_past =
+            parsed.table.symbols.iter().any(|s| {
+                s.range.1 > window_end || s.value.is_some_and(|(_, end)| end > window_end)
+            }) || parsed
+                .table
+                .identifiers
+                .iter()
+                .any(|&(_, end)| end > window_end);
+        if window_end < doc.len() && (parsed.unclosed || !anchored || reaches_past) {
+            return false;
+        }
+
+        // Whatever was parsed before the window stays, the window's gets replaced and the rest shifts
+        let old_window = (window_start, old_window_end);
+        let functions_before = self
+            .functions
+            .partition_point(|f| f.keyword_start < window_start);
+        let mut rest = self.functions.split_off(functions_before);
+        let after = rest.split_off(rest.partition_point(|f| f.keyword_start < old_window_end));
+        let functions_delta = parsed.table.functions.len() as isize - rest.len() as isize;
+        self.functions.extend(parsed.table.functions);
+        self.functions.extend(after.into_iter().map(|mut f| {
+            f.keyword_start = shift(f.keyword_start);
+            for (a, b) in f.param_types.iter_mut().flatten() {
+                (*a, *b) = (shift(*a), shift(*b));
+            }
+            f
+        }));
+
+        let symbols_before = self.symbols.partition_point(|s| s.range.0 < window_start);
+        for symbol in &mut self.symbols {
+            if symbol.scope.1 >= old_window_end {
+                symbol.scope.1 = shift(symbol.scope.1);
+            }
+        }
+        let mut after = self.symbols.split_off(symbols_before);
+        after.retain(|s| s.range.0 >= old_window_end);
+        self.symbols
+            .extend(parsed.table.symbols.into_iter().map(|mut s| {
+                s.function = s.function.map(|f| f + functions_before);
+                s
+            }));
+        // Values linked to functions(Even through memo) get linked again below
+        let relink_from = self.symbols.len();
+        self.symbols.extend(after.into_iter().map(|mut s| {
+            s.range = (shift(s.range.0), shift(s.range.1));
+            s.value = s.value.map(|(a, b)| (shift(a), shift(b)));
+            s.scope.0 = shift(s.scope.0);
+            s.function = s
+                .function
+                .filter(|_| !matches!(s.kind, SymbolKind::Local | SymbolKind::Assignment))
+                .map(|f| (f as isize + functions_delta) as usize);
+            s
+        }));
+
+        splice_spans(
+            &mut self.identifiers,
+            parsed.table.identifiers,
+            old_window,
+            shift,
+        );
+        splice_spans(&mut self.brackets, parsed.table.brackets, old_window, shift);
+        let after: Vec<usize> = self
+            .statements
+            .iter()
+            .filter(|&&s| s >= old_window_end)
+            .map(|&s| shift(s))
+            .collect();
+        self.statements.truncate(before.saturating_sub(2));
+        self.statements.extend(parsed.table.statements);
+        self.statements.extend(after);
+
+        let pending = parsed
+            .pending_links
+            .into_iter()
+            .map(|symbol| symbol + symbols_before);
+        for symbol in pending.chain(relink_from..self.symbols.len()) {
+            self.link_function_value(doc, symbol);
+        }
+        true
+    }
+
+    // Links symbol holding a function literal to the function it holds
+    // (Looking through React.memo/forwardRef, which wrap either a literal or an earlier function)
+    fn link_function_value(&mut self, doc: &str, symbol: usize) {
+        let Some((start, end)) = self.symbols[symbol].value else {
+            return;
+        };
+        let (offset, inner) = unwrap_component_wrapper(&doc[start..end]);
+        let start = start + offset;
+        let function = if inner.starts_with("function") {
+            self.functions.iter().position(|f| f.keyword_start == start)
+        } else if offset > 0 {
+            self.symbols[..symbol]
+                .iter()
+                .rev()
+                .find(|s| s.name == inner && s.function.is_some())
+                .and_then(|s| s.function)
+        } else {
+            None
+        };
+        if function.is_some() {
+            self.symbols[symbol].function = function;
+        }
+    }
+
+    pub fn value_text<'a>(&self, doc: &'a str, symbol: &Symbol) -> Option<&'a str> {
+        symbol.value.map(|(start, end)| &doc[start..end])
+    }
+
+    // Name React gets required under(First one wins)
+    fn is_react_require(&self, doc: &str, symbol: &Symbol) -> bool {
+        matches!(symbol.kind, SymbolKind::Local | SymbolKind::Assignment)
+            && self.value_text(doc, symbol).is_some_and(|value| {
+                REACT_REQUIRE_PATTERN.captures(value).is_some_and(|caps| {
+                    self.requires
+                        .is_react_module(&instance_path_segments(&caps[1]))
+                }) || string_require_path(value)
+                    .is_some_and(|path| self.requires.is_react_require_path(path))
+            })
+    }
+
+    // Every name React gets required under anywhere in doc, in declaration order
+    pub fn react_var_names(&self, doc: &str) -> Vec<String> {
+        let mut names: Vec<String> = Vec::new();
+        for symbol in self
+            .symbols
+            .iter()
+            .filter(|s| self.is_react_require(doc, s))
+        {
+            if !names.contains(&symbol.name) {
+                names.push(symbol.name.clone());
+            }
+        }
+        names
+    }
+
+    // Name React is reachable under at offset, nearest declaration before it wins
+    // (So a block requiring it as R gets R, and shadowing an alias with anything else hides it)
+    pub fn react_var_name_at(&self, doc: &str, offset: usize) -> Option<String> {
+        let visible = |s: &&Symbol| s.scope.0 <= offset && offset <= s.scope.1;
+        self.symbols
+            .iter()
+            .filter(visible)
+            .filter(|s| self.is_react_require(doc, s))
+            .filter(|alias| {
+                !self.symbols.iter().filter(visible).any(|other| {
+                    other.name == alias.name
+                        && other.scope.0 > alias.scope.0
+                        && !self.is_react_require(doc, other)
+                })
+            })
+            .max_by_key(|alias| alias.scope.0)
+            .map(|alias| alias.name.clone())
+    }
+
+    // Names assigned <react_var>.createElement before the given byte offset
+    pub fn create_element_macros(
+        &self,
+        doc: &str,
+        before_byte_offset: usize,
+        react_var_name: &str,
+    ) -> Vec<String> {
+        self.symbols
+            .iter()
+            .filter(|s| matches!(s.kind, SymbolKind::Local | SymbolKind::Assignment))
+            .filter(|s| s.range.0 < before_byte_offset)
+            .filter(|s| {
+                self.value_text(doc, s).is_some_and(|value| {
+                    let value: String = value.chars().filter(|c| !c.is_whitespace()).collect();
+                    value
+                        .strip_prefix(react_var_name)
+                        .and_then(|rest| rest.strip_prefix('.'))
+                        .is_some_and(|rest| rest.eq_ignore_ascii_case("createElement"))
+                })
+            })
+            .map(|s| s.name.clone())
+            .collect()
+    }
+
+    // Names createElement helpers required from other modules get assigned to before byte offset
+    // (local e = require(script.Parent.e), works without React being required in the file)
+    pub fn create_element_helpers(&self, doc: &str, before_byte_offset: usize) -> Vec<String> {
+        self.symbols
+            .iter()
+            .filter(|s| matches!(s.kind, SymbolKind::Local | SymbolKind::Assignment))
+            .filter(|s| s.range.0 < before_byte_offset)
+            .filter(|s| {
+                self.value_text(doc, s)
+                    .and_then(required_module_name)
+                    .is_some_and(|module| self.requires.is_create_element_module(&module))
+            })
+            .map(|s| s.name.clone())
+            .collect()
+    }
+
+    // Names holding a binding before byte offset, created by React.createBinding/useBinding/joinBindings
+    // or mapped from another binding(Only the first name of "local binding, setBinding = ..." gets the value)
+    pub fn binding_names(&self, doc: &str, before_byte_offset: usize) -> Vec<String> {
+        let react_var_names = self.react_var_names(doc);
+        let mut names: Vec<String> = Vec::new();
+        for symbol in self
+            .symbols
+            .iter()
+            .filter(|s| matches!(s.kind, SymbolKind::Local | SymbolKind::Assignment))
+            .filter(|s| s.range.0 < before_byte_offset)
+        {
+            let Some((callee, _)) = self.value_text(doc, symbol).and_then(|v| v.split_once('('))
+            else {
+                continue;
+            };
+            let is_binding = match callee.trim().split_once(['.', ':']) {
+                Some((base, "createBinding" | "useBinding" | "joinBindings")) => {
+                    react_var_names.iter().any(|name| name == base)
+                }
+                Some((base, "map")) => names.iter().any(|name| name == base),
+                _ => false,
+            };
+            if is_binding && !names.contains(&symbol.name) {
+                names.push(symbol.name.clone());
+            }
+        }
+        names
+    }
+
+    // Function components defined in document(Local functions whose first parameter is props)
+    pub fn component_names(&self) -> Vec<String> {
+        self.symbols
+            .iter()
+            .filter(|s| matches!(s.kind, SymbolKind::Local | SymbolKind::LocalFunction))
+            .filter(|s| {
+                s.function
+                    .and_then(|f| self.functions.get(f))
+                    .is_some_and(|f| f.params.first().is_some_and(|p| p == "props"))
+            })
+            .map(|s| s.name.clone())
+            .collect()
+    }
+
+    // Type annotation of function's first parameter(e.g. "Props" in function(props: Props))
+    pub fn first_param_type<'a>(&self, doc: &'a str, function: usize) -> Option<&'a str> {
+        let (start, end) = (*self.functions.get(function)?.param_types.first()?)?;
+        Some(doc[start..end].trim())
+    }
+
+    // Keys read off function's first parameter inside its body(props.text, props.onClick etc)
+    pub fn function_props(&self, doc: &str, function: usize) -> Vec<String> {
+        let Some(info) = self.functions.get(function) else {
+            return Vec::new();
+        };
+        let Some(param) = self.symbols.iter().find(|s| {
+            s.kind == SymbolKind::Parameter
+                && s.range.0 > info.keyword_start
+                && info.params.first() == Some(&s.name)
+        }) else {
+            return Vec::new();
+        };
+
+        let mut props: Vec<String> = self
+            .usages(doc, &param.name)
+            .into_iter()
+            .filter(|&(start, end)| start > param.range.0 && end <= param.scope.1)
+            .filter_map(|(_, end)| {
+                let rest = doc[end..].strip_prefix('.')?;
+                let len = rest
+                    .find(|c: char| !c.is_alphanumeric() && c != '_')
+                    .unwrap_or(rest.len());
+                (len > 0).then(|| rest[..len].to_string())
+            })
+            .collect();
+        props.sort();
+        props.dedup();
+        props
+    }
+
+    // Names declared through local, local function or function statements
+    pub fn declared_names(&self) -> impl Iterator<Item = &str> {
+        self.symbols
+            .iter()
+            .filter(|s| {
+                matches!(
+                    s.kind,
+                    SymbolKind::Local | SymbolKind::LocalFunction | SymbolKind::Function
+                )
+            })
+            .map(|s| s.name.as_str())
+    }
+
+    // Local, local function or parameter name refers to at byte offset(Innermost visible declaration)
+    pub fn resolve(&self, name: &str, offset: usize) -> Option<&Symbol> {
+        self.symbols
+            .iter()
+            .filter(|s| {
+                matches!(
+                    s.kind,
+                    SymbolKind::Local | SymbolKind::LocalFunction | SymbolKind::Parameter
+                )
+            })
+            .filter(|s| s.name == name && s.scope.0 <= offset && offset <= s.scope.1)
+            .max_by_key(|s| s.range.0)
+    }
+
+    // Brackets whose contents hold offset(Right before the closer counts), innermost first
+    pub fn enclosing_brackets(&self, offset: usize) -> Vec<(usize, usize)> {
+        let before = self.brackets.partition_point(|&(open, _)| open < offset);
+        self.brackets[..before]
+            .iter()
+            .rev()
+            .filter(|&&(_, close)| offset <= close)
+            .copied()
+            .collect()
+    }
+
+    // Start of the top level statement offset is in(0 before the first one)
+    pub fn statement_start_at(&self, offset: usize) -> usize {
+        let index = self.statements.partition_point(|&start| start <= offset);
+        index
+            .checked_sub(1)
+            .map_or(0, |index| self.statements[index])
+    }
+
+    // Spans of standalone identifiers within range, in order(Declared names included)
+    pub fn identifiers_in(&self, range: (usize, usize)) -> Vec<(usize, usize)> {
+        let mut identifiers: Vec<(usize, usize)> = self
+            .identifiers
+            .iter()
+            .filter(|&&(start, end)| start >= range.0 && end <= range.1)
+            .copied()
+            .collect();
+        identifiers.sort();
+        identifiers.dedup();
+        identifiers
+    }
+
+    // Standalone usages of identifier(Member accesses, strings and comments are skipped)
+    pub fn usages(&self, doc: &str, name: &str) -> Vec<(usize, usize)> {
+        let mut usages: Vec<(usize, usize)> = self
+            .identifiers
+            .iter()
+            .filter(|&&(start, end)| &doc[start..end] == name)
+            .copied()
+            .collect();
+        usages.sort();
+        usages.dedup();
+        usages
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::symbol_table::{SymbolKind, SymbolTable};
+
+    const DOC: &str = r#"--[[ local Fake = require(game.React) ]]
+local React = require(game.Packages.React)
+local e, create = React.createElement, React.createElement
+local label = "local Other = 1"
+
+local function Button(props, extra)
+    local inner = function(props) end
+    return e("TextButton", { Text = props.text })
+end
+
+local Card = function(props)
+    if props.big then
+        local size = 2
+    end
+end
+"#;
+
+    #[test]
+    fn test_symbols() {
+        let table = SymbolTable::build(DOC, &Default::default());
+        assert_eq!(table.react_var_names(DOC), ["React"]);
+        assert_eq!(
+            table.create_element_macros(DOC, DOC.len(), "React"),
+            vec!["e", "create"]
+        );
+        assert_eq!(table.component_names(), vec!["Button", "inner", "Card"]);
+        assert_eq!(table.function_props(DOC, 0), vec!["text"]);
+        assert_eq!(table.function_props(DOC, 2), vec!["big"]);
+
+        let wrapped = "local function Base(props) return props.a end\nlocal Memo = React.memo(Base)\nlocal Ref = React.forwardRef(function(props, ref) return props.b end)\n";
+        let table = SymbolTable::build(wrapped, &Default::default());
+        assert_eq!(table.component_names(), vec!["Base", "Memo", "Ref"]);
+        let memo = table.symbols.iter().find(|s| s.name == "Memo").unwrap();
+        assert_eq!(
+            table.function_props(wrapped, memo.function.unwrap()),
+            vec!["a"]
+        );
+
+        let typed = "local function Row(props: { Text: string, OnClick: () -> () }, index: number): any end";
+        let table = SymbolTable::build(typed, &Default::default());
+        assert_eq!(table.functions[0].params, vec!["props", "index"]);
+        assert_eq!(
+            table.first_param_type(typed, 0),
+            Some("{ Text: string, OnClick: () -> () }")
+        );
+        let (start, end) = table.functions[0].param_types[1].unwrap();
+        assert_eq!(typed[start..end].trim(), "number");
+        let table = SymbolTable::build(DOC, &Default::default());
+
+        let names: Vec<&str> = table.declared_names().collect();
+        assert!(names.contains(&"label") && !names.contains(&"Other") && !names.contains(&"Fake"));
+
+        let extra = table.symbols.iter().find(|s| s.name == "extra").unwrap();
+        assert_eq!(extra.kind, SymbolKind::Parameter);
+        assert_eq!(&DOC[extra.scope.1 - 3..extra.scope.1], "end");
+
+        let size = table.symbols.iter().find(|s| s.name == "size").unwrap();
+        assert_eq!(size.scope.1, DOC.find("    end\nend").unwrap() + 7);
+        assert_eq!(table.value_text(DOC, size), Some("2"));
+
+        // Statements nested in blocks or brackets belong to the top level one around them
+        let text = DOC.find("Text =").unwrap();
+        let button = DOC.find("local function Button").unwrap();
+        assert_eq!(table.statement_start_at(text), button);
+        let card = DOC.find("local Card").unwrap();
+        assert_eq!(
+            table.statement_start_at(DOC.find("local size").unwrap()),
+            card
+        );
+        assert_eq!(table.statement_start_at(card - 1), button);
+        assert_eq!(table.statement_start_at(5), 0);
+    }
+
+    #[test]
+    fn test_usages() {
+        let table = SymbolTable::build(DOC, &Default::default());
+        // Declaration and the two createElement accesses, not the comment or member names
+        assert_eq!(table.usages(DOC, "React").len(), 3);
+        assert_eq!(table.usages(DOC, "text").len(), 0);
+        assert_eq!(table.usages(DOC, "props").len(), 5);
+    }
+
+    #[test]
+    fn test_incremental_update() {
+        let doc = format!(
+            "{}local function Base(props) return props.a end\nlocal Memo = React.memo(Base)\n",
+            DOC
+        );
+        // (Text edited, replacement, whether only the statements around it get parsed again)
+        let edits = [
+            ("Text = props.text", "Text = props.label", true),
+            ("local size = 2", "local size, scale = 2, 1", true),
+            ("local label = \"local Other = 1\"\n", "", true),
+            ("\nlocal Card", "\nlocal Extra = e\nlocal Card", true),
+            ("props.a end", "props.b end", true),
+            ("local function Base", "local Base = function", true),
+            ("    end\nend", "    end\n", false),
+            (" end\n", " end\nlocal Opened = function(props)\n", false),
+            ("local label", "local label -- ", false),
+        ];
+        for (old, new, incremental) in edits {
+            let start = doc.find(old).unwrap();
+            let edited = doc.replacen(old, new, 1);
+            let mut table = SymbolTable::build(&doc, &Default::default());
+            assert_eq!(
+                table.update(&edited, start, old, new.len()),
+                incremental,
+                "{old:?}"
+            );
+            if incremental {
+                let expected = SymbolTable::build(&edited, &Default::default());
+                assert_eq!(format!("{table:?}"), format!("{expected:?}"), "{old:?}");
+            }
+        }
+    }
+}
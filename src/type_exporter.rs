@@ -0,0 +1,151 @@
+// This script writes the loaded API out as Luau types, so luau-lsp/stylua setups can use the same classes
+// completions offer without keeping their own generator around
+
+use crate::api_manager::ApiManager;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TypeExportFormat {
+    // "declare class Frame extends GuiObject ... end" blocks(.d.luau, what custom class stubs read back)
+    Definitions,
+    // "type Frame = { ... }" tables with every settable property optional, for props tables
+    GlobalTypes,
+}
+
+impl TypeExportFormat {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "definitions" => Some(Self::Definitions),
+            "globalTypes" => Some(Self::GlobalTypes),
+            _ => None,
+        }
+    }
+}
+
+// Luau type of a property's API type, classes are optional since they can be nil
+fn luau_type(api_manager: &ApiManager, data_type: &str) -> String {
+    match data_type {
+        "bool" => "boolean".to_string(),
+        "int" | "int64" | "float" | "double" => "number".to_string(),
+        "string" | "Content" | "BinaryString" | "ProtectedString" => "string".to_string(),
+        class if !api_manager.superclass_chain(class).is_empty() => format!("{}?", class),
+        other => other.to_string(),
+    }
+}
+
+// Creatable classes completions offer plus every superclass they need, base classes first
+fn exported_classes(api_manager: &ApiManager) -> Vec<String> {
+    let mut offered = api_manager.get_all_inst("").unwrap_or_default();
+    offered.sort();
+    let mut classes: Vec<String> = Vec::new();
+    for class in offered {
+        for ancestor in api_manager.superclass_chain(&class).into_iter().rev() {
+            if !classes.contains(&ancestor) {
+                classes.push(ancestor);
+            }
+        }
+    }
+    classes
+}
+
+pub fn export_types(api_manager: &ApiManager, format: TypeExportFormat) -> (String, usize) {
+    let classes = exported_classes(api_manager);
+    let mut out = format!(
+        "-- Generated by rblx-react-lsp from Roblox API {}\n",
+        api_manager.api_version().unwrap_or("unknown")
+    );
+
+    for class in &classes {
+        let properties = api_manager.lookup_properties(class).unwrap_or_default();
+        let mut properties: Vec<(String, String)> = match format {
+            // Superclass block already declares inherited ones
+            TypeExportFormat::Definitions => {
+                let inherited = api_manager
+                    .superclass_chain(class)
+                    .get(1)
+                    .and_then(|superclass| api_manager.lookup_properties(superclass))
+                    .unwrap_or_default();
+                properties
+                    .into_iter()
+                    .filter(|(name, _)| !inherited.iter().any(|(other, _)| other == name))
+                    .collect()
+            }
+            TypeExportFormat::GlobalTypes => properties,
+        };
+        properties.sort();
+
+        out.push('\n');
+        match format {
+            TypeExportFormat::Definitions => {
+                match api_manager.superclass_chain(class).get(1) {
+                    Some(superclass) => {
+                        out.push_str(&format!("declare class {} extends {}\n", class, superclass))
+                    }
+                    None => out.push_str(&format!("declare class {}\n", class)),
+                }
+                for (name, data_type) in properties {
+                    out.push_str(&format!(
+                        "\t{}: {}\n",
+                        name,
+                        luau_type(api_manager, &data_type)
+                    ));
+                }
+                out.push_str("end\n");
+            }
+            TypeExportFormat::GlobalTypes => {
+                out.push_str(&format!("type {} = {{\n", class));
+                for (name, data_type) in properties {
+                    // Props tables leave out whatever keeps its default
+                    let luau_type = luau_type(api_manager, &data_type);
+                    let optional = if luau_type.ends_with('?') { "" } else { "?" };
+                    out.push_str(&format!("\t{}: {}{},\n", name, luau_type, optional));
+                }
+                out.push_str("}\n");
+            }
+        }
+    }
+
+    (out, classes.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        api_manager::ApiManager,
+        custom_classes::parse_type_stubs,
+        type_exporter::{export_types, TypeExportFormat},
+    };
+
+    #[test]
+    fn test_export_types() {
+        let api_manager = ApiManager::from_local_dump();
+        let (definitions, count) = export_types(&api_manager, TypeExportFormat::Definitions);
+        assert!(definitions.contains("declare class TextLabel extends GuiLabel\n"));
+        assert!(definitions.contains("\tText: string\n"));
+        assert!(definitions.contains("\tVisible: boolean\n"));
+
+        // Custom class stubs read the same format, with each class declaring only its own properties
+        let classes = parse_type_stubs(&definitions);
+        assert_eq!(classes.len(), count);
+        let label = classes.iter().find(|c| c.instance == "TextLabel").unwrap();
+        assert!(!label.properties.iter().any(|p| p.name == "Visible"));
+        let gui_object = classes.iter().find(|c| c.instance == "GuiObject").unwrap();
+        assert!(gui_object.properties.iter().any(|p| p.name == "Visible"));
+        // Read only ones can't be set, so they aren't exported
+        assert!(!gui_object
+            .properties
+            .iter()
+            .any(|p| p.name == "AbsoluteSize"));
+        let frame = classes.iter().position(|c| c.instance == "Frame").unwrap();
+        let instance = classes
+            .iter()
+            .position(|c| c.instance == "Instance")
+            .unwrap();
+        assert!(instance < frame);
+
+        let (global_types, _) = export_types(&api_manager, TypeExportFormat::GlobalTypes);
+        assert!(global_types.contains("type TextLabel = {\n"));
+        assert!(global_types.contains("\tVisible: boolean?,\n"));
+        assert!(global_types.contains("\tFont: Enum.Font?,\n"));
+        assert!(global_types.contains("\tNextSelectionUp: GuiObject?,\n"));
+    }
+}